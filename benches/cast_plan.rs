@@ -0,0 +1,44 @@
+//! Per-call overhead of casting 1-row arrays, one-shot and by a plan.
+//!
+//! Run with `cargo bench --bench cast_plan`.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use arrow::array::Int64Array;
+use arrow::datatypes::{DataType, TimeUnit};
+use arrow_cast_guess_precision::{cast_with_options, CastOptions, CastPlan};
+
+const ITERATIONS: u32 = 200_000;
+
+fn bench(name: &str, f: impl Fn()) {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    println!("{name}: {:?} per call", start.elapsed() / ITERATIONS);
+}
+
+fn main() {
+    let array = Int64Array::from(vec![1701325744956]);
+    let to_type = DataType::Timestamp(TimeUnit::Nanosecond, Some("+08:00".into()));
+    let mut options = CastOptions::new();
+    options.timestamp_options.assume_unit = Some(TimeUnit::Millisecond);
+
+    bench("one-shot", || {
+        black_box(cast_with_options(black_box(&array), &to_type, &options).unwrap());
+    });
+    let plan = CastPlan::try_new(&DataType::Int64, &to_type, &options).unwrap();
+    bench("plan", || {
+        black_box(plan.cast(black_box(&array)).unwrap());
+    });
+
+    let options = CastOptions::new();
+    bench("one-shot guessing", || {
+        black_box(cast_with_options(black_box(&array), &to_type, &options).unwrap());
+    });
+    let plan = CastPlan::try_new(&DataType::Int64, &to_type, &options).unwrap();
+    bench("plan guessing", || {
+        black_box(plan.cast(black_box(&array)).unwrap());
+    });
+}
@@ -0,0 +1,50 @@
+//! Per-call overhead of casting 64-row arrays, concrete and behind `Arc<dyn Array>`.
+//!
+//! Run with `cargo bench --bench cast_small`.
+
+use std::hint::black_box;
+use std::sync::Arc;
+use std::time::Instant;
+
+use arrow::array::{Array, ArrayRef, Int64Array, StringArray};
+use arrow::datatypes::{DataType, TimeUnit};
+use arrow_cast_guess_precision::{cast_with_options, CastOptions};
+
+const ITERATIONS: u32 = 100_000;
+const ROWS: i64 = 64;
+
+fn bench(name: &str, f: impl Fn()) {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    println!("{name}: {:?} per call", start.elapsed() / ITERATIONS);
+}
+
+fn bench_array(name: &str, array: ArrayRef, to_type: &DataType, options: &CastOptions) {
+    bench(&format!("{name} behind Arc"), || {
+        black_box(cast_with_options(black_box(&array), to_type, options).unwrap());
+    });
+    let concrete: &dyn Array = array.as_ref();
+    bench(&format!("{name} concrete"), || {
+        black_box(cast_with_options(black_box(concrete), to_type, options).unwrap());
+    });
+}
+
+fn main() {
+    let to_type = DataType::Timestamp(TimeUnit::Millisecond, Some("+08:00".into()));
+    let options = CastOptions::new();
+
+    let integers = Int64Array::from_iter_values((0..ROWS).map(|i| 1701325744956 + i));
+    bench_array("int64", Arc::new(integers), &to_type, &options);
+
+    let strings: StringArray = (0..ROWS)
+        .map(|i| Some((1701325744956 + i).to_string()))
+        .collect();
+    bench_array("integer strings", Arc::new(strings), &to_type, &options);
+
+    let strings: StringArray = (0..ROWS)
+        .map(|i| Some(format!("2023-11-30T06:29:{:02}.956Z", i % 60)))
+        .collect();
+    bench_array("iso8601 strings", Arc::new(strings), &to_type, &options);
+}
@@ -1,13 +1,18 @@
 fn main() {
     const ENV: &str = "ARROW_CAST_GUESSING_BOUND_YEARS";
 
-    let years = std::env::var(ENV).unwrap_or_else(|_| "1000".to_string());
+    let from_env = std::env::var(ENV).ok();
+    let years = from_env.clone().unwrap_or_else(|| "1000".to_string());
 
     let out_dir = std::env::var("OUT_DIR").unwrap();
     let dest_path = std::path::Path::new(&out_dir).join("guessing_bound.rs");
     std::fs::write(
         &dest_path,
-        format!("pub const GUESSING_BOUND_YEARS: i64 = {};", years),
+        format!(
+            "pub const GUESSING_BOUND_YEARS: i64 = {};\nconst GUESSING_BOUND_YEARS_FROM_ENV: bool = {};",
+            years,
+            from_env.is_some()
+        ),
     )
     .unwrap();
     println!("cargo:rerun-if-env-changed={}", ENV);
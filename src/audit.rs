@@ -0,0 +1,249 @@
+//! Auditing timestamp columns for values written in the wrong unit.
+
+use arrow_array::{cast::AsArray, types::Int64Type, Array, RecordBatch};
+use arrow_schema::{ArrowError, DataType, TimeUnit};
+
+use crate::{
+    kernel::{make_timestamp_array, rescale, time_unit_multiple},
+    CastOptions, CastReport,
+};
+
+const UNITS: [TimeUnit; 4] = [
+    TimeUnit::Second,
+    TimeUnit::Millisecond,
+    TimeUnit::Microsecond,
+    TimeUnit::Nanosecond,
+];
+
+/// Largest fraction of values within the window under the declared unit for a correction.
+const DECLARED_FIT: f64 = 0.1;
+/// Smallest fraction of values within the window under the suggested unit.
+const SUGGESTED_FIT: f64 = 0.9;
+
+/// How the values of a timestamp or `Date64` column fit a window under each unit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnAudit {
+    /// Index of the column in the batch.
+    pub index: usize,
+    pub name: String,
+    /// The unit of the data type, milliseconds for `Date64`.
+    pub declared_unit: TimeUnit,
+    /// Fraction of non-null values within the window when taken in each unit, in the order
+    /// of [unit_code](crate::unit_code), zero for columns without values.
+    pub fractions: [f64; 4],
+    /// The unit the values are likely written in, if it fits at least 90% of the values
+    /// while the declared unit fits at most 10%.
+    pub suggested_unit: Option<TimeUnit>,
+    /// Factor to multiply values by to correct them, e.g. `1000.0` for seconds declared as
+    /// milliseconds, `None` without a suggested unit.
+    pub correction_factor: Option<f64>,
+}
+
+impl ColumnAudit {
+    /// Fraction of values within the window when taken in the unit.
+    pub fn fraction(&self, unit: TimeUnit) -> f64 {
+        self.fractions[crate::unit_code(unit) as usize]
+    }
+}
+
+/// Audit the timestamp and `Date64` columns of the batch against the window of epoch
+/// seconds `expected_range`, inclusive.
+///
+/// Values are taken in each unit and truncated to seconds, a column whose values fall in
+/// the window under another unit far more than under the declared one gets a suggested
+/// unit, see [apply_audit_fix] to correct it.
+pub fn audit_timestamp_columns(
+    batch: &RecordBatch,
+    expected_range: (i64, i64),
+) -> Vec<ColumnAudit> {
+    let (low, high) = expected_range;
+    let schema = batch.schema();
+    let mut audits = Vec::new();
+    for (index, field) in schema.fields().iter().enumerate() {
+        let declared_unit = match field.data_type() {
+            DataType::Timestamp(unit, _) => *unit,
+            DataType::Date64 => TimeUnit::Millisecond,
+            _ => continue,
+        };
+        let column = batch.column(index);
+        let Ok(values) = arrow_cast::cast(column, &DataType::Int64) else {
+            continue;
+        };
+        let values = values.as_primitive::<Int64Type>();
+        let mut counts = [0usize; 4];
+        for v in values.iter().flatten() {
+            for (count, unit) in counts.iter_mut().zip(UNITS) {
+                let seconds = v / time_unit_multiple(unit);
+                if (low..=high).contains(&seconds) {
+                    *count += 1;
+                }
+            }
+        }
+        let non_null = values.len() - values.null_count();
+        let fractions = counts.map(|count| match non_null {
+            0 => 0.0,
+            n => count as f64 / n as f64,
+        });
+        let declared = fractions[crate::unit_code(declared_unit) as usize];
+        let suggested_unit = UNITS
+            .into_iter()
+            .zip(fractions)
+            .filter(|(unit, fraction)| *unit != declared_unit && *fraction >= SUGGESTED_FIT)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .filter(|_| declared <= DECLARED_FIT)
+            .map(|(unit, _)| unit);
+        let correction_factor = suggested_unit
+            .map(|unit| time_unit_multiple(declared_unit) as f64 / time_unit_multiple(unit) as f64);
+        audits.push(ColumnAudit {
+            index,
+            name: field.name().clone(),
+            declared_unit,
+            fractions,
+            suggested_unit,
+            correction_factor,
+        });
+    }
+    audits
+}
+
+/// Rescale the columns of the audits with a suggested unit, from the suggested unit to
+/// the declared one, keeping the data types.
+///
+/// Overflowing values follow `safe` and the
+/// [overflow_behavior](crate::TimestampCastOptions::overflow_behavior), and precision lost
+/// the [downscale_policy](crate::TimestampCastOptions::downscale_policy). Audits must be
+/// of the same schema, a column of another name fails.
+pub fn apply_audit_fix(
+    batch: &RecordBatch,
+    audits: &[ColumnAudit],
+    cast_options: &CastOptions,
+) -> Result<RecordBatch, ArrowError> {
+    let schema = batch.schema();
+    let mut columns = batch.columns().to_vec();
+    for audit in audits {
+        let Some(from) = audit.suggested_unit else {
+            continue;
+        };
+        let field = schema
+            .fields()
+            .get(audit.index)
+            .filter(|field| field.name() == &audit.name);
+        let Some(field) = field else {
+            return Err(ArrowError::SchemaError(format!(
+                "Column {} of the audit not found at index {}",
+                audit.name, audit.index
+            )));
+        };
+        let values = arrow_cast::cast(&columns[audit.index], &DataType::Int64)?;
+        let mut report = CastReport::default();
+        let values = rescale(
+            values.as_primitive(),
+            from,
+            audit.declared_unit,
+            cast_options,
+            &mut report,
+        )?;
+        columns[audit.index] = match field.data_type() {
+            DataType::Timestamp(unit, tz) => make_timestamp_array(&values, *unit, tz.clone()),
+            data_type => arrow_cast::cast(&values, data_type)?,
+        };
+    }
+    RecordBatch::try_new(schema, columns)
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use arrow_array::{
+        types::TimestampMillisecondType, ArrayRef, Date64Array, Int64Array,
+        TimestampMillisecondArray, TimestampSecondArray,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_audit_timestamp_columns() {
+        // Seconds declared as milliseconds, nanoseconds declared as seconds, and correct ones.
+        let seconds: Vec<Option<i64>> = vec![Some(1701325744), None, Some(1701325745)];
+        let batch = RecordBatch::try_from_iter([
+            (
+                "divided",
+                Arc::new(TimestampMillisecondArray::from(seconds.clone()).with_timezone("+08:00"))
+                    as ArrayRef,
+            ),
+            (
+                "correct",
+                Arc::new(TimestampSecondArray::from(seconds.clone())) as ArrayRef,
+            ),
+            (
+                "multiplied",
+                Arc::new(TimestampSecondArray::from(vec![
+                    Some(1701325744956000000),
+                    Some(i64::MAX),
+                    None,
+                ])) as ArrayRef,
+            ),
+            (
+                "date",
+                Arc::new(Date64Array::from(seconds.clone())) as ArrayRef,
+            ),
+            ("integers", Arc::new(Int64Array::from(seconds)) as ArrayRef),
+        ])
+        .unwrap();
+        let window = (1577836800, 1893456000);
+        let audits = audit_timestamp_columns(&batch, window);
+        assert_eq!(audits.len(), 4);
+        assert_eq!(audits[0].name, "divided");
+        assert_eq!(audits[0].fraction(TimeUnit::Second), 1.0);
+        assert_eq!(audits[0].fraction(TimeUnit::Millisecond), 0.0);
+        assert_eq!(audits[0].suggested_unit, Some(TimeUnit::Second));
+        assert_eq!(audits[0].correction_factor, Some(1000.0));
+        assert_eq!(audits[1].suggested_unit, None);
+        assert_eq!(audits[1].correction_factor, None);
+        // Half of the values fit as nanoseconds, not dramatically better.
+        assert_eq!(audits[2].fraction(TimeUnit::Nanosecond), 0.5);
+        assert_eq!(audits[2].suggested_unit, None);
+        assert_eq!(audits[3].suggested_unit, Some(TimeUnit::Second));
+
+        let fixed =
+            apply_audit_fix(&batch, &audits, &CastOptions::new().with_passthrough(false)).unwrap();
+        assert_eq!(fixed.schema(), batch.schema());
+        let divided = fixed.column(0).as_primitive::<TimestampMillisecondType>();
+        assert_eq!(
+            divided.iter().collect::<Vec<_>>(),
+            vec![Some(1701325744000), None, Some(1701325745000)]
+        );
+        assert_eq!(fixed.column(1), batch.column(1));
+        let date = fixed
+            .column(3)
+            .as_primitive::<arrow_array::types::Date64Type>();
+        assert_eq!(date.value(0), 1701325744000);
+        assert!(audit_timestamp_columns(&fixed, window)
+            .iter()
+            .all(|audit| audit.suggested_unit.is_none()));
+
+        // Overflow follows `safe`.
+        let batch = RecordBatch::try_from_iter_with_nullable([(
+            "divided",
+            Arc::new(TimestampMillisecondArray::from(vec![
+                1701325744,
+                i64::MAX / 10,
+            ])) as ArrayRef,
+            true,
+        )])
+        .unwrap();
+        let mut audits = audit_timestamp_columns(&batch, window);
+        audits[0].suggested_unit = Some(TimeUnit::Second);
+        let fixed =
+            apply_audit_fix(&batch, &audits, &CastOptions::new().with_passthrough(false)).unwrap();
+        assert!(fixed.column(0).is_null(1));
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.safe = false;
+        assert!(apply_audit_fix(&batch, &audits, &options).is_err());
+        audits[0].name = "other".to_string();
+        assert!(
+            apply_audit_fix(&batch, &audits, &CastOptions::new().with_passthrough(false)).is_err()
+        );
+    }
+}
@@ -0,0 +1,1108 @@
+//! Casting record batches to a schema.
+
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{ArrowError, DataType, Field, FieldRef, Schema, SchemaRef, TimeUnit};
+
+use crate::{
+    annotate_epoch_extension, cast_with_report,
+    extension::epoch_unit,
+    guess,
+    kernel::{self, normalize_utc},
+    report::annotate_field,
+    string, CastOptions, CastReport, EpochKind, GuessStrategy, PreparedColumn, StringParseRoute,
+    TransformDescription, UtcSpelling,
+};
+
+/// Options for casting record batches to a schema.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaCastOptions<'a> {
+    /// Options for casting each column.
+    pub cast_options: CastOptions<'a>,
+    /// Groups of column names whose timestamp unit is guessed once and applied to all of them.
+    ///
+    /// The unit is guessed from the first column in the group with a usable value. With
+    /// [strict](crate::TimestampCastOptions::strict), a column guessing another unit on its own
+    /// fails the cast.
+    pub consistency_groups: Vec<Vec<String>>,
+    /// If true, output fields are annotated with the source unit decided for them,
+    /// see [SOURCE_UNIT_METADATA_KEY](crate::SOURCE_UNIT_METADATA_KEY).
+    pub annotate_metadata: bool,
+    /// If true, a target field without a source column of the same name is matched to the
+    /// source column at the same position.
+    pub position_fallback: bool,
+    /// Target fields whose units are guessed, by name.
+    ///
+    /// Other fields are cast without guessing, unless named in a consistency group.
+    pub guess_column_filter: Option<ColumnFilter>,
+    /// If true, source columns with epoch metadata are taken in its unit without guessing,
+    /// see [resolve_epoch_extension](crate::resolve_epoch_extension).
+    ///
+    /// With [annotate_metadata](Self::annotate_metadata), output fields are also tagged
+    /// with the decided units by [annotate_epoch_extension](crate::annotate_epoch_extension).
+    pub epoch_extensions: bool,
+    /// If true, output timestamp fields carry a description of their cast, to invert it
+    /// with [invert_cast](crate::invert_cast), see
+    /// [TRANSFORM_METADATA_KEY](crate::TRANSFORM_METADATA_KEY).
+    pub embed_transform: bool,
+    /// If true, columns are cast in parallel on the rayon thread pool.
+    ///
+    /// Columns of the target type already are not dispatched to the pool. The output and
+    /// the error, the one of the first failing column, are the same as casting serially.
+    #[cfg(feature = "rayon")]
+    pub parallel: bool,
+}
+
+impl<'a> SchemaCastOptions<'a> {
+    pub fn new(cast_options: CastOptions<'a>) -> Self {
+        Self {
+            cast_options,
+            consistency_groups: Vec::new(),
+            annotate_metadata: false,
+            position_fallback: false,
+            guess_column_filter: None,
+            epoch_extensions: false,
+            embed_transform: false,
+            #[cfg(feature = "rayon")]
+            parallel: false,
+        }
+    }
+
+    /// Add a consistency group of column names.
+    pub fn with_consistency_group<S: Into<String>>(
+        mut self,
+        columns: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.consistency_groups
+            .push(columns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Annotate output fields with the decided source unit.
+    pub fn with_annotate_metadata(mut self, annotate_metadata: bool) -> Self {
+        self.annotate_metadata = annotate_metadata;
+        self
+    }
+
+    /// Match target fields to source columns by position when no name matches.
+    pub fn with_position_fallback(mut self, position_fallback: bool) -> Self {
+        self.position_fallback = position_fallback;
+        self
+    }
+
+    /// Take source columns in the unit of their epoch metadata.
+    pub fn with_epoch_extensions(mut self, epoch_extensions: bool) -> Self {
+        self.epoch_extensions = epoch_extensions;
+        self
+    }
+
+    /// Describe the casts of timestamp fields in their metadata.
+    pub fn with_embed_transform(mut self, embed_transform: bool) -> Self {
+        self.embed_transform = embed_transform;
+        self
+    }
+
+    /// Cast columns in parallel.
+    #[cfg(feature = "rayon")]
+    pub fn with_parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    /// Guess units only for the target fields matched by the filter.
+    pub fn with_guess_column_filter(mut self, filter: ColumnFilter) -> Self {
+        self.guess_column_filter = Some(filter);
+        self
+    }
+
+    /// Whether the unit of the target field is guessed by the filter and consistency groups.
+    pub(crate) fn guesses_column(&self, name: &str) -> bool {
+        let Some(filter) = &self.guess_column_filter else {
+            return true;
+        };
+        filter.matches(name) || self.consistency_groups.iter().flatten().any(|n| n == name)
+    }
+}
+
+/// Filter of column names by exact names or glob patterns.
+///
+/// Patterns match the whole name, `*` matches any characters and `?` one character.
+/// A name matches if it matches any included pattern, or no pattern is included,
+/// and no excluded pattern.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnFilter {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl ColumnFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Include names matching the pattern.
+    pub fn with_include(mut self, pattern: impl Into<String>) -> Self {
+        self.include.push(pattern.into());
+        self
+    }
+
+    /// Exclude names matching the pattern.
+    pub fn with_exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.exclude.push(pattern.into());
+        self
+    }
+
+    /// Whether the name passes the filter.
+    pub fn matches(&self, name: &str) -> bool {
+        let name: Vec<char> = name.chars().collect();
+        let matching = |pattern: &String| {
+            let pattern: Vec<char> = pattern.chars().collect();
+            glob_match(&pattern, &name)
+        };
+        (self.include.is_empty() || self.include.iter().any(matching))
+            && !self.exclude.iter().any(matching)
+    }
+}
+
+/// Match the whole name by a glob pattern of `*` and `?`.
+fn glob_match(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|i| glob_match(rest, &name[i..])),
+        Some((p, rest)) => match name.split_first() {
+            Some((c, name)) if *p == '?' || p == c => glob_match(rest, name),
+            _ => false,
+        },
+    }
+}
+
+/// Cast a record batch to the schema, matching columns by name.
+///
+/// With [position_fallback](SchemaCastOptions::position_fallback), unmatched fields are
+/// matched by position.
+///
+/// With [annotate_metadata](SchemaCastOptions::annotate_metadata), the schema of the output
+/// batch carries the decided source units in field metadata, and with
+/// [embed_transform](SchemaCastOptions::embed_transform) the descriptions of the casts.
+pub fn cast_record_batch(
+    batch: &RecordBatch,
+    schema: SchemaRef,
+    options: &SchemaCastOptions,
+) -> Result<RecordBatch, ArrowError> {
+    let mut pins = vec![None; schema.fields().len()];
+    let (sources, columns) = resolve_columns(batch, &schema, options)?;
+    cast_batch(&columns, &sources, schema, options, &mut pins)
+}
+
+/// The schema with UTC timezones spelled by
+/// [normalize_utc_tz](crate::TimestampCastOptions::normalize_utc_tz).
+pub(crate) fn normalize_utc_schema(schema: SchemaRef, cast_options: &CastOptions) -> SchemaRef {
+    let options = &cast_options.timestamp_options;
+    if options
+        .normalize_utc_tz
+        .and_then(UtcSpelling::as_tz)
+        .is_none()
+    {
+        return schema;
+    }
+    let fields: Vec<Field> = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let data_type = normalize_utc(field.data_type(), options).into_owned();
+            field.as_ref().clone().with_data_type(data_type)
+        })
+        .collect();
+    Arc::new(Schema::new_with_metadata(fields, schema.metadata().clone()))
+}
+
+/// Index of the source column matched to the target field at `index`.
+pub(crate) fn source_index(
+    from: &Schema,
+    index: usize,
+    name: &str,
+    options: &SchemaCastOptions,
+) -> Option<usize> {
+    from.index_of(name)
+        .ok()
+        .or_else(|| (options.position_fallback && index < from.fields().len()).then_some(index))
+}
+
+/// Source fields and columns of the batch in the order of target fields.
+fn resolve_columns<'b>(
+    batch: &'b RecordBatch,
+    schema: &Schema,
+    options: &SchemaCastOptions,
+) -> Result<(Vec<FieldRef>, Vec<&'b ArrayRef>), ArrowError> {
+    let from = batch.schema();
+    let mut sources = Vec::with_capacity(schema.fields().len());
+    let mut columns = Vec::with_capacity(schema.fields().len());
+    for (index, field) in schema.fields().iter().enumerate() {
+        let Some(index) = source_index(&from, index, field.name(), options) else {
+            return Err(ArrowError::SchemaError(format!(
+                "Column {} not found in batch",
+                field.name()
+            )));
+        };
+        sources.push(from.fields()[index].clone());
+        columns.push(batch.column(index));
+    }
+    Ok((sources, columns))
+}
+
+/// Source unit decided for a column, with the strategy deciding it.
+pub(crate) type Pin = Option<(TimeUnit, &'static str)>;
+
+/// Cast batches to the schema one by one, pinning the unit of each column once decided.
+///
+/// The unit of a column is decided by the first batch with evidence, later batches
+/// are cast in the same unit, so a stream of batches is cast consistently.
+///
+/// With [guess_skip_rows](crate::TimestampCastOptions::guess_skip_rows), values are skipped
+/// across batches: batches with no more values than left to skip are guessed on their own
+/// without deciding the unit. Columns of consistency groups skip within each batch.
+#[derive(Debug, Clone)]
+pub struct BatchCaster<'a> {
+    schema: SchemaRef,
+    options: SchemaCastOptions<'a>,
+    pins: Vec<Pin>,
+    /// Values left to skip before the representative value of each column.
+    skips: Vec<usize>,
+}
+
+impl<'a> BatchCaster<'a> {
+    pub fn new(schema: SchemaRef, options: SchemaCastOptions<'a>) -> Self {
+        let schema = normalize_utc_schema(schema, &options.cast_options);
+        let pins = vec![None; schema.fields().len()];
+        let timestamp_options = &options.cast_options.timestamp_options;
+        let skipping = timestamp_options.guess_timestamp_precision
+            && timestamp_options.assume_unit.is_none()
+            && timestamp_options.epoch_kind == EpochKind::Unix
+            && matches!(
+                timestamp_options.guess_strategy,
+                GuessStrategy::FirstNonNull | GuessStrategy::PerValue
+            );
+        let skips = schema
+            .fields()
+            .iter()
+            .map(|field| {
+                let grouped = options.consistency_groups.iter().flatten();
+                match grouped.clone().any(|name| name == field.name()) {
+                    false if skipping && options.guesses_column(field.name()) => {
+                        timestamp_options.guess_skip_rows
+                    }
+                    _ => 0,
+                }
+            })
+            .collect();
+        Self {
+            schema,
+            options,
+            pins,
+            skips,
+        }
+    }
+
+    /// The target schema.
+    pub fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    /// Units pinned for the target fields so far, `None` if not decided yet.
+    pub fn pinned_units(&self) -> Vec<Option<TimeUnit>> {
+        self.pins
+            .iter()
+            .map(|pin| pin.map(|(unit, _)| unit))
+            .collect()
+    }
+
+    /// Cast the batch to the schema, see [cast_record_batch].
+    pub fn cast(&mut self, batch: &RecordBatch) -> Result<RecordBatch, ArrowError> {
+        let (sources, columns) = resolve_columns(batch, &self.schema, &self.options)?;
+        let warming = self.skip_values(&columns, &sources)?;
+        let casted = cast_batch(
+            &columns,
+            &sources,
+            self.schema.clone(),
+            &self.options,
+            &mut self.pins,
+        )?;
+        let strategy = self.options.cast_options.timestamp_options.guess_strategy;
+        for index in warming {
+            if matches!(self.pins[index], Some((_, decided_by)) if decided_by == strategy.as_str())
+            {
+                self.pins[index] = None;
+            }
+        }
+        Ok(casted)
+    }
+
+    /// Count the values of the batch against the values left to skip, pinning columns
+    /// with their representative value in the batch.
+    ///
+    /// Returns the columns whose values are all skipped.
+    fn skip_values(
+        &mut self,
+        columns: &[&ArrayRef],
+        sources: &[FieldRef],
+    ) -> Result<Vec<usize>, ArrowError> {
+        let cast_options = &self.options.cast_options;
+        let mut warming = Vec::new();
+        for (index, (column, source)) in columns.iter().zip(sources).enumerate() {
+            let left = self.skips[index];
+            if left == 0 || self.pins[index].is_some() {
+                continue;
+            }
+            if self.options.epoch_extensions
+                && matches!(epoch_unit(source, cast_options), Ok(Some(_)))
+            {
+                continue;
+            }
+            let mut options = cast_options.clone();
+            options.timestamp_options.guess_skip_rows = left;
+            let Ok(prepared) = PreparedColumn::try_new(column.as_ref(), &options) else {
+                continue;
+            };
+            let count = guess::candidates(prepared.integers(), &options.timestamp_options).count();
+            if count <= left {
+                self.skips[index] -= count;
+                warming.push(index);
+                continue;
+            }
+            self.skips[index] = 0;
+            let strategy = options.timestamp_options.guess_strategy;
+            self.pins[index] = prepared
+                .guessed_unit()?
+                .map(|unit| (unit, strategy.as_str()));
+        }
+        Ok(warming)
+    }
+}
+
+/// Cast source columns of the source fields, in the order of target fields, with units
+/// pinned in `pins`, pinning columns decided by this batch.
+pub(crate) fn cast_batch(
+    columns: &[&ArrayRef],
+    sources: &[FieldRef],
+    schema: SchemaRef,
+    options: &SchemaCastOptions,
+    pins: &mut [Pin],
+) -> Result<RecordBatch, ArrowError> {
+    let schema = normalize_utc_schema(schema, &options.cast_options);
+    if options.epoch_extensions {
+        for (pin, source) in pins.iter_mut().zip(sources) {
+            if let (None, Ok(Some(unit))) = (*pin, epoch_unit(source, &options.cast_options)) {
+                *pin = Some((unit, "epoch_extension"));
+            }
+        }
+    }
+    let groups = pin_group_units(columns, &schema, options, pins)?;
+    for (pin, unit) in pins.iter_mut().zip(groups) {
+        if let (None, Some(unit)) = (*pin, unit) {
+            *pin = Some((unit, "consistency_group"));
+        }
+    }
+    let routes = sample_string_routes(columns, &schema, &options.cast_options);
+    let cast_options: Vec<Cow<CastOptions>> = pins
+        .iter()
+        .zip(schema.fields())
+        .map(|(pin, field)| match pin {
+            Some((unit, _)) => {
+                let mut cast_options = options.cast_options.clone();
+                cast_options.timestamp_options.assume_unit = Some(*unit);
+                Cow::Owned(cast_options)
+            }
+            None if !options.guesses_column(field.name()) => {
+                let mut cast_options = options.cast_options.clone();
+                cast_options.timestamp_options.guess_timestamp_precision = false;
+                Cow::Owned(cast_options)
+            }
+            None => Cow::Borrowed(&options.cast_options),
+        })
+        .zip(routes)
+        .map(|(mut cast_options, route)| {
+            if let Some(route) = route {
+                cast_options.to_mut().timestamp_options.string_parse_route = route;
+            }
+            cast_options
+        })
+        .collect();
+    let cast = |index: usize| -> Casted {
+        let field = schema.field(index);
+        let column = columns[index].as_ref();
+        cast_with_report(column, field.data_type(), &cast_options[index]).map_err(|e| {
+            match (kernel::invalid_tz(column.data_type()), e) {
+                (Some(_), ArrowError::CastError(e)) => {
+                    ArrowError::CastError(format!("Column {}: {e}", field.name()))
+                }
+                (_, e) => e,
+            }
+        })
+    };
+    #[cfg(feature = "rayon")]
+    let mut parallel = options
+        .parallel
+        .then(|| cast_in_parallel(columns, &schema, &cast).into_iter());
+    #[cfg(not(feature = "rayon"))]
+    let mut parallel: Option<std::vec::IntoIter<Casted>> = None;
+    let mut fields = Vec::with_capacity(schema.fields().len());
+    let mut casted = Vec::with_capacity(schema.fields().len());
+    for (index, (field, pin)) in schema.fields().iter().zip(pins.iter_mut()).enumerate() {
+        let result = match parallel.as_mut() {
+            Some(results) => results.next().expect("one result per column"),
+            None => cast(index),
+        };
+        let (column, report) = result?;
+        casted.push(column);
+        if let (None, Some(unit)) = (*pin, report.guessed_unit) {
+            *pin = Some((unit, report.guess_strategy.unwrap_or_default().as_str()));
+        }
+        if !options.annotate_metadata && !options.embed_transform {
+            continue;
+        }
+        let mut field = field.as_ref().clone();
+        if options.annotate_metadata {
+            field = match pin {
+                Some((unit, strategy)) if options.epoch_extensions => {
+                    annotate_epoch_extension(annotate_field(field, *unit, strategy), *unit)
+                }
+                Some((unit, strategy)) => annotate_field(field, *unit, strategy),
+                None => field,
+            };
+        }
+        if options.embed_transform {
+            let source_type = columns[index].data_type();
+            let description = TransformDescription::new(
+                source_type,
+                field.data_type(),
+                &report,
+                &cast_options[index],
+            );
+            if let Some(description) = description {
+                field = description.annotate_field(field);
+            }
+        }
+        fields.push(field);
+    }
+    if !options.annotate_metadata && !options.embed_transform {
+        return RecordBatch::try_new(schema, casted);
+    }
+    let schema = Schema::new_with_metadata(fields, schema.metadata().clone());
+    RecordBatch::try_new(Arc::new(schema), casted)
+}
+
+/// Routes of string columns casting to timestamps, classified up front by sampling each
+/// column, so each column takes one parsing route. `None` for other columns and routes
+/// given in the options.
+fn sample_string_routes(
+    columns: &[&ArrayRef],
+    schema: &Schema,
+    cast_options: &CastOptions,
+) -> Vec<Option<StringParseRoute>> {
+    let options = &cast_options.timestamp_options;
+    columns
+        .iter()
+        .zip(schema.fields())
+        .map(|(column, field)| {
+            let sampled = options.string_parse_route == StringParseRoute::Sampled
+                && matches!(field.data_type(), DataType::Timestamp(_, _))
+                && matches!(column.data_type(), DataType::Utf8 | DataType::LargeUtf8);
+            sampled.then(|| string::sample_route(column, options))
+        })
+        .collect()
+}
+
+/// A casted column with its report.
+type Casted = Result<(ArrayRef, CastReport), ArrowError>;
+
+/// Cast columns by `cast` on the rayon thread pool, in the order of target fields.
+///
+/// Columns of the target type already are cast on the calling thread.
+#[cfg(feature = "rayon")]
+fn cast_in_parallel(
+    columns: &[&ArrayRef],
+    schema: &Schema,
+    cast: &(impl Fn(usize) -> Casted + Sync),
+) -> Vec<Casted> {
+    use rayon::prelude::*;
+
+    let mut results: Vec<Option<Casted>> = columns
+        .iter()
+        .zip(schema.fields())
+        .enumerate()
+        .map(|(index, (column, field))| {
+            (column.data_type() == field.data_type()).then(|| cast(index))
+        })
+        .collect();
+    results
+        .par_iter_mut()
+        .enumerate()
+        .filter(|(_, result)| result.is_none())
+        .for_each(|(index, result)| *result = Some(cast(index)));
+    results.into_iter().map(Option::unwrap).collect()
+}
+
+/// Guess the unit of each consistency group, in the order of target fields.
+///
+/// Groups with any column pinned already are skipped.
+fn pin_group_units(
+    columns: &[&ArrayRef],
+    schema: &SchemaRef,
+    options: &SchemaCastOptions,
+    pins: &[Pin],
+) -> Result<Vec<Option<TimeUnit>>, ArrowError> {
+    let timestamp_options = &options.cast_options.timestamp_options;
+    let mut pinned = vec![None; schema.fields().len()];
+    if !timestamp_options.guess_timestamp_precision || timestamp_options.assume_unit.is_some() {
+        return Ok(pinned);
+    }
+    for group in &options.consistency_groups {
+        let any_pinned = group
+            .iter()
+            .any(|name| matches!(schema.index_of(name), Ok(index) if pins[index].is_some()));
+        if any_pinned {
+            continue;
+        }
+        // (target field index, column name, guessed unit of the column alone)
+        let mut guesses = Vec::with_capacity(group.len());
+        for name in group {
+            let Ok(index) = schema.index_of(name) else {
+                continue;
+            };
+            if !matches!(schema.field(index).data_type(), DataType::Timestamp(_, _)) {
+                continue;
+            }
+            // Columns of any source type casting to timestamps count, as in skip_values.
+            let guessed =
+                match PreparedColumn::try_new(columns[index].as_ref(), &options.cast_options) {
+                    Ok(prepared) => prepared.guessed_unit()?,
+                    Err(_) => None,
+                };
+            guesses.push((index, name, guessed));
+        }
+        let Some((_, decided_by, unit)) = guesses
+            .iter()
+            .find_map(|(index, name, guessed)| guessed.map(|unit| (index, name, unit)))
+        else {
+            continue;
+        };
+        for (index, name, guessed) in &guesses {
+            match guessed {
+                Some(guessed) if *guessed != unit && timestamp_options.strict => {
+                    return Err(ArrowError::CastError(format!(
+                        "Column {name} guessed as {guessed:?} conflicts with column {decided_by} guessed as {unit:?} in the consistency group"
+                    )));
+                }
+                _ => pinned[*index] = Some(unit),
+            }
+        }
+    }
+    Ok(pinned)
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::{
+        cast::AsArray, types::TimestampMillisecondType, Array, Int32Array, Int64Array, StringArray,
+        StructArray, TimestampMillisecondArray,
+    };
+    use arrow_schema::{Field, Schema};
+
+    use super::*;
+    use crate::TzRepair;
+
+    #[test]
+    fn test_consistency_groups() {
+        // `end_ts` starts with a legacy outlier which looks like seconds.
+        let batch = RecordBatch::try_from_iter([
+            (
+                "start_ts",
+                Arc::new(Int64Array::from(vec![1701325744956, 1701325744956])) as ArrayRef,
+            ),
+            (
+                "end_ts",
+                Arc::new(Int64Array::from(vec![1701325744, 1701325745956])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                "start_ts",
+                DataType::Timestamp(TimeUnit::Millisecond, None),
+                true,
+            ),
+            Field::new(
+                "end_ts",
+                DataType::Timestamp(TimeUnit::Millisecond, None),
+                true,
+            ),
+        ]));
+
+        let options = SchemaCastOptions::new(CastOptions::new().with_passthrough(false));
+        let casted = cast_record_batch(&batch, schema.clone(), &options).unwrap();
+        let end = casted.column(1).as_primitive::<TimestampMillisecondType>();
+        assert_eq!(end.value(0), 1701325744000);
+        assert_eq!(end.value(1), 1701325745956000);
+
+        let mut options = options.with_consistency_group(["start_ts", "end_ts"]);
+        let casted = cast_record_batch(&batch, schema.clone(), &options).unwrap();
+        let end = casted.column(1).as_primitive::<TimestampMillisecondType>();
+        assert_eq!(end.value(0), 1701325744);
+        assert_eq!(end.value(1), 1701325745956);
+
+        options.cast_options.timestamp_options.strict = true;
+        let err = cast_record_batch(&batch, schema, &options).unwrap_err();
+        assert!(err.to_string().contains("end_ts guessed as Second"));
+        assert!(err.to_string().contains("start_ts guessed as Millisecond"));
+
+        // Narrow integers and numeric strings count in the group too.
+        let batch = RecordBatch::try_from_iter([
+            (
+                "secs",
+                Arc::new(Int32Array::from(vec![1701325744])) as ArrayRef,
+            ),
+            (
+                "strs",
+                Arc::new(StringArray::from(vec!["1701325745956"])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+        let ts = DataType::Timestamp(TimeUnit::Millisecond, None);
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("secs", ts.clone(), true),
+            Field::new("strs", ts, true),
+        ]));
+        let mut options = SchemaCastOptions::new(CastOptions::new().with_passthrough(false))
+            .with_consistency_group(["secs", "strs"]);
+        let casted = cast_record_batch(&batch, schema.clone(), &options).unwrap();
+        let secs = casted.column(0).as_primitive::<TimestampMillisecondType>();
+        let strs = casted.column(1).as_primitive::<TimestampMillisecondType>();
+        assert_eq!(secs.value(0), 1701325744000);
+        assert_eq!(strs.value(0), 1701325745956000);
+
+        options.cast_options.timestamp_options.strict = true;
+        let err = cast_record_batch(&batch, schema, &options).unwrap_err();
+        assert!(err.to_string().contains("strs guessed as Millisecond"));
+        assert!(err.to_string().contains("secs guessed as Second"));
+    }
+
+    #[test]
+    fn test_annotate_metadata() {
+        let batch = RecordBatch::try_from_iter([
+            (
+                "a",
+                Arc::new(Int64Array::from(vec![1701325744956])) as ArrayRef,
+            ),
+            (
+                "b",
+                Arc::new(Int64Array::from(vec![1701325744])) as ArrayRef,
+            ),
+            (
+                "c",
+                Arc::new(Int64Array::from(vec![1701325744])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+        let ts = DataType::Timestamp(TimeUnit::Nanosecond, None);
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", ts.clone(), true).with_metadata(
+                [("comment".to_string(), "kept".to_string())]
+                    .into_iter()
+                    .collect(),
+            ),
+            Field::new("b", ts.clone(), true),
+            Field::new("c", DataType::Int64, true),
+        ]));
+
+        let casted = cast_record_batch(
+            &batch,
+            schema.clone(),
+            &SchemaCastOptions::new(CastOptions::new().with_passthrough(false)),
+        )
+        .unwrap();
+        assert_eq!(casted.schema(), schema);
+
+        let options = SchemaCastOptions::new(CastOptions::new().with_passthrough(false))
+            .with_consistency_group(["b"])
+            .with_annotate_metadata(true);
+        let casted = cast_record_batch(&batch, schema, &options).unwrap();
+
+        // Round trip through IPC.
+        let mut buf = Vec::new();
+        let mut writer =
+            arrow::ipc::writer::StreamWriter::try_new(&mut buf, &casted.schema()).unwrap();
+        writer.write(&casted).unwrap();
+        writer.finish().unwrap();
+        drop(writer);
+        let reader = arrow::ipc::reader::StreamReader::try_new(buf.as_slice(), None).unwrap();
+        let schema = reader.schema();
+
+        let a = schema.field(0).metadata();
+        assert_eq!(a["comment"], "kept");
+        assert_eq!(a[crate::SOURCE_UNIT_METADATA_KEY], "ms");
+        assert_eq!(a[crate::STRATEGY_METADATA_KEY], "first_non_null");
+        let b = schema.field(1).metadata();
+        assert_eq!(b[crate::SOURCE_UNIT_METADATA_KEY], "s");
+        assert_eq!(b[crate::STRATEGY_METADATA_KEY], "consistency_group");
+        assert!(schema.field(2).metadata().is_empty());
+    }
+
+    #[test]
+    fn test_batch_caster() {
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "ts",
+            DataType::Timestamp(TimeUnit::Millisecond, None),
+            true,
+        )]));
+        let batch = |v: Option<i64>| {
+            RecordBatch::try_from_iter([("ts", Arc::new(Int64Array::from(vec![v])) as ArrayRef)])
+                .unwrap()
+        };
+        let mut caster = BatchCaster::new(
+            schema,
+            SchemaCastOptions::new(CastOptions::new().with_passthrough(false)),
+        );
+        caster.cast(&batch(None)).unwrap();
+        assert_eq!(caster.pinned_units(), vec![None]);
+        caster.cast(&batch(Some(1701325744956))).unwrap();
+        assert_eq!(caster.pinned_units(), vec![Some(TimeUnit::Millisecond)]);
+        let casted = caster.cast(&batch(Some(1701325744))).unwrap();
+        let casted = casted.column(0).as_primitive::<TimestampMillisecondType>();
+        assert_eq!(casted.value(0), 1701325744);
+
+        // Values are skipped across batches before pinning.
+        let batch = |v: Vec<Option<i64>>| {
+            RecordBatch::try_from_iter([("ts", Arc::new(Int64Array::from(v)) as ArrayRef)]).unwrap()
+        };
+        let mut options = SchemaCastOptions::new(CastOptions::new().with_passthrough(false));
+        options.cast_options.timestamp_options.guess_skip_rows = 2;
+        let mut caster = BatchCaster::new(caster.schema(), options);
+        let casted = caster.cast(&batch(vec![Some(7), None])).unwrap();
+        assert_eq!(caster.pinned_units(), vec![None]);
+        assert_eq!(
+            casted
+                .column(0)
+                .as_primitive::<TimestampMillisecondType>()
+                .value(0),
+            7000
+        );
+        caster
+            .cast(&batch(vec![None, Some(8), Some(1701325744956)]))
+            .unwrap();
+        assert_eq!(caster.pinned_units(), vec![Some(TimeUnit::Millisecond)]);
+    }
+
+    #[test]
+    fn test_guess_column_filter() {
+        let batch = RecordBatch::try_from_iter([
+            (
+                "event_ts",
+                Arc::new(Int64Array::from(vec![1701325744956])) as ArrayRef,
+            ),
+            (
+                "retry_count_ms",
+                Arc::new(Int64Array::from(vec![1701325744])) as ArrayRef,
+            ),
+            (
+                "duration",
+                Arc::new(Int64Array::from(vec![1701325744])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+        let ts = DataType::Timestamp(TimeUnit::Millisecond, None);
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("event_ts", ts.clone(), true),
+            Field::new("retry_count_ms", ts.clone(), true),
+            Field::new("duration", ts, true),
+        ]));
+        let filter = ColumnFilter::new()
+            .with_include("*_ts")
+            .with_include("*_ms")
+            .with_include("event_at")
+            .with_exclude("retry_count_*");
+        assert!(filter.matches("event_at"));
+        assert!(!filter.matches("event_at2"));
+        assert!(!filter.matches("retry_count_ms"));
+        let options = SchemaCastOptions::new(CastOptions::new().with_passthrough(false))
+            .with_guess_column_filter(filter);
+        let casted = cast_record_batch(&batch, schema.clone(), &options).unwrap();
+        let value = |i: usize| {
+            casted
+                .column(i)
+                .as_primitive::<TimestampMillisecondType>()
+                .value(0)
+        };
+        assert_eq!(value(0), 1701325744956);
+        assert_eq!(value(1), 1701325744);
+        assert_eq!(value(2), 1701325744);
+
+        // Consistency groups name columns explicitly.
+        let options = options.with_consistency_group(["duration"]);
+        let casted = cast_record_batch(&batch, schema, &options).unwrap();
+        let value = |i: usize| {
+            casted
+                .column(i)
+                .as_primitive::<TimestampMillisecondType>()
+                .value(0)
+        };
+        assert_eq!(value(1), 1701325744);
+        assert_eq!(value(2), 1701325744000);
+    }
+
+    #[test]
+    fn test_repair_invalid_tz() {
+        let ms = 1701325744956;
+        let good = RecordBatch::try_from_iter([
+            ("a", Arc::new(Int64Array::from(vec![ms])) as ArrayRef),
+            (
+                "b",
+                Arc::new(TimestampMillisecondArray::from(vec![ms]).with_timezone("+08:00")),
+            ),
+        ])
+        .unwrap();
+        let bad = Arc::new(TimestampMillisecondArray::from(vec![ms]).with_timezone("GMT+8:00"));
+        let nested = StructArray::from(vec![(
+            Arc::new(Field::new("ts", bad.data_type().clone(), true)),
+            bad.clone() as ArrayRef,
+        )]);
+        let mut columns = good.columns().to_vec();
+        columns.push(bad);
+        columns.push(Arc::new(nested));
+        let mut fields = good.schema().fields().to_vec();
+        fields.push(Arc::new(Field::new(
+            "c",
+            columns[2].data_type().clone(),
+            true,
+        )));
+        fields.push(Arc::new(Field::new(
+            "d",
+            columns[3].data_type().clone(),
+            true,
+        )));
+        let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).unwrap();
+
+        // Formatting parses the timezone.
+        let ts = DataType::Timestamp(TimeUnit::Second, None);
+        let nested = DataType::Struct(vec![Field::new("ts", DataType::Utf8, true)].into());
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", ts, true),
+            Field::new("b", DataType::Utf8, true),
+            Field::new("c", DataType::Utf8, true),
+            Field::new("d", nested, true),
+        ]));
+        let good_schema = Arc::new(Schema::new(schema.fields()[..2].to_vec()));
+        let expected = cast_record_batch(
+            &good,
+            good_schema,
+            &SchemaCastOptions::new(CastOptions::new().with_passthrough(false)),
+        )
+        .unwrap();
+
+        let mut options = SchemaCastOptions::new(CastOptions::new().with_passthrough(false));
+        let err = cast_record_batch(&batch, schema.clone(), &options).unwrap_err();
+        assert!(err.to_string().contains("Column c"), "{err}");
+        assert!(err.to_string().contains("\"GMT+8:00\""), "{err}");
+        let timestamp_options = &mut options.cast_options.timestamp_options;
+        timestamp_options.repair_invalid_tz = Some(TzRepair::Error);
+        let err = cast_record_batch(&batch, schema.clone(), &options).unwrap_err();
+        assert!(err.to_string().contains("Column c"), "{err}");
+
+        // The instants are kept, only the timezone is repaired.
+        let timestamp_options = &mut options.cast_options.timestamp_options;
+        timestamp_options.repair_invalid_tz = Some(TzRepair::Strip);
+        let casted = cast_record_batch(&batch, schema.clone(), &options).unwrap();
+        assert_eq!(casted.columns()[..2], expected.columns()[..]);
+        let c = casted.column(2).as_string::<i32>();
+        assert_eq!(c.value(0), "2023-11-30T06:29:04.956");
+        let d = casted.column(3).as_struct().column(0);
+        assert_eq!(d.as_ref(), c as &dyn Array);
+
+        let timestamp_options = &mut options.cast_options.timestamp_options;
+        timestamp_options.repair_invalid_tz = Some(TzRepair::Replace("+08:00".into()));
+        let casted = cast_record_batch(&batch, schema, &options).unwrap();
+        assert_eq!(casted.column(2), expected.column(1));
+        let (_, report) =
+            crate::cast_with_report(batch.column(2), &DataType::Utf8, &options.cast_options)
+                .unwrap();
+        assert_eq!(report.invalid_tz.as_deref(), Some("GMT+8:00"));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel() {
+        let ts = DataType::Timestamp(TimeUnit::Millisecond, None);
+        let mut fields = Vec::new();
+        let mut columns: Vec<ArrayRef> = Vec::new();
+        for i in 0..64 {
+            let (field, column): (_, ArrayRef) = match i % 4 {
+                0 => (
+                    Field::new(format!("noop{i}"), DataType::Int64, true),
+                    Arc::new(Int64Array::from(vec![i])),
+                ),
+                1 => (
+                    Field::new(format!("ms{i}"), ts.clone(), true),
+                    Arc::new(Int64Array::from(vec![1701325744956 + i])),
+                ),
+                2 => (
+                    Field::new(format!("s{i}"), ts.clone(), true),
+                    Arc::new(Int64Array::from(vec![1701325744 + i])),
+                ),
+                _ => (
+                    Field::new(format!("str{i}"), ts.clone(), true),
+                    Arc::new(arrow_array::StringArray::from(vec![format!("bad{i}")])),
+                ),
+            };
+            fields.push(field);
+            columns.push(column);
+        }
+        let schema = Arc::new(Schema::new(fields));
+        let source = Schema::new(
+            schema
+                .fields()
+                .iter()
+                .zip(&columns)
+                .map(|(field, column)| Field::new(field.name(), column.data_type().clone(), true))
+                .collect::<Vec<_>>(),
+        );
+        let batch = RecordBatch::try_new(Arc::new(source), columns).unwrap();
+
+        let serial = SchemaCastOptions::new(CastOptions::new().with_passthrough(false))
+            .with_annotate_metadata(true);
+        let parallel = serial.clone().with_parallel(true);
+        assert_eq!(
+            cast_record_batch(&batch, schema.clone(), &serial).unwrap(),
+            cast_record_batch(&batch, schema.clone(), &parallel).unwrap()
+        );
+
+        let mut serial = serial;
+        serial.cast_options.safe = false;
+        let mut parallel = parallel;
+        parallel.cast_options.safe = false;
+        let err = cast_record_batch(&batch, schema.clone(), &serial).unwrap_err();
+        assert!(err.to_string().contains("bad3"));
+        for _ in 0..8 {
+            let parallel_err = cast_record_batch(&batch, schema.clone(), &parallel).unwrap_err();
+            assert_eq!(parallel_err.to_string(), err.to_string());
+        }
+    }
+
+    #[test]
+    fn test_wide_numeric_strings() {
+        use arrow_array::StringArray;
+        // Columns of epoch strings, with timestamps and integers mixed after the samples.
+        let mut numbers: Vec<Option<String>> = (0..16)
+            .map(|i| Some(format!("{}", 1701325744956_i64 + i)))
+            .collect();
+        numbers[3] = None;
+        let mut mixed = numbers.clone();
+        mixed[12] = Some("2023-11-30T06:29:04.956Z".to_string());
+        let mut bad = numbers.clone();
+        bad[15] = Some("bad".to_string());
+        let ts = DataType::Timestamp(TimeUnit::Millisecond, None);
+        let mut columns: Vec<(String, ArrayRef)> = (0..500)
+            .map(|i| {
+                let column = StringArray::from(numbers.clone());
+                (format!("c{i}"), Arc::new(column) as ArrayRef)
+            })
+            .collect();
+        columns.push(("mixed".into(), Arc::new(StringArray::from(mixed))));
+        columns.push(("bad".into(), Arc::new(StringArray::from(bad))));
+        let iso = vec!["2023-11-30T06:29:04.956Z"; 16];
+        columns.push(("iso".into(), Arc::new(StringArray::from(iso))));
+        let batch = RecordBatch::try_from_iter(columns).unwrap();
+        let schema = Arc::new(Schema::new(
+            batch
+                .schema()
+                .fields()
+                .iter()
+                .map(|field| Field::new(field.name(), ts.clone(), true))
+                .collect::<Vec<_>>(),
+        ));
+        let options = SchemaCastOptions::new(CastOptions::new().with_passthrough(false));
+        #[cfg(feature = "rayon")]
+        let options = options.with_parallel(true);
+        // Columns are classified up front, with timestamps mixed in after the samples too.
+        let routes = sample_string_routes(
+            &batch.columns().iter().collect::<Vec<_>>(),
+            &schema,
+            &options.cast_options,
+        );
+        assert!(routes[..502]
+            .iter()
+            .all(|route| *route == Some(StringParseRoute::Integers)));
+        assert_eq!(routes[502], Some(StringParseRoute::Timestamps));
+        let casted = cast_record_batch(&batch, schema, &options).unwrap();
+        let expected: Vec<Option<i64>> = numbers
+            .iter()
+            .map(|s| s.as_ref().map(|s| s.parse().unwrap()))
+            .collect();
+        for column in casted.columns().iter().take(500) {
+            let column = column.as_primitive::<TimestampMillisecondType>();
+            assert_eq!(column.iter().collect::<Vec<_>>(), expected);
+        }
+        // The timestamp mixed in takes the two steps, and the integers are null as before.
+        let mixed = casted
+            .column(500)
+            .as_primitive::<TimestampMillisecondType>();
+        assert_eq!(mixed.value(12), 1701325744956);
+        assert_eq!(mixed.null_count(), 15);
+        let bad = casted
+            .column(501)
+            .as_primitive::<TimestampMillisecondType>();
+        assert_eq!(bad.null_count(), 2);
+        assert_eq!(bad.value(0), 1701325744956);
+        let iso = casted
+            .column(502)
+            .as_primitive::<TimestampMillisecondType>();
+        assert!(iso.iter().all(|v| v == Some(1701325744956)));
+
+        let counts = |index: usize| {
+            let to_type = &ts;
+            let (_, report) =
+                crate::cast_with_report(batch.column(index), to_type, &options.cast_options)
+                    .unwrap();
+            report.string_parse_counts
+        };
+        assert_eq!(
+            (counts(0).numeric_epoch, counts(0).numeric_sampled),
+            (15, 15)
+        );
+        assert_eq!((counts(500).iso8601, counts(500).numeric_sampled), (1, 0));
+        assert_eq!(
+            (counts(501).numeric_epoch, counts(501).numeric_sampled),
+            (14, 0)
+        );
+
+        // Falling back from integers, only the strings which are not integers are parsed as
+        // timestamps, casting the same as parsing timestamps first.
+        for (index, safe, mixed_string_epochs) in (500..502)
+            .flat_map(|index| [(index, true), (index, false)])
+            .flat_map(|(index, safe)| [(index, safe, false), (index, safe, true)])
+        {
+            let cast = |route| {
+                let mut cast_options = options.cast_options.clone();
+                cast_options.safe = safe;
+                cast_options.timestamp_options.mixed_string_epochs = mixed_string_epochs;
+                cast_options.timestamp_options.string_parse_route = route;
+                crate::cast_with_report(batch.column(index), &ts, &cast_options)
+            };
+            match (
+                cast(StringParseRoute::Integers),
+                cast(StringParseRoute::Timestamps),
+            ) {
+                (Ok((casted, report)), Ok((expected, expected_report))) => {
+                    assert_eq!(casted.to_data(), expected.to_data());
+                    assert_eq!(
+                        report.string_parse_counts,
+                        expected_report.string_parse_counts
+                    );
+                    assert_eq!(report.null_causes, expected_report.null_causes);
+                }
+                (Err(e), Err(expected)) => assert_eq!(e.to_string(), expected.to_string()),
+                (casted, expected) => panic!("{casted:?} != {expected:?}"),
+            }
+        }
+    }
+}
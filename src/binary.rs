@@ -0,0 +1,144 @@
+//! Interpreting binary values as epoch timestamps.
+
+use arrow_array::{cast::AsArray, Array, Int64Array};
+use arrow_schema::{ArrowError, DataType};
+
+use crate::{CastOptions, CastReport, Endianness};
+
+/// Julian day number of the unix epoch.
+const JULIAN_DAY_OF_EPOCH: i64 = 2_440_588;
+const NANOS_PER_DAY: i64 = 86_400_000_000_000;
+
+/// Decode Parquet INT96 values of `FixedSizeBinary(12)` to epoch nanoseconds.
+pub(crate) fn decode_int96(
+    array: &dyn Array,
+    cast_options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<Int64Array, ArrowError> {
+    decode(array, 12, cast_options, report, |bytes| {
+        let nanos = i64::from_le_bytes(bytes[..8].try_into().unwrap());
+        let days = u32::from_le_bytes(bytes[8..].try_into().unwrap()) as i64;
+        (days - JULIAN_DAY_OF_EPOCH)
+            .checked_mul(NANOS_PER_DAY)?
+            .checked_add(nanos)
+    })
+}
+
+/// Decode 8-byte values to raw `i64` integers in the byte order.
+pub(crate) fn decode_raw_i64(
+    array: &dyn Array,
+    endianness: Endianness,
+    cast_options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<Int64Array, ArrowError> {
+    decode(array, 8, cast_options, report, |bytes| {
+        let bytes = bytes.try_into().unwrap();
+        Some(match endianness {
+            Endianness::Little => i64::from_le_bytes(bytes),
+            Endianness::Big => i64::from_be_bytes(bytes),
+        })
+    })
+}
+
+/// Decode values of `width` bytes by `f`, returning `None` on overflow.
+///
+/// Invalid values are null when `safe` or error otherwise.
+fn decode(
+    array: &dyn Array,
+    width: usize,
+    cast_options: &CastOptions,
+    report: &mut CastReport,
+    f: impl Fn(&[u8]) -> Option<i64>,
+) -> Result<Int64Array, ArrowError> {
+    let values: Vec<Option<&[u8]>> = match array.data_type() {
+        DataType::Binary => array.as_binary::<i32>().iter().collect(),
+        DataType::LargeBinary => array.as_binary::<i64>().iter().collect(),
+        DataType::FixedSizeBinary(_) => array.as_fixed_size_binary().iter().collect(),
+        other => {
+            return Err(ArrowError::CastError(format!(
+                "Cannot decode epoch integers from {other:?}"
+            )))
+        }
+    };
+    values
+        .into_iter()
+        .map(|bytes| {
+            let Some(bytes) = bytes else {
+                return Ok(None);
+            };
+            if bytes.len() != width {
+                if cast_options.safe {
+                    report.null_causes.parse_failure += 1;
+                    return Ok(None);
+                }
+                return Err(ArrowError::CastError(format!(
+                    "Cannot decode {} bytes as a {width}-byte epoch timestamp",
+                    bytes.len()
+                )));
+            }
+            match f(bytes) {
+                Some(v) => Ok(Some(v)),
+                None if cast_options.safe => {
+                    report.null_causes.overflow += 1;
+                    Ok(None)
+                }
+                None => Err(ArrowError::CastError(format!(
+                    "Epoch timestamp decoded from {bytes:?} overflows"
+                ))),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::{
+        types::{TimestampMicrosecondType, TimestampSecondType},
+        FixedSizeBinaryArray,
+    };
+    use arrow_schema::TimeUnit;
+
+    use super::*;
+    use crate::BinaryEpochKind;
+
+    #[test]
+    fn test_binary_epoch() {
+        // INT96 of 2000-01-01T12:34:56 written by Impala.
+        let int96 = [
+            0x00, 0x60, 0xfd, 0x4b, 0x32, 0x29, 0x00, 0x00, 0x59, 0x68, 0x25, 0x00,
+        ];
+        let out_of_range = [0xff; 12];
+        let array = FixedSizeBinaryArray::try_from_sparse_iter_with_size(
+            vec![Some(int96), None, Some(out_of_range)].into_iter(),
+            12,
+        )
+        .unwrap();
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.binary_epoch_kind = BinaryEpochKind::Int96;
+        let to_type = DataType::Timestamp(TimeUnit::Second, None);
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        let casted = casted.as_primitive::<TimestampSecondType>();
+        assert_eq!(
+            casted.value_as_datetime(0).unwrap().to_string(),
+            "2000-01-01 12:34:56"
+        );
+        assert!(casted.is_null(1));
+        assert!(casted.is_null(2));
+        options.safe = false;
+        assert!(crate::cast_with_options(&array, &to_type, &options).is_err());
+
+        // Raw integers, with the unit guessed.
+        let millis: i64 = 1701325744956;
+        let array = arrow_array::BinaryArray::from_iter(vec![
+            Some(millis.to_be_bytes().to_vec()),
+            Some(vec![1, 2, 3]),
+        ]);
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.binary_epoch_kind = BinaryEpochKind::RawI64(Endianness::Big);
+        let to_type = DataType::Timestamp(TimeUnit::Microsecond, None);
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        let casted = casted.as_primitive::<TimestampMicrosecondType>();
+        assert_eq!(casted.value(0), millis * 1000);
+        assert!(casted.is_null(1));
+    }
+}
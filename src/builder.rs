@@ -0,0 +1,395 @@
+//! Building timestamp arrays row by row with unit guessing.
+
+use arrow_array::{
+    builder::{Int64Builder, StringBuilder},
+    cast::AsArray,
+    types::Int64Type,
+    ArrayRef, Int64Array,
+};
+use arrow_schema::{ArrowError, DataType, TimeUnit};
+
+use crate::{
+    cast_with_options, guess, guess_precision_with_bound_years,
+    kernel::{make_timestamp_array, normalize_utc, rescale_value},
+    shim::Timezone,
+    CastOptions, CastReport, GuessStrategy,
+};
+
+/// Builder of timestamps from integers and strings appended row by row.
+///
+/// The unit of integers is guessed from the first `warm_up` of them by the options, and
+/// rows appended before are converted once it is locked. Later integers are converted as
+/// they are appended: an integer guessed in another unit fails with
+/// [strict](crate::TimestampCastOptions::strict), is converted by its own unit with
+/// [GuessStrategy::PerValue], or else is taken in the locked unit.
+///
+/// Strings of integers are appended as integers, other strings are cast together when
+/// finishing, so errors of parsing them are returned by [finish](Self::finish).
+pub struct GuessingTimestampBuilder<'a> {
+    to_type: DataType,
+    unit: TimeUnit,
+    tz: Timezone,
+    options: CastOptions<'a>,
+    warm_up: usize,
+    locked: Option<TimeUnit>,
+    pending: Vec<Option<i64>>,
+    pending_values: usize,
+    integers: Int64Builder,
+    strings: StringBuilder,
+    has_strings: bool,
+    len: usize,
+    report: CastReport,
+}
+
+impl<'a> GuessingTimestampBuilder<'a> {
+    /// Create a builder of the timestamp type, guessing from the first integer.
+    pub fn try_new(to_type: DataType, options: CastOptions<'a>) -> Result<Self, ArrowError> {
+        let options = options.resolve().cast_options(&options);
+        let to_type = normalize_utc(&to_type, &options.timestamp_options).into_owned();
+        let DataType::Timestamp(unit, tz) = to_type.clone() else {
+            return Err(ArrowError::CastError(format!(
+                "Cannot build {to_type:?}, expected a timestamp"
+            )));
+        };
+        let locked = match options.timestamp_options.assume_unit {
+            Some(from) => Some(from),
+            None if options.timestamp_options.guess_timestamp_precision => None,
+            None => Some(unit),
+        };
+        Ok(Self {
+            to_type,
+            unit,
+            tz,
+            options,
+            warm_up: 1,
+            locked,
+            pending: Vec::new(),
+            pending_values: 0,
+            integers: Int64Builder::new(),
+            strings: StringBuilder::new(),
+            has_strings: false,
+            len: 0,
+            report: CastReport::default(),
+        })
+    }
+
+    /// Number of integers to guess the unit from, at least one.
+    pub fn with_warm_up(mut self, warm_up: usize) -> Self {
+        self.warm_up = warm_up.max(1);
+        self
+    }
+
+    /// The unit integers are taken in, `None` if still warming up.
+    pub fn locked_unit(&self) -> Option<TimeUnit> {
+        self.locked
+    }
+
+    /// What guessing decided so far.
+    pub fn report(&self) -> &CastReport {
+        &self.report
+    }
+
+    /// Number of rows appended since the last finish.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Append an integer, the row is not appended if converting it fails.
+    pub fn append_i64(&mut self, v: i64) -> Result<(), ArrowError> {
+        match self.locked {
+            Some(locked) => {
+                let v = self.convert(v, locked)?;
+                self.integers.append_option(v);
+            }
+            None => {
+                self.pending.push(Some(v));
+                self.pending_values += 1;
+                if self.pending_values >= self.warm_up {
+                    if let Err(e) = self.lock() {
+                        self.pending.pop();
+                        self.pending_values -= 1;
+                        return Err(e);
+                    }
+                }
+            }
+        }
+        self.append_string_null();
+        Ok(())
+    }
+
+    pub fn append_str(&mut self, s: &str) -> Result<(), ArrowError> {
+        if let Ok(v) = s.trim().parse() {
+            return self.append_i64(v);
+        }
+        if !self.has_strings {
+            for _ in 0..self.len {
+                self.strings.append_null();
+            }
+            self.has_strings = true;
+        }
+        self.strings.append_value(s);
+        self.len += 1;
+        self.append_integer_null();
+        Ok(())
+    }
+
+    pub fn append_null(&mut self) {
+        self.append_string_null();
+        self.append_integer_null();
+    }
+
+    /// Build the array of rows appended so far and reset the builder.
+    ///
+    /// If still warming up, the unit is locked by the integers appended, if any.
+    /// The locked unit is kept for rows appended later.
+    pub fn finish(&mut self) -> Result<ArrayRef, ArrowError> {
+        if self.locked.is_none() {
+            if self.pending_values > 0 {
+                self.lock()?;
+            } else {
+                self.integers.append_nulls(self.pending.len());
+                self.pending.clear();
+            }
+        }
+        let tz = if self.options.timestamp_options.use_timezone_as_is {
+            self.tz.clone()
+        } else {
+            None
+        };
+        let integers = make_timestamp_array(&self.integers.finish(), self.unit, tz);
+        let integers =
+            arrow_cast::cast_with_options(&integers, &self.to_type, &(&self.options).into())?;
+        self.len = 0;
+        if !std::mem::take(&mut self.has_strings) {
+            return Ok(integers);
+        }
+        let strings = self.strings.finish();
+        let strings = cast_with_options(&strings, &self.to_type, &self.options)?;
+        let integers = arrow_cast::cast(&integers, &DataType::Int64)?;
+        let strings = arrow_cast::cast(&strings, &DataType::Int64)?;
+        let merged: Int64Array = integers
+            .as_primitive::<Int64Type>()
+            .iter()
+            .zip(strings.as_primitive::<Int64Type>().iter())
+            .map(|(a, b)| a.or(b))
+            .collect();
+        Ok(make_timestamp_array(&merged, self.unit, self.tz.clone()))
+    }
+
+    /// Only rows of mixed builders are tracked as strings.
+    fn append_string_null(&mut self) {
+        if self.has_strings {
+            self.strings.append_null();
+        }
+        self.len += 1;
+    }
+
+    fn append_integer_null(&mut self) {
+        if self.locked.is_none() {
+            self.pending.push(None);
+        } else {
+            self.integers.append_null();
+        }
+    }
+
+    /// Guess the unit from the pending integers and convert them.
+    ///
+    /// On failure, the pending integers and the report are left as they were.
+    fn lock(&mut self) -> Result<(), ArrowError> {
+        let report = self.report.clone();
+        let converted = self.convert_pending();
+        let (locked, converted) = match converted {
+            Ok(converted) => converted,
+            Err(e) => {
+                self.report = report;
+                return Err(e);
+            }
+        };
+        self.integers.extend(converted);
+        self.pending.clear();
+        self.pending_values = 0;
+        self.locked = Some(locked);
+        Ok(())
+    }
+
+    fn convert_pending(&mut self) -> Result<(TimeUnit, Vec<Option<i64>>), ArrowError> {
+        let strategy = self.options.timestamp_options.guess_strategy;
+        self.report.guess_strategy = Some(strategy);
+        let pending = Int64Array::from(self.pending.clone());
+        let guessed =
+            guess::guess_unit(&pending, &self.options.timestamp_options, &mut self.report)?;
+        self.report.guessed_unit = guessed;
+        let from = guessed.unwrap_or(self.unit);
+        let locked = guess::cap_rescale(from, self.unit, &self.options, &mut self.report)?;
+        let converted = pending
+            .iter()
+            .map(|v| match v {
+                Some(v) => self.convert(v, locked),
+                None => Ok(None),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok((locked, converted))
+    }
+
+    /// Convert an integer to the target unit, checking it against the guessed unit.
+    fn convert(&mut self, v: i64, locked: TimeUnit) -> Result<Option<i64>, ArrowError> {
+        let options = &self.options.timestamp_options;
+        let mut from = locked;
+        if let Some(guessed) = self.report.guessed_unit {
+            let own = guess_precision_with_bound_years(v, options.guessing_bound_years);
+            if own != guessed {
+                if options.strict {
+                    return Err(ArrowError::CastError(format!(
+                        "Value {v} guessed as {own:?} contradicts the guessed unit {guessed:?}"
+                    )));
+                }
+                if options.guess_strategy == GuessStrategy::PerValue {
+                    from = own;
+                }
+            }
+        }
+        rescale_value(v, from, self.unit, &self.options, &mut self.report)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::{
+        types::{TimestampMillisecondType, TimestampNanosecondType},
+        Array,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_guessing_timestamp_builder() {
+        let to_type = DataType::Timestamp(TimeUnit::Millisecond, None);
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.guess_strategy = GuessStrategy::Majority;
+        let mut builder = GuessingTimestampBuilder::try_new(to_type.clone(), options.clone())
+            .unwrap()
+            .with_warm_up(3);
+        builder.append_i64(1701325744).unwrap();
+        builder.append_null();
+        builder.append_str("2023-11-30T06:29:05.956Z").unwrap();
+        builder.append_i64(1701325746).unwrap();
+        assert_eq!(builder.locked_unit(), None);
+        // Retro-converts the warm-up rows, then the later rows as they are appended.
+        builder.append_str("1701325747956").unwrap();
+        assert_eq!(builder.locked_unit(), Some(TimeUnit::Second));
+        builder.append_i64(1701325748).unwrap();
+        let casted = builder.finish().unwrap();
+        let casted = casted.as_primitive::<TimestampMillisecondType>();
+        assert_eq!(
+            casted.iter().collect::<Vec<_>>(),
+            vec![
+                Some(1701325744000),
+                None,
+                Some(1701325745956),
+                Some(1701325746000),
+                Some(1701325747956000),
+                Some(1701325748000)
+            ]
+        );
+        assert_eq!(builder.report().guessed_unit, Some(TimeUnit::Second));
+
+        // The unit stays locked after finishing.
+        assert!(builder.is_empty());
+        builder.append_i64(1701325749).unwrap();
+        let casted = builder.finish().unwrap();
+        assert_eq!(
+            casted.as_primitive::<TimestampMillisecondType>().value(0),
+            1701325749000
+        );
+
+        // Finishing while warming up locks by the integers so far.
+        let mut builder = GuessingTimestampBuilder::try_new(to_type.clone(), options.clone())
+            .unwrap()
+            .with_warm_up(100);
+        builder.append_null();
+        builder.append_i64(1701325744956).unwrap();
+        let casted = builder.finish().unwrap();
+        let casted = casted.as_primitive::<TimestampMillisecondType>();
+        assert!(casted.is_null(0));
+        assert_eq!(casted.value(1), 1701325744956);
+        assert_eq!(builder.locked_unit(), Some(TimeUnit::Millisecond));
+
+        // Contradicting values by strategy.
+        options.timestamp_options.guess_strategy = GuessStrategy::PerValue;
+        let mut builder =
+            GuessingTimestampBuilder::try_new(to_type.clone(), options.clone()).unwrap();
+        builder.append_i64(1701325744).unwrap();
+        builder.append_i64(1701325745956).unwrap();
+        let casted = builder.finish().unwrap();
+        let casted = casted.as_primitive::<TimestampMillisecondType>();
+        assert_eq!(casted.values(), &[1701325744000, 1701325745956]);
+
+        options.timestamp_options.strict = true;
+        let mut builder =
+            GuessingTimestampBuilder::try_new(to_type.clone(), options.clone()).unwrap();
+        builder.append_i64(1701325744).unwrap();
+        assert!(builder.append_i64(1701325745956).is_err());
+
+        // Errors of strings are returned when finishing without `safe`.
+        options.safe = false;
+        let mut builder = GuessingTimestampBuilder::try_new(to_type, options).unwrap();
+        builder.append_str("bad").unwrap();
+        assert!(builder.finish().is_err());
+        assert!(GuessingTimestampBuilder::try_new(
+            DataType::Int64,
+            CastOptions::new().with_passthrough(false)
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_guessing_timestamp_builder_failed_appends() {
+        let to_type = DataType::Timestamp(TimeUnit::Nanosecond, None);
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.safe = false;
+        options.timestamp_options.guess_strategy = GuessStrategy::Majority;
+
+        // A value overflowing when locking leaves the warm-up rows pending.
+        let mut builder = GuessingTimestampBuilder::try_new(to_type.clone(), options.clone())
+            .unwrap()
+            .with_warm_up(3);
+        builder.append_i64(1701325744).unwrap();
+        builder.append_str("2023-11-30T06:29:05Z").unwrap();
+        builder.append_i64(1701325746).unwrap();
+        assert!(builder.append_i64(10_000_000_000).is_err());
+        assert_eq!((builder.len(), builder.locked_unit()), (3, None));
+        assert_eq!(builder.report().guessed_unit, None);
+        builder.append_i64(1701325747).unwrap();
+        assert_eq!(builder.locked_unit(), Some(TimeUnit::Second));
+
+        // A value overflowing after locking is not appended either.
+        assert!(builder.append_str("10000000000").is_err());
+        builder.append_null();
+        builder.append_i64(1701325748).unwrap();
+        assert_eq!(builder.len(), 6);
+        let casted = builder.finish().unwrap();
+        let casted = casted.as_primitive::<TimestampNanosecondType>();
+        assert_eq!(
+            casted.iter().collect::<Vec<_>>(),
+            vec![
+                Some(1701325744000000000),
+                Some(1701325745000000000),
+                Some(1701325746000000000),
+                Some(1701325747000000000),
+                None,
+                Some(1701325748000000000)
+            ]
+        );
+
+        // Integers only.
+        assert!(builder.append_i64(10_000_000_000).is_err());
+        builder.append_i64(1701325749).unwrap();
+        let casted = builder.finish().unwrap();
+        let casted = casted.as_primitive::<TimestampNanosecondType>();
+        assert_eq!(casted.values(), &[1701325749000000000]);
+    }
+}
@@ -0,0 +1,319 @@
+//! Casting the chunks of one logical column.
+
+use std::borrow::Cow;
+
+use arrow_array::{
+    builder::BooleanBufferBuilder, cast::AsArray, types::Int64Type, Array, ArrayRef, PrimitiveArray,
+};
+use arrow_schema::{ArrowError, DataType, TimeUnit};
+
+use crate::{
+    cast_with_report, guess,
+    kernel::{make_timestamp_array, normalize_utc, rescale_value},
+    plan::only_rescales,
+    CastOptions, CastReport, EpochKind, GuessStrategy, PreparedColumn,
+};
+
+/// Cast chunks of one column, possibly of different types, to the same target type.
+///
+/// Each chunk is cast by its own type. The unit of numeric chunks is guessed by the first
+/// numeric chunk with evidence, and applied to all later numeric chunks, string chunks in
+/// between neither decide nor follow it. With
+/// [guess_skip_rows](crate::TimestampCastOptions::guess_skip_rows) or
+/// [guess_from_end](crate::TimestampCastOptions::guess_from_end), the unit is guessed from
+/// the representative value of the numeric chunks, and applied to all of them.
+pub fn cast_chunks(
+    chunks: &[&dyn Array],
+    to_type: &DataType,
+    cast_options: &CastOptions,
+) -> Result<Vec<ArrayRef>, ArrowError> {
+    let mut casted = Vec::with_capacity(chunks.len());
+    for_each_chunk(chunks, cast_options, |chunk, options| {
+        let (chunk, report) = cast_with_report(chunk, to_type, options)?;
+        casted.push(chunk);
+        Ok(report.guessed_unit)
+    })?;
+    Ok(casted)
+}
+
+/// Cast arrays to timestamps concatenated in one array, guessing units like [cast_chunks].
+///
+/// The values and validity of the output are built once, sized to the total length.
+/// `Int64` arrays only rescaled are written into them directly, other arrays are appended
+/// once cast, without keeping the cast arrays.
+pub fn cast_concat(
+    arrays: &[&dyn Array],
+    to_type: &DataType,
+    cast_options: &CastOptions,
+) -> Result<ArrayRef, ArrowError> {
+    let cast_options = &cast_options.resolve().cast_options(cast_options);
+    let to_type = &*normalize_utc(to_type, &cast_options.timestamp_options);
+    let DataType::Timestamp(unit, tz) = to_type else {
+        return Err(ArrowError::CastError(format!(
+            "Cannot cast and concatenate to {to_type:?}, expected a timestamp"
+        )));
+    };
+    let len = arrays.iter().map(|array| array.len()).sum();
+    let mut values: Vec<i64> = Vec::with_capacity(len);
+    let mut validity = BooleanBufferBuilder::new(len);
+    let mut null_count = 0;
+    for_each_chunk(arrays, cast_options, |array, options| {
+        let timestamp_options = &options.timestamp_options;
+        let direct = array.data_type() == &DataType::Int64
+            && only_rescales(tz, options)
+            && (tz.is_none() || timestamp_options.use_timezone_as_is)
+            && timestamp_options.guess_strategy != GuessStrategy::PerValue;
+        if !direct {
+            let (casted, report) = cast_with_report(array, to_type, options)?;
+            let data = casted.to_data();
+            values.extend_from_slice(data.buffer::<i64>(0));
+            null_count += casted.null_count();
+            match casted.nulls() {
+                Some(nulls) => validity.append_buffer(nulls.inner()),
+                None => validity.append_n(casted.len(), true),
+            }
+            return Ok(report.guessed_unit);
+        }
+        let integers = array.as_primitive::<Int64Type>();
+        let mut report = CastReport::default();
+        let (from, guessed) = match timestamp_options.assume_unit {
+            Some(from) => (from, None),
+            None if timestamp_options.guess_timestamp_precision => {
+                let guessed = guess::guess_unit(integers, timestamp_options, &mut report)?;
+                let from = guessed.unwrap_or(*unit);
+                (
+                    guess::cap_rescale(from, *unit, options, &mut report)?,
+                    guessed,
+                )
+            }
+            None => (*unit, None),
+        };
+        let start = values.len();
+        null_count += integers.null_count();
+        match integers.nulls() {
+            Some(nulls) => validity.append_buffer(nulls.inner()),
+            None => validity.append_n(integers.len(), true),
+        }
+        for (row, v) in integers.values().iter().enumerate() {
+            let rescaled = match integers.is_valid(row) {
+                true => rescale_value(*v, from, *unit, options, &mut report)?,
+                false => Some(0),
+            };
+            if rescaled.is_none() {
+                validity.set_bit(start + row, false);
+                null_count += 1;
+            }
+            values.push(rescaled.unwrap_or_default());
+        }
+        Ok(guessed)
+    })?;
+    let nulls = (null_count > 0).then(|| validity.finish().into());
+    let integers = PrimitiveArray::<Int64Type>::new(values.into(), nulls);
+    Ok(make_timestamp_array(&integers, *unit, tz.clone()))
+}
+
+/// Call `f` on each chunk in order with its options, pinning the unit of numeric chunks to
+/// the unit `f` returns as guessed.
+fn for_each_chunk(
+    chunks: &[&dyn Array],
+    cast_options: &CastOptions,
+    mut f: impl FnMut(&dyn Array, &CastOptions) -> Result<Option<TimeUnit>, ArrowError>,
+) -> Result<(), ArrowError> {
+    let mut pinned = representative_unit(chunks, cast_options)?;
+    for chunk in chunks {
+        let numeric = chunk.data_type().is_numeric();
+        let options = match pinned {
+            Some(unit) if numeric => {
+                let mut options = cast_options.clone();
+                options.timestamp_options.assume_unit = Some(unit);
+                Cow::Owned(options)
+            }
+            _ => Cow::Borrowed(cast_options),
+        };
+        let guessed = f(*chunk, &options)?;
+        if numeric && pinned.is_none() {
+            pinned = guessed;
+        }
+    }
+    Ok(())
+}
+
+/// The unit guessed from the representative value of the numeric chunks, counting
+/// [guess_skip_rows](crate::TimestampCastOptions::guess_skip_rows) across chunks, from the
+/// end with [guess_from_end](crate::TimestampCastOptions::guess_from_end).
+///
+/// `None` if first-value guessing reads the first value of the column anyway.
+fn representative_unit(
+    chunks: &[&dyn Array],
+    cast_options: &CastOptions,
+) -> Result<Option<TimeUnit>, ArrowError> {
+    let options = &cast_options.timestamp_options;
+    if options.guess_skip_rows == 0 && !options.guess_from_end
+        || !options.guess_timestamp_precision
+        || options.assume_unit.is_some()
+        || options.epoch_kind != EpochKind::Unix
+        || !matches!(
+            options.guess_strategy,
+            GuessStrategy::FirstNonNull | GuessStrategy::PerValue
+        )
+    {
+        return Ok(None);
+    }
+    let mut numeric: Vec<&dyn Array> = chunks
+        .iter()
+        .copied()
+        .filter(|chunk| chunk.data_type().is_numeric())
+        .collect();
+    if options.guess_from_end {
+        numeric.reverse();
+    }
+    // Too few values to skip, the first value is read as without skipping.
+    for skip in [options.guess_skip_rows, 0] {
+        let mut left = skip;
+        for chunk in &numeric {
+            let mut chunk_options = cast_options.clone();
+            chunk_options.timestamp_options.guess_skip_rows = left;
+            let prepared = PreparedColumn::try_new(*chunk, &chunk_options)?;
+            let count = guess::candidates(prepared.integers(), options).count();
+            if count > left {
+                return prepared.guessed_unit();
+            }
+            left -= count;
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::{
+        cast::AsArray, types::TimestampMillisecondType, Int32Array, Int64Array, StringArray,
+    };
+    use arrow_schema::TimeUnit;
+
+    use super::*;
+    use crate::OverflowBehavior;
+
+    #[test]
+    fn test_cast_chunks() {
+        let strings = StringArray::from(vec!["2023-11-30T06:29:04Z"]);
+        let millis = Int64Array::from(vec![1701325744956]);
+        let leading_null = Int64Array::from(vec![None, Some(1701325744)]);
+        let chunks: [&dyn Array; 3] = [&strings, &millis, &leading_null];
+        let to_type = DataType::Timestamp(TimeUnit::Millisecond, Some("+00:00".into()));
+        let casted = cast_chunks(
+            &chunks,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        assert!(casted.iter().all(|chunk| chunk.data_type() == &to_type));
+        let value = |i: usize, row: usize| {
+            casted[i]
+                .as_primitive::<TimestampMillisecondType>()
+                .value(row)
+        };
+        assert_eq!(value(0, 0), 1701325744000);
+        assert_eq!(value(1, 0), 1701325744956);
+        // Pinned to milliseconds by the chunk before.
+        assert!(casted[2].is_null(0));
+        assert_eq!(value(2, 1), 1701325744);
+
+        // Guessed on its own without the chunk before.
+        let casted = cast_chunks(
+            &[&strings, &leading_null],
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        assert_eq!(
+            casted[1]
+                .as_primitive::<TimestampMillisecondType>()
+                .value(1),
+            1701325744000
+        );
+
+        // Values are skipped across numeric chunks, and the unit applies to all of them.
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.guess_skip_rows = 2;
+        let boot = Int64Array::from(vec![Some(7), None]);
+        let chunks: [&dyn Array; 4] = [&boot, &strings, &leading_null, &millis];
+        let casted = cast_chunks(&chunks, &to_type, &options).unwrap();
+        let value = |i: usize, row: usize| {
+            casted[i]
+                .as_primitive::<TimestampMillisecondType>()
+                .value(row)
+        };
+        assert_eq!(value(0, 0), 7);
+        assert_eq!(value(2, 1), 1701325744);
+        assert_eq!(value(3, 0), 1701325744956);
+
+        // From the end, or from the first value if too few to skip.
+        options.timestamp_options.guess_skip_rows = 1;
+        options.timestamp_options.guess_from_end = true;
+        let casted = cast_chunks(&chunks, &to_type, &options).unwrap();
+        assert_eq!(
+            casted[3]
+                .as_primitive::<TimestampMillisecondType>()
+                .value(0),
+            1701325744956000
+        );
+        options.timestamp_options.guess_skip_rows = 3;
+        let casted = cast_chunks(&chunks, &to_type, &options).unwrap();
+        assert_eq!(
+            casted[3]
+                .as_primitive::<TimestampMillisecondType>()
+                .value(0),
+            1701325744956
+        );
+    }
+
+    #[test]
+    fn test_cast_concat() {
+        let strings = StringArray::from(vec![Some("2023-11-30T06:29:04Z"), None]);
+        let millis = Int64Array::from(vec![1701325744956]);
+        let leading_null = Int64Array::from(vec![None, Some(1701325744)]);
+        let arrays: [&dyn Array; 3] = [&strings, &millis, &leading_null];
+        let to_type = DataType::Timestamp(TimeUnit::Millisecond, Some("+08:00".into()));
+        let options = CastOptions::new().with_passthrough(false);
+        let casted = cast_concat(&arrays, &to_type, &options).unwrap();
+        assert_eq!(casted.data_type(), &to_type);
+
+        let chunks = cast_chunks(&arrays, &to_type, &options).unwrap();
+        let chunks: Vec<&dyn Array> = chunks.iter().map(|chunk| chunk.as_ref()).collect();
+        let concatenated = arrow::compute::concat(&chunks).unwrap();
+        assert_eq!(&casted, &concatenated);
+        assert_eq!(
+            casted.as_primitive::<TimestampMillisecondType>().value(4),
+            1701325744
+        );
+
+        // Rescaled directly or cast first, with nulls of sources and of overflowing values.
+        let sliced = Int64Array::from(vec![Some(1), None, Some(1701325744), Some(i64::MAX)]);
+        let sliced = sliced.slice(1, 3);
+        let int32 = Int32Array::from(vec![Some(1701325744), None]);
+        let arrays: [&dyn Array; 5] = [&strings, &sliced, &millis, &int32, &leading_null];
+        let mut assumed = options.clone();
+        assumed.timestamp_options.assume_unit = Some(TimeUnit::Second);
+        let mut saturated = assumed.clone();
+        saturated.timestamp_options.overflow_behavior = Some(OverflowBehavior::Saturate);
+        let mut local = options.clone();
+        local.timestamp_options.use_timezone_as_is = false;
+        let mut per_value = options.clone();
+        per_value.timestamp_options.guess_strategy = GuessStrategy::PerValue;
+        for options in [options.clone(), assumed, saturated, local, per_value] {
+            for to_type in [
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                DataType::Timestamp(TimeUnit::Second, Some("+08:00".into())),
+            ] {
+                let casted = cast_concat(&arrays, &to_type, &options).unwrap();
+                let chunks = cast_chunks(&arrays, &to_type, &options).unwrap();
+                let chunks: Vec<&dyn Array> = chunks.iter().map(|chunk| chunk.as_ref()).collect();
+                let concatenated = arrow::compute::concat(&chunks).unwrap();
+                assert_eq!(&casted, &concatenated, "{to_type:?} {options:?}");
+            }
+        }
+
+        assert!(cast_concat(&arrays, &DataType::Int64, &options).is_err());
+    }
+}
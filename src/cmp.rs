@@ -0,0 +1,327 @@
+//! Comparing integers of guessed units with timestamp scalars, without casting the integers.
+//!
+//! Each comparison is the same as casting the integers to the type of the scalar by the
+//! options and comparing the casts: the source unit is guessed once, the scalar is converted
+//! to the range of source values casting to it, and source values are compared with the
+//! range. With [GuessStrategy::PerValue], ranges are converted for each unit guessed.
+//!
+//! ```rust
+//! use arrow::{array::{Int64Array, Scalar, TimestampSecondArray}, datatypes::TimeUnit};
+//! use arrow_cast_guess_precision::{cmp, CastOptions};
+//!
+//! let array = Int64Array::from(vec![1701325743999, 1701325744000, 1701325744999]);
+//! let scalar = Scalar::new(TimestampSecondArray::from(vec![1701325744]));
+//! let eq = cmp::eq(&array, &scalar, &CastOptions::new()).unwrap();
+//! assert_eq!(eq.iter().collect::<Vec<_>>(), vec![Some(false), Some(true), Some(true)]);
+//! ```
+//!
+//! Values overflowing the unit of the scalar compare by their value, as if not overflowing,
+//! and the timezone of the scalar is ignored, comparing instants. With
+//! [DownscalePolicy::Error], values compare exactly instead of failing.
+
+use arrow_array::{cast::AsArray, types::Int64Type, Array, BooleanArray, Datum, PrimitiveArray};
+use arrow_schema::{ArrowError, DataType, TimeUnit};
+
+use crate::{
+    guess, guess_precision_with_bound_years, kernel::time_unit_multiple, CastOptions, CastReport,
+    DownscalePolicy, GuessStrategy,
+};
+
+/// Comparison operators.
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Eq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+}
+
+/// Whether values casting to the scalar equal it.
+pub fn eq(
+    array: &dyn Array,
+    scalar: &dyn Datum,
+    cast_options: &CastOptions,
+) -> Result<BooleanArray, ArrowError> {
+    compare(array, scalar, Op::Eq, cast_options)
+}
+
+/// Whether values casting to the type of the scalar are less than it.
+pub fn lt(
+    array: &dyn Array,
+    scalar: &dyn Datum,
+    cast_options: &CastOptions,
+) -> Result<BooleanArray, ArrowError> {
+    compare(array, scalar, Op::Lt, cast_options)
+}
+
+/// Whether values casting to the type of the scalar are less than or equal to it.
+pub fn lt_eq(
+    array: &dyn Array,
+    scalar: &dyn Datum,
+    cast_options: &CastOptions,
+) -> Result<BooleanArray, ArrowError> {
+    compare(array, scalar, Op::LtEq, cast_options)
+}
+
+/// Whether values casting to the type of the scalar are greater than it.
+pub fn gt(
+    array: &dyn Array,
+    scalar: &dyn Datum,
+    cast_options: &CastOptions,
+) -> Result<BooleanArray, ArrowError> {
+    compare(array, scalar, Op::Gt, cast_options)
+}
+
+/// Whether values casting to the type of the scalar are greater than or equal to it.
+pub fn gt_eq(
+    array: &dyn Array,
+    scalar: &dyn Datum,
+    cast_options: &CastOptions,
+) -> Result<BooleanArray, ArrowError> {
+    compare(array, scalar, Op::GtEq, cast_options)
+}
+
+fn compare(
+    array: &dyn Array,
+    scalar: &dyn Datum,
+    op: Op,
+    cast_options: &CastOptions,
+) -> Result<BooleanArray, ArrowError> {
+    let (scalar, is_scalar) = scalar.get();
+    let DataType::Timestamp(unit, _) = scalar.data_type() else {
+        return Err(ArrowError::InvalidArgumentError(format!(
+            "Cannot compare with {:?}, expected a timestamp scalar",
+            scalar.data_type()
+        )));
+    };
+    if !is_scalar || scalar.len() != 1 {
+        return Err(ArrowError::InvalidArgumentError(
+            "Cannot compare with an array, expected a timestamp scalar".to_string(),
+        ));
+    }
+    if !array.data_type().is_integer() {
+        return Err(ArrowError::CastError(format!(
+            "Cannot compare {:?} with timestamps, expected integers",
+            array.data_type()
+        )));
+    }
+    if scalar.is_null(0) {
+        return Ok(BooleanArray::new_null(array.len()));
+    }
+    let value = arrow_cast::cast(scalar, &DataType::Int64)?;
+    let value = value.as_primitive::<Int64Type>().value(0);
+    let integers = arrow_cast::cast(array, &DataType::Int64)?;
+    let integers: &PrimitiveArray<Int64Type> = integers.as_primitive();
+
+    let options = &cast_options.timestamp_options;
+    let policy = options.downscale_policy;
+    let from = match options.assume_unit {
+        Some(from) => from,
+        None if options.guess_timestamp_precision
+            && options.guess_strategy == GuessStrategy::PerValue =>
+        {
+            let years = options.guessing_bound_years;
+            let ranges = [
+                TimeUnit::Second,
+                TimeUnit::Millisecond,
+                TimeUnit::Microsecond,
+                TimeUnit::Nanosecond,
+            ]
+            .map(|from| range(value, from, *unit, policy));
+            return Ok(BooleanArray::from_unary(integers, |v| {
+                let range = match guess_precision_with_bound_years(v, years) {
+                    TimeUnit::Second => ranges[0],
+                    TimeUnit::Millisecond => ranges[1],
+                    TimeUnit::Microsecond => ranges[2],
+                    TimeUnit::Nanosecond => ranges[3],
+                };
+                test(v, range, op)
+            }));
+        }
+        None if options.guess_timestamp_precision => {
+            let mut report = CastReport::default();
+            let guessed = guess::guess_unit(integers, options, &mut report)?;
+            let from = guessed.unwrap_or(*unit);
+            guess::cap_rescale(from, *unit, cast_options, &mut report)?
+        }
+        None => *unit,
+    };
+    let range = range(value, from, *unit, policy);
+    Ok(BooleanArray::from_unary(integers, |v| test(v, range, op)))
+}
+
+/// Whether the value is in the relation with the values in `[lo, hi)` casting to the scalar.
+#[inline]
+fn test(v: i64, (lo, hi): (i128, i128), op: Op) -> bool {
+    let v = v as i128;
+    match op {
+        Op::Eq => lo <= v && v < hi,
+        Op::Lt => v < lo,
+        Op::LtEq => v < hi,
+        Op::Gt => v >= hi,
+        Op::GtEq => v >= lo,
+    }
+}
+
+/// The range `[lo, hi)` of values in the `from` unit casting to the value in the `to` unit.
+///
+/// Casting is monotonic, so values below the range cast below the value and values above
+/// the range cast above it. The range is empty if no value casts to it.
+fn range(value: i64, from: TimeUnit, to: TimeUnit, policy: DownscalePolicy) -> (i128, i128) {
+    let value = value as i128;
+    let from_size = time_unit_multiple(from) as i128;
+    let to_size = time_unit_multiple(to) as i128;
+    if from_size <= to_size {
+        // Upscaling multiplies exactly, the first value reaching `value` is its ceiling.
+        let k = to_size / from_size;
+        let first = |value: i128| value.div_euclid(k) + (value.rem_euclid(k) != 0) as i128;
+        return (first(value), first(value + 1));
+    }
+    let k = from_size / to_size;
+    // The first value casting to at least `value` by the downscale policy.
+    let first = |value: i128| match policy {
+        DownscalePolicy::Truncate if value > 0 => value * k,
+        DownscalePolicy::Truncate => value * k - k + 1,
+        DownscalePolicy::Round if value > 0 => value * k - k / 2,
+        DownscalePolicy::Round => value * k - k / 2 + 1,
+        DownscalePolicy::Error => value * k,
+    };
+    match policy {
+        DownscalePolicy::Error => (first(value), first(value) + 1),
+        _ => (first(value), first(value + 1)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::{
+        types::TimestampMillisecondType, Int64Array, Scalar, TimestampMillisecondArray,
+        TimestampSecondArray,
+    };
+
+    use super::*;
+
+    /// Compare by casting, as the reference.
+    fn cast_compare(
+        array: &Int64Array,
+        value: i64,
+        op: Op,
+        to: TimeUnit,
+        options: &CastOptions,
+    ) -> Vec<Option<bool>> {
+        let casted = crate::cast_with_options(array, &DataType::Timestamp(to, None), options);
+        let casted = arrow_cast::cast(&casted.unwrap(), &DataType::Int64).unwrap();
+        casted
+            .as_primitive::<Int64Type>()
+            .iter()
+            .map(|v| {
+                v.map(|v| match op {
+                    Op::Eq => v == value,
+                    Op::Lt => v < value,
+                    Op::LtEq => v <= value,
+                    Op::Gt => v > value,
+                    Op::GtEq => v >= value,
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compare() {
+        let ops = [Op::Eq, Op::Lt, Op::LtEq, Op::Gt, Op::GtEq];
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.assume_unit = Some(TimeUnit::Millisecond);
+
+        // Values around the boundaries of seconds, positive, zero and negative.
+        let mut values = vec![None];
+        for second in [-1701325744, -2, -1, 0, 1, 2, 1701325744_i64] {
+            for offset in [
+                -1000, -999, -501, -500, -499, -1, 0, 1, 499, 500, 501, 999, 1000,
+            ] {
+                values.push(Some(second * 1000 + offset));
+            }
+        }
+        let array = Int64Array::from(values);
+        for policy in [DownscalePolicy::Truncate, DownscalePolicy::Round] {
+            options.timestamp_options.downscale_policy = policy;
+            for value in [-1701325744, -2, -1, 0, 1, 2, 1701325744] {
+                let scalar = Scalar::new(TimestampSecondArray::from(vec![value]));
+                for op in ops {
+                    let compared = compare(&array, &scalar, op, &options).unwrap();
+                    assert_eq!(
+                        compared.iter().collect::<Vec<_>>(),
+                        cast_compare(&array, value, op, TimeUnit::Second, &options),
+                        "{policy:?} {op:?} {value}"
+                    );
+                }
+            }
+        }
+
+        // Exact with the error policy.
+        options.timestamp_options.downscale_policy = DownscalePolicy::Error;
+        let array = Int64Array::from(vec![999, 1000, 1001]);
+        let scalar = Scalar::new(TimestampSecondArray::from(vec![1]));
+        let compared = |op| {
+            compare(&array, &scalar, op, &options)
+                .unwrap()
+                .iter()
+                .map(Option::unwrap)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(compared(Op::Eq), vec![false, true, false]);
+        assert_eq!(compared(Op::Lt), vec![true, false, false]);
+        assert_eq!(compared(Op::LtEq), vec![true, true, false]);
+        assert_eq!(compared(Op::Gt), vec![false, false, true]);
+        assert_eq!(compared(Op::GtEq), vec![false, true, true]);
+
+        // Upscaling from seconds, the scalar between two values.
+        options.timestamp_options.assume_unit = Some(TimeUnit::Second);
+        let array = Int64Array::from(vec![-2, -1, 0, 1, 2]);
+        for value in [-1500, -1000, -1, 0, 1, 1000, 1500] {
+            let scalar = Scalar::new(TimestampMillisecondArray::from(vec![value]));
+            for op in ops {
+                let compared = compare(&array, &scalar, op, &options).unwrap();
+                assert_eq!(
+                    compared.iter().collect::<Vec<_>>(),
+                    cast_compare(&array, value, op, TimeUnit::Millisecond, &options),
+                    "{op:?} {value}"
+                );
+            }
+        }
+
+        // Guessed once, or per value.
+        let mut options = CastOptions::new().with_passthrough(false);
+        let array = Int64Array::from(vec![1701325744956, 1701325744, 1701325745956]);
+        let scalar = Scalar::new(TimestampMillisecondArray::from(vec![1701325744956]));
+        assert_eq!(
+            gt_eq(&array, &scalar, &options).unwrap(),
+            BooleanArray::from(vec![true, false, true])
+        );
+        options.timestamp_options.guess_strategy = GuessStrategy::PerValue;
+        assert_eq!(
+            lt(&array, &scalar, &options).unwrap(),
+            BooleanArray::from(vec![false, true, false])
+        );
+        assert_eq!(
+            eq(&array, &scalar, &options).unwrap(),
+            BooleanArray::from(vec![true, false, false])
+        );
+        let casted = crate::cast_with_options(
+            &array,
+            &DataType::Timestamp(TimeUnit::Millisecond, None),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(
+            casted.as_primitive::<TimestampMillisecondType>().value(1),
+            1701325744000
+        );
+
+        // Null scalars and arrays instead of scalars.
+        let null = Scalar::new(TimestampSecondArray::from(vec![None]));
+        assert_eq!(lt_eq(&array, &null, &options).unwrap().null_count(), 3);
+        let timestamps = TimestampSecondArray::from(vec![1701325744, 1701325745]);
+        assert!(eq(&array, &timestamps, &options).is_err());
+    }
+}
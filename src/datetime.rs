@@ -0,0 +1,99 @@
+//! Guessing datetime from single integer values.
+
+use arrow_schema::TimeUnit;
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+use crate::kernel::time_unit_multiple;
+use crate::{guess_precision, guess_precision_with_bound_years, TimestampCastOptions};
+
+/// Convert a timestamp in the unit to chrono datetime.
+#[inline]
+fn to_datetime(value: i64, unit: TimeUnit) -> Option<DateTime<Utc>> {
+    let per_second = time_unit_multiple(unit);
+    let secs = value.div_euclid(per_second);
+    let nanos = value.rem_euclid(per_second) * (1_000_000_000 / per_second);
+    DateTime::from_timestamp(secs, nanos as u32)
+}
+
+/// Guess the datetime an integer most plausibly represents.
+///
+/// Returns `None` when the value is out of the range of chrono datetime.
+///
+/// ```rust
+/// use arrow_cast_guess_precision::guess_datetime;
+///
+/// let ms = guess_datetime(1701325744956).unwrap();
+/// let s = guess_datetime(1701325744).unwrap();
+/// assert_eq!(ms.timestamp(), s.timestamp());
+/// assert_eq!(ms.to_rfc3339(), "2023-11-30T06:29:04.956+00:00");
+/// ```
+pub fn guess_datetime(value: i64) -> Option<DateTime<Utc>> {
+    to_datetime(value, guess_precision(value))
+}
+
+/// Guess the naive datetime (in UTC) an integer most plausibly represents.
+pub fn guess_naive_datetime(value: i64) -> Option<NaiveDateTime> {
+    guess_datetime(value).map(|dt| dt.naive_utc())
+}
+
+/// Guess datetime with timestamp options, consistent with casting arrays.
+///
+/// The `unit` is used when guessing is disabled by options.
+pub fn guess_datetime_with_options(
+    value: i64,
+    unit: TimeUnit,
+    options: &TimestampCastOptions,
+) -> Option<DateTime<Utc>> {
+    let unit = if options.guess_timestamp_precision {
+        guess_precision_with_bound_years(value, options.guessing_bound_years)
+    } else {
+        unit
+    };
+    to_datetime(value, unit)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_guess_datetime() {
+        let now = Utc::now();
+        let expected = now.timestamp_micros();
+        for value in [now.timestamp_micros(), now.timestamp_nanos_opt().unwrap()] {
+            assert_eq!(guess_datetime(value).unwrap().timestamp_micros(), expected);
+        }
+        assert_eq!(
+            guess_datetime(now.timestamp()).unwrap().timestamp(),
+            now.timestamp()
+        );
+        assert_eq!(
+            guess_naive_datetime(-1).unwrap().to_string(),
+            "1969-12-31 23:59:59"
+        );
+
+        let options = TimestampCastOptions {
+            guessing_bound_years: 10,
+            ..Default::default()
+        };
+        let dt = guess_datetime_with_options(1701325744, TimeUnit::Second, &options).unwrap();
+        assert_eq!(dt.timestamp(), 1701325);
+
+        // Out of chrono range in seconds.
+        let options = TimestampCastOptions {
+            guessing_bound_years: 1_000_000,
+            ..Default::default()
+        };
+        assert_eq!(
+            guess_datetime_with_options(20_000_000_000_000, TimeUnit::Second, &options),
+            None
+        );
+
+        let options = TimestampCastOptions {
+            guess_timestamp_precision: false,
+            ..Default::default()
+        };
+        let dt = guess_datetime_with_options(1701325744, TimeUnit::Millisecond, &options).unwrap();
+        assert_eq!(dt.timestamp_millis(), 1701325744);
+    }
+}
@@ -0,0 +1,165 @@
+//! Casting timestamps to decimal epoch seconds.
+
+use std::sync::Arc;
+
+use arrow_array::{cast::AsArray, types::Int64Type, Array, ArrayRef, Decimal128Array};
+use arrow_schema::{ArrowError, DataType, TimeUnit};
+
+use crate::{CastOptions, CastReport, DownscalePolicy};
+
+/// Divide by the policy, `None` if the policy rejects it.
+fn downscale(v: i128, divisor: i128, policy: DownscalePolicy) -> Option<i128> {
+    let (q, r) = (v / divisor, v % divisor);
+    match policy {
+        DownscalePolicy::Truncate => Some(q),
+        DownscalePolicy::Round if r.abs() * 2 >= divisor => Some(q + r.signum()),
+        DownscalePolicy::Round => Some(q),
+        DownscalePolicy::Error if r == 0 => Some(q),
+        DownscalePolicy::Error => None,
+    }
+}
+
+/// Cast timestamps to `Decimal128(precision, scale)` of epoch seconds.
+///
+/// The fraction of a second is in the scale digits, e.g. `-1.5` seconds is `-1.500000` at scale 6.
+pub(crate) fn cast_timestamp_to_decimal(
+    array: &dyn Array,
+    unit: TimeUnit,
+    precision: u8,
+    scale: i8,
+    cast_options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<ArrayRef, ArrowError> {
+    let digits: i32 = match unit {
+        TimeUnit::Second => 0,
+        TimeUnit::Millisecond => 3,
+        TimeUnit::Microsecond => 6,
+        TimeUnit::Nanosecond => 9,
+    };
+    let to_type = DataType::Decimal128(precision, scale);
+    let bound = 10_i128
+        .checked_pow(precision as u32)
+        .ok_or_else(|| ArrowError::CastError(format!("Invalid decimal type {to_type:?}")))?;
+    let shift = scale as i32 - digits;
+    let policy = cast_options.timestamp_options.downscale_policy;
+
+    let values = arrow_cast::cast(array, &DataType::Int64)?;
+    let values = values.as_primitive::<Int64Type>();
+    let mut decimals = Vec::with_capacity(values.len());
+    for v in values.iter() {
+        let Some(v) = v else {
+            decimals.push(None);
+            continue;
+        };
+        let scaled = if shift >= 0 {
+            10_i128
+                .checked_pow(shift as u32)
+                .and_then(|mul| (v as i128).checked_mul(mul))
+        } else {
+            let divisor = 10_i128
+                .checked_pow(shift.unsigned_abs())
+                .unwrap_or(i128::MAX);
+            match downscale(v as i128, divisor, policy) {
+                Some(scaled) => Some(scaled),
+                None => {
+                    return Err(ArrowError::CastError(format!(
+                    "Cannot cast timestamp {v} in {unit:?} to {to_type:?} without losing precision"
+                )))
+                }
+            }
+        };
+        match scaled.filter(|d| d.unsigned_abs() < bound as u128) {
+            Some(d) => decimals.push(Some(d)),
+            None if cast_options.safe => {
+                report.null_causes.overflow += 1;
+                decimals.push(None);
+            }
+            None => {
+                return Err(ArrowError::CastError(format!(
+                    "Cannot cast timestamp {v} in {unit:?} to {to_type:?}: overflow"
+                )))
+            }
+        }
+    }
+    let decimals = Decimal128Array::from(decimals).with_precision_and_scale(precision, scale)?;
+    Ok(Arc::new(decimals))
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::{types::Decimal128Type, TimestampMicrosecondArray};
+
+    use super::*;
+
+    #[test]
+    fn test_timestamp_to_decimal() {
+        let array = TimestampMicrosecondArray::from(vec![
+            Some(1701325744956123),
+            Some(-1_500_000),
+            Some(-1),
+            None,
+        ]);
+        let to_type = DataType::Decimal128(20, 6);
+        let casted = crate::cast_with_options(
+            &array,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        let casted = casted.as_primitive::<Decimal128Type>();
+        assert_eq!(casted.value_as_string(0), "1701325744.956123");
+        assert_eq!(casted.value_as_string(1), "-1.500000");
+        assert_eq!(casted.value_as_string(2), "-0.000001");
+        assert!(casted.is_null(3));
+
+        // Finer scale.
+        let casted = crate::cast_with_options(
+            &array,
+            &DataType::Decimal128(22, 9),
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        let casted = casted.as_primitive::<Decimal128Type>();
+        assert_eq!(casted.value_as_string(1), "-1.500000000");
+
+        // Coarser scale by the downscale policy.
+        let to_type = DataType::Decimal128(20, 0);
+        let casted = crate::cast_with_options(
+            &array,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        let casted = casted.as_primitive::<Decimal128Type>();
+        assert_eq!(casted.value_as_string(0), "1701325744");
+        assert_eq!(casted.value_as_string(1), "-1");
+        assert_eq!(casted.value_as_string(2), "0");
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.downscale_policy = DownscalePolicy::Round;
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        let casted = casted.as_primitive::<Decimal128Type>();
+        assert_eq!(casted.value_as_string(0), "1701325745");
+        assert_eq!(casted.value_as_string(1), "-2");
+        assert_eq!(casted.value_as_string(2), "0");
+        options.timestamp_options.downscale_policy = DownscalePolicy::Error;
+        assert!(crate::cast_with_options(&array, &to_type, &options).is_err());
+
+        // Precision too small.
+        let to_type = DataType::Decimal128(10, 6);
+        let (casted, report) = crate::cast_with_report(
+            &array,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        assert!(casted.is_null(0));
+        assert_eq!(report.null_causes.overflow, 1);
+        assert_eq!(
+            casted.as_primitive::<Decimal128Type>().value_as_string(1),
+            "-1.500000"
+        );
+        options.safe = false;
+        options.timestamp_options.downscale_policy = DownscalePolicy::Truncate;
+        assert!(crate::cast_with_options(&array, &to_type, &options).is_err());
+    }
+}
@@ -0,0 +1,230 @@
+//! Casting dictionary arrays by their values, without expanding the keys.
+
+use std::sync::Arc;
+
+use arrow_array::{
+    downcast_dictionary_array, types::ArrowDictionaryKeyType, Array, ArrayRef, DictionaryArray,
+    Int64Array,
+};
+use arrow_schema::{ArrowError, DataType};
+
+use crate::{cast_inner, CastOptions, CastReport, DictionaryHandling};
+
+/// Cast the values of a dictionary array, keeping its keys.
+///
+/// The output is a dictionary with the same keys if the target type is a dictionary, or with
+/// [DictionaryHandling::Preserve], else it is flattened after casting the values. Units are
+/// guessed from the dictionary values referenced by keys, each counted once. Rows of valid keys
+/// referencing null values are null, and keys out of the bounds of the values fail the cast.
+pub(crate) fn cast_dictionary(
+    array: &dyn Array,
+    to_type: &DataType,
+    cast_options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<ArrayRef, ArrowError> {
+    downcast_dictionary_array!(
+        array => cast_values(array, to_type, cast_options, report),
+        from_type => Err(ArrowError::CastError(format!(
+            "Cannot cast {from_type:?} as a dictionary"
+        )))
+    )
+}
+
+fn cast_values<K: ArrowDictionaryKeyType>(
+    array: &DictionaryArray<K>,
+    to_type: &DataType,
+    cast_options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<ArrayRef, ArrowError> {
+    let len = array.values().len();
+    let mut referenced = vec![false; len];
+    for row in 0..array.len() {
+        match array.key(row) {
+            Some(key) if key >= len => {
+                return Err(ArrowError::CastError(format!(
+                    "Cannot cast dictionary key {key} at row {row}: out of bounds of {len} values"
+                )))
+            }
+            Some(key) => referenced[key] = true,
+            None => {}
+        }
+    }
+    let value_type = match to_type {
+        DataType::Dictionary(_, value_type) => value_type.as_ref(),
+        _ => to_type,
+    };
+    let values = if referenced.iter().all(|referenced| *referenced) {
+        array.values().clone()
+    } else {
+        // Values not referenced, as of slices, take no part in guessing nor fail the cast.
+        let keys: Int64Array = (0..len as i64)
+            .map(|i| referenced[i as usize].then_some(i))
+            .collect();
+        let values = DictionaryArray::try_new(keys, array.values().clone())?;
+        arrow_cast::cast(&values, array.values().data_type())?
+    };
+    let values = cast_inner(&values, value_type, cast_options, report)?;
+    if let Some(remainders) = report.remainders.take() {
+        report.remainders = Some(Arc::new(array.with_values(remainders)));
+    }
+    let casted = Arc::new(array.with_values(values)) as ArrayRef;
+    match to_type {
+        DataType::Dictionary(key_type, _) if key_type.as_ref() == &K::DATA_TYPE => Ok(casted),
+        _ if !matches!(to_type, DataType::Dictionary(_, _))
+            && cast_options.timestamp_options.dictionary_output == DictionaryHandling::Preserve =>
+        {
+            Ok(casted)
+        }
+        _ => arrow_cast::cast_with_options(&casted, to_type, &cast_options.into()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::{
+        cast::AsArray,
+        types::{Int16Type, Int32Type, TimestampMillisecondType},
+        BinaryArray, Int16Array, Int32Array,
+    };
+    use arrow_schema::TimeUnit;
+
+    use super::*;
+
+    #[test]
+    fn test_cast_dictionary() {
+        // As many values as keys, not low-cardinality.
+        let values: Int64Array = (0..100_000).map(|i| 1701325744956 + i).collect();
+        let keys: Int32Array = (0..200_000).map(|i| Some(i % 100_000)).collect();
+        let array = DictionaryArray::new(keys, Arc::new(values));
+        let timestamp = DataType::Timestamp(TimeUnit::Millisecond, None);
+        let to_type = DataType::Dictionary(Box::new(DataType::Int32), Box::new(timestamp.clone()));
+        let (casted, report) = crate::cast_with_report(
+            &array,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        assert_eq!(report.guessed_unit, Some(TimeUnit::Millisecond));
+        assert_eq!(casted.data_type(), &to_type);
+        let casted = casted.as_dictionary::<Int32Type>();
+        assert_eq!(
+            casted.keys().values().inner().as_ptr(),
+            array.keys().values().inner().as_ptr()
+        );
+        assert_eq!(casted.values().len(), 100_000);
+        let first = casted.values().as_primitive::<TimestampMillisecondType>();
+        assert_eq!(first.value(0), 1701325744956);
+
+        // Flattened by default, or kept as a dictionary.
+        let flat = crate::cast_with_options(
+            &array,
+            &timestamp,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        assert_eq!(flat.len(), 200_000);
+        assert_eq!(
+            flat.as_primitive::<TimestampMillisecondType>()
+                .value(100_000),
+            1701325744956
+        );
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.dictionary_output = DictionaryHandling::Preserve;
+        let kept = crate::cast_with_options(&array, &timestamp, &options).unwrap();
+        assert_eq!(kept.data_type(), &to_type);
+        assert_eq!(
+            kept.as_dictionary::<Int32Type>()
+                .keys()
+                .values()
+                .inner()
+                .as_ptr(),
+            array.keys().values().inner().as_ptr()
+        );
+
+        // Valid keys referencing null values are null.
+        let array = DictionaryArray::new(
+            Int32Array::from(vec![Some(1), None, Some(0)]),
+            Arc::new(Int64Array::from(vec![Some(1701325744), None])),
+        );
+        let flat = crate::cast_with_options(
+            &array,
+            &timestamp,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        let flat = flat.as_primitive::<TimestampMillisecondType>();
+        assert!(flat.is_null(0));
+        assert!(flat.is_null(1));
+        assert_eq!(flat.value(2), 1701325744000);
+
+        // Keys out of bounds of corrupt input fail instead of panicking.
+        let corrupt = unsafe {
+            DictionaryArray::new_unchecked(
+                Int32Array::from(vec![0, 2]),
+                Arc::new(Int64Array::from(vec![1701325744, 1701325745])),
+            )
+        };
+        let err = crate::cast_with_options(
+            &corrupt,
+            &timestamp,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("key 2 at row 1"), "{err}");
+    }
+
+    #[test]
+    fn test_cast_dictionary_of_binary() {
+        // ASCII epochs and timestamps in binary, as sent by some Flight producers.
+        let values = BinaryArray::from_iter(vec![
+            Some(b"2023-11-30T06:29:04.956Z".as_slice()),
+            Some(b"1701325745956".as_slice()),
+            Some(b"\xff\xfe".as_slice()),
+            Some(b"1701325746956".as_slice()),
+            Some(b"unreferenced".as_slice()),
+        ]);
+        let keys = Int16Array::from(vec![Some(0), Some(1), Some(1), None, Some(3), Some(2)]);
+        let array = DictionaryArray::new(keys, Arc::new(values));
+        let timestamp = DataType::Timestamp(TimeUnit::Millisecond, None);
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.mixed_string_epochs = true;
+        let (flat, report) = crate::cast_with_report(&array, &timestamp, &options).unwrap();
+        let flat = flat.as_primitive::<TimestampMillisecondType>();
+        assert_eq!(
+            flat.iter().collect::<Vec<_>>(),
+            vec![
+                Some(1701325744956),
+                Some(1701325745956),
+                Some(1701325745956),
+                None,
+                Some(1701325746956),
+                None
+            ]
+        );
+        // Each referenced value counted once.
+        assert_eq!(report.guessed_unit, Some(TimeUnit::Millisecond));
+        assert_eq!(report.string_parse_counts.iso8601, 1);
+        assert_eq!(report.string_parse_counts.numeric_epoch, 2);
+        assert_eq!(report.null_causes.parse_failure, 1);
+
+        options.timestamp_options.dictionary_output = DictionaryHandling::Preserve;
+        let kept = crate::cast_with_options(&array, &timestamp, &options).unwrap();
+        let kept = kept.as_dictionary::<Int16Type>();
+        assert_eq!(kept.values().len(), 5);
+        assert_eq!(
+            kept.keys().values().inner().as_ptr(),
+            array.keys().values().inner().as_ptr()
+        );
+        options.safe = false;
+        assert!(crate::cast_with_options(&array, &timestamp, &options).is_err());
+
+        // Without mixing, the epochs are null among timestamps.
+        let flat = crate::cast_with_options(
+            &array,
+            &timestamp,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        assert_eq!(flat.null_count(), 5);
+    }
+}
@@ -0,0 +1,315 @@
+//! Integer timestamps counted from epochs other than the unix epoch.
+
+use arrow_array::{types::Int64Type, PrimitiveArray};
+use arrow_schema::{ArrowError, TimeUnit};
+
+use crate::{
+    kernel::{downscale, map_or_null, overflow, rescale, time_unit_multiple},
+    CastOptions, CastReport, EpochKind,
+};
+
+/// Seconds from 1601-01-01 to 1970-01-01.
+pub(crate) const FILETIME_EPOCH_OFFSET_SECONDS: i64 = 11_644_473_600;
+/// 100-nanosecond intervals in one second.
+const FILETIME_TICKS_PER_SECOND: i64 = 10_000_000;
+/// Seconds from 1900-01-01 to 1970-01-01.
+pub(crate) const NTP_EPOCH_OFFSET_SECONDS: i64 = 2_208_988_800;
+
+/// Convert integers of the epoch kind to unix timestamps in the unit.
+pub(crate) fn to_unix(
+    array: &PrimitiveArray<Int64Type>,
+    kind: EpochKind,
+    to: TimeUnit,
+    options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<PrimitiveArray<Int64Type>, ArrowError> {
+    let (offset, ticks_per_second) = match kind {
+        EpochKind::Unix => return Ok(array.clone()),
+        EpochKind::WindowsFiletime => (
+            FILETIME_EPOCH_OFFSET_SECONDS * FILETIME_TICKS_PER_SECOND,
+            FILETIME_TICKS_PER_SECOND,
+        ),
+        EpochKind::NtpFixedPoint => return ntp_to_unix(array, to, options, report),
+    };
+    let to_size = time_unit_multiple(to);
+    map_or_null(array, |_, v| {
+        let Some(ticks) = v.checked_sub(offset) else {
+            return overflow(true, options, report, || {
+                format!("Cannot cast {kind:?} {v} to {to:?}: overflow")
+            });
+        };
+        if ticks_per_second >= to_size {
+            let policy = options.timestamp_options.downscale_policy;
+            return match downscale(ticks, ticks_per_second / to_size, policy) {
+                Some(v) => Ok(Some(v)),
+                None => Err(ArrowError::CastError(format!(
+                    "Cannot cast {kind:?} {v} to {to:?} without losing precision"
+                ))),
+            };
+        }
+        match ticks.checked_mul(to_size / ticks_per_second) {
+            Some(v) => Ok(Some(v)),
+            None => overflow(ticks < 0, options, report, || {
+                format!("Cannot cast {kind:?} {v} to {to:?}: overflow")
+            }),
+        }
+    })
+}
+
+/// Convert NTP 64-bit fixed-point timestamps to unix timestamps in the unit.
+///
+/// The fraction is rounded to the nearest tick of the unit.
+fn ntp_to_unix(
+    array: &PrimitiveArray<Int64Type>,
+    to: TimeUnit,
+    options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<PrimitiveArray<Int64Type>, ArrowError> {
+    let to_size = time_unit_multiple(to);
+    let rollover = options.timestamp_options.ntp_era_rollover;
+    map_or_null(array, |_, v| {
+        let bits = v as u64;
+        let mut seconds = (bits >> 32) as i64;
+        if rollover && seconds < 1 << 31 {
+            seconds += 1 << 32;
+        }
+        let seconds = seconds - NTP_EPOCH_OFFSET_SECONDS;
+        let fraction = ((bits & 0xFFFF_FFFF) as i128 * to_size as i128 + (1 << 31)) >> 32;
+        match seconds
+            .checked_mul(to_size)
+            .and_then(|v| v.checked_add(fraction as i64))
+        {
+            Some(v) => Ok(Some(v)),
+            None => overflow(seconds < 0, options, report, || {
+                format!("Cannot cast NTP timestamp {bits:#018x} to {to:?}: overflow")
+            }),
+        }
+    })
+}
+
+/// Whole unix seconds of one integer of the epoch kind, `None` on overflow.
+///
+/// Integers of [EpochKind::Unix] are taken as seconds.
+pub(crate) fn unix_seconds(v: i64, kind: EpochKind, ntp_era_rollover: bool) -> Option<i64> {
+    match kind {
+        EpochKind::Unix => Some(v),
+        EpochKind::WindowsFiletime => {
+            Some((v / FILETIME_TICKS_PER_SECOND).checked_sub(FILETIME_EPOCH_OFFSET_SECONDS)?)
+        }
+        EpochKind::NtpFixedPoint => {
+            let mut seconds = (v as u64 >> 32) as i64;
+            if ntp_era_rollover && seconds < 1 << 31 {
+                seconds += 1 << 32;
+            }
+            Some(seconds - NTP_EPOCH_OFFSET_SECONDS)
+        }
+    }
+}
+
+/// Convert durations in the `from` unit to timestamps in the `to` unit, offset from the anchor.
+pub(crate) fn from_anchor(
+    array: &PrimitiveArray<Int64Type>,
+    from: TimeUnit,
+    (anchor, anchor_unit): (i64, TimeUnit),
+    to: TimeUnit,
+    options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<PrimitiveArray<Int64Type>, ArrowError> {
+    let anchor_size = time_unit_multiple(anchor_unit);
+    let to_size = time_unit_multiple(to);
+    let anchored = if anchor_size >= to_size {
+        let policy = options.timestamp_options.downscale_policy;
+        downscale(anchor, anchor_size / to_size, policy)
+    } else {
+        anchor.checked_mul(to_size / anchor_size)
+    };
+    let Some(anchor) = anchored else {
+        return Err(ArrowError::CastError(format!(
+            "Cannot convert anchor {anchor} {anchor_unit:?} to {to:?}"
+        )));
+    };
+    let offsets = rescale(array, from, to, options, report)?;
+    map_or_null(&offsets, |_, v| match anchor.checked_add(v) {
+        Some(v) => Ok(Some(v)),
+        None => overflow(v < 0, options, report, || {
+            format!("Cannot offset anchor {anchor} {to:?} by {v}: overflow")
+        }),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::{
+        cast::AsArray,
+        types::{
+            TimestampMicrosecondType, TimestampMillisecondType, TimestampNanosecondType,
+            TimestampSecondType,
+        },
+        Array, FixedSizeBinaryArray, Int64Array, UInt64Array,
+    };
+    use arrow_schema::DataType;
+
+    use super::*;
+    use crate::{BinaryEpochKind, Endianness};
+
+    #[test]
+    fn test_windows_filetime() {
+        // 1970-01-01T00:00:00Z, 2009-07-25T23:00:00Z and 1601-01-01T00:00:00Z.
+        let array = UInt64Array::from(vec![
+            Some(116444736000000000),
+            Some(128930364000001234),
+            Some(0),
+            None,
+        ]);
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.epoch_kind = EpochKind::WindowsFiletime;
+        let to_type = DataType::Timestamp(TimeUnit::Second, None);
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        let casted = casted.as_primitive::<TimestampSecondType>();
+        assert_eq!(casted.value(0), 0);
+        assert_eq!(
+            casted.value_as_datetime(1).unwrap().to_string(),
+            "2009-07-25 23:00:00"
+        );
+        assert_eq!(casted.value(2), -FILETIME_EPOCH_OFFSET_SECONDS);
+        assert!(casted.is_null(3));
+
+        let to_type = DataType::Timestamp(TimeUnit::Nanosecond, None);
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        let casted = casted.as_primitive::<TimestampNanosecondType>();
+        assert_eq!(casted.value(1), 1248562800000123400);
+        // Before 1677 overflows nanoseconds.
+        assert!(casted.is_null(2));
+        options.safe = false;
+        assert!(crate::cast_with_options(&array, &to_type, &options).is_err());
+
+        // Not guessed without opting in, but taken as nanoseconds of 1974.
+        let array = Int64Array::from(vec![133444736000000000]);
+        let casted = crate::cast_with_options(
+            &array,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        assert_eq!(
+            casted.as_primitive::<TimestampNanosecondType>().value(0),
+            133444736000000000
+        );
+    }
+
+    #[test]
+    fn test_epoch_kinds() {
+        // 2023-11-14T22:13:20.5Z counted from the epoch of each kind.
+        let cases = [
+            (EpochKind::Unix, 1700000000500),
+            (
+                EpochKind::WindowsFiletime,
+                (1700000000 + FILETIME_EPOCH_OFFSET_SECONDS) * 10_000_000 + 5_000_000,
+            ),
+            (
+                EpochKind::NtpFixedPoint,
+                ((1700000000 + NTP_EPOCH_OFFSET_SECONDS) << 32 | 0x8000_0000) as u64 as i64,
+            ),
+        ];
+        let to_type = DataType::Timestamp(TimeUnit::Microsecond, None);
+        for (kind, value) in cases {
+            let mut options = CastOptions::new().with_passthrough(false);
+            options.timestamp_options.epoch_kind = kind;
+            let array = Int64Array::from(vec![value]);
+            let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+            assert_eq!(
+                casted.as_primitive::<TimestampMicrosecondType>().value(0),
+                1700000000500000,
+                "{kind:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_ntp_fixed_point() {
+        // 1900-01-01T00:00:00Z, 1970-01-01T00:00:00.5Z, the last second of era 0 and
+        // 2023-11-30T06:29:04.25Z.
+        let array = UInt64Array::from(vec![
+            Some(0),
+            Some(0x83AA_7E80_8000_0000),
+            Some(0xFFFF_FFFF_0000_0000),
+            Some(0xE912_AA30_4000_0000),
+            None,
+        ]);
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.epoch_kind = EpochKind::NtpFixedPoint;
+        let to_type = DataType::Timestamp(TimeUnit::Millisecond, None);
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        let casted = casted.as_primitive::<TimestampMillisecondType>();
+        assert_eq!(casted.value(0), -NTP_EPOCH_OFFSET_SECONDS * 1000);
+        assert_eq!(casted.value(1), 500);
+        assert_eq!(
+            casted.value_as_datetime(2).unwrap().to_string(),
+            "2036-02-07 06:28:15"
+        );
+        assert_eq!(
+            casted.value_as_datetime(3).unwrap().to_string(),
+            "2023-11-30 06:29:04.250"
+        );
+        assert!(casted.is_null(4));
+
+        // The fraction is rounded to the unit.
+        let array = UInt64Array::from(vec![0x83AA_7E80_FFFF_FFFF]);
+        let to_type = DataType::Timestamp(TimeUnit::Second, None);
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        assert_eq!(casted.as_primitive::<TimestampSecondType>().value(0), 1);
+
+        // Era 1 starts where era 0 ends.
+        options.timestamp_options.ntp_era_rollover = true;
+        let array = UInt64Array::from(vec![0x0000_0000_0000_0000, 0xE912_AA30_0000_0000]);
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        let casted = casted.as_primitive::<TimestampSecondType>();
+        assert_eq!(
+            casted.value_as_datetime(0).unwrap().to_string(),
+            "2036-02-07 06:28:16"
+        );
+        assert_eq!(casted.value(1), 1701325744);
+
+        // Big-endian binary values.
+        options.timestamp_options.binary_epoch_kind = BinaryEpochKind::RawI64(Endianness::Big);
+        let bytes = 0xE912_AA30_4000_0000u64.to_be_bytes();
+        let array = FixedSizeBinaryArray::try_from_iter([bytes].into_iter()).unwrap();
+        let to_type = DataType::Timestamp(TimeUnit::Millisecond, None);
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        let casted = casted.as_primitive::<TimestampMillisecondType>();
+        assert_eq!(casted.value(0), 1701325744250);
+    }
+
+    #[test]
+    fn test_anchor() {
+        use arrow_array::DurationMicrosecondArray;
+
+        let array = DurationMicrosecondArray::from(vec![Some(1_500_000), Some(-250), None]);
+        let to_type = DataType::Timestamp(TimeUnit::Millisecond, None);
+        assert!(crate::cast_with_options(
+            &array,
+            &to_type,
+            &CastOptions::new().with_passthrough(false)
+        )
+        .is_err());
+
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.anchor = Some((1701325744, TimeUnit::Second));
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        let casted = casted.as_primitive::<TimestampMillisecondType>();
+        assert_eq!(casted.value(0), 1701325745500);
+        // Truncated toward zero like other downscaling.
+        assert_eq!(casted.value(1), 1701325744000);
+        assert!(casted.is_null(2));
+
+        // Anchored near the end of nanoseconds.
+        options.timestamp_options.anchor = Some((i64::MAX - 1_000, TimeUnit::Nanosecond));
+        let to_type = DataType::Timestamp(TimeUnit::Nanosecond, None);
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        let casted = casted.as_primitive::<TimestampNanosecondType>();
+        assert!(casted.is_null(0));
+        assert_eq!(casted.value(1), i64::MAX - 251_000);
+        options.safe = false;
+        assert!(crate::cast_with_options(&array, &to_type, &options).is_err());
+    }
+}
@@ -0,0 +1,189 @@
+//! Epoch columns tagged with their unit in field metadata, in the style of extension types.
+
+use std::collections::HashMap;
+
+use arrow_array::ArrayRef;
+use arrow_schema::{ArrowError, DataType, Field, TimeUnit};
+
+use crate::{
+    cast_with_report,
+    time_unit::{unit_from_str, unit_str},
+    CastOptions, CastReport,
+};
+
+/// Field metadata key of the extension name, as in the arrow columnar format.
+pub const EXTENSION_NAME_KEY: &str = "ARROW:extension:name";
+/// Extension name written by [annotate_epoch_extension].
+pub const EPOCH_EXTENSION_NAME: &str = "arrow_cast_guess_precision.epoch";
+/// Default field metadata key of the unit of epoch columns, see
+/// [epoch_unit_metadata_key](crate::TimestampCastOptions::epoch_unit_metadata_key).
+pub const EPOCH_UNIT_METADATA_KEY: &str = "arrow_cast_guess_precision.epoch_unit";
+/// Spellings of units in epoch metadata, by [unit_code](crate::unit_code), see
+/// [time_unit](crate::time_unit).
+pub const EPOCH_UNITS: [&str; 4] = [
+    unit_str(TimeUnit::Second),
+    unit_str(TimeUnit::Millisecond),
+    unit_str(TimeUnit::Microsecond),
+    unit_str(TimeUnit::Nanosecond),
+];
+
+/// Cast the column of the field, taking values in the unit of its epoch metadata if any.
+///
+/// The unit is read from the
+/// [epoch_unit_metadata_key](crate::TimestampCastOptions::epoch_unit_metadata_key) of the
+/// field, bypassing guessing. Without it, or with a malformed one, the unit is guessed as usual.
+pub fn resolve_epoch_extension(
+    field: &Field,
+    array: &ArrayRef,
+    to_type: &DataType,
+    options: &CastOptions,
+) -> Result<ArrayRef, ArrowError> {
+    resolve_epoch_extension_with_report(field, array, to_type, options).map(|(array, _)| array)
+}
+
+/// [resolve_epoch_extension] with the report, a malformed unit is reported in
+/// [malformed_epoch_unit](CastReport::malformed_epoch_unit).
+pub fn resolve_epoch_extension_with_report(
+    field: &Field,
+    array: &ArrayRef,
+    to_type: &DataType,
+    options: &CastOptions,
+) -> Result<(ArrayRef, CastReport), ArrowError> {
+    match epoch_unit(field, options) {
+        Ok(Some(unit)) => {
+            let mut options = options.clone();
+            options.timestamp_options.assume_unit = Some(unit);
+            cast_with_report(array, to_type, &options)
+        }
+        Ok(None) => cast_with_report(array, to_type, options),
+        Err(malformed) => {
+            let (array, mut report) = cast_with_report(array, to_type, options)?;
+            report.malformed_epoch_unit = Some(malformed);
+            Ok((array, report))
+        }
+    }
+}
+
+/// Tag the field as an epoch column of the unit, keeping existing metadata.
+///
+/// The unit is written under [EPOCH_UNIT_METADATA_KEY], and the extension name under
+/// [EXTENSION_NAME_KEY] unless the field has one already.
+pub fn annotate_epoch_extension(field: Field, guessed_unit: TimeUnit) -> Field {
+    let mut metadata: HashMap<String, String> = field.metadata().clone();
+    metadata
+        .entry(EXTENSION_NAME_KEY.to_string())
+        .or_insert_with(|| EPOCH_EXTENSION_NAME.to_string());
+    metadata.insert(
+        EPOCH_UNIT_METADATA_KEY.to_string(),
+        unit_str(guessed_unit).to_string(),
+    );
+    field.with_metadata(metadata)
+}
+
+/// The unit of the epoch metadata of the field, `None` if absent, or the malformed value.
+pub(crate) fn epoch_unit(field: &Field, options: &CastOptions) -> Result<Option<TimeUnit>, String> {
+    let key = &options.timestamp_options.epoch_unit_metadata_key;
+    let Some(value) = field.metadata().get(key) else {
+        return Ok(None);
+    };
+    unit_from_str(value).map(Some).ok_or_else(|| value.clone())
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use arrow_array::{
+        cast::AsArray, types::TimestampMillisecondType, Array, Int64Array, RecordBatch,
+    };
+    use arrow_schema::Schema;
+
+    use super::*;
+    use crate::{cast_record_batch, SchemaCastOptions};
+
+    #[test]
+    fn test_epoch_extension() {
+        // Milliseconds near the epoch, guessed as seconds on their own.
+        let raw: ArrayRef = Arc::new(Int64Array::from(vec![Some(1701325744), None]));
+        let to_type = DataType::Timestamp(TimeUnit::Millisecond, None);
+        let field = annotate_epoch_extension(
+            Field::new("ts", DataType::Int64, true).with_metadata(
+                [(EXTENSION_NAME_KEY.to_string(), "my.epoch".to_string())]
+                    .into_iter()
+                    .collect(),
+            ),
+            TimeUnit::Millisecond,
+        );
+        assert_eq!(field.metadata()[EXTENSION_NAME_KEY], "my.epoch");
+        assert_eq!(field.metadata()[EPOCH_UNIT_METADATA_KEY], "ms");
+
+        // The metadata survives IPC and short-circuits guessing on re-ingest.
+        let batch = RecordBatch::try_new(Arc::new(Schema::new(vec![field])), vec![raw]).unwrap();
+        let mut buf = Vec::new();
+        let mut writer =
+            arrow::ipc::writer::StreamWriter::try_new(&mut buf, &batch.schema()).unwrap();
+        writer.write(&batch).unwrap();
+        writer.finish().unwrap();
+        drop(writer);
+        let mut reader = arrow::ipc::reader::StreamReader::try_new(buf.as_slice(), None).unwrap();
+        let batch = reader.next().unwrap().unwrap();
+        let schema = batch.schema();
+        let field = schema.field(0);
+        let options = CastOptions::new().with_passthrough(false);
+        let (casted, report) =
+            resolve_epoch_extension_with_report(field, batch.column(0), &to_type, &options)
+                .unwrap();
+        assert_eq!(report.guessed_unit, None);
+        let casted = casted.as_primitive::<TimestampMillisecondType>();
+        assert_eq!(casted.value(0), 1701325744);
+        assert!(casted.is_null(1));
+
+        let to_schema = Arc::new(Schema::new(vec![Field::new("ts", to_type.clone(), true)]));
+        let guessed = cast_record_batch(
+            &batch,
+            to_schema.clone(),
+            &SchemaCastOptions::new(CastOptions::new().with_passthrough(false)),
+        )
+        .unwrap();
+        let guessed = guessed.column(0).as_primitive::<TimestampMillisecondType>();
+        assert_eq!(guessed.value(0), 1701325744000);
+        let batch_options = SchemaCastOptions::new(CastOptions::new().with_passthrough(false))
+            .with_epoch_extensions(true)
+            .with_annotate_metadata(true);
+        let resolved = cast_record_batch(&batch, to_schema, &batch_options).unwrap();
+        assert_eq!(resolved.column(0).as_ref(), casted as &dyn Array);
+        let metadata = resolved.schema().field(0).metadata().clone();
+        assert_eq!(metadata[EPOCH_UNIT_METADATA_KEY], "ms");
+        assert_eq!(metadata[crate::STRATEGY_METADATA_KEY], "epoch_extension");
+
+        // Spellings are case-insensitive, the key is configurable.
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.epoch_unit_metadata_key = "unit".to_string();
+        let field = Field::new("ts", DataType::Int64, true).with_metadata(
+            [("unit".to_string(), "MS".to_string())]
+                .into_iter()
+                .collect(),
+        );
+        let casted = resolve_epoch_extension(&field, batch.column(0), &to_type, &options).unwrap();
+        assert_eq!(
+            casted.as_primitive::<TimestampMillisecondType>().value(0),
+            1701325744
+        );
+
+        // Malformed units fall back to guessing.
+        let field = Field::new("ts", DataType::Int64, true).with_metadata(
+            [("unit".to_string(), "min".to_string())]
+                .into_iter()
+                .collect(),
+        );
+        let (casted, report) =
+            resolve_epoch_extension_with_report(&field, batch.column(0), &to_type, &options)
+                .unwrap();
+        assert_eq!(report.malformed_epoch_unit.as_deref(), Some("min"));
+        assert_eq!(report.guessed_unit, Some(TimeUnit::Second));
+        assert_eq!(
+            casted.as_primitive::<TimestampMillisecondType>().value(0),
+            1701325744000
+        );
+    }
+}
@@ -0,0 +1,238 @@
+//! Floats casting from and to timestamps.
+
+use std::sync::Arc;
+
+use arrow_array::{
+    cast::AsArray,
+    types::{Float64Type, Int64Type},
+    Array, ArrayRef, Float64Array, PrimitiveArray,
+};
+use arrow_schema::{ArrowError, DataType, TimeUnit};
+
+use crate::{kernel, kernel::time_unit_multiple, CastOptions, CastReport, NonFinitePolicy};
+
+/// Whether the float array has any NaN or infinite value.
+pub(crate) fn has_non_finite(array: &dyn Array) -> Result<bool, ArrowError> {
+    let floats = arrow_cast::cast(array, &DataType::Float64)?;
+    Ok(floats
+        .as_primitive::<Float64Type>()
+        .iter()
+        .flatten()
+        .any(|v| !v.is_finite()))
+}
+
+/// Null out non-finite values by the policy, keeping the float type.
+///
+/// Returns the array and the infinite values to saturate later, as `(row, is_positive)`.
+pub(crate) fn null_non_finite(
+    array: &dyn Array,
+    cast_options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<(ArrayRef, Vec<(usize, bool)>), ArrowError> {
+    let policy = cast_options.non_finite_policy();
+    let floats = arrow_cast::cast(array, &DataType::Float64)?;
+    let floats = floats.as_primitive::<Float64Type>();
+    let mut infinities = Vec::new();
+    let mut values = Vec::with_capacity(floats.len());
+    for (i, v) in floats.iter().enumerate() {
+        let Some(v) = v.filter(|v| !v.is_finite()) else {
+            values.push(v);
+            continue;
+        };
+        match policy {
+            NonFinitePolicy::Saturate if v.is_infinite() => infinities.push((i, v > 0.)),
+            NonFinitePolicy::Error => {
+                return Err(ArrowError::CastError(format!(
+                    "Cannot cast non-finite value {v} at row {i} to timestamp"
+                )))
+            }
+            NonFinitePolicy::Saturate if !cast_options.safe => {
+                return Err(ArrowError::CastError(format!(
+                    "Cannot cast NaN at row {i} to timestamp"
+                )))
+            }
+            _ => report.null_causes.policy += 1,
+        }
+        values.push(None);
+    }
+    // Finite values are exact when casting back to the narrower float type.
+    let array = arrow_cast::cast(&Float64Array::from(values), array.data_type())?;
+    Ok((array, infinities))
+}
+
+/// Set the infinite values to the clamp bounds of the timestamp array.
+pub(crate) fn saturate_infinities(
+    array: ArrayRef,
+    infinities: &[(usize, bool)],
+    cast_options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<ArrayRef, ArrowError> {
+    if infinities.is_empty() {
+        return Ok(array);
+    }
+    let DataType::Timestamp(unit, tz) = array.data_type().clone() else {
+        return Ok(array);
+    };
+    let (min, max) = cast_options
+        .timestamp_options
+        .clamp_range
+        .unwrap_or((i64::MIN, i64::MAX));
+    let values = arrow_cast::cast(&array, &DataType::Int64)?;
+    let mut values: Vec<Option<i64>> = values.as_primitive::<Int64Type>().iter().collect();
+    for (i, positive) in infinities {
+        values[*i] = Some(if *positive { max } else { min });
+    }
+    report.saturated += infinities.len();
+    let values = values.into_iter().collect();
+    Ok(kernel::make_timestamp_array(&values, unit, tz))
+}
+
+/// Timestamps in the unit to epoch seconds with fraction.
+pub(crate) fn timestamp_to_seconds(
+    array: &dyn Array,
+    unit: TimeUnit,
+) -> Result<ArrayRef, ArrowError> {
+    let scale = time_unit_multiple(unit);
+    let integers = arrow_cast::cast(array, &DataType::Int64)?;
+    // Add the whole seconds and the fraction, rounding once.
+    let seconds: Float64Array = integers
+        .as_primitive::<Int64Type>()
+        .unary(|v| (v / scale) as f64 + (v % scale) as f64 / scale as f64);
+    Ok(Arc::new(seconds))
+}
+
+/// Finite epoch seconds with fraction to timestamps in the unit, rounded to the nearest.
+pub(crate) fn seconds_to_timestamp(
+    array: &dyn Array,
+    unit: TimeUnit,
+    cast_options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<PrimitiveArray<Int64Type>, ArrowError> {
+    let scale = time_unit_multiple(unit);
+    let floats = arrow_cast::cast(array, &DataType::Float64)?;
+    kernel::map_or_null(floats.as_primitive::<Float64Type>(), |_, v| {
+        let (seconds, fraction) = (v.floor(), v - v.floor());
+        let scaled = (seconds >= i64::MIN as f64 && seconds < i64::MAX as f64)
+            .then(|| (seconds as i64).checked_mul(scale))
+            .flatten()
+            .and_then(|s| s.checked_add((fraction * scale as f64).round() as i64));
+        match scaled {
+            Some(scaled) => Ok(Some(scaled)),
+            None => kernel::overflow(v < 0.0, cast_options, report, || {
+                format!("Cannot cast {v} seconds to {unit:?}: overflow")
+            }),
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::{
+        types::{TimestampMillisecondType, TimestampSecondType},
+        Float32Array,
+    };
+    use arrow_schema::TimeUnit;
+
+    use super::*;
+
+    #[test]
+    fn test_non_finite_policy() {
+        let array = Float64Array::from(vec![
+            Some(f64::NAN),
+            Some(1701325744956.),
+            Some(f64::INFINITY),
+            Some(f64::NEG_INFINITY),
+            None,
+        ]);
+        let to_type = DataType::Timestamp(TimeUnit::Millisecond, None);
+
+        // NaN first is not the representative value.
+        let (casted, report) = crate::cast_with_report(
+            &array,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        assert_eq!(report.guessed_unit, Some(TimeUnit::Millisecond));
+        assert_eq!(report.null_causes.policy, 3);
+        let casted = casted.as_primitive::<TimestampMillisecondType>();
+        assert_eq!(casted.null_count(), 4);
+        assert_eq!(casted.value(1), 1701325744956);
+
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.non_finite_policy = Some(NonFinitePolicy::Error);
+        assert!(crate::cast_with_options(&array, &to_type, &options).is_err());
+        options.timestamp_options.non_finite_policy = None;
+        options.safe = false;
+        assert!(crate::cast_with_options(&array, &to_type, &options).is_err());
+
+        options.timestamp_options.non_finite_policy = Some(NonFinitePolicy::Saturate);
+        assert!(crate::cast_with_options(&array, &to_type, &options).is_err());
+        options.safe = true;
+        let (casted, report) = crate::cast_with_report(&array, &to_type, &options).unwrap();
+        assert_eq!(report.saturated, 2);
+        let casted = casted.as_primitive::<TimestampMillisecondType>();
+        assert!(casted.is_null(0));
+        assert_eq!(casted.value(1), 1701325744956);
+        assert_eq!(casted.value(2), i64::MAX);
+        assert_eq!(casted.value(3), i64::MIN);
+
+        // Infinities only, within the clamp range.
+        let array = Float64Array::from(vec![f64::INFINITY, f64::NEG_INFINITY]);
+        options.timestamp_options.clamp_range = Some((0, 100));
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        let casted = casted.as_primitive::<TimestampMillisecondType>();
+        assert_eq!(casted.values(), &[100, 0]);
+
+        // Float32 keeps casting as seconds.
+        let array = Float32Array::from(vec![f32::NAN, 1e9, f32::INFINITY]);
+        let to_type = DataType::Timestamp(TimeUnit::Second, None);
+        let casted = crate::cast_with_options(
+            &array,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        let casted = casted.as_primitive::<TimestampSecondType>();
+        assert!(casted.is_null(0));
+        assert_eq!(casted.value(1), 1_000_000_000);
+        assert!(casted.is_null(2));
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        let casted = casted.as_primitive::<TimestampSecondType>();
+        assert_eq!(casted.value(2), 100);
+    }
+
+    #[test]
+    fn test_fractional_seconds() {
+        let array = arrow_array::TimestampMicrosecondArray::from(vec![
+            Some(1701325744956789),
+            Some(-1),
+            None,
+        ]);
+        let mut options = CastOptions::new().with_passthrough(false);
+        let casted = crate::cast_with_options(&array, &DataType::Float64, &options).unwrap();
+        assert_eq!(
+            casted.as_primitive::<Float64Type>().value(0),
+            1701325744956789.0
+        );
+
+        options.timestamp_options.timestamps_as_fractional_seconds = true;
+        let casted = crate::cast_with_options(&array, &DataType::Float64, &options).unwrap();
+        let seconds = casted.as_primitive::<Float64Type>();
+        assert_eq!(seconds.value(0), 1701325744.956789);
+        assert_eq!(seconds.value(1), -0.000001);
+        assert!(seconds.is_null(2));
+
+        // Round trip within one unit.
+        for unit in [TimeUnit::Microsecond, TimeUnit::Nanosecond] {
+            let to_type = DataType::Timestamp(unit, None);
+            let back = crate::cast_with_options(seconds, &to_type, &options).unwrap();
+            let back = arrow_cast::cast(&back, &DataType::Int64).unwrap();
+            let back = back.as_primitive::<Int64Type>();
+            let scale = time_unit_multiple(unit) / 1_000_000;
+            assert!((back.value(0) - 1701325744956789 * scale).abs() <= scale);
+            assert_eq!(back.value(1), -scale);
+            assert!(back.is_null(2));
+        }
+    }
+}
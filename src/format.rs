@@ -0,0 +1,131 @@
+//! Format settings used when casting temporal values to strings.
+
+use arrow_cast::display::FormatOptions;
+
+/// The subset of [FormatOptions] that affects casting.
+///
+/// Casting only consults these settings when the target type is a string type,
+/// so callers could build them without depending on `arrow-cast` directly, and apply
+/// them by [CastOptions::with_format_settings](crate::CastOptions::with_format_settings).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FormatSettings<'a> {
+    null: &'a str,
+    date_format: Option<&'a str>,
+    datetime_format: Option<&'a str>,
+    timestamp_format: Option<&'a str>,
+    timestamp_tz_format: Option<&'a str>,
+    time_format: Option<&'a str>,
+    timezone: Option<&'a str>,
+}
+
+impl Default for FormatSettings<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> FormatSettings<'a> {
+    pub const fn new() -> Self {
+        Self {
+            null: "",
+            date_format: None,
+            datetime_format: None,
+            timestamp_format: None,
+            timestamp_tz_format: None,
+            time_format: None,
+            timezone: None,
+        }
+    }
+
+    /// Format string for nulls.
+    pub const fn null(&self) -> &'a str {
+        self.null
+    }
+
+    /// Date format for date arrays.
+    pub const fn date_format(&self) -> Option<&'a str> {
+        self.date_format
+    }
+
+    /// Format for DateTime arrays.
+    pub const fn datetime_format(&self) -> Option<&'a str> {
+        self.datetime_format
+    }
+
+    /// Timestamp format for timestamp arrays.
+    pub const fn timestamp_format(&self) -> Option<&'a str> {
+        self.timestamp_format
+    }
+
+    /// Timestamp format for timestamp with timezone arrays.
+    pub const fn timestamp_tz_format(&self) -> Option<&'a str> {
+        self.timestamp_tz_format
+    }
+
+    /// Time format for time arrays.
+    pub const fn time_format(&self) -> Option<&'a str> {
+        self.time_format
+    }
+
+    /// Timezone timestamps are displayed in.
+    pub const fn timezone(&self) -> Option<&'a str> {
+        self.timezone
+    }
+
+    /// Set the format string for nulls.
+    pub fn with_null(mut self, null: &'a str) -> Self {
+        self.null = null;
+        self
+    }
+
+    /// Set the date format for date arrays.
+    pub fn with_date_format(mut self, format: Option<&'a str>) -> Self {
+        self.date_format = format;
+        self
+    }
+
+    /// Set the format for DateTime arrays.
+    pub fn with_datetime_format(mut self, format: Option<&'a str>) -> Self {
+        self.datetime_format = format;
+        self
+    }
+
+    /// Set the timestamp format for timestamp arrays.
+    pub fn with_timestamp_format(mut self, format: Option<&'a str>) -> Self {
+        self.timestamp_format = format;
+        self
+    }
+
+    /// Set the timestamp format for timestamp with timezone arrays.
+    pub fn with_timestamp_tz_format(mut self, format: Option<&'a str>) -> Self {
+        self.timestamp_tz_format = format;
+        self
+    }
+
+    /// Set the time format for time arrays.
+    pub fn with_time_format(mut self, format: Option<&'a str>) -> Self {
+        self.time_format = format;
+        self
+    }
+
+    /// Display timestamps in the timezone, e.g. `+08:00` or `Asia/Shanghai`.
+    ///
+    /// Timestamps without timezone are taken as UTC. Arrow format options have no such
+    /// setting, the timestamps are relabeled with the timezone before formatting, so they
+    /// follow the timestamp with timezone format.
+    pub fn with_timezone(mut self, timezone: Option<&'a str>) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Convert to arrow format options, without the [timezone](Self::timezone).
+    pub fn to_format_options(&self) -> FormatOptions<'a> {
+        crate::shim::format_options(self)
+    }
+}
+
+impl<'a> From<&FormatSettings<'a>> for FormatOptions<'a> {
+    fn from(settings: &FormatSettings<'a>) -> Self {
+        settings.to_format_options()
+    }
+}
@@ -0,0 +1,513 @@
+//! Guessing the unit of an array by strategies.
+
+use arrow_array::{types::Int64Type, Array, PrimitiveArray};
+use arrow_schema::{ArrowError, TimeUnit};
+
+use crate::{
+    guess_precision_with_bound_years, kernel::time_unit_multiple, report::NearBoundary,
+    CastOptions, CastReport, GuessStrategy, TimestampCastOptions,
+};
+
+const UNITS: [TimeUnit; 4] = [
+    TimeUnit::Second,
+    TimeUnit::Millisecond,
+    TimeUnit::Microsecond,
+    TimeUnit::Nanosecond,
+];
+
+/// Values commonly standing for missing or unknown timestamps.
+#[inline]
+pub(crate) const fn is_sentinel(value: i64) -> bool {
+    matches!(value, 0 | -1 | i64::MIN | i64::MAX)
+}
+
+/// The unit whose lower bound the value is within the boundary margin of.
+pub(crate) fn near_boundary(value: i64, options: &TimestampCastOptions) -> Option<TimeUnit> {
+    let margin = options.boundary_margin?;
+    let millis = (86400 * 365_i64).saturating_mul(options.guessing_bound_years);
+    let micros = millis.saturating_mul(1000);
+    let nanos = micros.saturating_mul(1000);
+    let value = value.unsigned_abs() as f64;
+    [
+        (TimeUnit::Millisecond, millis),
+        (TimeUnit::Microsecond, micros),
+        (TimeUnit::Nanosecond, nanos),
+    ]
+    .into_iter()
+    .find(|(_, bound)| (value - *bound as f64).abs() <= margin * *bound as f64)
+    .map(|(unit, _)| unit)
+}
+
+/// Count the value if near a boundary, failing with [strict](TimestampCastOptions::strict).
+pub(crate) fn check_boundary(
+    value: i64,
+    options: &TimestampCastOptions,
+    counts: &mut NearBoundary,
+) -> Result<(), ArrowError> {
+    let Some(unit) = near_boundary(value, options) else {
+        return Ok(());
+    };
+    if options.strict {
+        return Err(ArrowError::CastError(format!(
+            "Value {value} is near the lower bound of {unit:?}, the unit is ambiguous"
+        )));
+    }
+    counts.add(unit);
+    Ok(())
+}
+
+/// Values guessing reads, with their rows.
+pub(crate) fn candidates<'a>(
+    array: &'a PrimitiveArray<Int64Type>,
+    options: &'a TimestampCastOptions,
+) -> impl DoubleEndedIterator<Item = (usize, i64)> + 'a {
+    (0..array.len())
+        .filter(|row| array.is_valid(*row))
+        .map(|row| (row, array.value(row)))
+        .filter(|(_, v)| !(options.skip_sentinels && is_sentinel(*v)))
+}
+
+/// Guess the unit of integers by the strategy of the options, `None` if there is no evidence.
+///
+/// [GuessStrategy::PerValue] guesses like [GuessStrategy::FirstNonNull] here, for callers
+/// needing one unit for the array.
+///
+/// Values near the boundary and values scanned are counted in the report.
+pub(crate) fn guess_unit(
+    array: &PrimitiveArray<Int64Type>,
+    options: &TimestampCastOptions,
+    report: &mut CastReport,
+) -> Result<Option<TimeUnit>, ArrowError> {
+    let guess = |v: i64| guess_precision_with_bound_years(v, options.guessing_bound_years);
+    if matches!(
+        options.guess_strategy,
+        GuessStrategy::FirstNonNull | GuessStrategy::PerValue
+    ) {
+        let pick = |skip| match options.guess_from_end {
+            true => candidates(array, options).rev().nth(skip),
+            false => candidates(array, options).nth(skip),
+        };
+        let first = match pick(options.guess_skip_rows) {
+            None if options.guess_skip_rows > 0 => pick(0),
+            first => first,
+        };
+        if let Some((row, v)) = first {
+            report.values_scanned += 1;
+            report.representative_row = Some(row);
+            check_boundary(v, options, &mut report.near_boundary)?;
+        }
+        return Ok(first.map(|(_, v)| guess(v)));
+    }
+
+    let early_stop = options.early_stop
+        && !options.strict
+        && options.trim_outlier_fraction.is_none()
+        && options.guess_strategy == GuessStrategy::Majority;
+    let sample = options.guess_sample_size.unwrap_or(usize::MAX);
+    let total = (array.len() - array.null_count()).min(sample);
+    let mut counts = [0usize; 4];
+    let mut scanned = 0;
+    for (_, v) in candidates(array, options).take(sample) {
+        scanned += 1;
+        check_boundary(v, options, &mut report.near_boundary)?;
+        let unit = guess(v);
+        counts[UNITS.iter().position(|u| *u == unit).unwrap()] += 1;
+        if early_stop {
+            let mut sorted = counts;
+            sorted.sort_unstable();
+            if sorted[3] - sorted[2] > total - scanned {
+                break;
+            }
+        }
+    }
+    report.values_scanned += scanned;
+    if let Some(fraction) = options.trim_outlier_fraction {
+        trim_outliers(&mut counts, fraction, report)?;
+    }
+    if options.strict && counts.iter().filter(|c| **c > 0).count() > 1 {
+        let mixed: Vec<String> = UNITS
+            .iter()
+            .zip(counts)
+            .filter(|(_, c)| *c > 0)
+            .map(|(unit, c)| format!("{c} as {unit:?}"))
+            .collect();
+        return Err(ArrowError::CastError(format!(
+            "Values guessed in mixed units: {}",
+            mixed.join(", ")
+        )));
+    }
+    Ok(match options.guess_strategy {
+        GuessStrategy::Majority => UNITS
+            .iter()
+            .zip(counts)
+            .enumerate()
+            .filter(|(_, (_, c))| *c > 0)
+            .max_by_key(|(i, (_, c))| (*c, *i))
+            .map(|(_, (unit, _))| *unit),
+        // Units are bands of magnitudes, the largest value is in the finest unit counted.
+        _ => UNITS
+            .iter()
+            .zip(counts)
+            .rev()
+            .find(|(_, c)| *c > 0)
+            .map(|(unit, _)| *unit),
+    })
+}
+
+/// Remove values of the smallest and of the largest magnitudes, `fraction` of all values
+/// each, from the counts of units, recording them in the report.
+///
+/// Units are bands of magnitudes, so values are trimmed from the coarsest and the finest
+/// units counted, without sorting them.
+fn trim_outliers(
+    counts: &mut [usize; 4],
+    fraction: f64,
+    report: &mut CastReport,
+) -> Result<(), ArrowError> {
+    if !(0.0..0.5).contains(&fraction) {
+        return Err(ArrowError::CastError(format!(
+            "Cannot trim {fraction} of values as outliers, expected a fraction in [0, 0.5)"
+        )));
+    }
+    let trim = (counts.iter().sum::<usize>() as f64 * fraction) as usize;
+    for bands in [[0, 1, 2, 3], [3, 2, 1, 0]] {
+        let mut left = trim;
+        for band in bands {
+            let trimmed = counts[band].min(left);
+            counts[band] -= trimmed;
+            report.trimmed_outliers[band] += trimmed;
+            left -= trimmed;
+        }
+    }
+    Ok(())
+}
+
+/// The source unit after capping the factor of rescaling from the guessed unit,
+/// see [max_rescale_factor](crate::TimestampCastOptions::max_rescale_factor).
+pub(crate) fn cap_rescale(
+    guessed: TimeUnit,
+    to: TimeUnit,
+    options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<TimeUnit, ArrowError> {
+    let Some(cap) = options.timestamp_options.max_rescale_factor else {
+        return Ok(guessed);
+    };
+    let (from_size, to_size) = (time_unit_multiple(guessed), time_unit_multiple(to));
+    let factor = from_size.max(to_size) / from_size.min(to_size);
+    if factor <= cap {
+        return Ok(guessed);
+    }
+    if options.timestamp_options.strict {
+        return Err(ArrowError::CastError(format!(
+            "Cannot rescale timestamps guessed as {guessed:?} to {to:?}: factor {factor} exceeds {cap}"
+        )));
+    }
+    report.rescale_refused = true;
+    Ok(to)
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::cast::AsArray;
+    use arrow_schema::DataType;
+
+    use super::*;
+    use crate::{CastOptions, GuessProfile, OverflowBehavior};
+
+    #[test]
+    fn test_guess_profiles() {
+        let conservative = TimestampCastOptions::from_profile(GuessProfile::Conservative);
+        assert_eq!(
+            conservative,
+            TimestampCastOptions {
+                guessing_bound_years: 100,
+                guess_strategy: GuessStrategy::Majority,
+                strict: true,
+                overflow_behavior: Some(OverflowBehavior::Error),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            TimestampCastOptions::from_profile(GuessProfile::Default),
+            TimestampCastOptions::default()
+        );
+        let lenient = TimestampCastOptions::from_profile(GuessProfile::Lenient);
+        assert_eq!(
+            lenient,
+            TimestampCastOptions {
+                guessing_bound_years: 10000,
+                guess_strategy: GuessStrategy::PerValue,
+                skip_sentinels: true,
+                ..Default::default()
+            }
+        );
+
+        // Seconds, with a millisecond value sneaking in.
+        let array = arrow_array::Int64Array::from(vec![0, 1701325744, 1701325745956, 1701325746]);
+        let to_type = DataType::Timestamp(TimeUnit::Second, None);
+        let cast = |profile| {
+            let options = CastOptions::new()
+                .with_passthrough(false)
+                .with_profile(profile);
+            crate::cast_with_options(&array, &to_type, &options)
+        };
+        assert!(cast(GuessProfile::Conservative).is_err());
+        let casted = cast(GuessProfile::Default).unwrap();
+        let casted = casted.as_primitive::<arrow_array::types::TimestampSecondType>();
+        assert_eq!(casted.value(2), 1701325745956);
+        let casted = cast(GuessProfile::Lenient).unwrap();
+        let casted = casted.as_primitive::<arrow_array::types::TimestampSecondType>();
+        assert_eq!(casted.values(), &[0, 1701325744, 1701325745, 1701325746]);
+
+        // Tweaked after starting from a profile.
+        let options = CastOptions::new()
+            .with_passthrough(false)
+            .with_timestamp_options(TimestampCastOptions {
+                strict: false,
+                ..TimestampCastOptions::from_profile(GuessProfile::Conservative)
+            });
+        let (_, report) = crate::cast_with_report(&array, &to_type, &options).unwrap();
+        assert_eq!(report.guessed_unit, Some(TimeUnit::Second));
+    }
+
+    #[test]
+    fn test_boundary_margin() {
+        let millis: i64 = 86400 * 365 * 1000;
+        let micros = millis * 1000;
+        let array = PrimitiveArray::<Int64Type>::from(vec![
+            millis * 104 / 100,
+            millis * 106 / 100,
+            -micros * 97 / 100,
+            micros * 90 / 100,
+        ]);
+        let mut options = TimestampCastOptions {
+            guessing_bound_years: 1000,
+            guess_strategy: GuessStrategy::Majority,
+            ..Default::default()
+        };
+        let mut report = CastReport::default();
+        guess_unit(&array, &options, &mut report).unwrap();
+        assert_eq!(report.near_boundary, NearBoundary::default());
+
+        options.boundary_margin = Some(0.05);
+        guess_unit(&array, &options, &mut report).unwrap();
+        assert_eq!(
+            report.near_boundary,
+            NearBoundary {
+                millisecond: 1,
+                microsecond: 1,
+                nanosecond: 0
+            }
+        );
+
+        // Only the first value with the first non-null strategy.
+        options.guess_strategy = GuessStrategy::FirstNonNull;
+        let mut report = CastReport::default();
+        guess_unit(&array, &options, &mut report).unwrap();
+        assert_eq!(report.near_boundary.millisecond, 1);
+        assert_eq!(report.near_boundary.microsecond, 0);
+
+        options.strict = true;
+        let err = guess_unit(&array, &options, &mut report).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("near the lower bound of Millisecond"));
+
+        // Reported by casting.
+        let mut cast_options = CastOptions::new().with_passthrough(false);
+        cast_options.timestamp_options.boundary_margin = Some(0.05);
+        cast_options.timestamp_options.guessing_bound_years = 1000;
+        cast_options.timestamp_options.guess_strategy = GuessStrategy::PerValue;
+        let to_type = DataType::Timestamp(TimeUnit::Nanosecond, None);
+        let (_, report) = crate::cast_with_report(&array, &to_type, &cast_options).unwrap();
+        assert_eq!(report.near_boundary.millisecond, 1);
+        assert_eq!(report.near_boundary.microsecond, 1);
+    }
+
+    #[test]
+    fn test_max_rescale_factor() {
+        let array = arrow_array::Int64Array::from(vec![1701325744]);
+        let to_type = DataType::Timestamp(TimeUnit::Nanosecond, None);
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.max_rescale_factor = Some(1000);
+        let (casted, report) = crate::cast_with_report(&array, &to_type, &options).unwrap();
+        let casted = casted.as_primitive::<arrow_array::types::TimestampNanosecondType>();
+        assert_eq!(casted.value(0), 1701325744);
+        assert_eq!(report.guessed_unit, Some(TimeUnit::Second));
+        assert!(report.rescale_refused);
+
+        // Within the cap downward.
+        let to_type = DataType::Timestamp(TimeUnit::Millisecond, None);
+        let (_, report) = crate::cast_with_report(&array, &to_type, &options).unwrap();
+        assert!(!report.rescale_refused);
+
+        let to_type = DataType::Timestamp(TimeUnit::Nanosecond, None);
+        options.timestamp_options.strict = true;
+        assert!(crate::cast_with_options(&array, &to_type, &options).is_err());
+
+        options.timestamp_options.max_rescale_factor = Some(1_000_000_000);
+        let (casted, report) = crate::cast_with_report(&array, &to_type, &options).unwrap();
+        let casted = casted.as_primitive::<arrow_array::types::TimestampNanosecondType>();
+        assert_eq!(casted.value(0), 1701325744000000000);
+        assert!(!report.rescale_refused);
+    }
+
+    #[test]
+    fn test_early_stop() {
+        // Ten milliseconds, then seconds in the rest of 1_000_000 values.
+        let mut values = vec![Some(1701325744956); 10];
+        values.push(None);
+        values.extend((0..999_990).map(|i| Some(1701325744 + i)));
+        let array = PrimitiveArray::<Int64Type>::from(values);
+        let mut options = TimestampCastOptions {
+            guess_strategy: GuessStrategy::Majority,
+            ..Default::default()
+        };
+        let mut report = CastReport::default();
+        let full = guess_unit(&array, &options, &mut report).unwrap();
+        assert_eq!(full, Some(TimeUnit::Second));
+        assert_eq!(report.values_scanned, 1_000_000);
+
+        // Seconds overtake milliseconds, so the scan goes on until the lead settles.
+        options.early_stop = true;
+        let mut report = CastReport::default();
+        assert_eq!(guess_unit(&array, &options, &mut report).unwrap(), full);
+        assert!(report.values_scanned > 500_000 && report.values_scanned < 1_000_000);
+
+        let array = PrimitiveArray::<Int64Type>::from_iter_values(
+            std::iter::repeat_n(1701325744956, 10).chain(std::iter::repeat_n(1701325744, 9)),
+        );
+        let mut report = CastReport::default();
+        assert_eq!(
+            guess_unit(&array, &options, &mut report).unwrap(),
+            Some(TimeUnit::Millisecond)
+        );
+        assert_eq!(report.values_scanned, 10);
+
+        // The lead of milliseconds is insurmountable within a sample after 10 of 1_000_000.
+        let mut values = vec![None; 5];
+        values.extend((0..1_000_000).map(|i| match i < 10 {
+            true => Some(1701325744956),
+            false => Some(1701325744 + i),
+        }));
+        let array = PrimitiveArray::<Int64Type>::from(values);
+        options.guess_sample_size = Some(19);
+        let mut report = CastReport::default();
+        assert_eq!(
+            guess_unit(&array, &options, &mut report).unwrap(),
+            Some(TimeUnit::Millisecond)
+        );
+        assert_eq!(report.values_scanned, 10);
+
+        // The same decision as scanning all of the sample.
+        options.early_stop = false;
+        let mut report = CastReport::default();
+        assert_eq!(
+            guess_unit(&array, &options, &mut report).unwrap(),
+            Some(TimeUnit::Millisecond)
+        );
+        assert_eq!(report.values_scanned, 19);
+    }
+
+    #[test]
+    fn test_trim_outlier_fraction() {
+        // Milliseconds, a producer writing a corrupted constant into a hundred rows, and
+        // twenty small counters.
+        let corrupted = 9_200_000_000_000_000_000;
+        let mut values: Vec<i64> = (0..9880).map(|i| 1701325744956 + i).collect();
+        values.extend([corrupted; 100]);
+        values.extend(1..=20);
+        values.swap(0, 9900);
+        let array = PrimitiveArray::<Int64Type>::from_iter_values(values);
+        let mut options = TimestampCastOptions {
+            guess_strategy: GuessStrategy::MaxAbs,
+            ..Default::default()
+        };
+        let guess = |options: &TimestampCastOptions| {
+            let mut report = CastReport::default();
+            guess_unit(&array, options, &mut report).map(|unit| (unit, report.trimmed_outliers))
+        };
+        assert_eq!(
+            guess(&options).unwrap(),
+            (Some(TimeUnit::Nanosecond), [0; 4])
+        );
+        options.trim_outlier_fraction = Some(0.01);
+        assert_eq!(
+            guess(&options).unwrap(),
+            (Some(TimeUnit::Millisecond), [20, 80, 0, 100])
+        );
+        // Strict majority checks the values kept.
+        options.guess_strategy = GuessStrategy::Majority;
+        options.strict = true;
+        assert_eq!(guess(&options).unwrap().0, Some(TimeUnit::Millisecond));
+        options.trim_outlier_fraction = Some(0.005);
+        assert!(guess(&options).is_err());
+        options.trim_outlier_fraction = Some(0.5);
+        assert!(guess(&options).is_err());
+
+        // The first value is corrupted, only consensus strategies trim.
+        let mut cast_options = CastOptions::new().with_passthrough(false);
+        cast_options.timestamp_options.trim_outlier_fraction = Some(0.01);
+        let to_type = DataType::Timestamp(TimeUnit::Millisecond, None);
+        let (_, report) = crate::cast_with_report(&array, &to_type, &cast_options).unwrap();
+        assert_eq!(report.guessed_unit, Some(TimeUnit::Nanosecond));
+        cast_options.timestamp_options.guess_strategy = GuessStrategy::MaxAbs;
+        let (casted, report) = crate::cast_with_report(&array, &to_type, &cast_options).unwrap();
+        assert_eq!(report.guessed_unit, Some(TimeUnit::Millisecond));
+        assert_eq!(report.trimmed_outliers, [20, 80, 0, 100]);
+        let casted = casted.as_primitive::<arrow_array::types::TimestampMillisecondType>();
+        assert_eq!(casted.value(1), 1701325744957);
+    }
+
+    #[test]
+    fn test_guess_skip_rows() {
+        // A boot record near the epoch, then milliseconds, then seconds.
+        let array = PrimitiveArray::<Int64Type>::from(vec![
+            None,
+            Some(7),
+            None,
+            Some(0),
+            Some(1701325744956),
+            Some(1701325745),
+            None,
+        ]);
+        let mut options = TimestampCastOptions::default();
+        let guess = |options: &TimestampCastOptions| {
+            let mut report = CastReport::default();
+            let unit = guess_unit(&array, options, &mut report).unwrap();
+            (unit, report.representative_row)
+        };
+        assert_eq!(guess(&options), (Some(TimeUnit::Second), Some(1)));
+        // Only non-null values are counted.
+        options.guess_skip_rows = 2;
+        assert_eq!(guess(&options), (Some(TimeUnit::Millisecond), Some(4)));
+        options.skip_sentinels = true;
+        options.guess_skip_rows = 1;
+        assert_eq!(guess(&options), (Some(TimeUnit::Millisecond), Some(4)));
+        options.guess_from_end = true;
+        assert_eq!(guess(&options), (Some(TimeUnit::Millisecond), Some(4)));
+        options.guess_skip_rows = 0;
+        assert_eq!(guess(&options), (Some(TimeUnit::Second), Some(5)));
+
+        // Too few values to skip, the first value is read as without skipping.
+        options.guess_skip_rows = 3;
+        assert_eq!(guess(&options), (Some(TimeUnit::Second), Some(5)));
+        options.guess_from_end = false;
+        assert_eq!(guess(&options), (Some(TimeUnit::Second), Some(1)));
+        let empty = PrimitiveArray::<Int64Type>::from(Vec::<i64>::new());
+        assert_eq!(
+            guess_unit(&empty, &options, &mut CastReport::default()).unwrap(),
+            None
+        );
+
+        // Skipping applies to casting as well.
+        let mut cast_options = CastOptions::new().with_passthrough(false);
+        cast_options.timestamp_options.guess_skip_rows = 2;
+        let to_type = DataType::Timestamp(TimeUnit::Millisecond, None);
+        let (casted, report) = crate::cast_with_report(&array, &to_type, &cast_options).unwrap();
+        let casted = casted.as_primitive::<arrow_array::types::TimestampMillisecondType>();
+        assert_eq!(casted.value(1), 7);
+        assert_eq!(report.representative_row, Some(4));
+    }
+}
@@ -0,0 +1,289 @@
+//! Resolving special interpretations of integers casting to timestamps.
+
+use arrow_array::{types::Int64Type, PrimitiveArray};
+use arrow_schema::{ArrowError, TimeUnit};
+
+use crate::{
+    epoch,
+    kernel::{map_or_null, overflow, rescale_per_value, time_unit_multiple},
+    CastOptions, CastReport, EpochKind, GuessStrategy, Interpretation, MultipleMatchPolicy,
+};
+
+/// Convert integers by the first matching interpretation of the options.
+///
+/// `None` if the unit is to be guessed as usual: no interpretation matches the array, or
+/// [EpochKind::Unix] does first. With [GuessStrategy::PerValue], each row is converted by
+/// its first matching interpretation, and rows matching none are guessed.
+pub(crate) fn cast(
+    array: &PrimitiveArray<Int64Type>,
+    to: TimeUnit,
+    options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<Option<PrimitiveArray<Int64Type>>, ArrowError> {
+    let interpretations = &options.timestamp_options.interpretations;
+    if options.timestamp_options.guess_strategy == GuessStrategy::PerValue {
+        return cast_per_value(array, to, options, report).map(Some);
+    }
+    let matching: Vec<Interpretation> = interpretations
+        .iter()
+        .copied()
+        .filter(|i| array.iter().flatten().all(|v| matches(v, *i, options)))
+        .collect();
+    let applied = resolve(&matching, options, report, || "Values match".to_string())?;
+    report.interpretation = applied;
+    match applied {
+        None | Some(Interpretation::Epoch(EpochKind::Unix)) => Ok(None),
+        Some(interpretation) => convert(array, interpretation, to, options, report).map(Some),
+    }
+}
+
+/// Convert each row by its own first matching interpretation.
+fn cast_per_value(
+    array: &PrimitiveArray<Int64Type>,
+    to: TimeUnit,
+    options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<PrimitiveArray<Int64Type>, ArrowError> {
+    let interpretations = &options.timestamp_options.interpretations;
+    let mut rows = Vec::with_capacity(array.len());
+    for v in array.iter() {
+        let Some(v) = v else {
+            rows.push(None);
+            continue;
+        };
+        let matching: Vec<Interpretation> = interpretations
+            .iter()
+            .copied()
+            .filter(|i| matches(v, *i, options))
+            .collect();
+        rows.push(resolve(&matching, options, report, || {
+            format!("Value {v} matches")
+        })?);
+    }
+    // Convert the rows of each interpretation at once, rows matching none are guessed.
+    let mut values: Vec<Option<i64>> = vec![None; array.len()];
+    let mut applied: Vec<Option<Interpretation>> = Vec::new();
+    for row in &rows {
+        if !applied.contains(row) {
+            applied.push(*row);
+        }
+    }
+    for interpretation in applied {
+        let masked: PrimitiveArray<Int64Type> = array
+            .iter()
+            .zip(&rows)
+            .map(|(v, row)| v.filter(|_| *row == interpretation))
+            .collect();
+        let converted = match interpretation {
+            None | Some(Interpretation::Epoch(EpochKind::Unix)) => {
+                rescale_per_value(&masked, to, options, report)?
+            }
+            Some(interpretation) => convert(&masked, interpretation, to, options, report)?,
+        };
+        for (i, v) in converted.iter().enumerate() {
+            if rows[i] == interpretation {
+                values[i] = v;
+            }
+        }
+    }
+    report.row_interpretations = Some(rows);
+    Ok(PrimitiveArray::from(values))
+}
+
+/// The interpretation applied among the matching ones, by the multiple match policy.
+fn resolve(
+    matching: &[Interpretation],
+    options: &CastOptions,
+    report: &mut CastReport,
+    values: impl FnOnce() -> String,
+) -> Result<Option<Interpretation>, ArrowError> {
+    let Some((first, others)) = matching.split_first() else {
+        return Ok(None);
+    };
+    if !others.is_empty() {
+        match options.timestamp_options.on_multiple_matches {
+            MultipleMatchPolicy::FirstWins => {}
+            MultipleMatchPolicy::Error => {
+                return Err(ArrowError::CastError(format!(
+                    "{} more than one interpretation: {matching:?}",
+                    values()
+                )))
+            }
+            MultipleMatchPolicy::Report => {
+                for other in others {
+                    if !report.other_interpretations.contains(other) {
+                        report.other_interpretations.push(*other);
+                    }
+                }
+            }
+        }
+    }
+    Ok(Some(*first))
+}
+
+/// Whether the value decodes by the interpretation within the guessing bound.
+fn matches(v: i64, interpretation: Interpretation, options: &CastOptions) -> bool {
+    let options = &options.timestamp_options;
+    let seconds = match interpretation {
+        Interpretation::Epoch(EpochKind::Unix) => return true,
+        Interpretation::Epoch(kind) => epoch::unix_seconds(v, kind, options.ntp_era_rollover),
+        Interpretation::PackedDateTime => packed_seconds(v),
+    };
+    let bound = (86400 * 365_i64).saturating_mul(options.guessing_bound_years);
+    seconds.is_some_and(|seconds| seconds.unsigned_abs() <= bound.unsigned_abs())
+}
+
+/// Convert integers of one interpretation other than [EpochKind::Unix].
+fn convert(
+    array: &PrimitiveArray<Int64Type>,
+    interpretation: Interpretation,
+    to: TimeUnit,
+    options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<PrimitiveArray<Int64Type>, ArrowError> {
+    let Interpretation::Epoch(kind) = interpretation else {
+        return packed_to_unix(array, to, options, report);
+    };
+    epoch::to_unix(array, kind, to, options, report)
+}
+
+/// Unix seconds of `YYYYMMDDhhmmss` or `YYYYMMDD` digits, `None` if not a valid date time.
+fn packed_seconds(v: i64) -> Option<i64> {
+    let (date, time) = match v {
+        10_000_000..=99_999_999 => (v, 0),
+        10_000_000_000_000..=99_999_999_999_999 => (v / 1_000_000, v % 1_000_000),
+        _ => return None,
+    };
+    let (year, month, day) = (date / 10_000, date / 100 % 100, date % 100);
+    let (hour, minute, second) = (time / 10_000, time / 100 % 100, time % 100);
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let month_days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return None,
+    };
+    if day == 0 || day > month_days || hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+    // Days since the epoch of the civil date, years starting in March.
+    let (y, m) = match month {
+        1 | 2 => (year - 1, month + 9),
+        _ => (year, month - 3),
+    };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * m + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+    Some(days * 86400 + hour * 3600 + minute * 60 + second)
+}
+
+fn packed_to_unix(
+    array: &PrimitiveArray<Int64Type>,
+    to: TimeUnit,
+    options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<PrimitiveArray<Int64Type>, ArrowError> {
+    let to_size = time_unit_multiple(to);
+    map_or_null(array, |_, v| {
+        let Some(seconds) = packed_seconds(v) else {
+            return Err(ArrowError::CastError(format!(
+                "Cannot cast {v} as a packed date time"
+            )));
+        };
+        match seconds.checked_mul(to_size) {
+            Some(v) => Ok(Some(v)),
+            None => overflow(seconds < 0, options, report, || {
+                format!("Cannot cast packed date time {v} to {to:?}: overflow")
+            }),
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::{cast::AsArray, types::TimestampSecondType, Array, Int64Array};
+    use arrow_schema::DataType;
+
+    use super::*;
+
+    #[test]
+    fn test_interpretations() {
+        // Both 2023-11-30T06:29:04Z packed and 1601-01-24T09:58:33.006Z in FILETIME.
+        let array = Int64Array::from(vec![Some(20231130062904), None]);
+        let to_type = DataType::Timestamp(TimeUnit::Second, None);
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.interpretations = vec![
+            Interpretation::PackedDateTime,
+            Interpretation::Epoch(EpochKind::WindowsFiletime),
+        ];
+        let (casted, report) = crate::cast_with_report(&array, &to_type, &options).unwrap();
+        let casted = casted.as_primitive::<TimestampSecondType>();
+        assert_eq!(
+            casted.value_as_datetime(0).unwrap().to_string(),
+            "2023-11-30 06:29:04"
+        );
+        assert!(casted.is_null(1));
+        assert_eq!(report.interpretation, Some(Interpretation::PackedDateTime));
+        assert!(report.other_interpretations.is_empty());
+
+        options.timestamp_options.interpretations.reverse();
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        assert_eq!(
+            casted
+                .as_primitive::<TimestampSecondType>()
+                .value_as_datetime(0)
+                .unwrap()
+                .to_string(),
+            // Truncated toward the unix epoch.
+            "1601-01-24 09:58:34"
+        );
+
+        options.timestamp_options.on_multiple_matches = MultipleMatchPolicy::Report;
+        let (_, report) = crate::cast_with_report(&array, &to_type, &options).unwrap();
+        assert_eq!(
+            report.interpretation,
+            Some(Interpretation::Epoch(EpochKind::WindowsFiletime))
+        );
+        assert_eq!(
+            report.other_interpretations,
+            vec![Interpretation::PackedDateTime]
+        );
+
+        options.timestamp_options.on_multiple_matches = MultipleMatchPolicy::Error;
+        assert!(crate::cast_with_options(&array, &to_type, &options).is_err());
+
+        // Not packed, so guessed as usual with the unix epoch first.
+        let array = Int64Array::from(vec![20231130062904, 1701325744956]);
+        options.timestamp_options.interpretations = vec![
+            Interpretation::PackedDateTime,
+            Interpretation::Epoch(EpochKind::Unix),
+        ];
+        let (casted, report) = crate::cast_with_report(&array, &to_type, &options).unwrap();
+        assert_eq!(
+            report.interpretation,
+            Some(Interpretation::Epoch(EpochKind::Unix))
+        );
+        assert_eq!(report.guessed_unit, Some(TimeUnit::Millisecond));
+        assert_eq!(
+            casted.as_primitive::<TimestampSecondType>().value(1),
+            1701325744
+        );
+
+        // Each row by its own.
+        options.timestamp_options.on_multiple_matches = MultipleMatchPolicy::FirstWins;
+        options.timestamp_options.guess_strategy = GuessStrategy::PerValue;
+        let (casted, report) = crate::cast_with_report(&array, &to_type, &options).unwrap();
+        let casted = casted.as_primitive::<TimestampSecondType>();
+        assert_eq!(casted.values(), &[1701325744, 1701325744]);
+        assert_eq!(
+            report.row_interpretations,
+            Some(vec![
+                Some(Interpretation::PackedDateTime),
+                Some(Interpretation::Epoch(EpochKind::Unix))
+            ])
+        );
+    }
+}
@@ -0,0 +1,511 @@
+//! Casting integers of elapsed time and interval strings to intervals.
+
+use std::sync::Arc;
+
+use arrow_array::{
+    cast::AsArray,
+    types::{Int64Type, IntervalDayTimeType, IntervalMonthDayNanoType, IntervalYearMonthType},
+    Array, ArrayRef, PrimitiveArray,
+};
+use arrow_schema::{ArrowError, DataType, IntervalUnit};
+
+use crate::{kernel::time_unit_multiple, time_unit::unit_from_str, CastOptions, CastReport};
+
+/// Unit of elapsed time in integers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ElapsedUnit {
+    Millisecond,
+    Second,
+    Minute,
+    Day,
+}
+
+impl ElapsedUnit {
+    /// Number of this unit in one day.
+    #[inline]
+    const fn per_day(self) -> i64 {
+        match self {
+            ElapsedUnit::Millisecond => 86_400_000,
+            ElapsedUnit::Second => 86_400,
+            ElapsedUnit::Minute => 1_440,
+            ElapsedUnit::Day => 1,
+        }
+    }
+
+    /// Number of nanoseconds in this unit.
+    #[inline]
+    const fn nanos(self) -> i64 {
+        86_400_000_000_000 / self.per_day()
+    }
+}
+
+/// Guess the unit of elapsed time, assuming it is no longer than `bound_days`.
+///
+/// ```text
+/// |value| <= bound in days    => Day
+/// |value| <= bound in minutes => Minute
+/// |value| <= bound in seconds => Second
+/// otherwise                   => Millisecond
+/// ```
+#[inline]
+pub const fn guess_elapsed_unit(value: i64, bound_days: i64) -> ElapsedUnit {
+    let value = value.unsigned_abs();
+    if value <= bound_days as u64 {
+        return ElapsedUnit::Day;
+    }
+    if value <= bound_days.saturating_mul(1_440) as u64 {
+        return ElapsedUnit::Minute;
+    }
+    if value <= bound_days.saturating_mul(86_400) as u64 {
+        return ElapsedUnit::Second;
+    }
+    ElapsedUnit::Millisecond
+}
+
+/// Cast integers of elapsed time to `Interval(DayTime)` or `Interval(MonthDayNano)`.
+///
+/// Days and the remainder within a day always share the sign of the value.
+pub(crate) fn cast_int_to_interval(
+    array: &dyn Array,
+    interval: IntervalUnit,
+    cast_options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<ArrayRef, ArrowError> {
+    let array = arrow_cast::cast(array, &DataType::Int64)?;
+    let array = array.as_primitive::<Int64Type>();
+    let options = &cast_options.timestamp_options;
+    let unit = match options.elapsed_unit {
+        Some(unit) => unit,
+        None if options.guess_timestamp_precision => {
+            let guessed = array
+                .iter()
+                .flatten()
+                .next()
+                .map(|v| guess_elapsed_unit(v, options.elapsed_bound_days));
+            report.guessed_elapsed_unit = guessed;
+            guessed.unwrap_or(ElapsedUnit::Millisecond)
+        }
+        None => ElapsedUnit::Millisecond,
+    };
+
+    // Split into days and the remainder in nanoseconds.
+    let mut split = |v: i64| -> Result<Option<(i32, i64)>, ArrowError> {
+        let days = i32::try_from(v / unit.per_day()).ok();
+        match days {
+            Some(days) => Ok(Some((days, v % unit.per_day() * unit.nanos()))),
+            None if cast_options.safe => {
+                report.null_causes.overflow += 1;
+                Ok(None)
+            }
+            None => Err(ArrowError::CastError(format!(
+                "Cannot cast {v} {unit:?}s to interval: days overflow"
+            ))),
+        }
+    };
+    let mut parts = Vec::with_capacity(array.len());
+    for v in array.iter() {
+        parts.push(match v {
+            Some(v) => split(v)?,
+            None => None,
+        });
+    }
+
+    match interval {
+        IntervalUnit::DayTime => {
+            let array: PrimitiveArray<IntervalDayTimeType> = parts
+                .into_iter()
+                .map(|p| p.map(|(days, nanos)| (days, (nanos / 1_000_000) as i32)))
+                .map(|p| p.map(|(days, millis)| IntervalDayTimeType::make_value(days, millis)))
+                .collect();
+            Ok(Arc::new(array))
+        }
+        IntervalUnit::MonthDayNano => {
+            let array: PrimitiveArray<IntervalMonthDayNanoType> = parts
+                .into_iter()
+                .map(|p| {
+                    p.map(|(days, nanos)| IntervalMonthDayNanoType::make_value(0, days, nanos))
+                })
+                .collect();
+            Ok(Arc::new(array))
+        }
+        IntervalUnit::YearMonth => Err(ArrowError::CastError(
+            "Casting integers to Interval(YearMonth) is not supported".to_string(),
+        )),
+    }
+}
+
+/// Months, days and nanoseconds of an interval, with the semantics of `Interval(MonthDayNano)`.
+type MonthDayNano = (i32, i32, i64);
+
+const NANOS_PER_SECOND: i64 = 1_000_000_000;
+
+/// Field of an interval a unit folds into, with the multiple of the field.
+#[derive(Clone, Copy)]
+enum Field {
+    Months(i32),
+    Days(i32),
+    Nanos(i64),
+}
+
+/// Parse a signed decimal number to the field, fractions are only allowed in nanoseconds.
+fn fold(number: &str, field: Field, interval: &mut MonthDayNano) -> Option<()> {
+    let (negative, number) = match number.strip_prefix('-') {
+        Some(number) => (true, number),
+        None => (false, number.strip_prefix('+').unwrap_or(number)),
+    };
+    let (int, fraction) = number.split_once('.').unwrap_or((number, ""));
+    let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if int.is_empty() && fraction.is_empty() || !digits(int) || !digits(fraction) {
+        return None;
+    }
+    let int: i64 = if int.is_empty() { 0 } else { int.parse().ok()? };
+    let int = if negative { -int } else { int };
+    match field {
+        Field::Months(mul) if fraction.is_empty() => {
+            let v = i32::try_from(int).ok()?.checked_mul(mul)?;
+            interval.0 = interval.0.checked_add(v)?;
+        }
+        Field::Days(mul) if fraction.is_empty() => {
+            let v = i32::try_from(int).ok()?.checked_mul(mul)?;
+            interval.1 = interval.1.checked_add(v)?;
+        }
+        Field::Nanos(mul) => {
+            // Fraction digits beyond nanoseconds are truncated.
+            let fraction = &fraction[..fraction.len().min(9)];
+            let scale = 10_i128.pow(fraction.len() as u32);
+            let frac: i128 = if fraction.is_empty() {
+                0
+            } else {
+                fraction.parse().ok()?
+            };
+            let frac = if negative { -frac } else { frac };
+            let v = (int as i128 * scale + frac) * mul as i128 / scale;
+            interval.2 = interval.2.checked_add(i64::try_from(v).ok()?)?;
+        }
+        _ => return None,
+    }
+    Some(())
+}
+
+/// The field of a unit name in SQL-style interval strings.
+fn sql_field(unit: &str) -> Option<Field> {
+    Some(match unit.to_ascii_lowercase().as_str() {
+        "y" | "yr" | "yrs" | "year" | "years" => Field::Months(12),
+        "mon" | "mons" | "month" | "months" => Field::Months(1),
+        "w" | "week" | "weeks" => Field::Days(7),
+        "d" | "day" | "days" => Field::Days(1),
+        "h" | "hr" | "hrs" | "hour" | "hours" => Field::Nanos(3_600 * NANOS_PER_SECOND),
+        "m" | "min" | "mins" | "minute" | "minutes" => Field::Nanos(60 * NANOS_PER_SECOND),
+        unit => Field::Nanos(NANOS_PER_SECOND / time_unit_multiple(unit_from_str(unit)?)),
+    })
+}
+
+/// Parse SQL-style interval strings like `3 months -2 days 1.5 seconds`.
+///
+/// Numbers and units could be separated by whitespace or not, like `3months`.
+fn parse_sql_interval(s: &str) -> Option<MonthDayNano> {
+    let mut interval = (0, 0, 0);
+    let mut rest = s.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | '-' | '+')))
+            .unwrap_or(rest.len());
+        let (number, tail) = rest.split_at(number_len);
+        let tail = tail.trim_start();
+        let unit_len = tail
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+        fold(number, sql_field(unit)?, &mut interval)?;
+        rest = tail.trim_start();
+    }
+    Some(interval)
+}
+
+/// Parse ISO 8601 durations like `P3M2DT4H` or `-P1Y`, components could be signed.
+fn parse_iso8601_duration(s: &str) -> Option<MonthDayNano> {
+    let s = s.trim();
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let s = s.strip_prefix(['P', 'p'])?;
+    let (date, time) = match s.split_once(['T', 't']) {
+        Some((date, time)) if !time.is_empty() => (date, Some(time)),
+        Some(_) => return None,
+        None => (s, None),
+    };
+    if date.is_empty() && time.is_none() {
+        return None;
+    }
+    let mut interval = (0, 0, 0);
+    let mut components = |part: &str, field: fn(char) -> Option<Field>| -> Option<()> {
+        let mut rest = part;
+        while !rest.is_empty() {
+            let end = rest.find(|c: char| c.is_ascii_alphabetic())?;
+            let designator = rest[end..].chars().next()?;
+            let number = rest[..end].replace(',', ".");
+            fold(
+                &number,
+                field(designator.to_ascii_uppercase())?,
+                &mut interval,
+            )?;
+            rest = &rest[end + 1..];
+        }
+        Some(())
+    };
+    components(date, |c| match c {
+        'Y' => Some(Field::Months(12)),
+        'M' => Some(Field::Months(1)),
+        'W' => Some(Field::Days(7)),
+        'D' => Some(Field::Days(1)),
+        _ => None,
+    })?;
+    if let Some(time) = time {
+        components(time, |c| match c {
+            'H' => Some(Field::Nanos(3_600 * NANOS_PER_SECOND)),
+            'M' => Some(Field::Nanos(60 * NANOS_PER_SECOND)),
+            'S' => Some(Field::Nanos(NANOS_PER_SECOND)),
+            _ => None,
+        })?;
+    }
+    if negative {
+        interval = (
+            interval.0.checked_neg()?,
+            interval.1.checked_neg()?,
+            interval.2.checked_neg()?,
+        );
+    }
+    Some(interval)
+}
+
+/// Cast interval strings to intervals.
+///
+/// SQL-style strings are always parsed, ISO 8601 durations when enabled by the options.
+/// Intervals not representable in the target unit, like months in `Interval(DayTime)`,
+/// are rejected the same as unparsable strings.
+pub(crate) fn cast_string_to_interval(
+    array: &dyn Array,
+    interval: IntervalUnit,
+    cast_options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<ArrayRef, ArrowError> {
+    let iso8601 = cast_options.timestamp_options.iso8601_durations;
+    let strings = arrow_cast::cast(array, &DataType::Utf8)?;
+    let strings = strings.as_string::<i32>();
+    let mut values = Vec::with_capacity(strings.len());
+    for s in strings.iter() {
+        let Some(s) = s else {
+            values.push(None);
+            continue;
+        };
+        let parsed =
+            parse_sql_interval(s).or_else(|| iso8601.then(|| parse_iso8601_duration(s)).flatten());
+        let representable = parsed.filter(|(months, days, nanos)| match interval {
+            IntervalUnit::YearMonth => *days == 0 && *nanos == 0,
+            IntervalUnit::DayTime => {
+                *months == 0 && nanos % 1_000_000 == 0 && i32::try_from(nanos / 1_000_000).is_ok()
+            }
+            IntervalUnit::MonthDayNano => true,
+        });
+        match representable {
+            Some(value) => values.push(Some(value)),
+            None if cast_options.safe => {
+                match parsed {
+                    Some(_) => report.null_causes.policy += 1,
+                    None => report.null_causes.parse_failure += 1,
+                }
+                values.push(None);
+            }
+            None => {
+                return Err(ArrowError::CastError(format!(
+                    "Cannot cast string '{s}' to value of {:?} type",
+                    DataType::Interval(interval)
+                )))
+            }
+        }
+    }
+    let values = values.into_iter();
+    Ok(match interval {
+        IntervalUnit::YearMonth => Arc::new(
+            values
+                .map(|v| v.map(|(months, _, _)| IntervalYearMonthType::make_value(0, months)))
+                .collect::<PrimitiveArray<IntervalYearMonthType>>(),
+        ),
+        IntervalUnit::DayTime => Arc::new(
+            values
+                .map(|v| {
+                    v.map(|(_, days, nanos)| {
+                        IntervalDayTimeType::make_value(days, (nanos / 1_000_000) as i32)
+                    })
+                })
+                .collect::<PrimitiveArray<IntervalDayTimeType>>(),
+        ),
+        IntervalUnit::MonthDayNano => Arc::new(
+            values
+                .map(|v| {
+                    v.map(|(months, days, nanos)| {
+                        IntervalMonthDayNanoType::make_value(months, days, nanos)
+                    })
+                })
+                .collect::<PrimitiveArray<IntervalMonthDayNanoType>>(),
+        ),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_guess_elapsed_unit() {
+        assert_eq!(guess_elapsed_unit(0, 365), ElapsedUnit::Day);
+        assert_eq!(guess_elapsed_unit(-365, 365), ElapsedUnit::Day);
+        assert_eq!(guess_elapsed_unit(366, 365), ElapsedUnit::Minute);
+        assert_eq!(guess_elapsed_unit(365 * 1440 + 1, 365), ElapsedUnit::Second);
+        assert_eq!(
+            guess_elapsed_unit(-(365 * 86400 + 1), 365),
+            ElapsedUnit::Millisecond
+        );
+    }
+
+    #[test]
+    fn test_int_to_interval() {
+        // 1 day 1 hour 1 minute 1 second 1 millisecond
+        let millis: i64 = 90_061_001;
+        let array = arrow_array::Int64Array::from(vec![Some(millis), None, Some(-millis)]);
+        let to_type = DataType::Interval(IntervalUnit::DayTime);
+        let (casted, report) = crate::cast_with_report(
+            &array,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        assert_eq!(report.guessed_elapsed_unit, Some(ElapsedUnit::Millisecond));
+        let casted = casted.as_primitive::<IntervalDayTimeType>();
+        assert_eq!(
+            IntervalDayTimeType::to_parts(casted.value(0)),
+            (1, 3_661_001)
+        );
+        assert!(casted.is_null(1));
+        assert_eq!(
+            IntervalDayTimeType::to_parts(casted.value(2)),
+            (-1, -3_661_001)
+        );
+
+        let to_type = DataType::Interval(IntervalUnit::MonthDayNano);
+        let casted = crate::cast_with_options(
+            &array,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        let casted = casted.as_primitive::<IntervalMonthDayNanoType>();
+        assert_eq!(
+            IntervalMonthDayNanoType::to_parts(casted.value(0)),
+            (0, 1, 3_661_001_000_000)
+        );
+        assert_eq!(
+            IntervalMonthDayNanoType::to_parts(casted.value(2)),
+            (0, -1, -3_661_001_000_000)
+        );
+
+        // Minutes, negative values only.
+        let array = arrow_array::Int32Array::from(vec![-1_441, -1]);
+        let casted = crate::cast_with_options(
+            &array,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        let casted = casted.as_primitive::<IntervalMonthDayNanoType>();
+        assert_eq!(
+            IntervalMonthDayNanoType::to_parts(casted.value(0)),
+            (0, -1, -60_000_000_000)
+        );
+        assert_eq!(
+            IntervalMonthDayNanoType::to_parts(casted.value(1)),
+            (0, 0, -60_000_000_000)
+        );
+
+        // Days overflowing.
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.elapsed_unit = Some(ElapsedUnit::Day);
+        let array = arrow_array::Int64Array::from(vec![i64::MAX, 1]);
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        assert!(casted.is_null(0));
+        assert!(casted.is_valid(1));
+        options.safe = false;
+        assert!(crate::cast_with_options(&array, &to_type, &options).is_err());
+    }
+
+    #[test]
+    fn test_string_to_interval() {
+        let array = arrow_array::StringArray::from(vec![
+            Some("3 months 2 days"),
+            Some("1 year"),
+            Some("-1 year 2 months -3 days 1.5 seconds"),
+            Some("2weeks -1h"),
+            Some("P3M2DT4H"),
+            Some("-P1Y2M"),
+            Some("1 fortnight"),
+            None,
+        ]);
+        let to_type = DataType::Interval(IntervalUnit::MonthDayNano);
+        let (casted, report) = crate::cast_with_report(
+            &array,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        let casted = casted.as_primitive::<IntervalMonthDayNanoType>();
+        let parts = |i| IntervalMonthDayNanoType::to_parts(casted.value(i));
+        assert_eq!(parts(0), (3, 2, 0));
+        assert_eq!(parts(1), (12, 0, 0));
+        assert_eq!(parts(2), (-10, -3, 1_500_000_000));
+        assert_eq!(parts(3), (0, 14, -3_600_000_000_000));
+        // ISO 8601 durations are not parsed by default.
+        assert!(casted.is_null(4));
+        assert!(casted.is_null(6));
+        assert!(casted.is_null(7));
+        assert_eq!(report.null_causes.parse_failure, 3);
+
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.iso8601_durations = true;
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        let casted = casted.as_primitive::<IntervalMonthDayNanoType>();
+        let parts = |i| IntervalMonthDayNanoType::to_parts(casted.value(i));
+        assert_eq!(parts(4), (3, 2, 4 * 3_600_000_000_000));
+        assert_eq!(parts(5), (-14, 0, 0));
+
+        // Not representable in the target unit.
+        let casted = crate::cast_with_options(
+            &array,
+            &DataType::Interval(IntervalUnit::YearMonth),
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        let casted = casted.as_primitive::<IntervalYearMonthType>();
+        assert!(casted.is_null(0));
+        assert_eq!(casted.value(1), 12);
+        let casted = crate::cast_with_options(
+            &array,
+            &DataType::Interval(IntervalUnit::DayTime),
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        let casted = casted.as_primitive::<IntervalDayTimeType>();
+        assert!(casted.is_null(0));
+        assert_eq!(
+            IntervalDayTimeType::to_parts(casted.value(3)),
+            (14, -3_600_000)
+        );
+
+        options.safe = false;
+        assert!(crate::cast_with_options(&array, &to_type, &options).is_err());
+    }
+}
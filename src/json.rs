@@ -0,0 +1,194 @@
+//! Decoding JSON with timestamp guessing.
+
+use std::io::BufRead;
+use std::sync::Arc;
+
+use arrow_array::{
+    cast::AsArray, types::Int64Type, Array, Int64Array, RecordBatch, RecordBatchReader, StringArray,
+};
+use arrow_json::reader::{Reader, ReaderBuilder};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef, TimeUnit};
+
+use crate::{
+    batch::normalize_utc_schema, cast_with_options, cast_with_report, kernel::make_timestamp_array,
+    CastOptions,
+};
+
+/// The type a target field is decoded as before casting.
+///
+/// Timestamps, times and intervals are decoded as strings, numbers included,
+/// so integers of unknown unit and formatted strings could share a column.
+fn relaxed_type(data_type: &DataType) -> DataType {
+    match data_type {
+        DataType::Timestamp(_, _)
+        | DataType::Time32(_)
+        | DataType::Time64(_)
+        | DataType::Interval(_) => DataType::Utf8,
+        other => other.clone(),
+    }
+}
+
+/// The schema JSON is decoded as before casting to `schema`.
+///
+/// Timestamp, time and interval fields are `Utf8`, others are the same.
+pub fn relaxed_json_schema(schema: &Schema) -> Schema {
+    let fields: Vec<Field> = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            field
+                .as_ref()
+                .clone()
+                .with_data_type(relaxed_type(field.data_type()))
+        })
+        .collect();
+    Schema::new_with_metadata(fields, schema.metadata().clone())
+}
+
+/// Read newline-delimited JSON to batches of `schema`, guessing the units of timestamps.
+///
+/// Rows are decoded in the [relaxed schema](relaxed_json_schema) and cast by the options.
+/// Integers and formatted strings could be mixed in a timestamp column, the unit of
+/// integers is guessed by the first batch with integers and pinned for later batches.
+pub fn json_reader_with_guess<R: BufRead>(
+    reader: R,
+    schema: SchemaRef,
+    options: CastOptions<'static>,
+) -> Result<GuessJsonReader<R>, ArrowError> {
+    let schema = normalize_utc_schema(schema, &options);
+    let relaxed = Arc::new(relaxed_json_schema(&schema));
+    let inner = ReaderBuilder::new(relaxed)
+        .with_coerce_primitive(true)
+        .build(reader)?;
+    let pins = vec![None; schema.fields().len()];
+    Ok(GuessJsonReader {
+        inner,
+        schema,
+        options,
+        pins,
+    })
+}
+
+/// Reader of JSON batches cast to the target schema, see [json_reader_with_guess].
+pub struct GuessJsonReader<R> {
+    inner: Reader<R>,
+    schema: SchemaRef,
+    options: CastOptions<'static>,
+    pins: Vec<Option<TimeUnit>>,
+}
+
+impl<R> GuessJsonReader<R> {
+    /// Units pinned for the target fields so far, `None` if not decided yet.
+    pub fn pinned_units(&self) -> &[Option<TimeUnit>] {
+        &self.pins
+    }
+
+    fn cast(&mut self, batch: &RecordBatch) -> Result<RecordBatch, ArrowError> {
+        let mut columns = Vec::with_capacity(batch.num_columns());
+        for ((field, column), pin) in self
+            .schema
+            .fields()
+            .iter()
+            .zip(batch.columns())
+            .zip(self.pins.iter_mut())
+        {
+            let column = match field.data_type() {
+                DataType::Timestamp(unit, tz) if column.data_type() == &DataType::Utf8 => {
+                    let mut options = self.options.clone();
+                    if let Some(unit) = pin {
+                        options.timestamp_options.assume_unit = Some(*unit);
+                    }
+                    let strings = column.as_string::<i32>();
+                    let integers: Int64Array = strings
+                        .iter()
+                        .map(|s| s.and_then(|s| s.trim().parse().ok()))
+                        .collect();
+                    let texts: StringArray = strings
+                        .iter()
+                        .map(|s| s.filter(|s| s.trim().parse::<i64>().is_err()))
+                        .collect();
+                    let (from_integers, report) =
+                        cast_with_report(&integers, field.data_type(), &options)?;
+                    if pin.is_none() {
+                        *pin = report.guessed_unit;
+                    }
+                    let from_texts = cast_with_options(&texts, field.data_type(), &options)?;
+                    let from_integers = arrow_cast::cast(&from_integers, &DataType::Int64)?;
+                    let from_texts = arrow_cast::cast(&from_texts, &DataType::Int64)?;
+                    let merged: Int64Array = from_integers
+                        .as_primitive::<Int64Type>()
+                        .iter()
+                        .zip(from_texts.as_primitive::<Int64Type>().iter())
+                        .map(|(a, b)| a.or(b))
+                        .collect();
+                    make_timestamp_array(&merged, *unit, tz.clone())
+                }
+                _ => cast_with_options(column, field.data_type(), &self.options)?,
+            };
+            columns.push(column);
+        }
+        RecordBatch::try_new(self.schema.clone(), columns)
+    }
+}
+
+impl<R: BufRead> Iterator for GuessJsonReader<R> {
+    type Item = Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(match self.inner.next()? {
+            Ok(batch) => self.cast(&batch),
+            Err(err) => Err(err),
+        })
+    }
+}
+
+impl<R: BufRead> RecordBatchReader for GuessJsonReader<R> {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::types::{Time64MicrosecondType, TimestampMillisecondType};
+
+    use super::*;
+
+    #[test]
+    fn test_json_reader_with_guess() {
+        let json = r#"{"id": 1, "ts": 1701325744956, "at": "06:29:04"}
+{"id": 2, "ts": "2023-11-30T06:29:05.956Z", "at": 23345}
+{"id": 3, "ts": "1701325746956"}
+{"id": 4, "ts": null}
+{"id": 5, "ts": "bad"}
+"#;
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("ts", DataType::Timestamp(TimeUnit::Millisecond, None), true),
+            Field::new("at", DataType::Time64(TimeUnit::Microsecond), true),
+        ]));
+        assert_eq!(
+            relaxed_json_schema(&schema).field(1).data_type(),
+            &DataType::Utf8
+        );
+        let mut reader = json_reader_with_guess(
+            json.as_bytes(),
+            schema.clone(),
+            CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        assert_eq!(reader.schema(), schema);
+        let batch = reader.next().unwrap().unwrap();
+        assert_eq!(reader.pinned_units()[1], Some(TimeUnit::Millisecond));
+        let ts = batch.column(1).as_primitive::<TimestampMillisecondType>();
+        assert_eq!(ts.value(0), 1701325744956);
+        assert_eq!(ts.value(1), 1701325745956);
+        assert_eq!(ts.value(2), 1701325746956);
+        assert!(ts.is_null(3));
+        assert!(ts.is_null(4));
+        let at = batch.column(2).as_primitive::<Time64MicrosecondType>();
+        assert_eq!(at.value(0), 23_344_000_000);
+        assert_eq!(at.value(1), 23_345_000_000);
+        assert!(reader.next().is_none());
+    }
+}
@@ -0,0 +1,850 @@
+//! Kernels rescaling integer timestamps between units.
+
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::sync::Arc;
+
+use arrow_array::{
+    builder::BooleanBufferBuilder,
+    cast::AsArray,
+    make_array,
+    temporal_conversions::as_datetime_with_timezone,
+    timezone::Tz,
+    types::{
+        Int16Type, Int32Type, Int64Type, Int8Type, TimestampMicrosecondType,
+        TimestampMillisecondType, TimestampNanosecondType, TimestampSecondType, UInt16Type,
+        UInt32Type, UInt8Type,
+    },
+    Array, ArrayRef, ArrowPrimitiveType, FixedSizeListArray, Int32Array, LargeListArray, ListArray,
+    PrimitiveArray, StructArray, UInt8Array,
+};
+use arrow_schema::{ArrowError, DataType, FieldRef, TimeUnit};
+
+use crate::{
+    shim::Timezone, time_unit::unit_code, CastOptions, CastReport, DownscalePolicy,
+    OverflowBehavior, TimestampCastOptions, UtcSpelling,
+};
+
+/// Number of units in one second.
+#[inline]
+pub(crate) const fn time_unit_multiple(unit: TimeUnit) -> i64 {
+    match unit {
+        TimeUnit::Second => 1,
+        TimeUnit::Millisecond => 1_000,
+        TimeUnit::Microsecond => 1_000_000,
+        TimeUnit::Nanosecond => 1_000_000_000,
+    }
+}
+
+/// Divide a value when downscaling by the policy, `None` if the policy rejects it.
+#[inline]
+pub(crate) const fn downscale(v: i64, divisor: i64, policy: DownscalePolicy) -> Option<i64> {
+    let (q, r) = (v / divisor, v % divisor);
+    match policy {
+        DownscalePolicy::Truncate => Some(q),
+        DownscalePolicy::Round if r.abs() * 2 >= divisor => Some(q + r.signum()),
+        DownscalePolicy::Round => Some(q),
+        DownscalePolicy::Error if r == 0 => Some(q),
+        DownscalePolicy::Error => None,
+    }
+}
+
+/// Downscale a value by the policy, with the signed remainder in the source unit such that
+/// `coarse * divisor + remainder` is the value.
+///
+/// [DownscalePolicy::Error] is rejected by [check_keep_remainder] beforehand.
+#[inline]
+const fn downscale_keeping_remainder(v: i64, divisor: i64, policy: DownscalePolicy) -> (i64, i32) {
+    let (q, r) = (v / divisor, v % divisor);
+    let coarse = match downscale(v, divisor, policy) {
+        Some(coarse) => coarse,
+        None => q,
+    };
+    (coarse, (r - (coarse - q) * divisor) as i32)
+}
+
+/// Fail if remainders are kept with [DownscalePolicy::Error], which has none to keep.
+fn check_keep_remainder(options: &TimestampCastOptions) -> Result<(), ArrowError> {
+    match (options.keep_downscale_remainder, options.downscale_policy) {
+        (true, DownscalePolicy::Error) => Err(ArrowError::CastError(
+            "keep_downscale_remainder can not be combined with DownscalePolicy::Error".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Convert one integer timestamp to another unit, truncating toward zero when downscaling.
+///
+/// `None` on overflow.
+#[inline]
+pub(crate) const fn convert_unit(v: i64, from: TimeUnit, to: TimeUnit) -> Option<i64> {
+    let from_size = time_unit_multiple(from);
+    let to_size = time_unit_multiple(to);
+    if from_size >= to_size {
+        Some(v / (from_size / to_size))
+    } else {
+        v.checked_mul(to_size / from_size)
+    }
+}
+
+/// Map valid values with their rows by `f`, null where it returns `None`, in one pass.
+///
+/// The null buffer of the source is shared while no value is nulled, and built along with
+/// the values from the first value nulled.
+pub(crate) fn map_or_null<T: ArrowPrimitiveType>(
+    array: &PrimitiveArray<T>,
+    mut f: impl FnMut(usize, T::Native) -> Result<Option<i64>, ArrowError>,
+) -> Result<PrimitiveArray<Int64Type>, ArrowError> {
+    let mut validity: Option<BooleanBufferBuilder> = None;
+    let mut values = Vec::with_capacity(array.len());
+    for (row, v) in array.values().iter().enumerate() {
+        let v = match array.is_valid(row) {
+            true => f(row, *v)?,
+            false => None,
+        };
+        if v.is_none() && array.is_valid(row) && validity.is_none() {
+            let mut builder = BooleanBufferBuilder::new(array.len());
+            (0..row).for_each(|row| builder.append(array.is_valid(row)));
+            validity = Some(builder);
+        }
+        if let Some(validity) = &mut validity {
+            validity.append(v.is_some());
+        }
+        values.push(v.unwrap_or_default());
+    }
+    let nulls = match validity {
+        Some(mut validity) => Some(validity.finish().into()),
+        None => array.nulls().cloned(),
+    };
+    Ok(PrimitiveArray::new(values.into(), nulls))
+}
+
+/// Widen integers to `Int64`, sharing the null buffer of integers that always fit.
+pub(crate) fn widen(array: &dyn Array) -> Result<ArrayRef, ArrowError> {
+    fn widen_as<T: ArrowPrimitiveType>(array: &dyn Array) -> ArrayRef
+    where
+        T::Native: Into<i64>,
+    {
+        Arc::new(array.as_primitive::<T>().unary::<_, Int64Type>(Into::into))
+    }
+    Ok(match array.data_type() {
+        DataType::Int8 => widen_as::<Int8Type>(array),
+        DataType::Int16 => widen_as::<Int16Type>(array),
+        DataType::Int32 => widen_as::<Int32Type>(array),
+        DataType::UInt8 => widen_as::<UInt8Type>(array),
+        DataType::UInt16 => widen_as::<UInt16Type>(array),
+        DataType::UInt32 => widen_as::<UInt32Type>(array),
+        _ => arrow_cast::cast(array, &DataType::Int64)?,
+    })
+}
+
+/// Rescale integer timestamps from one unit to another.
+///
+/// Upscaling overflow is handled by the overflow behavior of the options,
+/// and downscaling by the downscale policy.
+pub(crate) fn rescale(
+    array: &PrimitiveArray<Int64Type>,
+    from: TimeUnit,
+    to: TimeUnit,
+    options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<PrimitiveArray<Int64Type>, ArrowError> {
+    let from_size = time_unit_multiple(from);
+    let to_size = time_unit_multiple(to);
+    if from_size == to_size {
+        return Ok(array.clone());
+    }
+    if from_size > to_size {
+        let divisor = from_size / to_size;
+        return match options.timestamp_options.downscale_policy {
+            DownscalePolicy::Truncate => Ok(array.unary(|v| v / divisor)),
+            policy @ DownscalePolicy::Round => {
+                Ok(array.unary(|v| downscale(v, divisor, policy).unwrap_or_default()))
+            }
+            policy @ DownscalePolicy::Error => array.try_unary(|v| {
+                downscale(v, divisor, policy).ok_or_else(|| {
+                    ArrowError::CastError(format!(
+                        "Cannot cast timestamp {v} from {from:?} to {to:?} without losing precision"
+                    ))
+                })
+            }),
+        };
+    }
+    let mul = to_size / from_size;
+    match options.overflow_behavior() {
+        OverflowBehavior::Null => {
+            let mut overflow = 0;
+            let array = map_or_null(array, |_, v| {
+                let scaled = v.checked_mul(mul);
+                if scaled.is_none() {
+                    overflow += 1;
+                }
+                Ok(scaled)
+            })?;
+            report.null_causes.overflow += overflow;
+            Ok(array)
+        }
+        OverflowBehavior::Error => array.try_unary(|v| {
+            v.checked_mul(mul).ok_or_else(|| {
+                ArrowError::CastError(format!(
+                    "Cannot cast timestamp {v} from {from:?} to {to:?}: overflow"
+                ))
+            })
+        }),
+        OverflowBehavior::Saturate => {
+            let (min, max) = options
+                .timestamp_options
+                .clamp_range
+                .unwrap_or((i64::MIN, i64::MAX));
+            let saturated = Cell::new(0);
+            let array = array.unary(|v| {
+                v.checked_mul(mul).unwrap_or_else(|| {
+                    saturated.set(saturated.get() + 1);
+                    if v < 0 {
+                        min
+                    } else {
+                        max
+                    }
+                })
+            });
+            report.saturated += saturated.get();
+            Ok(array)
+        }
+    }
+}
+
+/// [rescale] keeping the remainders of downscaling in the report with
+/// [keep_downscale_remainder](crate::TimestampCastOptions::keep_downscale_remainder).
+///
+/// Values are downscaled by the policy and their remainders computed in one pass.
+pub(crate) fn rescale_keeping_remainder(
+    array: &PrimitiveArray<Int64Type>,
+    from: TimeUnit,
+    to: TimeUnit,
+    options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<PrimitiveArray<Int64Type>, ArrowError> {
+    if !options.timestamp_options.keep_downscale_remainder {
+        return rescale(array, from, to, options, report);
+    }
+    check_keep_remainder(&options.timestamp_options)?;
+    let divisor = time_unit_multiple(from) / time_unit_multiple(to);
+    if divisor <= 1 {
+        let array = rescale(array, from, to, options, report)?;
+        let zeros = vec![0_i32; array.len()];
+        report.remainders = Some(Arc::new(Int32Array::new(
+            zeros.into(),
+            array.nulls().cloned(),
+        )));
+        return Ok(array);
+    }
+    let policy = options.timestamp_options.downscale_policy;
+    let mut remainders = Vec::with_capacity(array.len());
+    let values: Vec<i64> = array
+        .values()
+        .iter()
+        .map(|v| {
+            let (coarse, remainder) = downscale_keeping_remainder(*v, divisor, policy);
+            remainders.push(remainder);
+            coarse
+        })
+        .collect();
+    let nulls = array.nulls().cloned();
+    report.remainders = Some(Arc::new(Int32Array::new(remainders.into(), nulls.clone())));
+    Ok(PrimitiveArray::new(values.into(), nulls))
+}
+
+/// The decision for a value rescaled per value, repeated for the rows of its run.
+#[derive(Debug, Clone, Copy)]
+struct RunDecision {
+    /// The last row of the run so far.
+    row: usize,
+    value: i64,
+    unit: u8,
+    near: Option<TimeUnit>,
+    remainder: i32,
+    output: Option<i64>,
+    /// Values nulled by overflow and saturated for the value, counted again for each row.
+    overflowed: usize,
+    saturated: usize,
+    repeated: bool,
+}
+
+/// Rescale each value from the unit guessed by itself.
+///
+/// Consecutive rows of equal values repeat the decision of the first of their run, counted
+/// in [CastReport::runs_detected], a null row ending the run.
+pub(crate) fn rescale_per_value(
+    array: &PrimitiveArray<Int64Type>,
+    to: TimeUnit,
+    options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<PrimitiveArray<Int64Type>, ArrowError> {
+    let timestamp_options = &options.timestamp_options;
+    let years = timestamp_options.guessing_bound_years;
+    let keep_remainder = timestamp_options.keep_downscale_remainder;
+    check_keep_remainder(timestamp_options)?;
+    let mut units = vec![0; array.len()];
+    let mut remainders = vec![0; if keep_remainder { array.len() } else { 0 }];
+    let mut run: Option<RunDecision> = None;
+    let values = map_or_null(array, |row, v| {
+        if let Some(decision) = run
+            .as_mut()
+            .filter(|decision| decision.row + 1 == row && decision.value == v)
+        {
+            decision.row = row;
+            if !decision.repeated {
+                decision.repeated = true;
+                report.runs_detected += 1;
+            }
+            units[row] = decision.unit;
+            if keep_remainder {
+                remainders[row] = decision.remainder;
+            }
+            if let Some(unit) = decision.near {
+                report.near_boundary.add(unit);
+            }
+            report.null_causes.overflow += decision.overflowed;
+            report.saturated += decision.saturated;
+            return Ok(decision.output);
+        }
+        crate::guess::check_boundary(v, timestamp_options, &mut report.near_boundary)?;
+        let from = crate::guess_precision_with_bound_years(v, years);
+        units[row] = unit_code(from);
+        let (overflowed, saturated) = (report.null_causes.overflow, report.saturated);
+        let divisor = time_unit_multiple(from) / time_unit_multiple(to);
+        let (output, remainder) = match keep_remainder && divisor > 1 {
+            true => {
+                let policy = timestamp_options.downscale_policy;
+                let (coarse, remainder) = downscale_keeping_remainder(v, divisor, policy);
+                (Some(coarse), remainder)
+            }
+            false => (rescale_value(v, from, to, options, report)?, 0),
+        };
+        if keep_remainder {
+            remainders[row] = remainder;
+        }
+        run = Some(RunDecision {
+            row,
+            value: v,
+            unit: units[row],
+            near: crate::guess::near_boundary(v, timestamp_options),
+            remainder,
+            output,
+            overflowed: report.null_causes.overflow - overflowed,
+            saturated: report.saturated - saturated,
+            repeated: false,
+        });
+        Ok(output)
+    })?;
+    // Units of all valid values are guessed, and remainders kept of values not nulled.
+    if timestamp_options.record_row_units {
+        report.row_units = Some(UInt8Array::new(units.into(), array.nulls().cloned()));
+    }
+    if keep_remainder {
+        let nulls = values.nulls().cloned();
+        report.remainders = Some(Arc::new(Int32Array::new(remainders.into(), nulls)));
+    }
+    Ok(values)
+}
+
+/// Rescale one value, the same as [rescale] for arrays.
+pub(crate) fn rescale_value(
+    v: i64,
+    from: TimeUnit,
+    to: TimeUnit,
+    options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<Option<i64>, ArrowError> {
+    let from_size = time_unit_multiple(from);
+    let to_size = time_unit_multiple(to);
+    if from_size >= to_size {
+        let policy = options.timestamp_options.downscale_policy;
+        return match downscale(v, from_size / to_size, policy) {
+            Some(v) => Ok(Some(v)),
+            None => Err(ArrowError::CastError(format!(
+                "Cannot cast timestamp {v} from {from:?} to {to:?} without losing precision"
+            ))),
+        };
+    }
+    let Some(scaled) = v.checked_mul(to_size / from_size) else {
+        return overflow(v < 0, options, report, || {
+            format!("Cannot cast timestamp {v} from {from:?} to {to:?}: overflow")
+        });
+    };
+    Ok(Some(scaled))
+}
+
+/// Handle an overflowing value by the overflow behavior, `negative` if it overflows downward.
+pub(crate) fn overflow(
+    negative: bool,
+    options: &CastOptions,
+    report: &mut CastReport,
+    message: impl FnOnce() -> String,
+) -> Result<Option<i64>, ArrowError> {
+    match options.overflow_behavior() {
+        OverflowBehavior::Null => {
+            report.null_causes.overflow += 1;
+            Ok(None)
+        }
+        OverflowBehavior::Error => Err(ArrowError::CastError(message())),
+        OverflowBehavior::Saturate => {
+            let (min, max) = options
+                .timestamp_options
+                .clamp_range
+                .unwrap_or((i64::MIN, i64::MAX));
+            report.saturated += 1;
+            Ok(Some(if negative { min } else { max }))
+        }
+    }
+}
+
+/// Shift UTC timestamps to the wall clock of the timezone, by its offset at each instant.
+pub(crate) fn shift_to_wall_clock(
+    array: &ArrayRef,
+    unit: TimeUnit,
+    tz: &str,
+    options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<ArrayRef, ArrowError> {
+    let timezone: Tz = tz.parse()?;
+    let multiple = time_unit_multiple(unit);
+    let integers = arrow_cast::cast(array, &DataType::Int64)?;
+    let values = map_or_null(integers.as_primitive::<Int64Type>(), |_, v| {
+        let shifted =
+            as_datetime_with_timezone::<TimestampSecondType>(v.div_euclid(multiple), timezone)
+                .and_then(|datetime| {
+                    let offset = (datetime.naive_local() - datetime.naive_utc()).num_seconds();
+                    v.checked_add(offset.checked_mul(multiple)?)
+                });
+        match shifted {
+            Some(v) => Ok(Some(v)),
+            None if options.safe => {
+                report.null_causes.overflow += 1;
+                Ok(None)
+            }
+            None => Err(ArrowError::CastError(format!(
+                "Cannot shift timestamp {v} to the wall clock in {tz}: overflow"
+            ))),
+        }
+    })?;
+    Ok(make_timestamp_array(&values, unit, None))
+}
+
+/// Shift timestamps by the offset in seconds of each row.
+///
+/// Rows with a null offset are null, or kept with
+/// [null_offset_as_utc](crate::TimestampCastOptions::null_offset_as_utc).
+pub(crate) fn shift_by_offsets(
+    array: &ArrayRef,
+    offsets: &PrimitiveArray<Int64Type>,
+    unit: TimeUnit,
+    options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<ArrayRef, ArrowError> {
+    let multiple = time_unit_multiple(unit);
+    let integers = arrow_cast::cast(array, &DataType::Int64)?;
+    let values = map_or_null(integers.as_primitive::<Int64Type>(), |row, v| {
+        let offset = match offsets.is_valid(row) {
+            true => offsets.value(row),
+            false if options.timestamp_options.null_offset_as_utc => 0,
+            false => {
+                report.null_causes.policy += 1;
+                return Ok(None);
+            }
+        };
+        match offset.checked_mul(multiple).and_then(|o| v.checked_add(o)) {
+            Some(v) => Ok(Some(v)),
+            None if options.safe => {
+                report.null_causes.overflow += 1;
+                Ok(None)
+            }
+            None => Err(ArrowError::CastError(format!(
+                "Cannot shift timestamp {v} by offset {offset} seconds: overflow"
+            ))),
+        }
+    })?;
+    Ok(make_timestamp_array(&values, unit, None))
+}
+
+/// Whether the timezone is UTC, by any spelling.
+pub(crate) fn is_utc(tz: &str) -> bool {
+    matches!(
+        tz,
+        "UTC" | "utc" | "Etc/UTC" | "Z" | "+00:00" | "-00:00" | "+0000" | "+00"
+    )
+}
+
+/// The data type with UTC timezones spelled by
+/// [normalize_utc_tz](crate::TimestampCastOptions::normalize_utc_tz), nested types included.
+pub(crate) fn normalize_utc<'t>(
+    data_type: &'t DataType,
+    options: &TimestampCastOptions,
+) -> Cow<'t, DataType> {
+    let Some(spelling) = options.normalize_utc_tz.and_then(UtcSpelling::as_tz) else {
+        return Cow::Borrowed(data_type);
+    };
+    let field = |field: &FieldRef| {
+        Arc::new(
+            field
+                .as_ref()
+                .clone()
+                .with_data_type(normalize_utc(field.data_type(), options).into_owned()),
+        )
+    };
+    Cow::Owned(match data_type {
+        DataType::Timestamp(unit, Some(tz)) if is_utc(tz) => {
+            DataType::Timestamp(*unit, Some(spelling.into()))
+        }
+        DataType::List(f) => DataType::List(field(f)),
+        DataType::LargeList(f) => DataType::LargeList(field(f)),
+        DataType::FixedSizeList(f, size) => DataType::FixedSizeList(field(f), *size),
+        DataType::Struct(fields) => DataType::Struct(fields.iter().map(field).collect()),
+        _ => return Cow::Borrowed(data_type),
+    })
+}
+
+/// Whether arrow accepts the timezone.
+///
+/// Named timezones are only checked with the `chrono-tz` feature, without it any name like
+/// `Asia/Shanghai` is taken as valid.
+pub(crate) fn is_valid_tz(tz: &str) -> bool {
+    if is_utc(tz) || tz.parse::<Tz>().is_ok() {
+        return true;
+    }
+    !cfg!(feature = "chrono-tz")
+        && tz.contains('/')
+        && tz
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '_' | '-' | '+'))
+}
+
+/// The first invalid timezone of timestamps in the data type, nested types included.
+pub(crate) fn invalid_tz(data_type: &DataType) -> Option<&str> {
+    match data_type {
+        DataType::Timestamp(_, Some(tz)) => (!is_valid_tz(tz)).then_some(tz.as_ref()),
+        DataType::List(f) | DataType::LargeList(f) | DataType::FixedSizeList(f, _) => {
+            invalid_tz(f.data_type())
+        }
+        DataType::Struct(fields) => fields.iter().find_map(|f| invalid_tz(f.data_type())),
+        _ => None,
+    }
+}
+
+/// The data type with invalid timezones replaced by `tz`, nested types included.
+pub(crate) fn repair_tz(data_type: &DataType, tz: &Timezone) -> DataType {
+    let field = |field: &FieldRef| {
+        Arc::new(
+            field
+                .as_ref()
+                .clone()
+                .with_data_type(repair_tz(field.data_type(), tz)),
+        )
+    };
+    match data_type {
+        DataType::Timestamp(unit, Some(invalid)) if !is_valid_tz(invalid) => {
+            DataType::Timestamp(*unit, tz.clone())
+        }
+        DataType::List(f) => DataType::List(field(f)),
+        DataType::LargeList(f) => DataType::LargeList(field(f)),
+        DataType::FixedSizeList(f, size) => DataType::FixedSizeList(field(f), *size),
+        DataType::Struct(fields) => DataType::Struct(fields.iter().map(field).collect()),
+        _ => data_type.clone(),
+    }
+}
+
+/// The array as another data type of the same layout, like timestamps of another timezone.
+///
+/// Children of lists and structs are retagged by the types of the target fields.
+pub(crate) fn with_data_type(
+    array: &dyn Array,
+    data_type: &DataType,
+) -> Result<ArrayRef, ArrowError> {
+    match data_type {
+        DataType::List(field) => {
+            let array = array.as_list::<i32>();
+            let values = with_data_type(array.values(), field.data_type())?;
+            Ok(Arc::new(ListArray::try_new(
+                field.clone(),
+                array.offsets().clone(),
+                values,
+                array.nulls().cloned(),
+            )?))
+        }
+        DataType::LargeList(field) => {
+            let array = array.as_list::<i64>();
+            let values = with_data_type(array.values(), field.data_type())?;
+            Ok(Arc::new(LargeListArray::try_new(
+                field.clone(),
+                array.offsets().clone(),
+                values,
+                array.nulls().cloned(),
+            )?))
+        }
+        DataType::FixedSizeList(field, size) => {
+            let array = array.as_fixed_size_list();
+            let values = with_data_type(array.values(), field.data_type())?;
+            Ok(Arc::new(FixedSizeListArray::try_new(
+                field.clone(),
+                *size,
+                values,
+                array.nulls().cloned(),
+            )?))
+        }
+        DataType::Struct(fields) => {
+            let array = array.as_struct();
+            let columns = array
+                .columns()
+                .iter()
+                .zip(fields.iter())
+                .map(|(column, field)| with_data_type(column, field.data_type()))
+                .collect::<Result<_, _>>()?;
+            Ok(Arc::new(StructArray::try_new(
+                fields.clone(),
+                columns,
+                array.nulls().cloned(),
+            )?))
+        }
+        _ => {
+            let data = array.to_data().into_builder().data_type(data_type.clone());
+            Ok(make_array(data.build()?))
+        }
+    }
+}
+
+/// Make a timestamp array from integers in the unit.
+pub(crate) fn make_timestamp_array(
+    array: &PrimitiveArray<Int64Type>,
+    unit: TimeUnit,
+    tz: Timezone,
+) -> ArrayRef {
+    match unit {
+        TimeUnit::Second => Arc::new(
+            array
+                .reinterpret_cast::<TimestampSecondType>()
+                .with_timezone_opt(tz),
+        ),
+        TimeUnit::Millisecond => Arc::new(
+            array
+                .reinterpret_cast::<TimestampMillisecondType>()
+                .with_timezone_opt(tz),
+        ),
+        TimeUnit::Microsecond => Arc::new(
+            array
+                .reinterpret_cast::<TimestampMicrosecondType>()
+                .with_timezone_opt(tz),
+        ),
+        TimeUnit::Nanosecond => Arc::new(
+            array
+                .reinterpret_cast::<TimestampNanosecondType>()
+                .with_timezone_opt(tz),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::Int64Array;
+
+    use super::*;
+    use crate::{cast_with_report, guess, guess_precision_with_bound_years, GuessStrategy};
+
+    /// A deterministic xorshift generator of pseudo-random fixtures.
+    fn xorshift() -> impl FnMut() -> u64 {
+        let mut state = 0x2545f4914f6cdd1d_u64;
+        move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        }
+    }
+
+    #[test]
+    fn test_keep_downscale_remainder() {
+        // Nanoseconds of both signs, with nulls.
+        let mut next = xorshift();
+        let values: Int64Array = (0..1000)
+            .map(|i| {
+                let state = next();
+                let v = (state % 9_000_000_000_000_000_000) as i64 + 100_000_000_000_000_000;
+                (i % 7 != 0).then_some(if state & 1 == 0 { v } else { -v })
+            })
+            .collect();
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.keep_downscale_remainder = true;
+        for unit in [
+            TimeUnit::Second,
+            TimeUnit::Millisecond,
+            TimeUnit::Microsecond,
+        ] {
+            let mut report = CastReport::default();
+            let coarse = rescale_keeping_remainder(
+                &values,
+                TimeUnit::Nanosecond,
+                unit,
+                &options,
+                &mut report,
+            )
+            .unwrap();
+            let remainders = report.remainders.unwrap();
+            let remainders = remainders.as_primitive::<Int32Type>();
+            let divisor = 1_000_000_000 / time_unit_multiple(unit);
+            for (row, v) in values.iter().enumerate() {
+                let Some(v) = v else {
+                    assert!(coarse.is_null(row) && remainders.is_null(row));
+                    continue;
+                };
+                assert_eq!(remainders.value(row) as i64, v % divisor);
+                assert_eq!(
+                    coarse.value(row) * divisor + remainders.value(row) as i64,
+                    v
+                );
+            }
+        }
+
+        // Zero for rows not downscaled, per value or not.
+        let values = Int64Array::from(vec![Some(-1701325744956789), None, Some(1701325744)]);
+        let to_type = DataType::Timestamp(TimeUnit::Millisecond, None);
+        options.timestamp_options.guess_strategy = GuessStrategy::PerValue;
+        let (casted, report) = cast_with_report(&values, &to_type, &options).unwrap();
+        assert_eq!(
+            casted
+                .as_primitive::<arrow_array::types::TimestampMillisecondType>()
+                .values(),
+            &[-1701325744956, 0, 1701325744000]
+        );
+        let expected = Int32Array::from(vec![Some(-789), None, Some(0)]);
+        assert_eq!(report.remainders.unwrap().as_ref(), &expected as &dyn Array);
+        options.timestamp_options.guess_strategy = GuessStrategy::FirstNonNull;
+        let (_, report) = cast_with_report(&values, &to_type, &options).unwrap();
+        let expected = Int32Array::from(vec![Some(-789), None, Some(744)]);
+        assert_eq!(report.remainders.unwrap().as_ref(), &expected as &dyn Array);
+
+        // Coarse values follow the downscale policy, per value or not.
+        let values = Int64Array::from(vec![1701325744500, -1701325744500, -1701325744499]);
+        let to_type = DataType::Timestamp(TimeUnit::Second, None);
+        for strategy in [GuessStrategy::FirstNonNull, GuessStrategy::PerValue] {
+            options.timestamp_options.guess_strategy = strategy;
+            for (policy, coarse, remainders) in [
+                (
+                    DownscalePolicy::Truncate,
+                    [1701325744, -1701325744, -1701325744],
+                    [500, -500, -499],
+                ),
+                (
+                    DownscalePolicy::Round,
+                    [1701325745, -1701325745, -1701325744],
+                    [-500, 500, -499],
+                ),
+            ] {
+                options.timestamp_options.downscale_policy = policy;
+                let (casted, report) = cast_with_report(&values, &to_type, &options).unwrap();
+                assert_eq!(
+                    casted
+                        .as_primitive::<arrow_array::types::TimestampSecondType>()
+                        .values(),
+                    &coarse
+                );
+                let expected = Int32Array::from(remainders.to_vec());
+                assert_eq!(report.remainders.unwrap().as_ref(), &expected as &dyn Array);
+            }
+            options.timestamp_options.downscale_policy = DownscalePolicy::Error;
+            let err = cast_with_report(&values, &to_type, &options).unwrap_err();
+            assert!(err.to_string().contains("DownscalePolicy::Error"));
+        }
+        options.timestamp_options.downscale_policy = DownscalePolicy::Truncate;
+        options.timestamp_options.guess_strategy = GuessStrategy::FirstNonNull;
+        options.timestamp_options.keep_downscale_remainder = false;
+        let (_, report) = cast_with_report(&values, &to_type, &options).unwrap();
+        assert!(report.remainders.is_none());
+    }
+
+    #[test]
+    fn test_per_value_runs() {
+        // Values of each unit, near the bounds, and sentinels, in runs cut by nulls. Seconds
+        // overflow nanoseconds under the bound of a million years.
+        let years = 1_000_000;
+        let pool = [
+            1701325744,
+            1701325744956,
+            -1701325744956789,
+            1701325744956789012,
+            86400 * 365 * years + 1,
+            0,
+            -1,
+        ];
+        let mut next = xorshift();
+        let mut values = Vec::new();
+        while values.len() < 4096 {
+            let v = pool[next() as usize % pool.len()];
+            for _ in 0..=next() % 16 {
+                values.push((next() & 7 != 0).then_some(v));
+            }
+        }
+        let array = Int64Array::from(values.clone());
+        let runs = values
+            .windows(2)
+            .enumerate()
+            .filter(|(row, pair)| {
+                pair[0].is_some() && pair[0] == pair[1] && (*row == 0 || values[row - 1] != pair[0])
+            })
+            .count();
+
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.guess_strategy = GuessStrategy::PerValue;
+        options.timestamp_options.record_row_units = true;
+        options.timestamp_options.boundary_margin = Some(0.01);
+        options.timestamp_options.guessing_bound_years = years;
+        for to in [TimeUnit::Millisecond, TimeUnit::Nanosecond] {
+            for policy in [OverflowBehavior::Null, OverflowBehavior::Saturate] {
+                options.timestamp_options.overflow_behavior = Some(policy);
+                // Each value decided on its own.
+                let mut naive = CastReport::default();
+                let expected: Int64Array = array
+                    .iter()
+                    .map(|v| {
+                        let v = v?;
+                        let timestamp_options = &options.timestamp_options;
+                        guess::check_boundary(v, timestamp_options, &mut naive.near_boundary)
+                            .unwrap();
+                        let from = guess_precision_with_bound_years(v, years);
+                        rescale_value(v, from, to, &options, &mut naive).unwrap()
+                    })
+                    .collect();
+                let mut report = CastReport::default();
+                let casted = rescale_per_value(&array, to, &options, &mut report).unwrap();
+                assert_eq!(casted, expected);
+                assert_eq!(report.near_boundary, naive.near_boundary);
+                assert_eq!(report.null_causes, naive.null_causes);
+                assert_eq!(report.saturated, naive.saturated);
+                let overflowed = report.null_causes.overflow + report.saturated;
+                assert_eq!(overflowed > 0, to == TimeUnit::Nanosecond);
+                assert_eq!(report.runs_detected, runs);
+                let units = report.row_units.unwrap();
+                for (row, v) in array.iter().enumerate() {
+                    if let Some(v) = v {
+                        let unit = guess_precision_with_bound_years(v, years);
+                        assert_eq!(units.value(row), unit_code(unit));
+                    }
+                }
+            }
+        }
+
+        // Through the cast, with remainders repeated in runs.
+        options.timestamp_options.overflow_behavior = None;
+        options.timestamp_options.keep_downscale_remainder = true;
+        let to_type = DataType::Timestamp(TimeUnit::Second, None);
+        let (casted, report) = cast_with_report(&array, &to_type, &options).unwrap();
+        assert_eq!(report.runs_detected, runs);
+        let remainders = report.remainders.unwrap();
+        let remainders = remainders.as_primitive::<arrow_array::types::Int32Type>();
+        for (row, v) in array.iter().enumerate() {
+            if let Some(v) = v {
+                let multiple = time_unit_multiple(guess_precision_with_bound_years(v, years));
+                assert_eq!(remainders.value(row) as i64, v % multiple);
+                assert!(casted.is_valid(row));
+            }
+        }
+    }
+}
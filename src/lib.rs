@@ -19,7 +19,12 @@
 //!     .as_any()
 //!     .downcast_ref::<TimestampNanosecondArray>()
 //!     .unwrap();
-//! assert_eq!(nanos.value(0), 1701325744956 * 1000 * 1000);
+//! // With the `passthrough` feature, integers are taken as nanoseconds as arrow does.
+//! let expected = match cfg!(feature = "passthrough") {
+//!     true => 1701325744956,
+//!     false => 1701325744956 * 1000 * 1000,
+//! };
+//! assert_eq!(nanos.value(0), expected);
 //! ```
 //!
 //! The difference to official [arrow::compute::cast] is that:
@@ -70,11 +75,168 @@
 //!
 //! Like [arrow::compute::cast], this crate also supports casting with specific options, checkout [CastOptions](arrow_cast_guess_precision::CastOptions).
 //!
+//! All cast functions accept sliced arrays, as from `RecordBatch::slice` or IPC readers, and cast them
+//! exactly as arrays built afresh from the same values: guessing only sees the values in the slice.
+//!
+//! Casts of integers and floats to timestamps share the null buffer of the source when no
+//! value is nulled, and build the null buffer along with the values when some are, as by
+//! overflow with `safe`.
+//!
+//! The arrow version is selected by features, exactly one of `arrow-50`, `arrow-51`, `arrow-52` and `arrow-54`
+//! (the default) must be enabled, for example:
+//!
+//! ```toml
+//! arrow-cast-guess-precision = { version = "0.4", default-features = false, features = ["arrow-52", "chrono"] }
+//! ```
+//!
 //! [arrow::compute::cast]: https://docs.rs/arrow/latest/arrow/compute/fn.cast.html
 //! [arrow_cast_guess_precision::cast]: https://docs.rs/arrow-cast-guess-precision/latest/arrow_cast_guess_precision/fn.cast.html
 
-use arrow_array::{make_array, new_empty_array, new_null_array, Array, ArrayRef, Int64Array};
-use arrow_schema::{ArrowError, DataType, TimeUnit};
+#[cfg(not(any(
+    feature = "arrow-50",
+    feature = "arrow-51",
+    feature = "arrow-52",
+    feature = "arrow-54"
+)))]
+compile_error!(
+    "one of the features `arrow-50`, `arrow-51`, `arrow-52` or `arrow-54` must be enabled"
+);
+#[cfg(any(
+    all(
+        feature = "arrow-50",
+        any(feature = "arrow-51", feature = "arrow-52", feature = "arrow-54")
+    ),
+    all(feature = "arrow-51", any(feature = "arrow-52", feature = "arrow-54")),
+    all(feature = "arrow-52", feature = "arrow-54"),
+))]
+compile_error!(
+    "only one of the features `arrow-50`, `arrow-51`, `arrow-52` or `arrow-54` can be enabled"
+);
+
+#[cfg(all(
+    feature = "json",
+    any(feature = "arrow-50", feature = "arrow-51", feature = "arrow-52")
+))]
+compile_error!("the feature `json` is only supported with `arrow-54`");
+
+#[cfg(feature = "arrow-50")]
+extern crate arrow_array_50 as arrow_array;
+#[cfg(feature = "arrow-51")]
+extern crate arrow_array_51 as arrow_array;
+#[cfg(feature = "arrow-52")]
+extern crate arrow_array_52 as arrow_array;
+#[cfg(feature = "arrow-54")]
+extern crate arrow_array_54 as arrow_array;
+#[cfg(feature = "arrow-50")]
+extern crate arrow_cast_50 as arrow_cast;
+#[cfg(feature = "arrow-51")]
+extern crate arrow_cast_51 as arrow_cast;
+#[cfg(feature = "arrow-52")]
+extern crate arrow_cast_52 as arrow_cast;
+#[cfg(feature = "arrow-54")]
+extern crate arrow_cast_54 as arrow_cast;
+#[cfg(feature = "json")]
+extern crate arrow_json_54 as arrow_json;
+#[cfg(feature = "arrow-50")]
+extern crate arrow_schema_50 as arrow_schema;
+#[cfg(feature = "arrow-51")]
+extern crate arrow_schema_51 as arrow_schema;
+#[cfg(feature = "arrow-52")]
+extern crate arrow_schema_52 as arrow_schema;
+#[cfg(feature = "arrow-54")]
+extern crate arrow_schema_54 as arrow_schema;
+
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use arrow_array::{
+    cast::AsArray,
+    make_array, new_empty_array, new_null_array,
+    types::{Int32Type, Int64Type, UInt64Type},
+    Array, ArrayRef, PrimitiveArray,
+};
+use arrow_schema::{ArrowError, DataType, IntervalUnit, TimeUnit};
+
+mod audit;
+mod batch;
+mod binary;
+mod builder;
+mod chunked;
+pub mod cmp;
+#[cfg(feature = "chrono")]
+mod datetime;
+mod decimal;
+mod dictionary;
+mod epoch;
+mod extension;
+mod float;
+mod format;
+mod guess;
+mod interpret;
+mod interval;
+#[cfg(feature = "json")]
+mod json;
+mod kernel;
+mod map;
+mod nested;
+mod options;
+#[cfg(feature = "test-utils")]
+pub mod parity;
+mod parts;
+mod plan;
+mod precision;
+mod prepared;
+mod report;
+mod resolved;
+mod shim;
+mod sortable;
+#[cfg(feature = "async")]
+mod stream;
+mod string;
+mod time;
+pub mod time_unit;
+mod transform;
+mod typed;
+mod units;
+mod verify;
+
+pub use audit::{apply_audit_fix, audit_timestamp_columns, ColumnAudit};
+pub use batch::{cast_record_batch, BatchCaster, ColumnFilter, SchemaCastOptions};
+pub use builder::GuessingTimestampBuilder;
+pub use chunked::{cast_chunks, cast_concat};
+#[cfg(feature = "chrono")]
+pub use datetime::{guess_datetime, guess_datetime_with_options, guess_naive_datetime};
+pub use extension::{
+    annotate_epoch_extension, resolve_epoch_extension, resolve_epoch_extension_with_report,
+    EPOCH_EXTENSION_NAME, EPOCH_UNITS, EPOCH_UNIT_METADATA_KEY, EXTENSION_NAME_KEY,
+};
+pub use format::FormatSettings;
+pub use interval::{guess_elapsed_unit, ElapsedUnit};
+#[cfg(feature = "json")]
+pub use json::{json_reader_with_guess, relaxed_json_schema, GuessJsonReader};
+pub use options::{
+    set_global_defaults, BinaryEpochKind, CastOptions, DateOrder, DictionaryHandling,
+    DownscalePolicy, Endianness, EpochKind, GuessProfile, GuessStrategy, Interpretation,
+    LeapSecondPolicy, MultipleMatchPolicy, NaiveStringPolicy, NonFinitePolicy, OverflowBehavior,
+    StringParseRoute, TimestampCastOptions, TzRepair, UtcSpelling,
+};
+pub use parts::{timestamp_parts, timestamp_value_as_parts};
+pub use plan::{plan_schema_cast, CastPlan, FieldCastAction, FieldCastPlan, SchemaCastPlan};
+pub use precision::cast_with_min_precision;
+pub use prepared::PreparedColumn;
+pub use report::{
+    CastReport, NearBoundary, NullCauses, RoundtripMismatch, StringParseCounts, StringParseKind,
+    SOURCE_UNIT_METADATA_KEY, STRATEGY_METADATA_KEY,
+};
+pub use resolved::{ConfigSource, GuessingBounds, Resolved, ResolvedConfig};
+pub use shim::ARROW_MAJOR_VERSION;
+pub use sortable::{sortable_view, SortableEpochs};
+#[cfg(feature = "async")]
+pub use stream::GuessCastStream;
+pub use time::guess_time_of_day_unit;
+pub use time_unit::{unit_code, unit_from_code, unit_from_str, unit_str};
+pub use transform::{invert_cast, TransformDescription, TRANSFORM_METADATA_KEY, TRANSFORM_VERSION};
+pub use units::cast_with_unit_column;
 
 include!(concat!(env!("OUT_DIR"), "/guessing_bound.rs"));
 
@@ -84,163 +246,740 @@ const LOWER_BOUND_NANOS: i64 = 1000 * 1000 * 86400 * 365 * GUESSING_BOUND_YEARS;
 
 #[inline]
 const fn guess_precision(timestamp: i64) -> TimeUnit {
-    let timestamp = timestamp.abs();
-    if timestamp > LOWER_BOUND_NANOS {
+    let timestamp = timestamp.unsigned_abs();
+    if timestamp > LOWER_BOUND_NANOS as u64 {
         return TimeUnit::Nanosecond;
     }
-    if timestamp > LOWER_BOUND_MICROS {
+    if timestamp > LOWER_BOUND_MICROS as u64 {
         return TimeUnit::Microsecond;
     }
-    if timestamp > LOWER_BOUND_MILLIS {
+    if timestamp > LOWER_BOUND_MILLIS as u64 {
         return TimeUnit::Millisecond;
     }
     TimeUnit::Second
 }
 
-/// Guessing precision from an array of integers.
+/// Lower bounds of milliseconds, microseconds and nanoseconds by the guessing bound in years.
+#[inline]
+const fn lower_bounds(years: i64) -> [i64; 3] {
+    let millis = (86400 * 365_i64).saturating_mul(years);
+    let micros = millis.saturating_mul(1000);
+    [millis, micros, micros.saturating_mul(1000)]
+}
+
+/// Guessing precision with a runtime guessing bound in years.
 ///
-/// The array should be an [Int64Array](arrow_array::Int64Array).
+/// Same as `guess_precision` when `years` is the build-time `GUESSING_BOUND_YEARS`.
 #[inline]
-fn guess_precision_in_array(array: &dyn Array) -> Option<TimeUnit> {
-    let v = array.as_any().downcast_ref::<Int64Array>().unwrap();
-    v.into_iter().flatten().next().map(guess_precision)
+const fn guess_precision_with_bound_years(timestamp: i64, years: i64) -> TimeUnit {
+    if years == GUESSING_BOUND_YEARS {
+        return guess_precision(timestamp);
+    }
+    let [millis, micros, nanos] = lower_bounds(years);
+    let timestamp = timestamp.unsigned_abs();
+    if timestamp > nanos as u64 {
+        return TimeUnit::Nanosecond;
+    }
+    if timestamp > micros as u64 {
+        return TimeUnit::Microsecond;
+    }
+    if timestamp > millis as u64 {
+        return TimeUnit::Millisecond;
+    }
+    TimeUnit::Second
 }
 
 pub fn cast(array: &dyn Array, to_type: &DataType) -> Result<ArrayRef, ArrowError> {
     cast_with_options(array, to_type, &CastOptions::default())
 }
 
-#[derive(Debug, Clone)]
-pub struct TimestampCastOptions {
-    /// If true, try to guess the precision of the timestamp from integers.
-    ///
-    /// Caster will first convert the integer to i64 and then guess the precision.
-    pub guess_timestamp_precision: bool,
-    /// If true, caster use the timezone in target type. If false, caster will use UTC.
-    pub use_timezone_as_is: bool,
+pub fn cast_with_options(
+    array: &dyn Array,
+    to_type: &DataType,
+    cast_options: &CastOptions,
+) -> Result<ArrayRef, ArrowError> {
+    cast_with_report(array, to_type, cast_options).map(|(array, _)| array)
 }
 
-impl Default for TimestampCastOptions {
-    fn default() -> Self {
-        Self {
-            guess_timestamp_precision: true,
-            use_timezone_as_is: true,
-        }
-    }
+/// Cast with options, and report what the caster decided and did.
+///
+/// ```rust
+/// use arrow::{array::Int64Array, datatypes::{DataType, TimeUnit}};
+/// use arrow_cast_guess_precision::{cast_with_report, CastOptions};
+///
+/// let array = Int64Array::from(vec![1701325744956]);
+/// let (_, report) = cast_with_report(
+///     &array,
+///     &DataType::Timestamp(TimeUnit::Nanosecond, None),
+///     &CastOptions::new().with_passthrough(false),
+/// )
+/// .unwrap();
+/// assert_eq!(report.guessed_unit, Some(TimeUnit::Millisecond));
+/// ```
+pub fn cast_with_report(
+    array: &dyn Array,
+    to_type: &DataType,
+    cast_options: &CastOptions,
+) -> Result<(ArrayRef, CastReport), ArrowError> {
+    typed::cast_typed(array, to_type, cast_options)
 }
 
-pub struct CastOptions<'a> {
-    pub safe: bool,
-    pub timestamp_options: TimestampCastOptions,
-    pub format_options: arrow_cast::display::FormatOptions<'a>,
+/// [cast_with_report] of an array of its concrete type, not behind an `Arc`.
+fn cast_with_report_concrete(
+    array: &dyn Array,
+    to_type: &DataType,
+    cast_options: &CastOptions,
+) -> Result<(ArrayRef, CastReport), ArrowError> {
+    // Cast by the options of the snapshot reported.
+    let resolved = cast_options.resolve();
+    let cast_options = &resolved.cast_options(cast_options);
+    let mut report = CastReport {
+        resolved_config: Some(resolved),
+        ..Default::default()
+    };
+    if cast_options.passthrough {
+        let casted = arrow_cast::cast_with_options(array, to_type, &cast_options.into())?;
+        report.nulls_introduced = casted.null_count().saturating_sub(array.null_count());
+        return Ok((casted, report));
+    }
+    let invalid_tz = kernel::invalid_tz(array.data_type());
+    let repaired;
+    let array = match (
+        invalid_tz,
+        &cast_options.timestamp_options.repair_invalid_tz,
+    ) {
+        (None, _) | (Some(_), None) => array,
+        (Some(invalid), Some(repair)) => {
+            let tz = match repair {
+                TzRepair::Strip => None,
+                TzRepair::Replace(tz) => Some(tz.clone()),
+                TzRepair::Error => {
+                    return Err(ArrowError::CastError(format!(
+                        "Cannot cast {:?} of invalid timezone {invalid:?}",
+                        array.data_type()
+                    )))
+                }
+            };
+            report.invalid_tz = Some(invalid.to_string());
+            repaired = kernel::with_data_type(array, &kernel::repair_tz(array.data_type(), &tz))?;
+            repaired.as_ref()
+        }
+    };
+    let to_type = kernel::normalize_utc(to_type, &cast_options.timestamp_options);
+    let to_type = to_type.as_ref();
+    let wall_clock_tz = cast_options
+        .timestamp_options
+        .output_as_wall_clock_in
+        .as_deref();
+    if let (Some(_), DataType::Timestamp(_, Some(_))) = (wall_clock_tz, to_type) {
+        return Err(ArrowError::CastError(format!(
+            "Cannot output wall clock timestamps as {to_type:?} with a timezone"
+        )));
+    }
+    // UTC by any spelling is cast as `+00:00`, which parses without `chrono-tz`.
+    let cast_type = match to_type {
+        DataType::Timestamp(unit, Some(tz)) if kernel::is_utc(tz) && tz.as_ref() != "+00:00" => {
+            Cow::Owned(DataType::Timestamp(*unit, Some("+00:00".into())))
+        }
+        _ => Cow::Borrowed(to_type),
+    };
+    let mut casted =
+        cast_inner(array, &cast_type, cast_options, &mut report).map_err(|e| {
+            match (
+                invalid_tz,
+                &cast_options.timestamp_options.repair_invalid_tz,
+            ) {
+                (Some(invalid), None) => ArrowError::CastError(format!(
+                    "Cannot cast {:?} of invalid timezone {invalid:?}: {e}",
+                    array.data_type()
+                )),
+                _ => e,
+            }
+        })?;
+    if cast_type.as_ref() != to_type {
+        casted = kernel::with_data_type(&casted, to_type)?;
+    }
+    if cast_options.timestamp_options.verify_roundtrip {
+        verify::verify_roundtrip(array, &casted, cast_options, &mut report)?;
+    }
+    if let (Some(tz), DataType::Timestamp(unit, None)) = (wall_clock_tz, to_type) {
+        casted = kernel::shift_to_wall_clock(&casted, *unit, tz, cast_options, &mut report)?;
+    }
+    report.nulls_introduced = casted.null_count().saturating_sub(array.null_count());
+    Ok((casted, report))
 }
 
-impl Default for CastOptions<'_> {
-    fn default() -> Self {
-        Self::new()
+/// Cast to timezone-less timestamps of the wall clock, shifted by the offset of each row.
+///
+/// `values` are cast as usual, with guessing, then each row is shifted by the offset
+/// in seconds of the same row in `offsets_seconds`. Rows with a null offset are null,
+/// or kept in UTC with [null_offset_as_utc](TimestampCastOptions::null_offset_as_utc).
+///
+/// ```rust
+/// use arrow::array::{Int32Array, Int64Array, TimestampSecondArray};
+/// use arrow::datatypes::{DataType, TimeUnit};
+/// use arrow_cast_guess_precision::{cast_with_tz_offsets, CastOptions};
+///
+/// let values = Int64Array::from(vec![1701325744]);
+/// let offsets = Int32Array::from(vec![8 * 3600]);
+/// let to_type = DataType::Timestamp(TimeUnit::Second, None);
+/// let array = cast_with_tz_offsets(&values, &offsets, &to_type, &CastOptions::new()).unwrap();
+/// let array = array.as_any().downcast_ref::<TimestampSecondArray>().unwrap();
+/// assert_eq!(array.value(0), 1701325744 + 8 * 3600);
+/// ```
+pub fn cast_with_tz_offsets(
+    values: &dyn Array,
+    offsets_seconds: &dyn Array,
+    to_type: &DataType,
+    cast_options: &CastOptions,
+) -> Result<ArrayRef, ArrowError> {
+    if values.len() != offsets_seconds.len() {
+        return Err(ArrowError::InvalidArgumentError(format!(
+            "Length of offsets {} does not match length of values {}",
+            offsets_seconds.len(),
+            values.len()
+        )));
     }
+    let DataType::Timestamp(unit, None) = to_type else {
+        return Err(ArrowError::CastError(format!(
+            "Cannot cast with timezone offsets to {to_type:?}, expected a timestamp without timezone"
+        )));
+    };
+    let (casted, mut report) = cast_with_report(values, to_type, cast_options)?;
+    let offsets = arrow_cast::cast(offsets_seconds, &DataType::Int64)?;
+    kernel::shift_by_offsets(
+        &casted,
+        offsets.as_primitive(),
+        *unit,
+        cast_options,
+        &mut report,
+    )
 }
 
-impl CastOptions<'_> {
-    pub fn new() -> Self {
-        Self {
-            safe: true,
-            timestamp_options: TimestampCastOptions::default(),
-            format_options: arrow_cast::display::FormatOptions::default(),
-        }
-    }
+/// Combine dates and times of day to timestamps.
+///
+/// Dates are `Date32` or `Date64`, times are `Time32`, `Time64` or integers, whose unit is
+/// guessed by [guess_time_of_day_unit] when guessing is enabled, or the target unit otherwise.
+/// A row is null if either side is null.
+///
+/// ```rust
+/// use arrow::array::{Date32Array, Time32SecondArray, TimestampSecondArray};
+/// use arrow::datatypes::{DataType, TimeUnit};
+/// use arrow_cast_guess_precision::{combine_date_time, CastOptions};
+///
+/// let dates = Date32Array::from(vec![19691]);
+/// let times = Time32SecondArray::from(vec![23344]);
+/// let to_type = DataType::Timestamp(TimeUnit::Second, None);
+/// let array = combine_date_time(&dates, &times, &to_type, &CastOptions::new()).unwrap();
+/// let array = array.as_any().downcast_ref::<TimestampSecondArray>().unwrap();
+/// assert_eq!(array.value(0), 1701325744);
+/// ```
+pub fn combine_date_time(
+    date: &dyn Array,
+    time: &dyn Array,
+    to_type: &DataType,
+    cast_options: &CastOptions,
+) -> Result<ArrayRef, ArrowError> {
+    let to_type = kernel::normalize_utc(to_type, &cast_options.timestamp_options);
+    time::combine_date_time(date, time, &to_type, cast_options)
 }
 
-impl<'r, 'a> From<&'r CastOptions<'a>> for arrow_cast::CastOptions<'r> {
-    fn from(options: &'r CastOptions) -> arrow_cast::CastOptions<'r> {
-        arrow_cast::CastOptions {
-            safe: options.safe.clone(),
-            format_options: options.format_options.clone(),
-        }
-    }
+/// Extract the values of `keys` from a map array, cast to a struct of one child per key.
+///
+/// Keys and values are read as strings and each child is cast on its own, guessing like
+/// other strings. A row without the key is null in the child. If a key appears more than
+/// once in a row, the first value is taken.
+///
+/// ```rust
+/// use arrow::array::{builder::{MapBuilder, StringBuilder}, AsArray};
+/// use arrow::datatypes::{DataType, TimeUnit, TimestampMillisecondType};
+/// use arrow_cast_guess_precision::{cast_map_values_by_key, CastOptions};
+///
+/// let mut builder = MapBuilder::new(None, StringBuilder::new(), StringBuilder::new());
+/// builder.keys().append_value("created_at");
+/// builder.values().append_value("1701325744956");
+/// builder.append(true).unwrap();
+/// let map = builder.finish();
+/// let to_type = DataType::Timestamp(TimeUnit::Millisecond, None);
+/// let options = CastOptions::new().with_passthrough(false);
+/// let array = cast_map_values_by_key(&map, &["created_at"], &to_type, &options).unwrap();
+/// let created = array.as_struct().column(0).as_primitive::<TimestampMillisecondType>();
+/// assert_eq!(created.value(0), 1701325744956);
+/// ```
+pub fn cast_map_values_by_key(
+    map: &dyn Array,
+    keys: &[&str],
+    to_type: &DataType,
+    cast_options: &CastOptions,
+) -> Result<ArrayRef, ArrowError> {
+    let to_type = kernel::normalize_utc(to_type, &cast_options.timestamp_options);
+    map::cast_map_values_by_key(map, keys, &to_type, cast_options)
 }
 
-pub fn cast_with_options(
+fn cast_inner(
     array: &dyn Array,
     to_type: &DataType,
     cast_options: &CastOptions,
+    report: &mut CastReport,
 ) -> Result<ArrayRef, ArrowError> {
     use DataType::*;
     let from_type = array.data_type();
     if from_type == to_type {
         return Ok(make_array(array.to_data()));
     }
-    if array.len() == 0 {
+    if let (Timestamp(from_unit, Some(from_tz)), Timestamp(unit, Some(tz))) = (from_type, to_type) {
+        if from_unit == unit && kernel::is_utc(from_tz) && kernel::is_utc(tz) {
+            return kernel::with_data_type(array, to_type);
+        }
+    }
+    if array.is_empty() {
         return Ok(new_empty_array(to_type));
     }
     if from_type == &Null {
         return Ok(new_null_array(to_type, array.len()));
     }
 
-    // to_type, Timestamp(unit, tz)) {
     match (from_type, to_type) {
+        (Dictionary(_, _), Timestamp(_, _)) => {
+            dictionary::cast_dictionary(array, to_type, cast_options, report)
+        }
+        (Dictionary(_, _), Dictionary(_, value_type))
+            if matches!(value_type.as_ref(), Timestamp(_, _)) =>
+        {
+            dictionary::cast_dictionary(array, to_type, cast_options, report)
+        }
+        (Struct(_), Struct(to_fields)) => {
+            nested::cast_struct(array, to_fields, cast_options, report)
+        }
+        (Float16 | Float32 | Float64, Timestamp(_, _)) if float::has_non_finite(array)? => {
+            let (finite, infinities) = float::null_non_finite(array, cast_options, report)?;
+            let casted = cast_inner(&finite, to_type, cast_options, report)?;
+            float::saturate_infinities(casted, &infinities, cast_options, report)
+        }
+        (Int8 | Int16 | Int32 | Int64 | UInt8 | UInt16 | UInt32 | UInt64, Timestamp(unit, tz))
+            if !cast_options.timestamp_options.interpretations.is_empty() =>
+        {
+            let integers = match from_type {
+                // Keep the bits of NTP timestamps beyond `i64::MAX`.
+                UInt64 => Arc::new(
+                    array
+                        .as_primitive::<UInt64Type>()
+                        .unary::<_, Int64Type>(|v| v as i64),
+                ),
+                _ => arrow_cast::cast(array, &Int64)?,
+            };
+            let Some(casted) =
+                interpret::cast(integers.as_primitive(), *unit, cast_options, report)?
+            else {
+                let mut options = cast_options.clone();
+                options.timestamp_options.interpretations = Vec::new();
+                return cast_inner(array, to_type, &options, report);
+            };
+            let tz = if cast_options.timestamp_options.use_timezone_as_is {
+                tz.clone()
+            } else {
+                None
+            };
+            let casted = kernel::make_timestamp_array(&casted, *unit, tz);
+            arrow_cast::cast_with_options(&casted, to_type, &cast_options.into())
+        }
         (
-            // Convert to second precision integer.
-            Int8 | Int16 | Int32 | UInt8 | UInt32 | Float16 | Float32 | UInt16,
+            Int8
+            | Int16
+            | Int32
+            | Int64
+            | UInt8
+            | UInt16
+            | UInt32
+            | UInt64
+            | Float16
+            | Float32
+            | Float64
+            | Decimal128(_, _)
+            | Decimal256(_, _),
             Timestamp(unit, tz),
-        ) => {
+        ) if cast_options.timestamp_options.epoch_kind != EpochKind::Unix => {
             let tz = if cast_options.timestamp_options.use_timezone_as_is {
                 tz.clone()
             } else {
                 None
             };
-            let array = arrow_cast::cast(array, &Int64)?;
-            if cast_options.timestamp_options.guess_timestamp_precision {
-                let array = arrow_cast::cast(&array, &Timestamp(TimeUnit::Second, tz))?;
-                return arrow_cast::cast_with_options(&array, to_type, &cast_options.into());
+            let kind = cast_options.timestamp_options.epoch_kind;
+            let array = match (kind, from_type) {
+                // Keep the bits of NTP timestamps beyond `i64::MAX`.
+                (EpochKind::NtpFixedPoint, UInt64) => Arc::new(
+                    array
+                        .as_primitive::<UInt64Type>()
+                        .unary::<_, Int64Type>(|v| v as i64),
+                ),
+                _ => arrow_cast::cast(array, &Int64)?,
+            };
+            let array = epoch::to_unix(array.as_primitive(), kind, *unit, cast_options, report)?;
+            let array = kernel::make_timestamp_array(&array, *unit, tz);
+            arrow_cast::cast_with_options(&array, to_type, &cast_options.into())
+        }
+        (Float16 | Float32 | Float64, Timestamp(unit, tz))
+            if cast_options
+                .timestamp_options
+                .timestamps_as_fractional_seconds =>
+        {
+            let tz = if cast_options.timestamp_options.use_timezone_as_is {
+                tz.clone()
             } else {
-                let array = arrow_cast::cast(&array, &Timestamp(unit.clone(), tz))?;
-                return arrow_cast::cast_with_options(&array, to_type, &cast_options.into());
+                None
+            };
+            let array = float::seconds_to_timestamp(array, *unit, cast_options, report)?;
+            let array = kernel::make_timestamp_array(&array, *unit, tz);
+            arrow_cast::cast_with_options(&array, to_type, &cast_options.into())
+        }
+        (Int8 | Int16 | Int32 | UInt8 | UInt32 | Float16 | Float32 | UInt16, Timestamp(_, _)) => {
+            let options = &cast_options.timestamp_options;
+            let wrapped = from_type == &Int32 && options.fix_y2038_wrap;
+            let guessing = options.guess_timestamp_precision || options.assume_unit.is_some();
+            if !guessing && !wrapped && options.use_timezone_as_is {
+                return arrow_cast::cast_with_options(array, to_type, &cast_options.into());
             }
+            // Widen to 64-bit integers, guessed and rescaled the same way.
+            let array = if wrapped {
+                let array = array.as_primitive::<Int32Type>();
+                Arc::new(array.unary::<_, Int64Type>(|v| v as u32 as i64)) as ArrayRef
+            } else {
+                kernel::widen(array)?
+            };
+            cast_inner(&array, to_type, cast_options, report)
         }
 
+        (FixedSizeBinary(12), Timestamp(unit, tz))
+            if cast_options.timestamp_options.binary_epoch_kind == BinaryEpochKind::Int96 =>
+        {
+            let tz = if cast_options.timestamp_options.use_timezone_as_is {
+                tz.clone()
+            } else {
+                None
+            };
+            let array = binary::decode_int96(array, cast_options, report)?;
+            let array = kernel::rescale(&array, TimeUnit::Nanosecond, *unit, cast_options, report)?;
+            let array = kernel::make_timestamp_array(&array, *unit, tz);
+            arrow_cast::cast_with_options(&array, to_type, &cast_options.into())
+        }
+        (Binary | LargeBinary | FixedSizeBinary(8), Timestamp(_, _))
+            if matches!(
+                cast_options.timestamp_options.binary_epoch_kind,
+                BinaryEpochKind::RawI64(_)
+            ) =>
+        {
+            let BinaryEpochKind::RawI64(endianness) =
+                cast_options.timestamp_options.binary_epoch_kind
+            else {
+                return Err(ArrowError::InvalidArgumentError(
+                    "Binary epoch kind is not a raw i64".to_string(),
+                ));
+            };
+            let array = binary::decode_raw_i64(array, endianness, cast_options, report)?;
+            cast_inner(&array, to_type, cast_options, report)
+        }
+        (Binary | LargeBinary, Timestamp(_, _)) => {
+            // Strings in binary, invalid UTF-8 is null or fails by `safe`.
+            let string_type = match from_type {
+                Binary => Utf8,
+                _ => LargeUtf8,
+            };
+            let strings = arrow_cast::cast_with_options(array, &string_type, &cast_options.into())?;
+            report.null_causes.parse_failure += strings.null_count() - array.null_count();
+            cast_inner(&strings, to_type, cast_options, report)
+        }
+        (Utf8 | LargeUtf8, Timestamp(unit, tz)) if string::has_leap_seconds(array) => {
+            let (array, leaps) = string::replace_leap_seconds(array, cast_options, report)?;
+            let casted = cast_inner(&array, to_type, cast_options, report)?;
+            string::shift_leap_seconds(&casted, &leaps, *unit, tz, cast_options)
+        }
+        (Utf8 | LargeUtf8, Interval(interval)) => {
+            interval::cast_string_to_interval(array, *interval, cast_options, report)
+        }
+        (Utf8 | LargeUtf8, Time32(_) | Time64(_)) => {
+            time::cast_string_to_time(array, to_type, cast_options, report)
+        }
         (Binary | FixedSizeBinary(_) | LargeBinary | Utf8 | LargeUtf8, _) => {
-            let string_to_ts = arrow_cast::cast_with_options(array, to_type, &cast_options.into())?;
+            let resolved = match to_type {
+                Timestamp(_, _) => {
+                    string::resolve_tz_abbreviations(array, &cast_options.timestamp_options)?
+                }
+                _ => None,
+            };
+            let array = resolved.as_deref().unwrap_or(array);
+            let expanded = match to_type {
+                Timestamp(_, _) => string::expand_dates(array, &cast_options.timestamp_options),
+                _ => None,
+            };
+            let array = expanded.as_deref().unwrap_or(array);
+            let normalized = match to_type {
+                Timestamp(_, _) => string::normalize(array, &cast_options.timestamp_options),
+                _ => None,
+            };
+            let array = normalized.as_deref().unwrap_or(array);
+            let naive = match to_type {
+                Timestamp(_, None) => string::apply_naive_policy(array, cast_options, report)?,
+                _ => None,
+            };
+            let array = naive.as_deref().unwrap_or(array);
+            let route = match cast_options.timestamp_options.string_parse_route {
+                StringParseRoute::Sampled => {
+                    string::sample_route(array, &cast_options.timestamp_options)
+                }
+                route => route,
+            };
+            // Strings of integers are parsed once, as integers, and only the other strings
+            // are parsed as timestamps.
+            let mut preparsed = None;
+            if let (Timestamp(_, _), StringParseRoute::Integers) = (to_type, route) {
+                let (integers, failures) =
+                    string::integers_of(array, &cast_options.timestamp_options)?;
+                if failures == 0 && integers.null_count() < integers.len() {
+                    let parsed = integers.len() - integers.null_count();
+                    report.string_parse_counts.numeric_sampled += parsed;
+                    let integers =
+                        string::accept_integers(array, integers, 0, cast_options, report)?;
+                    let integers = integers.expect("integers are not all null");
+                    let casted = cast_inner(&integers, to_type, cast_options, report)?;
+                    report.string_parse_kind = Some(StringParseKind::NumericEpoch {
+                        guessed_unit: report.guessed_unit,
+                    });
+                    return Ok(casted);
+                }
+                if integers.null_count() < integers.len() {
+                    preparsed = Some((integers, failures));
+                }
+            }
+            let rest;
+            let timestamps = match &preparsed {
+                Some((integers, _)) => {
+                    rest = string::unparsed(array, integers)?;
+                    &rest as &dyn Array
+                }
+                None => array,
+            };
+            // Parse safely first, strings may be integers.
+            let string_to_ts = match to_type {
+                Timestamp(unit, tz)
+                    if *unit != TimeUnit::Second
+                        && cast_options.overflow_behavior() == OverflowBehavior::Saturate =>
+                {
+                    saturate_string_to_timestamp(timestamps, *unit, tz, cast_options, report)?
+                }
+                _ => {
+                    let options = arrow_cast::CastOptions {
+                        safe: true,
+                        ..cast_options.into()
+                    };
+                    arrow_cast::cast_with_options(timestamps, to_type, &options)?
+                }
+            };
             if string_to_ts.null_count() == string_to_ts.len() {
-                if let Ok(array) =
-                    arrow_cast::cast_with_options(array, &Int64, &cast_options.into())
+                let integers = match preparsed.take() {
+                    Some((integers, failures)) => {
+                        string::accept_integers(array, integers, failures, cast_options, report)?
+                    }
+                    None => string::parse_integers(array, cast_options, report)?,
+                };
+                if let Some(integers) = integers {
+                    // Indicate that the string is timestamp integer.
+                    let casted = cast_inner(&integers, to_type, cast_options, report)?;
+                    if let Timestamp(_, _) = to_type {
+                        report.string_parse_kind = Some(StringParseKind::NumericEpoch {
+                            guessed_unit: report.guessed_unit,
+                        });
+                    }
+                    return Ok(casted);
+                }
+            }
+            // Strings of integers among timestamps, guessed among themselves.
+            let mut numeric = 0;
+            let string_to_ts = match to_type {
+                Timestamp(unit, tz)
+                    if cast_options.timestamp_options.mixed_string_epochs
+                        && string_to_ts.null_count() > array.null_count() =>
                 {
-                    if array.null_count() < array.len() {
-                        // Indicate that the string is timestamp integer.
-                        return cast_with_options(array.as_ref(), to_type, cast_options);
+                    // Strings of integers are the ones parsed already, if parsed first.
+                    let integers = match preparsed {
+                        Some((integers, _)) => Some(integers),
+                        None => {
+                            let rest = string::unparsed(array, &string_to_ts)?;
+                            let safe = CastOptions {
+                                safe: true,
+                                ..cast_options.clone()
+                            };
+                            string::parse_integers(&rest, &safe, &mut CastReport::default())?
+                        }
+                    };
+                    match integers {
+                        Some(integers) => {
+                            numeric = integers.len() - integers.null_count();
+                            let casted = cast_inner(&integers, to_type, cast_options, report)?;
+                            report.string_parse_counts.numeric_epoch += numeric;
+                            string::fill_timestamps(&string_to_ts, &casted, *unit, tz)?
+                        }
+                        None => string_to_ts,
                     }
                 }
+                _ => string_to_ts,
+            };
+            if !cast_options.safe && string_to_ts.null_count() > array.null_count() {
+                // Fail the same way as arrow.
+                return arrow_cast::cast_with_options(array, to_type, &cast_options.into());
             }
-            return Ok(string_to_ts);
+            report.null_causes.parse_failure +=
+                string_to_ts.null_count().saturating_sub(array.null_count());
+            let parsed = string_to_ts.len() - string_to_ts.null_count() - numeric;
+            if let (Timestamp(_, _), 1..) = (to_type, parsed) {
+                report.string_parse_counts.iso8601 += parsed;
+                report.string_parse_kind = Some(StringParseKind::Iso8601);
+            }
+            Ok(string_to_ts)
         }
-        (Int64 | UInt64 | Float64, Timestamp(unit, tz)) => {
-            let array = arrow_cast::cast(array, &Int64)?;
+        (Int64 | UInt64 | Float64 | Decimal128(_, _) | Decimal256(_, _), Timestamp(unit, tz)) => {
+            let widened;
+            let array = match from_type {
+                Int64 => array.as_primitive::<Int64Type>(),
+                _ => {
+                    widened = arrow_cast::cast(array, &Int64)?;
+                    widened.as_primitive()
+                }
+            };
 
             let tz = if cast_options.timestamp_options.use_timezone_as_is {
                 tz.clone()
             } else {
                 None
             };
-            if cast_options.timestamp_options.guess_timestamp_precision {
-                let array = arrow_cast::cast(
-                    &array,
-                    &Timestamp(
-                        guess_precision_in_array(&array).unwrap_or_else(|| unit.clone()),
-                        tz,
-                    ),
-                )?;
-                return arrow_cast::cast_with_options(&array, to_type, &cast_options.into());
+            let from = match cast_options.timestamp_options.assume_unit {
+                Some(from) => from,
+                None if cast_options.timestamp_options.guess_timestamp_precision => {
+                    let strategy = cast_options.timestamp_options.guess_strategy;
+                    report.guess_strategy = Some(strategy);
+                    if strategy == GuessStrategy::PerValue {
+                        let array = kernel::rescale_per_value(array, *unit, cast_options, report)?;
+                        let array = kernel::make_timestamp_array(&array, *unit, tz);
+                        return arrow_cast::cast_with_options(
+                            &array,
+                            to_type,
+                            &cast_options.into(),
+                        );
+                    }
+                    let guessed =
+                        guess::guess_unit(array, &cast_options.timestamp_options, report)?;
+                    report.guessed_unit = guessed;
+                    let from = guessed.unwrap_or(*unit);
+                    guess::cap_rescale(from, *unit, cast_options, report)?
+                }
+                None => *unit,
+            };
+            let array =
+                kernel::rescale_keeping_remainder(array, from, *unit, cast_options, report)?;
+            let array = kernel::make_timestamp_array(&array, *unit, tz);
+            arrow_cast::cast_with_options(&array, to_type, &cast_options.into())
+        }
+        (Duration(from), Timestamp(unit, tz))
+            if cast_options.timestamp_options.anchor.is_some() =>
+        {
+            let tz = if cast_options.timestamp_options.use_timezone_as_is {
+                tz.clone()
             } else {
-                let array = cast(&array, &Timestamp(unit.clone(), tz))?;
-                return arrow_cast::cast_with_options(&array, to_type, &cast_options.into());
-            }
+                None
+            };
+            let Some(anchor) = cast_options.timestamp_options.anchor else {
+                return Err(ArrowError::InvalidArgumentError(
+                    "Casting durations to timestamps needs an anchor".to_string(),
+                ));
+            };
+            let array = arrow_cast::cast(array, &Int64)?;
+            let array = epoch::from_anchor(
+                array.as_primitive(),
+                *from,
+                anchor,
+                *unit,
+                cast_options,
+                report,
+            )?;
+            let array = kernel::make_timestamp_array(&array, *unit, tz);
+            arrow_cast::cast_with_options(&array, to_type, &cast_options.into())
         }
+        (Timestamp(unit, _), Float64)
+            if cast_options
+                .timestamp_options
+                .timestamps_as_fractional_seconds =>
+        {
+            float::timestamp_to_seconds(array, *unit)
+        }
+        (Timestamp(unit, _), Utf8 | LargeUtf8) if cast_options.format_timezone.is_some() => {
+            let tz = cast_options.format_timezone.map(Arc::from);
+            let array = kernel::with_data_type(array, &Timestamp(*unit, tz))?;
+            arrow_cast::cast_with_options(&array, to_type, &cast_options.into())
+        }
+        (Timestamp(unit, _), Decimal128(precision, scale)) => decimal::cast_timestamp_to_decimal(
+            array,
+            *unit,
+            *precision,
+            *scale,
+            cast_options,
+            report,
+        ),
+        (
+            Int32 | Int64,
+            Interval(interval @ (IntervalUnit::DayTime | IntervalUnit::MonthDayNano)),
+        ) => interval::cast_int_to_interval(array, *interval, cast_options, report),
         _ => arrow_cast::cast_with_options(array, to_type, &cast_options.into()),
     }
 }
 
+/// Parse strings to timestamp, clamping values overflowing the unit.
+///
+/// Rows failing to parse in the unit but parsed in seconds are overflowing,
+/// others failing to parse are null regardless of `safe`.
+fn saturate_string_to_timestamp(
+    array: &dyn Array,
+    unit: TimeUnit,
+    tz: &shim::Timezone,
+    cast_options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<ArrayRef, ArrowError> {
+    let safe = arrow_cast::CastOptions {
+        safe: true,
+        ..cast_options.into()
+    };
+    let parsed =
+        arrow_cast::cast_with_options(array, &DataType::Timestamp(unit, tz.clone()), &safe)?;
+    let parsed = arrow_cast::cast(&parsed, &DataType::Int64)?;
+    let parsed = parsed.as_primitive::<Int64Type>();
+    let seconds = arrow_cast::cast_with_options(
+        array,
+        &DataType::Timestamp(TimeUnit::Second, tz.clone()),
+        &safe,
+    )?;
+    let seconds = arrow_cast::cast(&seconds, &DataType::Int64)?;
+    let seconds = seconds.as_primitive::<Int64Type>();
+    let (min, max) = cast_options
+        .timestamp_options
+        .clamp_range
+        .unwrap_or((i64::MIN, i64::MAX));
+    let mut values = Vec::with_capacity(array.len());
+    for i in 0..array.len() {
+        if parsed.is_valid(i) {
+            values.push(Some(parsed.value(i)));
+        } else if seconds.is_valid(i) {
+            report.saturated += 1;
+            values.push(Some(if seconds.value(i) < 0 { min } else { max }));
+        } else {
+            values.push(None);
+        }
+    }
+    let values = PrimitiveArray::<Int64Type>::from(values);
+    Ok(kernel::make_timestamp_array(&values, unit, tz.clone()))
+}
+
 #[cfg(test)]
 mod test {
+    use std::sync::Arc;
+
     use arrow_array::TimestampNanosecondArray;
 
     use super::*;
@@ -249,9 +988,10 @@ mod test {
     fn test_int_to_timestamp() {
         let data = vec![1701325744956, 1701325744956];
         let array = arrow_array::Int64Array::from(data);
-        let array = crate::cast(
+        let array = crate::cast_with_options(
             &array,
             &arrow_schema::DataType::Timestamp(arrow_schema::TimeUnit::Nanosecond, None),
+            &CastOptions::new().with_passthrough(false),
         )
         .unwrap();
         let nanos = array
@@ -267,9 +1007,10 @@ mod test {
     fn test_string_to_timestamp() {
         let string = vec!["1701325744956", "1701325744956"];
         let array = arrow_array::StringArray::from(string);
-        let array = crate::cast(
+        let array = crate::cast_with_options(
             &array,
             &arrow_schema::DataType::Timestamp(arrow_schema::TimeUnit::Nanosecond, None),
+            &CastOptions::new().with_passthrough(false),
         )
         .unwrap();
         let nanos = array
@@ -280,6 +1021,265 @@ mod test {
         assert_eq!(nanos.value(0), 1701325744956 * 1000 * 1000);
         dbg!(array);
     }
+    #[test]
+    fn test_format_settings() {
+        let array = arrow_array::TimestampSecondArray::from(vec![Some(1701325744), None]);
+        let options = CastOptions::new()
+            .with_passthrough(false)
+            .with_null("NULL")
+            .with_timestamp_format("%Y/%m/%d %H:%M:%S");
+        let array = crate::cast_with_options(&array, &DataType::Utf8, &options).unwrap();
+        let strings = array
+            .as_any()
+            .downcast_ref::<arrow_array::StringArray>()
+            .unwrap();
+        assert_eq!(strings.value(0), "2023/11/30 06:29:04");
+        assert!(strings.is_null(1));
+
+        // Timestamps are displayed in the timezone, naive ones taken as UTC.
+        let options = CastOptions::new()
+            .with_passthrough(false)
+            .with_timezone("+08:00");
+        for array in [
+            Arc::new(arrow_array::TimestampSecondArray::from(vec![1701325744])) as ArrayRef,
+            Arc::new(
+                arrow_array::TimestampSecondArray::from(vec![1701325744]).with_timezone("-05:00"),
+            ),
+        ] {
+            let array = crate::cast_with_options(&array, &DataType::Utf8, &options).unwrap();
+            assert_eq!(
+                array.as_string::<i32>().value(0),
+                "2023-11-30T14:29:04+08:00"
+            );
+        }
+
+        // Settings are read back as set, and applied to the cast options.
+        let settings = FormatSettings::new()
+            .with_null("N/A")
+            .with_timezone(Some("UTC"));
+        assert_eq!((settings.null(), settings.timezone()), ("N/A", Some("UTC")));
+        let format_options = arrow_cast::display::FormatOptions::new().with_null("N/A");
+        assert_eq!(settings.to_format_options(), format_options);
+        let options = CastOptions::new()
+            .with_passthrough(false)
+            .with_format_settings(settings);
+        assert_eq!(options.format_options, format_options);
+        assert_eq!(options.format_timezone, Some("UTC"));
+
+        let raw = arrow_cast::display::FormatOptions::new().with_null("NULL");
+        let options = CastOptions::new()
+            .with_passthrough(false)
+            .with_format_options(raw.clone());
+        let arrow_options: arrow_cast::CastOptions = (&options).into();
+        assert_eq!(arrow_options.format_options, raw);
+    }
+
+    #[test]
+    fn test_from_arrow_cast_options() {
+        let arrow_options = arrow_cast::CastOptions {
+            safe: false,
+            format_options: arrow_cast::display::FormatOptions::new()
+                .with_null("NULL")
+                .with_timestamp_format(Some("%s")),
+        };
+        let options = CastOptions::from(&arrow_options);
+        assert!(!options.safe);
+        assert_eq!(options.timestamp_options, TimestampCastOptions::default());
+        let back: arrow_cast::CastOptions = (&options).into();
+        assert_eq!(back, arrow_options);
+
+        let timestamp_options = TimestampCastOptions {
+            guess_timestamp_precision: false,
+            use_timezone_as_is: false,
+            ..Default::default()
+        };
+        let options = CastOptions::from_arrow(arrow_options.clone(), timestamp_options.clone());
+        assert_eq!(options.timestamp_options, timestamp_options);
+        let again = CastOptions::from(arrow_cast::CastOptions::from(&options));
+        assert_eq!(again.safe, options.safe);
+        assert_eq!(again.format_options, options.format_options);
+    }
+
+    #[test]
+    fn test_overflow_behavior() {
+        // Seconds at the boundary of nanosecond timestamps.
+        let max_secs = i64::MAX / 1_000_000_000;
+        let data = vec![
+            Some(max_secs),
+            Some(max_secs + 1),
+            None,
+            Some(-max_secs - 1),
+        ];
+        let int64 = arrow_array::Int64Array::from(data.clone());
+        let float64 = arrow_array::Float64Array::from(
+            data.iter().map(|v| v.map(|v| v as f64)).collect::<Vec<_>>(),
+        );
+        let decimal = arrow_array::Decimal128Array::from(
+            data.iter()
+                .map(|v| v.map(|v| v as i128))
+                .collect::<Vec<_>>(),
+        )
+        .with_precision_and_scale(20, 0)
+        .unwrap();
+        let to_type = DataType::Timestamp(TimeUnit::Nanosecond, None);
+        let arrays: [&dyn Array; 3] = [&int64, &float64, &decimal];
+        for array in arrays {
+            let (casted, report) =
+                cast_with_report(array, &to_type, &CastOptions::new().with_passthrough(false))
+                    .unwrap();
+            let casted = casted.as_primitive::<arrow_array::types::TimestampNanosecondType>();
+            assert_eq!(casted.value(0), max_secs * 1_000_000_000);
+            assert!(casted.is_null(1));
+            assert!(casted.is_null(3));
+            assert_eq!(report.saturated, 0);
+
+            let mut options = CastOptions::new().with_passthrough(false);
+            options.timestamp_options.overflow_behavior = Some(OverflowBehavior::Error);
+            assert!(cast_with_options(array, &to_type, &options).is_err());
+            options.safe = false;
+            options.timestamp_options.overflow_behavior = None;
+            assert!(cast_with_options(array, &to_type, &options).is_err());
+
+            options.timestamp_options.overflow_behavior = Some(OverflowBehavior::Saturate);
+            let (casted, report) = cast_with_report(array, &to_type, &options).unwrap();
+            let casted = casted.as_primitive::<arrow_array::types::TimestampNanosecondType>();
+            assert_eq!(casted.value(0), max_secs * 1_000_000_000);
+            assert_eq!(casted.value(1), i64::MAX);
+            assert!(casted.is_null(2));
+            assert_eq!(casted.value(3), i64::MIN);
+            assert_eq!(report.saturated, 2);
+
+            options.timestamp_options.clamp_range = Some((0, 1));
+            let casted = cast_with_options(array, &to_type, &options).unwrap();
+            let casted = casted.as_primitive::<arrow_array::types::TimestampNanosecondType>();
+            assert_eq!(casted.value(1), 1);
+            assert_eq!(casted.value(3), 0);
+        }
+
+        let strings = arrow_array::StringArray::from(vec![
+            Some("2262-04-11T23:47:16"),
+            Some("2262-04-11T23:47:17"),
+            None,
+            Some("1677-09-21T00:12:43"),
+        ]);
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.overflow_behavior = Some(OverflowBehavior::Saturate);
+        let (casted, report) = cast_with_report(&strings, &to_type, &options).unwrap();
+        let casted = casted.as_primitive::<arrow_array::types::TimestampNanosecondType>();
+        assert_eq!(casted.value(0), 9223372036000000000);
+        assert_eq!(casted.value(1), i64::MAX);
+        assert!(casted.is_null(2));
+        assert_eq!(casted.value(3), i64::MIN);
+        assert_eq!(report.saturated, 2);
+    }
+
+    #[test]
+    fn test_nulls_introduced() {
+        let to_type = DataType::Timestamp(TimeUnit::Nanosecond, None);
+
+        // Overflow when upscaling.
+        let array = arrow_array::Int64Array::from(vec![Some(i64::MAX / 10), None, Some(1)]);
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.assume_unit = Some(TimeUnit::Second);
+        let (_, report) = cast_with_report(&array, &to_type, &options).unwrap();
+        assert_eq!(report.nulls_introduced, 1);
+        assert_eq!(report.null_causes.overflow, 1);
+
+        // Unparsable strings, then overflow of integers parsed from strings.
+        let array = arrow_array::StringArray::from(vec![
+            Some("abc"),
+            None,
+            Some("9999999999999999"),
+            Some("1701325744"),
+        ]);
+        let (casted, report) = cast_with_report(
+            &array,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        assert_eq!(casted.null_count(), 3);
+        assert_eq!(report.nulls_introduced, 2);
+        assert_eq!(
+            report.null_causes,
+            NullCauses {
+                parse_failure: 1,
+                overflow: 1,
+                policy: 0,
+            }
+        );
+
+        // Times out of the day.
+        let array = arrow_array::StringArray::from(vec!["24:00:00", "86400", "bad"]);
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.guess_timestamp_precision = false;
+        let (_, report) =
+            cast_with_report(&array, &DataType::Time32(TimeUnit::Second), &options).unwrap();
+        assert_eq!(report.nulls_introduced, 3);
+        assert_eq!(report.null_causes.parse_failure, 2);
+        assert_eq!(report.null_causes.policy, 1);
+    }
+
+    #[test]
+    #[cfg(not(feature = "chrono"))]
+    fn test_tz_abbreviations_without_chrono() {
+        let strings = arrow_array::StringArray::from(vec!["2023-11-30 06:29:04 CST"]);
+        let to_type = DataType::Timestamp(TimeUnit::Second, None);
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.tz_abbreviations = Some(
+            [("CST".to_string(), Arc::from("+08:00"))]
+                .into_iter()
+                .collect(),
+        );
+        assert!(cast_with_options(&strings, &to_type, &options).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn test_tz_abbreviations() {
+        let strings = arrow_array::StringArray::from(vec![
+            Some("2023-11-30 06:29:04 CST"),
+            Some("2023-11-30 06:29:04 cst"),
+            Some("2023-11-30T06:29:04Z"),
+            Some("2023-11-30 06:29:04 AEST"),
+            None,
+        ]);
+        let to_type = DataType::Timestamp(TimeUnit::Second, None);
+        let cast_with_cst = |tz: &str, ignore_case: bool| {
+            let mut options = CastOptions::new().with_passthrough(false);
+            options.timestamp_options.tz_abbreviations =
+                Some([("CST".to_string(), Arc::from(tz))].into_iter().collect());
+            options.timestamp_options.tz_abbreviations_ignore_case = ignore_case;
+            let array = cast_with_options(&strings, &to_type, &options).unwrap();
+            array
+                .as_primitive::<arrow_array::types::TimestampSecondType>()
+                .iter()
+                .collect::<Vec<_>>()
+        };
+        let utc = 1701325744;
+        // China Standard Time.
+        assert_eq!(
+            cast_with_cst("+08:00", false),
+            vec![Some(utc - 8 * 3600), None, Some(utc), None, None]
+        );
+        // Central Standard Time.
+        assert_eq!(
+            cast_with_cst("-06:00", true),
+            vec![
+                Some(utc + 6 * 3600),
+                Some(utc + 6 * 3600),
+                Some(utc),
+                None,
+                None
+            ]
+        );
+        #[cfg(feature = "chrono-tz")]
+        assert_eq!(
+            cast_with_cst("America/Chicago", false)[0],
+            Some(utc + 6 * 3600)
+        );
+    }
+
     #[test]
     fn test() {
         let now = chrono::Utc::now();
@@ -312,7 +1312,7 @@ mod test {
         ints.push(i32::MAX as _);
         pres.push(TimeUnit::Second);
 
-        for (i, u) in ints.into_iter().zip(pres.into_iter()) {
+        for (i, u) in ints.into_iter().zip(pres) {
             println!("Timestamp {} in {:?}", i, guess_precision(i),);
             assert_eq!(guess_precision(i), u);
         }
@@ -320,7 +1320,7 @@ mod test {
 
     #[test]
     fn bound() {
-        let zero = chrono::NaiveDateTime::from_timestamp_opt(0, 0).unwrap();
+        let zero = chrono::DateTime::from_timestamp(0, 0).unwrap().naive_utc();
         let seconds_upper_bound = zero + std::time::Duration::from_secs(LOWER_BOUND_MILLIS as _);
         println!("{:?}", (zero..seconds_upper_bound));
         let millis_lower_bound = zero + std::time::Duration::from_millis(LOWER_BOUND_MILLIS as _);
@@ -335,7 +1335,7 @@ mod test {
 
     #[test]
     fn bound_sample() {
-        let zero = chrono::NaiveDateTime::from_timestamp_opt(0, 0).unwrap();
+        let zero = chrono::DateTime::from_timestamp(0, 0).unwrap().naive_utc();
 
         println!("ARROW_CAST_GUESSING_BOUND_YEARS |     Lower Bound     |     Upper Bound    ");
         println!("------------------------------- | ------------------- | -------------------");
@@ -385,4 +1385,531 @@ mod test {
             );
         }
     }
+
+    #[test]
+    fn test_fix_y2038_wrap() {
+        // 2039-01-01T00:00:00Z wrapped in i32, and 1965-01-01T00:00:00Z.
+        let wrapped = (2177452800_i64 - (1 << 32)) as i32;
+        let array = arrow_array::Int32Array::from(vec![wrapped, -157766400]);
+        let to_type = DataType::Timestamp(TimeUnit::Second, None);
+        let casted = cast_with_options(
+            &array,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        let casted = casted.as_primitive::<arrow_array::types::TimestampSecondType>();
+        assert_eq!(casted.value(0), wrapped as i64);
+        assert_eq!(casted.value(1), -157766400);
+
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.fix_y2038_wrap = true;
+        let casted = cast_with_options(&array, &to_type, &options).unwrap();
+        let casted = casted.as_primitive::<arrow_array::types::TimestampSecondType>();
+        assert_eq!(
+            casted.value_as_datetime(0).unwrap().to_string(),
+            "2039-01-01 00:00:00"
+        );
+        // Pre-1970 values are mangled.
+        assert_eq!(casted.value(1), -157766400_i32 as u32 as i64);
+    }
+
+    #[test]
+    fn test_small_integers() {
+        use arrow_array::{
+            Float32Array, Int16Array, Int32Array, Int8Array, UInt16Array, UInt32Array, UInt8Array,
+        };
+
+        let arrays: Vec<ArrayRef> = vec![
+            Arc::new(Int8Array::from(vec![Some(-7), None])),
+            Arc::new(Int16Array::from(vec![Some(-7), None])),
+            Arc::new(Int32Array::from(vec![Some(1701325744), None])),
+            Arc::new(UInt8Array::from(vec![Some(7), None])),
+            Arc::new(UInt16Array::from(vec![Some(7), None])),
+            Arc::new(UInt32Array::from(vec![Some(4_000_000_000), None])),
+            Arc::new(Float32Array::from(vec![Some(1.5), None])),
+        ];
+        let to_type = DataType::Timestamp(TimeUnit::Millisecond, Some("+08:00".into()));
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.guess_timestamp_precision = false;
+        for array in &arrays {
+            assert_eq!(
+                &cast_with_options(array, &to_type, &options).unwrap(),
+                &arrow_cast::cast(array, &to_type).unwrap()
+            );
+        }
+
+        // Seconds by guessing, milliseconds when assumed.
+        let array = UInt32Array::from(vec![4_000_000_000]);
+        let (casted, report) = cast_with_report(
+            &array,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        let casted = casted.as_primitive::<arrow_array::types::TimestampMillisecondType>();
+        assert_eq!(casted.value(0), 4_000_000_000_000);
+        assert_eq!(report.guessed_unit, Some(TimeUnit::Second));
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.assume_unit = Some(TimeUnit::Millisecond);
+        let casted = cast_with_options(&array, &to_type, &options).unwrap();
+        let casted = casted.as_primitive::<arrow_array::types::TimestampMillisecondType>();
+        assert_eq!(casted.value(0), 4_000_000_000);
+    }
+
+    #[test]
+    fn test_output_as_wall_clock() {
+        let array = arrow_array::Int64Array::from(vec![Some(1701325744956), None]);
+        let to_type = DataType::Timestamp(TimeUnit::Millisecond, None);
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.output_as_wall_clock_in = Some(Arc::from("+08:00"));
+        let casted = cast_with_options(&array, &to_type, &options).unwrap();
+        let casted = casted.as_primitive::<arrow_array::types::TimestampMillisecondType>();
+        assert_eq!(
+            casted.value_as_datetime(0).unwrap().to_string(),
+            "2023-11-30 14:29:04.956"
+        );
+        assert!(casted.is_null(1));
+        let with_tz = DataType::Timestamp(TimeUnit::Millisecond, Some("+08:00".into()));
+        assert!(cast_with_options(&array, &with_tz, &options).is_err());
+
+        // Around the DST transitions of New York in 2023.
+        #[cfg(feature = "chrono-tz")]
+        {
+            let array = arrow_array::Int64Array::from(vec![
+                1678604399, // 2023-03-12T06:59:59Z, EST
+                1678604400, // 2023-03-12T07:00:00Z, EDT
+                1699163999, // 2023-11-05T05:59:59Z, EDT
+                1699164000, // 2023-11-05T06:00:00Z, EST
+            ]);
+            let to_type = DataType::Timestamp(TimeUnit::Second, None);
+            options.timestamp_options.output_as_wall_clock_in = Some(Arc::from("America/New_York"));
+            let casted = cast_with_options(&array, &to_type, &options).unwrap();
+            let casted = casted.as_primitive::<arrow_array::types::TimestampSecondType>();
+            let wall_clock: Vec<_> = (0..casted.len())
+                .map(|i| casted.value_as_datetime(i).unwrap().to_string())
+                .collect();
+            assert_eq!(
+                wall_clock,
+                vec![
+                    "2023-03-12 01:59:59",
+                    "2023-03-12 03:00:00",
+                    "2023-11-05 01:59:59",
+                    "2023-11-05 01:00:00",
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_cast_with_tz_offsets() {
+        let values = arrow_array::Int64Array::from(vec![1701325744956; 3]);
+        let offsets = arrow_array::Int32Array::from(vec![Some(8 * 3600), Some(-5 * 3600), None]);
+        let to_type = DataType::Timestamp(TimeUnit::Millisecond, None);
+        let mut options = CastOptions::new().with_passthrough(false);
+        let casted = cast_with_tz_offsets(&values, &offsets, &to_type, &options).unwrap();
+        let wall_clock = |array: &ArrayRef| -> Vec<Option<String>> {
+            let array = array.as_primitive::<arrow_array::types::TimestampMillisecondType>();
+            (0..array.len())
+                .map(|i| {
+                    array
+                        .value_as_datetime(i)
+                        .filter(|_| array.is_valid(i))
+                        .map(|dt| dt.to_string())
+                })
+                .collect()
+        };
+        assert_eq!(
+            wall_clock(&casted),
+            vec![
+                Some("2023-11-30 14:29:04.956".to_string()),
+                Some("2023-11-30 01:29:04.956".to_string()),
+                None
+            ]
+        );
+        options.timestamp_options.null_offset_as_utc = true;
+        let casted = cast_with_tz_offsets(&values, &offsets, &to_type, &options).unwrap();
+        assert_eq!(
+            wall_clock(&casted)[2].as_deref(),
+            Some("2023-11-30 06:29:04.956")
+        );
+
+        let offsets = arrow_array::Int32Array::from(vec![0]);
+        assert!(cast_with_tz_offsets(&values, &offsets, &to_type, &options).is_err());
+    }
+
+    fn assert_same_as_arrow(options: &CastOptions) {
+        let int64 = arrow_array::Int64Array::from(vec![Some(1701325744956), None, Some(-1)]);
+        let int32 = arrow_array::Int32Array::from(vec![Some(1701325744), None]);
+        let strings = arrow_array::StringArray::from(vec![
+            Some("1701325744956"),
+            Some("2016-12-31T23:59:60Z"),
+            Some("2023-11-30T06:29:04.956Z"),
+            Some("3 days"),
+        ]);
+        let float = arrow_array::Float64Array::from(vec![1701325744956.0, f64::NAN]);
+        let cases: Vec<(&dyn Array, DataType)> = vec![
+            (&int64, DataType::Timestamp(TimeUnit::Nanosecond, None)),
+            (
+                &int64,
+                DataType::Timestamp(TimeUnit::Second, Some("+08:00".into())),
+            ),
+            (&int64, DataType::Interval(IntervalUnit::MonthDayNano)),
+            (&int32, DataType::Timestamp(TimeUnit::Millisecond, None)),
+            (&strings, DataType::Timestamp(TimeUnit::Millisecond, None)),
+            (&strings, DataType::Interval(IntervalUnit::MonthDayNano)),
+            (&strings, DataType::Time64(TimeUnit::Microsecond)),
+            (&float, DataType::Timestamp(TimeUnit::Microsecond, None)),
+        ];
+        for (array, to_type) in cases {
+            let expected = arrow_cast::cast_with_options(array, &to_type, &options.into());
+            let casted = cast_with_options(array, &to_type, options);
+            match (casted, expected) {
+                (Ok(casted), Ok(expected)) => assert_eq!(casted.to_data(), expected.to_data()),
+                (Err(casted), Err(expected)) => {
+                    assert_eq!(casted.to_string(), expected.to_string())
+                }
+                (casted, expected) => panic!("{casted:?} != {expected:?} for {to_type:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_passthrough() {
+        let mut options = CastOptions::new().with_passthrough(true);
+        options.timestamp_options.assume_unit = Some(TimeUnit::Second);
+        options.timestamp_options.output_as_wall_clock_in = Some(Arc::from("+08:00"));
+        assert_same_as_arrow(&options);
+    }
+
+    #[test]
+    #[cfg(feature = "passthrough")]
+    fn test_passthrough_feature() {
+        // The feature delegates to arrow by default, and guessing is still opted in.
+        let options = CastOptions::new();
+        assert_same_as_arrow(&options);
+        assert_eq!(
+            options.resolve().passthrough.source,
+            resolved::ConfigSource::Feature
+        );
+        let array = arrow_array::Int64Array::from(vec![1701325744956]);
+        let to_type = DataType::Timestamp(TimeUnit::Nanosecond, None);
+        let casted = cast(&array, &to_type).unwrap();
+        assert_eq!(
+            casted
+                .as_primitive::<arrow_array::types::TimestampNanosecondType>()
+                .value(0),
+            1701325744956
+        );
+        let options = options.with_passthrough(false);
+        let casted = cast_with_options(&array, &to_type, &options).unwrap();
+        assert_eq!(
+            casted
+                .as_primitive::<arrow_array::types::TimestampNanosecondType>()
+                .value(0),
+            1701325744956 * 1_000_000
+        );
+    }
+
+    #[test]
+    fn test_sliced() {
+        use arrow_array::{
+            types::Int32Type, DictionaryArray, Float64Array, Int32Array, Int64Array, ListArray,
+            StringArray, UInt64Array,
+        };
+
+        // Each array is sliced past its first two rows, and cast the same as the rest built
+        // afresh, even if guessing from the first two rows would decide otherwise.
+        fn check(full: ArrayRef, fresh: ArrayRef, to_type: &DataType, options: &CastOptions) {
+            let sliced = full.slice(2, fresh.len());
+            let casted = cast_with_report(&sliced, to_type, options);
+            let expected = cast_with_report(&fresh, to_type, options);
+            match (casted, expected) {
+                (Ok((casted, report)), Ok((expected, expected_report))) => {
+                    assert_eq!(casted.to_data(), expected.to_data(), "{to_type:?}");
+                    assert_eq!(report, expected_report, "{to_type:?}");
+                }
+                (casted, expected) => panic!("{casted:?} != {expected:?} for {to_type:?}"),
+            }
+        }
+
+        let integers = [
+            Some(1701325744956000000),
+            Some(-1701325744956000000),
+            Some(1701325744956),
+            None,
+            Some(1701325745956),
+            Some(0),
+        ];
+        let strings = [
+            Some("2023-11-30T06:29:04.956+08:00"),
+            Some("bad"),
+            Some("1701325744956"),
+            None,
+            Some("1701325745"),
+        ];
+        let dates = [
+            Some("1701325744"),
+            Some("x"),
+            Some("2023-11-30 06:29:04"),
+            None,
+            Some("2023-11-30"),
+            Some("2023-11-30 06:29:04"),
+        ];
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.guess_strategy = GuessStrategy::Majority;
+        let timestamp = DataType::Timestamp(TimeUnit::Millisecond, None);
+        let cases: Vec<(ArrayRef, ArrayRef, DataType)> = vec![
+            (
+                Arc::new(Int64Array::from(integers.to_vec())),
+                Arc::new(Int64Array::from(integers[2..].to_vec())),
+                timestamp.clone(),
+            ),
+            (
+                Arc::new(UInt64Array::from_iter(
+                    integers.iter().map(|v| v.map(|v| v.unsigned_abs())),
+                )),
+                Arc::new(UInt64Array::from_iter(
+                    integers[2..].iter().map(|v| v.map(|v| v.unsigned_abs())),
+                )),
+                timestamp.clone(),
+            ),
+            (
+                Arc::new(Int32Array::from(vec![
+                    Some(i32::MIN),
+                    None,
+                    Some(1701325744),
+                    None,
+                ])),
+                Arc::new(Int32Array::from(vec![Some(1701325744), None])),
+                timestamp.clone(),
+            ),
+            (
+                Arc::new(Float64Array::from(vec![
+                    Some(1e18),
+                    None,
+                    Some(1701325744.5),
+                    None,
+                ])),
+                Arc::new(Float64Array::from(vec![Some(1701325744.5), None])),
+                timestamp.clone(),
+            ),
+            (
+                Arc::new(StringArray::from(strings.to_vec())),
+                Arc::new(StringArray::from(strings[2..].to_vec())),
+                timestamp.clone(),
+            ),
+            (
+                Arc::new(StringArray::from(dates.to_vec())),
+                Arc::new(StringArray::from(dates[2..].to_vec())),
+                DataType::Timestamp(TimeUnit::Second, Some("+08:00".into())),
+            ),
+            (
+                Arc::new(
+                    dates
+                        .iter()
+                        .copied()
+                        .collect::<DictionaryArray<Int32Type>>(),
+                ),
+                Arc::new(
+                    dates[2..]
+                        .iter()
+                        .copied()
+                        .collect::<DictionaryArray<Int32Type>>(),
+                ),
+                timestamp.clone(),
+            ),
+            (
+                Arc::new(ListArray::from_iter_primitive::<Int64Type, _, _>(vec![
+                    Some(integers[..2].to_vec()),
+                    None,
+                    Some(integers[2..].to_vec()),
+                    Some(vec![]),
+                ])),
+                Arc::new(ListArray::from_iter_primitive::<Int64Type, _, _>(vec![
+                    Some(integers[2..].to_vec()),
+                    Some(vec![]),
+                ])),
+                DataType::new_list(timestamp.clone(), true),
+            ),
+        ];
+        for (full, fresh, to_type) in cases {
+            check(full.clone(), fresh.clone(), &to_type, &options);
+            check(
+                full,
+                fresh,
+                &to_type,
+                &CastOptions::new().with_passthrough(false),
+            );
+        }
+
+        // Entry points of two arrays, sliced by different offsets.
+        let values = Int64Array::from(integers.to_vec());
+        let units = StringArray::from(vec![None, Some("ns"), Some("s"), Some("ms"), None]);
+        let casted = units::cast_with_unit_column(
+            &values.slice(2, 3),
+            &units.slice(1, 3),
+            &timestamp,
+            &options,
+        )
+        .unwrap();
+        let expected = units::cast_with_unit_column(
+            &Int64Array::from(integers[2..5].to_vec()),
+            &StringArray::from(vec![Some("ns"), Some("s"), Some("ms")]),
+            &timestamp,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(casted.to_data(), expected.to_data());
+
+        let dates = arrow_array::Date32Array::from(vec![Some(0), Some(19691), None]);
+        let times = Int64Array::from(vec![Some(23344956000000), Some(23344), Some(1)]);
+        let casted =
+            combine_date_time(&dates.slice(1, 2), &times.slice(0, 2), &timestamp, &options)
+                .unwrap();
+        let expected = combine_date_time(
+            &arrow_array::Date32Array::from(vec![Some(19691), None]),
+            &Int64Array::from(vec![Some(23344956000000), Some(23344)]),
+            &timestamp,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(casted.to_data(), expected.to_data());
+
+        let offsets = Int32Array::from(vec![Some(0), None, Some(28800)]);
+        let casted = cast_with_tz_offsets(
+            &values.slice(2, 2),
+            &offsets.slice(1, 2),
+            &timestamp,
+            &options,
+        )
+        .unwrap();
+        let expected = cast_with_tz_offsets(
+            &Int64Array::from(integers[2..4].to_vec()),
+            &Int32Array::from(vec![None, Some(28800)]),
+            &timestamp,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(casted.to_data(), expected.to_data());
+    }
+
+    #[test]
+    fn test_normalize_utc_tz() {
+        use arrow_array::{Int64Array, RecordBatch, StringArray, TimestampMillisecondArray};
+        use arrow_schema::{Field, Schema};
+
+        let utc = DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into()));
+        let plus = DataType::Timestamp(TimeUnit::Millisecond, Some("+00:00".into()));
+        let integers = Int64Array::from(vec![Some(1701325744956), None]);
+        let strings = StringArray::from(vec![Some("2023-11-30T06:29:04.956Z"), None]);
+        let expected = TimestampMillisecondArray::from(vec![Some(1701325744956), None]);
+        let mut options = CastOptions::new().with_passthrough(false);
+        for (spelling, target, output) in [
+            (UtcSpelling::Utc, &plus, &utc),
+            (UtcSpelling::PlusZeroZero, &utc, &plus),
+            (UtcSpelling::AsRequested, &plus, &plus),
+        ] {
+            options.timestamp_options.normalize_utc_tz = Some(spelling);
+            for array in [
+                &integers as &dyn Array,
+                &strings,
+                &expected.clone().with_timezone("Z"),
+            ] {
+                let casted = cast_with_options(array, target, &options).unwrap();
+                assert_eq!(casted.data_type(), output);
+                assert_eq!(
+                    casted
+                        .as_primitive::<arrow_array::types::TimestampMillisecondType>()
+                        .values(),
+                    expected.values()
+                );
+            }
+        }
+
+        // Other timezones and nested types.
+        options.timestamp_options.normalize_utc_tz = Some(UtcSpelling::Utc);
+        let shanghai = DataType::Timestamp(TimeUnit::Millisecond, Some("+08:00".into()));
+        let casted = cast_with_options(&integers, &shanghai, &options).unwrap();
+        assert_eq!(casted.data_type(), &shanghai);
+        let to_type = DataType::new_list(plus.clone(), true);
+        let list = arrow_array::ListArray::from_iter_primitive::<Int64Type, _, _>(vec![Some(
+            vec![Some(1701325744956)],
+        )]);
+        let casted = cast_with_options(&list, &to_type, &options).unwrap();
+        assert_eq!(casted.data_type(), &DataType::new_list(utc.clone(), true));
+
+        // Batches in the schema expected downstream.
+        let schema = Arc::new(Schema::new(vec![Field::new("ts", plus, true)]));
+        let batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![Field::new("ts", DataType::Int64, true)])),
+            vec![Arc::new(integers)],
+        )
+        .unwrap();
+        let casted = cast_record_batch(&batch, schema, &SchemaCastOptions::new(options)).unwrap();
+        assert_eq!(casted.schema().field(0).data_type(), &utc);
+    }
+
+    #[test]
+    fn test_null_buffer_reuse() {
+        use arrow_array::{Float64Array, Int32Array, Int64Array, StringArray};
+        let nulls = |array: &dyn Array| array.nulls().unwrap().buffer().as_ptr();
+        let positions = |array: &dyn Array| {
+            (0..array.len())
+                .filter(|i| array.is_null(*i))
+                .collect::<Vec<_>>()
+        };
+        let to_type = DataType::Timestamp(TimeUnit::Nanosecond, Some("+08:00".into()));
+
+        // Null positions preserved share the null buffer of the source.
+        let mut options = CastOptions::new().with_passthrough(false);
+        let millis = Int64Array::from(vec![Some(1701325744956), None, Some(1701325744957)]);
+        let nanos = Int64Array::from(vec![Some(1701325744956789012), None]);
+        let seconds = Int32Array::from(vec![None, Some(1701325744)]);
+        for array in [&millis as &dyn Array, &nanos, &seconds] {
+            for to_type in [&to_type, &DataType::Timestamp(TimeUnit::Millisecond, None)] {
+                let casted = cast_with_options(array, to_type, &options).unwrap();
+                assert_eq!(nulls(&casted), nulls(array), "{array:?} to {to_type:?}");
+            }
+        }
+        options.timestamp_options.guess_strategy = GuessStrategy::PerValue;
+        options.timestamp_options.record_row_units = true;
+        let (casted, report) = cast_with_report(&millis, &to_type, &options).unwrap();
+        assert_eq!(nulls(&casted), nulls(&millis));
+        assert_eq!(nulls(&report.row_units.unwrap()), nulls(&millis));
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.timestamps_as_fractional_seconds = true;
+        let floats = Float64Array::from(vec![Some(1701325744.5), None]);
+        let casted = cast_with_options(&floats, &to_type, &options).unwrap();
+        assert_eq!(nulls(&casted), nulls(&floats));
+
+        // Nulls introduced by overflow and parse failures are at their rows.
+        let overflowing = Int64Array::from(vec![Some(1701325744), None, Some(i64::MAX / 1000)]);
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.assume_unit = Some(TimeUnit::Second);
+        let (casted, report) = cast_with_report(&overflowing, &to_type, &options).unwrap();
+        assert_eq!(positions(&casted), vec![1, 2]);
+        assert_eq!(casted.null_count(), 2);
+        assert_eq!(report.null_causes.overflow, 1);
+        assert_eq!(positions(&overflowing), vec![1]);
+        options.timestamp_options.assume_unit = None;
+        options.timestamp_options.timestamps_as_fractional_seconds = true;
+        let floats = Float64Array::from(vec![None, Some(1e12), Some(1701325744.5)]);
+        let casted = cast_with_options(&floats, &to_type, &options).unwrap();
+        assert_eq!(positions(&casted), vec![0, 1]);
+        let strings = StringArray::from(vec![
+            Some("2023-11-30T06:29:04.956Z"),
+            Some("bad"),
+            None,
+            Some("2023-11-30T06:29:05Z"),
+        ]);
+        let (casted, report) = cast_with_report(
+            &strings,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        assert_eq!(positions(&casted), vec![1, 2]);
+        assert_eq!(report.null_causes.parse_failure, 1);
+    }
 }
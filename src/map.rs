@@ -0,0 +1,118 @@
+//! Extracting and casting values of map arrays by key.
+
+use std::sync::Arc;
+
+use arrow_array::{cast::AsArray, Array, ArrayRef, StringArray, StructArray};
+use arrow_schema::{ArrowError, DataType, Field};
+
+use crate::{cast_with_options, CastOptions};
+
+/// Extract the values of `keys` from a map array, cast to a struct of one child per key.
+///
+/// Keys and values are read as strings, so epoch strings of unknown unit are guessed like
+/// other strings. Each child is cast on its own. A row without the key is null in the child,
+/// a key appearing more than once in a row takes its first value.
+pub(crate) fn cast_map_values_by_key(
+    array: &dyn Array,
+    keys: &[&str],
+    to_type: &DataType,
+    cast_options: &CastOptions,
+) -> Result<ArrayRef, ArrowError> {
+    let Some(map) = array.as_map_opt() else {
+        return Err(ArrowError::CastError(format!(
+            "Cannot extract values by key from {:?}, expected a map",
+            array.data_type()
+        )));
+    };
+    let map_keys = arrow_cast::cast(map.keys(), &DataType::Utf8)?;
+    let map_keys = map_keys.as_string::<i32>();
+    let values = arrow_cast::cast(map.values(), &DataType::Utf8)?;
+    let values = values.as_string::<i32>();
+    let offsets = map.value_offsets();
+    let mut fields = Vec::with_capacity(keys.len());
+    let mut children = Vec::with_capacity(keys.len());
+    for key in keys {
+        let extracted: StringArray = (0..map.len())
+            .map(|row| {
+                if map.is_null(row) {
+                    return None;
+                }
+                let entries = offsets[row] as usize..offsets[row + 1] as usize;
+                let entry = entries
+                    .into_iter()
+                    .find(|&i| map_keys.is_valid(i) && map_keys.value(i) == *key)?;
+                values.is_valid(entry).then(|| values.value(entry))
+            })
+            .collect();
+        children.push(cast_with_options(&extracted, to_type, cast_options)?);
+        fields.push(Field::new(*key, to_type.clone(), true));
+    }
+    Ok(Arc::new(StructArray::try_new(
+        fields.into(),
+        children,
+        map.nulls().cloned(),
+    )?))
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::{
+        builder::{MapBuilder, StringBuilder},
+        types::TimestampMillisecondType,
+    };
+    use arrow_schema::TimeUnit;
+
+    use super::*;
+
+    #[test]
+    fn test_cast_map_values_by_key() {
+        let mut builder = MapBuilder::new(None, StringBuilder::new(), StringBuilder::new());
+        for entries in [
+            vec![
+                ("created_at", "1701325744956"),
+                ("updated_at", "1701325745"),
+            ],
+            vec![("updated_at", "1701325747"), ("other", "x")],
+            // The first of duplicate keys.
+            vec![("created_at", "1701325746"), ("created_at", "1")],
+        ] {
+            for (key, value) in entries {
+                builder.keys().append_value(key);
+                builder.values().append_value(value);
+            }
+            builder.append(true).unwrap();
+        }
+        builder.append(false).unwrap();
+        let map = builder.finish();
+
+        let to_type = DataType::Timestamp(TimeUnit::Millisecond, None);
+        let keys = ["created_at", "updated_at"];
+        let casted = crate::cast_map_values_by_key(
+            &map,
+            &keys,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        let casted = casted.as_struct();
+        assert_eq!(casted.column_names(), keys);
+        assert!(casted.is_null(3));
+        let created = casted.column(0).as_primitive::<TimestampMillisecondType>();
+        assert_eq!(created.value(0), 1701325744956);
+        assert!(created.is_null(1));
+        // Guessed by the first value of the child, milliseconds.
+        assert_eq!(created.value(2), 1701325746);
+        let updated = casted.column(1).as_primitive::<TimestampMillisecondType>();
+        assert_eq!(updated.value(0), 1701325745000);
+        assert_eq!(updated.value(1), 1701325747000);
+        assert!(updated.is_null(2));
+
+        let err = crate::cast_map_values_by_key(
+            &StringArray::from(vec!["a"]),
+            &keys,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        );
+        assert!(err.is_err());
+    }
+}
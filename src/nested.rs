@@ -0,0 +1,154 @@
+//! Casting struct arrays field by field, guessing like top-level arrays.
+
+use std::sync::Arc;
+
+use arrow_array::{cast::AsArray, new_null_array, Array, ArrayRef, StructArray};
+use arrow_schema::{ArrowError, Fields};
+
+use crate::{cast_inner, CastOptions, CastReport};
+
+/// Cast each field of a struct array to the target fields, keeping the parent validity.
+///
+/// Fields are matched by position, or by name if missing or extra fields are allowed, see
+/// [allow_missing_struct_fields](CastOptions::allow_missing_struct_fields).
+pub(crate) fn cast_struct(
+    array: &dyn Array,
+    to_fields: &Fields,
+    cast_options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<ArrayRef, ArrowError> {
+    let array = array.as_struct();
+    let from_fields = array.fields();
+    let by_name =
+        cast_options.allow_missing_struct_fields || cast_options.allow_extra_struct_fields;
+    let mut children = Vec::with_capacity(to_fields.len());
+    if !by_name {
+        if from_fields.len() != to_fields.len() {
+            return Err(ArrowError::CastError(format!(
+                "Cannot cast struct of {} fields to struct of {} fields",
+                from_fields.len(),
+                to_fields.len()
+            )));
+        }
+        for (column, field) in array.columns().iter().zip(to_fields.iter()) {
+            children.push(cast_inner(column, field.data_type(), cast_options, report)?);
+        }
+    } else {
+        if !cast_options.allow_extra_struct_fields {
+            if let Some(extra) = from_fields
+                .iter()
+                .find(|f| to_fields.find(f.name()).is_none())
+            {
+                return Err(ArrowError::CastError(format!(
+                    "Cannot cast struct field {} missing from the target",
+                    extra.name()
+                )));
+            }
+        }
+        for field in to_fields.iter() {
+            let child = match array.column_by_name(field.name()) {
+                Some(column) => cast_inner(column, field.data_type(), cast_options, report)?,
+                None if cast_options.allow_missing_struct_fields => {
+                    new_null_array(field.data_type(), array.len())
+                }
+                None => {
+                    return Err(ArrowError::CastError(format!(
+                        "Cannot cast to struct field {} missing from the source",
+                        field.name()
+                    )))
+                }
+            };
+            children.push(child);
+        }
+    }
+    Ok(Arc::new(StructArray::try_new(
+        to_fields.clone(),
+        children,
+        array.nulls().cloned(),
+    )?))
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::{types::TimestampMillisecondType, Int64Array};
+    use arrow_schema::{DataType, Field, TimeUnit};
+
+    use super::*;
+
+    #[test]
+    fn test_cast_struct() {
+        // Null in the middle row of the parent.
+        let parent_nulls = Int64Array::from(vec![Some(0), None, Some(0)]);
+        let source = StructArray::try_new(
+            vec![
+                Field::new("id", DataType::Int64, true),
+                Field::new("ts", DataType::Int64, true),
+            ]
+            .into(),
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2, 3])),
+                Arc::new(Int64Array::from(vec![1701325744, 1701325745, 1701325746])),
+            ],
+            parent_nulls.nulls().cloned(),
+        )
+        .unwrap();
+        let timestamp = DataType::Timestamp(TimeUnit::Millisecond, None);
+
+        // Children are guessed, matched by position.
+        let to_type = DataType::Struct(
+            vec![
+                Field::new("id", DataType::Int64, true),
+                Field::new("at", timestamp.clone(), true),
+            ]
+            .into(),
+        );
+        let casted = crate::cast_with_options(
+            &source,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        let casted = casted.as_struct();
+        let at = casted.column(1).as_primitive::<TimestampMillisecondType>();
+        assert_eq!(at.value(0), 1701325744000);
+
+        // Evolving `{id}` into `{created_at, id}` by name.
+        let evolving = StructArray::try_new(
+            vec![Field::new("id", DataType::Int64, true)].into(),
+            vec![source.column(0).clone()],
+            parent_nulls.nulls().cloned(),
+        )
+        .unwrap();
+        let to_type = DataType::Struct(
+            vec![
+                Field::new("created_at", timestamp, true),
+                Field::new("id", DataType::Int64, true),
+            ]
+            .into(),
+        );
+        assert!(crate::cast_with_options(
+            &evolving,
+            &to_type,
+            &CastOptions::new().with_passthrough(false)
+        )
+        .is_err());
+        let options = CastOptions::new()
+            .with_passthrough(false)
+            .with_allow_missing_struct_fields(true);
+        let casted = crate::cast_with_options(&evolving, &to_type, &options).unwrap();
+        assert_eq!(casted.data_type(), &to_type);
+        let casted = casted.as_struct();
+        assert_eq!(casted.nulls(), evolving.nulls());
+        assert_eq!(casted.column(0).null_count(), 3);
+        assert_eq!(casted.column(1), evolving.column(0));
+        let plan = crate::CastPlan::try_new(evolving.data_type(), &to_type, &options).unwrap();
+        assert!(!plan.guesses());
+        assert!(crate::CastPlan::try_new(source.data_type(), &to_type, &options).is_err());
+
+        // `ts` of the source is extra.
+        assert!(crate::cast_with_options(&source, &to_type, &options).is_err());
+        let options = options.with_allow_extra_struct_fields(true);
+        let casted = crate::cast_with_options(&source, &to_type, &options).unwrap();
+        assert_eq!(casted.as_struct().column(1), source.column(0));
+    }
+}
@@ -0,0 +1,827 @@
+//! Options for casting.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use arrow_schema::TimeUnit;
+
+use crate::{ElapsedUnit, FormatSettings, EPOCH_UNIT_METADATA_KEY, GUESSING_BOUND_YEARS};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimestampCastOptions {
+    /// If true, try to guess the precision of the timestamp from integers.
+    ///
+    /// Caster will first convert the integer to i64 and then guess the precision.
+    pub guess_timestamp_precision: bool,
+    /// If true, caster use the timezone in target type. If false, caster will use UTC.
+    pub use_timezone_as_is: bool,
+    /// Guessing bound in years, defaults to `ARROW_CAST_GUESSING_BOUND_YEARS` at build-time.
+    pub guessing_bound_years: i64,
+    /// How to handle values overflowing when upscaling to a finer unit.
+    ///
+    /// If `None`, overflowing values are null when `safe` or error otherwise.
+    pub overflow_behavior: Option<OverflowBehavior>,
+    /// Clamp range in the target unit for [OverflowBehavior::Saturate].
+    ///
+    /// If `None`, overflowing values are clamped to `i64::MIN` or `i64::MAX`.
+    pub clamp_range: Option<(i64, i64)>,
+    /// Timezone abbreviations to timezones, for strings like `2023-11-30 06:29:04 CST`.
+    ///
+    /// A timezone is an IANA name (with the `chrono-tz` feature) or a fixed offset like `+08:00`.
+    /// Strings with a trailing abbreviation in the map are interpreted in the mapped timezone,
+    /// others follow the normal parsing. Requires the `chrono` feature, casting strings fails
+    /// without it.
+    pub tz_abbreviations: Option<HashMap<String, Arc<str>>>,
+    /// If true, abbreviations are matched case-insensitively.
+    pub tz_abbreviations_ignore_case: bool,
+    /// Bound in days of elapsed time, for guessing the unit of integers casting to intervals.
+    pub elapsed_bound_days: i64,
+    /// Unit of integers casting to intervals, guessed if not set.
+    ///
+    /// If not set and guessing is disabled, integers are milliseconds.
+    pub elapsed_unit: Option<ElapsedUnit>,
+    /// How to downscale values to a coarser target unit.
+    pub downscale_policy: DownscalePolicy,
+    /// If true, six-digit strings like `062904` casting to times are parsed as `HHMMSS`
+    /// instead of guessing the unit from the integer.
+    pub parse_compact_time: bool,
+    /// Source unit of integers casting to timestamps, skipping guessing when set.
+    pub assume_unit: Option<TimeUnit>,
+    /// If true, conflicting guesses fail the cast instead of being resolved silently.
+    ///
+    /// With [GuessStrategy::Majority] and [GuessStrategy::MaxAbs], values of an array guessing
+    /// different units fail the cast.
+    pub strict: bool,
+    /// How to interpret binary values casting to timestamps.
+    pub binary_epoch_kind: BinaryEpochKind,
+    /// How to guess the unit from the values of an array.
+    pub guess_strategy: GuessStrategy,
+    /// If true, the sentinel values `0`, `-1`, `i64::MIN` and `i64::MAX` are not used for guessing.
+    pub skip_sentinels: bool,
+    /// How to handle NaN and infinite values of float sources, before guessing.
+    ///
+    /// If `None`, non-finite values are null when `safe` or error otherwise.
+    /// Infinities are clamped within `clamp_range` when saturating.
+    pub non_finite_policy: Option<NonFinitePolicy>,
+    /// If true, ISO 8601 durations like `P3M2DT4H` are parsed when casting strings to intervals,
+    /// besides SQL-style strings like `3 months 2 days`.
+    pub iso8601_durations: bool,
+    /// How to handle strings with a leap second like `2016-12-31T23:59:60Z` casting to timestamps.
+    ///
+    /// If `None`, leap seconds are null when `safe` or error otherwise.
+    pub leap_second_policy: Option<LeapSecondPolicy>,
+    /// If true, the unit of each value is recorded in [CastReport::row_units](crate::CastReport::row_units)
+    /// when guessing with [GuessStrategy::PerValue].
+    pub record_row_units: bool,
+    /// If true, negative `Int32` seconds casting to timestamps are reinterpreted as unsigned
+    /// 32-bit seconds, i.e. `value as u32 as i64`, fixing values wrapped past 2038-01-19.
+    ///
+    /// **Genuine timestamps before 1970 are mangled**, e.g. `-1` becomes 2106-02-07T06:28:15,
+    /// only enable it for sources known to wrap. The reinterpretation happens before guessing
+    /// and any other handling of negative values.
+    pub fix_y2038_wrap: bool,
+    /// Timezone of the wall clock to output timestamps in, e.g. `Asia/Shanghai` or `+08:00`.
+    ///
+    /// If set, timestamps are shifted by the offset of the timezone at each instant, so the
+    /// timezone-less output reads as the local wall clock. It is lossy and only for display,
+    /// casting to timestamps with a timezone fails. Named timezones need the `chrono-tz` feature.
+    pub output_as_wall_clock_in: Option<Arc<str>>,
+    /// If true, rows with a null offset in [cast_with_tz_offsets](crate::cast_with_tz_offsets)
+    /// are kept in UTC instead of null.
+    pub null_offset_as_utc: bool,
+    /// Fractional margin around the lower bound of each unit, e.g. `0.05`, within which values
+    /// are flagged as near the boundary when guessing.
+    ///
+    /// Flagged values are counted in [CastReport::near_boundary](crate::CastReport::near_boundary),
+    /// or fail the cast with [strict](Self::strict). Only the first value is checked with
+    /// [GuessStrategy::FirstNonNull].
+    pub boundary_margin: Option<f64>,
+    /// If true, eight-digit strings like `20231130` casting to timestamps are parsed as
+    /// `YYYYMMDD` dates instead of integers.
+    pub parse_compact_date: bool,
+    /// If set, strings with English month names like `Nov 30, 2023 12:00 PM` or
+    /// `30-Nov-2023 06:29:04` casting to timestamps are parsed, in the order of day and month.
+    ///
+    /// Numeric dates like `03-04-2023` are never guessed.
+    pub named_month_order: Option<DateOrder>,
+    /// Epoch and tick of integers casting to timestamps.
+    ///
+    /// Epochs other than [EpochKind::Unix] are never guessed, and skip guessing the unit.
+    pub epoch_kind: EpochKind,
+    /// If true, timestamps casting to `Float64` are epoch seconds with fraction, like
+    /// `1701325744.956789`, and floats casting to timestamps are read back as such, without
+    /// guessing.
+    ///
+    /// `f64` has about 16 significant digits, so current epochs are exact to about a
+    /// microsecond, nanoseconds are rounded to the nearest representable value.
+    pub timestamps_as_fractional_seconds: bool,
+    /// If true, strings casting to timestamps which fail to parse are normalized and parsed
+    /// again: the space before a trailing offset is removed, `±HHMM` and `±HH` offsets get a
+    /// colon, and a trailing ` UTC` or ` Z` becomes `Z`. Strings which parse are kept as is.
+    pub normalize_strings: bool,
+    /// If true with [normalize_strings](Self::normalize_strings), a date part like
+    /// `2023/11/30` is also rewritten to `2023-11-30` if that is a valid date.
+    pub normalize_date_slashes: bool,
+    /// If true, [EpochKind::NtpFixedPoint] seconds with the most significant bit clear are in
+    /// era 1, from 2036-02-07T06:28:16Z, so timestamps cover 1968 to 2104 as in RFC 4330.
+    pub ntp_era_rollover: bool,
+    /// Instant of the anchor durations are offsets from, as an epoch integer in the unit.
+    ///
+    /// If set, durations cast to timestamps as the anchor plus the duration, overflow handled
+    /// like rescaling. Durations are not cast to timestamps without an anchor.
+    pub anchor: Option<(i64, TimeUnit)>,
+    /// If true, integer, float and numeric string sources casting to timestamps are verified
+    /// by converting the output back to the source unit, see
+    /// [CastReport::roundtrip_mismatches](crate::CastReport::roundtrip_mismatches).
+    ///
+    /// Rows guessed with [GuessStrategy::PerValue] are verified only with
+    /// [record_row_units](Self::record_row_units). Rows nulled by the cast are not verified.
+    pub verify_roundtrip: bool,
+    /// Most mismatches kept in the report when verifying round trips, all are counted.
+    pub roundtrip_max_mismatches: usize,
+    /// Largest difference in the source unit of a float source and its round trip.
+    pub roundtrip_epsilon: f64,
+    /// If true, a round trip mismatch fails the cast.
+    pub roundtrip_error: bool,
+    /// Largest factor guessing may rescale values by, upward or downward.
+    ///
+    /// If the unit guessed for an array is further from the target unit, values are taken
+    /// in the target unit as is, or fail the cast with [strict](Self::strict), see
+    /// [CastReport::rescale_refused](crate::CastReport::rescale_refused).
+    /// Units guessed per value are not capped.
+    pub max_rescale_factor: Option<i64>,
+    /// How strings casting to timestamps without a timezone are taken, with or without
+    /// an explicit offset.
+    pub naive_string_policy: NaiveStringPolicy,
+    /// Units of integer codes in unit columns of [cast_with_unit_column](crate::cast_with_unit_column).
+    ///
+    /// If `None`, codes are those of [unit_code](crate::unit_code).
+    pub unit_column_codes: Option<HashMap<i64, TimeUnit>>,
+    /// If true, [GuessStrategy::Majority] stops scanning once the leading unit can not be
+    /// overtaken by the values left, deciding the same as a full scan.
+    ///
+    /// Ignored with [strict](Self::strict), which checks all values. Values near the boundary
+    /// are counted only among the values scanned.
+    pub early_stop: bool,
+    /// Most values multi-value strategies guess from, the first ones not null, or all values
+    /// if `None`. With [early_stop](Self::early_stop), scanning stops within the sample.
+    pub guess_sample_size: Option<usize>,
+    /// Special interpretations of integers casting to timestamps, in order of precedence.
+    ///
+    /// An interpretation matches an array if all of its values decode within
+    /// [guessing_bound_years](Self::guessing_bound_years) of the unix epoch, or matches each
+    /// value on its own with [GuessStrategy::PerValue]. The first matching interpretation is
+    /// applied, or else the unit is guessed as usual. [EpochKind::Unix] always matches.
+    ///
+    /// If not empty, [epoch_kind](Self::epoch_kind) is ignored.
+    pub interpretations: Vec<Interpretation>,
+    /// What to do if more than one of the [interpretations](Self::interpretations) matches.
+    pub on_multiple_matches: MultipleMatchPolicy,
+    /// Spelling of UTC in the timezone of output timestamp types, nested ones included.
+    ///
+    /// Applied to target types whose timezone is UTC by any spelling, like `UTC`, `Etc/UTC`,
+    /// `Z` or `+00:00`, without changing the values. Timestamps between UTC spellings are
+    /// cast by the type alone.
+    pub normalize_utc_tz: Option<UtcSpelling>,
+    /// Field metadata key of the unit of epoch columns, see
+    /// [resolve_epoch_extension](crate::resolve_epoch_extension).
+    pub epoch_unit_metadata_key: String,
+    /// How to repair source timestamp types whose timezone arrow rejects, like `GMT+8:00`,
+    /// nested ones included.
+    ///
+    /// Only the type is repaired, the values are kept as instants. If `None`, the array is
+    /// cast as is, and fails with the invalid timezone named if the cast fails.
+    pub repair_invalid_tz: Option<TzRepair>,
+    /// If true, ISO 8601 ordinal dates like `2023-334` and week dates like `2023-W48-4`
+    /// casting to timestamps are parsed as midnight.
+    pub parse_iso_ordinal_week: bool,
+    /// Whether dictionary arrays casting to non-dictionary timestamp types are flattened.
+    ///
+    /// Dictionaries are cast by their values either way, see [DictionaryHandling].
+    pub dictionary_output: DictionaryHandling,
+    /// Number of values skipped before the value guessing reads with
+    /// [GuessStrategy::FirstNonNull] and [GuessStrategy::PerValue], e.g. to pass over header
+    /// or boot records.
+    ///
+    /// Only non-null values guessing reads are counted, null rows, and sentinels with
+    /// [skip_sentinels](Self::skip_sentinels), are passed over without counting. If the
+    /// array has no more values than skipped, the first value is read as without skipping.
+    /// Chunks of [cast_chunks](crate::cast_chunks) and batches of
+    /// [BatchCaster](crate::BatchCaster) count values across the logical column.
+    pub guess_skip_rows: usize,
+    /// If true, first-value guessing reads from the last value backwards, skipping
+    /// [guess_skip_rows](Self::guess_skip_rows) from the end.
+    ///
+    /// [BatchCaster](crate::BatchCaster) has no end to read from, it reads backwards
+    /// within each batch.
+    pub guess_from_end: bool,
+    /// If true, integers downscaled casting to timestamps keep the remainders of the division
+    /// in [CastReport::remainders](crate::CastReport::remainders).
+    ///
+    /// Values follow [downscale_policy](Self::downscale_policy) and remainders are signed, so
+    /// `coarse * divisor + remainder` is the source value exactly: truncated values keep the
+    /// sign of the value, rounded ones the opposite sign when rounded away from zero.
+    /// [DownscalePolicy::Error] has no remainders, combining it fails the cast.
+    pub keep_downscale_remainder: bool,
+    /// If true, Unicode decimal digits like full-width `１７０１３２５７４４` in strings of
+    /// integers casting to timestamps are read as ASCII digits, with full-width signs.
+    ///
+    /// Only strings retried as integers are affected, a leading byte order mark is always
+    /// stripped from them.
+    pub normalize_unicode_digits: bool,
+    /// If true, strings of integers among strings of timestamps casting to timestamps are
+    /// epochs, with the unit guessed among the integers, instead of null.
+    ///
+    /// Without it, strings are retried as integers only if none parses as a timestamp.
+    pub mixed_string_epochs: bool,
+    /// Fraction of values, e.g. `0.01`, of both the smallest and the largest magnitudes,
+    /// ignored by [GuessStrategy::Majority] and [GuessStrategy::MaxAbs], for arrays with a
+    /// repeated corrupted value.
+    ///
+    /// Ignored values are counted in
+    /// [CastReport::trimmed_outliers](crate::CastReport::trimmed_outliers), and take no part in
+    /// [strict](Self::strict) checks. Fractions outside `[0, 0.5)` fail the cast.
+    pub trim_outlier_fraction: Option<f64>,
+    /// The route strings casting to timestamps are parsed by.
+    ///
+    /// Both routes cast the same values, the route only decides how many passes over the
+    /// strings are taken. [cast_record_batch](crate::cast_record_batch) classifies string
+    /// columns up front when [StringParseRoute::Sampled].
+    pub string_parse_route: StringParseRoute,
+}
+
+/// How to guess the unit from the values of an array.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum GuessStrategy {
+    /// Guess from the first non-null value.
+    #[default]
+    FirstNonNull,
+    /// The unit guessed by most values, ties go to the finer unit.
+    Majority,
+    /// Guess from the value of the largest magnitude.
+    MaxAbs,
+    /// Guess each value on its own, values in an array could be in different units.
+    PerValue,
+}
+
+impl GuessStrategy {
+    /// Name of the strategy, as written in field metadata.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            GuessStrategy::FirstNonNull => "first_non_null",
+            GuessStrategy::Majority => "majority",
+            GuessStrategy::MaxAbs => "max_abs",
+            GuessStrategy::PerValue => "per_value",
+        }
+    }
+}
+
+/// Curated presets of [TimestampCastOptions].
+///
+/// What a preset expands to is part of the public API, changing it is a breaking change.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum GuessProfile {
+    /// Narrow bounds and fail on anything ambiguous:
+    ///
+    /// - `guessing_bound_years = 100`
+    /// - `guess_strategy = GuessStrategy::Majority`
+    /// - `strict = true`
+    /// - `overflow_behavior = Some(OverflowBehavior::Error)`
+    ///
+    /// Others are the defaults.
+    Conservative,
+    /// The defaults, see [TimestampCastOptions::default].
+    #[default]
+    Default,
+    /// Wide bounds and make the best of messy data:
+    ///
+    /// - `guessing_bound_years = 10000`
+    /// - `guess_strategy = GuessStrategy::PerValue`
+    /// - `skip_sentinels = true`
+    ///
+    /// Others are the defaults.
+    Lenient,
+}
+
+/// Behavior when a timestamp overflows in the target unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum OverflowBehavior {
+    /// Overflowing values become null.
+    Null,
+    /// Overflowing values fail the cast.
+    Error,
+    /// Overflowing values are clamped to the minimum or maximum timestamp.
+    Saturate,
+}
+
+/// Policy for NaN and infinite values of float sources casting to timestamps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum NonFinitePolicy {
+    /// Non-finite values become null.
+    Null,
+    /// Non-finite values fail the cast.
+    Error,
+    /// Infinities are clamped to the minimum or maximum timestamp, NaN follows `safe`.
+    Saturate,
+}
+
+/// Policy for strings with a seconds field of `60` casting to timestamps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum LeapSecondPolicy {
+    /// The last instant of the previous second in the target unit, e.g. `23:59:59.999` in milliseconds.
+    ClampTo59,
+    /// The start of the next second, e.g. `00:00:00` of the next day.
+    FoldToNextSecond,
+    /// Leap seconds become null.
+    Null,
+    /// Leap seconds fail the cast.
+    Error,
+}
+
+/// Policy for strings casting to timestamps without a timezone.
+///
+/// Strings with an explicit offset are instants, stored in UTC by all policies accepting them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum NaiveStringPolicy {
+    /// Strings without an offset are in UTC.
+    #[default]
+    AssumeUtc,
+    /// Strings without an offset are the wall clock of the timezone, stored in UTC.
+    AssumeZone(Arc<str>),
+    /// Strings with an explicit offset are rejected, null when `safe` or error otherwise.
+    /// Strings without an offset are in UTC.
+    ErrorOnOffset,
+}
+
+/// Which parsing route strings casting to timestamps take first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum StringParseRoute {
+    /// Decided per array by sampling the first strings, see
+    /// [cast_record_batch](crate::cast_record_batch) for batches.
+    #[default]
+    Sampled,
+    /// Parse as integers, then only the strings which are not as timestamps.
+    Integers,
+    /// Parse as timestamps, then as integers if none is a timestamp or with
+    /// [mixed_string_epochs](TimestampCastOptions::mixed_string_epochs).
+    Timestamps,
+}
+
+/// Epoch and tick of integer timestamps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum EpochKind {
+    /// Seconds or finer units since 1970-01-01T00:00:00Z, the unit guessed or given.
+    #[default]
+    Unix,
+    /// Windows `FILETIME`, 100-nanosecond intervals since 1601-01-01T00:00:00Z.
+    WindowsFiletime,
+    /// NTP 64-bit timestamps: seconds since 1900-01-01T00:00:00Z in the high 32 bits and a
+    /// binary fraction of a second in the low 32 bits. `Int64` values are taken as the bits
+    /// of the `u64`, so big-endian binary values decode with [BinaryEpochKind::RawI64].
+    NtpFixedPoint,
+}
+
+/// Spelling of UTC in the timezone of output timestamp types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum UtcSpelling {
+    /// `UTC`.
+    Utc,
+    /// `+00:00`.
+    PlusZeroZero,
+    /// The spelling of the target type.
+    AsRequested,
+}
+
+impl UtcSpelling {
+    /// The timezone string of the spelling, `None` for [UtcSpelling::AsRequested].
+    pub const fn as_tz(self) -> Option<&'static str> {
+        match self {
+            UtcSpelling::Utc => Some("UTC"),
+            UtcSpelling::PlusZeroZero => Some("+00:00"),
+            UtcSpelling::AsRequested => None,
+        }
+    }
+}
+
+/// Output of dictionary arrays casting to timestamps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum DictionaryHandling {
+    /// Keep the keys, the output is a dictionary of timestamps instead of the target type.
+    Preserve,
+    /// Expand the casted values by the keys to the target type.
+    #[default]
+    Flatten,
+}
+
+/// Repair of source timestamp types whose timezone arrow rejects.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum TzRepair {
+    /// Take the timestamps as timezone-less.
+    Strip,
+    /// Take the timestamps in the timezone.
+    Replace(Arc<str>),
+    /// Fail the cast before casting, naming the invalid timezone.
+    #[default]
+    Error,
+}
+
+/// Special interpretation of integers casting to timestamps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Interpretation {
+    /// Digits of `YYYYMMDDhhmmss` or `YYYYMMDD` in UTC, like `20231130062904`.
+    PackedDateTime,
+    /// Ticks since the epoch of the kind.
+    Epoch(EpochKind),
+}
+
+/// What to do if more than one interpretation matches integers casting to timestamps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum MultipleMatchPolicy {
+    /// The first matching interpretation is applied.
+    #[default]
+    FirstWins,
+    /// The cast fails.
+    Error,
+    /// The first matching interpretation is applied, and the others are reported in
+    /// [CastReport::other_interpretations](crate::CastReport::other_interpretations).
+    Report,
+}
+
+/// Order of the day and the month in strings with month names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum DateOrder {
+    /// `Nov 30, 2023` and `Nov 30 2023`.
+    MonthFirst,
+    /// `30-Nov-2023`, `30 Nov 2023` and `30 Nov, 2023`.
+    DayFirst,
+}
+
+/// Policy when downscaling to a coarser unit loses precision.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum DownscalePolicy {
+    /// Truncate toward zero, the same as arrow.
+    #[default]
+    Truncate,
+    /// Round half away from zero.
+    Round,
+    /// Fail the cast if any precision would be lost, regardless of `safe`.
+    Error,
+}
+
+/// How to interpret binary values casting to timestamps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum BinaryEpochKind {
+    /// Binary values are parsed as strings.
+    #[default]
+    None,
+    /// 8-byte values are raw `i64` epoch integers in the byte order, with the unit guessed.
+    RawI64(Endianness),
+    /// 12-byte `FixedSizeBinary(12)` values are Parquet INT96 timestamps: little-endian
+    /// nanoseconds within the day followed by the little-endian Julian day number.
+    Int96,
+}
+
+/// Byte order of binary integers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+impl Default for TimestampCastOptions {
+    fn default() -> Self {
+        Self {
+            guess_timestamp_precision: true,
+            use_timezone_as_is: true,
+            guessing_bound_years: GUESSING_BOUND_YEARS,
+            overflow_behavior: None,
+            clamp_range: None,
+            tz_abbreviations: None,
+            tz_abbreviations_ignore_case: false,
+            elapsed_bound_days: 365,
+            elapsed_unit: None,
+            downscale_policy: DownscalePolicy::Truncate,
+            parse_compact_time: false,
+            assume_unit: None,
+            strict: false,
+            binary_epoch_kind: BinaryEpochKind::None,
+            guess_strategy: GuessStrategy::FirstNonNull,
+            skip_sentinels: false,
+            non_finite_policy: None,
+            iso8601_durations: false,
+            leap_second_policy: None,
+            record_row_units: false,
+            fix_y2038_wrap: false,
+            output_as_wall_clock_in: None,
+            null_offset_as_utc: false,
+            boundary_margin: None,
+            parse_compact_date: false,
+            named_month_order: None,
+            epoch_kind: EpochKind::Unix,
+            timestamps_as_fractional_seconds: false,
+            normalize_strings: false,
+            normalize_date_slashes: false,
+            ntp_era_rollover: false,
+            anchor: None,
+            verify_roundtrip: false,
+            roundtrip_max_mismatches: 100,
+            roundtrip_epsilon: 0.0,
+            roundtrip_error: false,
+            max_rescale_factor: None,
+            naive_string_policy: NaiveStringPolicy::AssumeUtc,
+            unit_column_codes: None,
+            early_stop: false,
+            guess_sample_size: None,
+            interpretations: Vec::new(),
+            on_multiple_matches: MultipleMatchPolicy::FirstWins,
+            normalize_utc_tz: None,
+            epoch_unit_metadata_key: EPOCH_UNIT_METADATA_KEY.to_string(),
+            repair_invalid_tz: None,
+            parse_iso_ordinal_week: false,
+            dictionary_output: DictionaryHandling::Flatten,
+            guess_skip_rows: 0,
+            guess_from_end: false,
+            keep_downscale_remainder: false,
+            normalize_unicode_digits: false,
+            mixed_string_epochs: false,
+            trim_outlier_fraction: None,
+            string_parse_route: StringParseRoute::Sampled,
+        }
+    }
+}
+
+impl TimestampCastOptions {
+    /// Options of the preset profile, tweak the public fields afterwards if needed.
+    pub fn from_profile(profile: GuessProfile) -> Self {
+        let default = Self::default();
+        match profile {
+            GuessProfile::Conservative => Self {
+                guessing_bound_years: 100,
+                guess_strategy: GuessStrategy::Majority,
+                strict: true,
+                overflow_behavior: Some(OverflowBehavior::Error),
+                ..default
+            },
+            GuessProfile::Default => default,
+            GuessProfile::Lenient => Self {
+                guessing_bound_years: 10000,
+                guess_strategy: GuessStrategy::PerValue,
+                skip_sentinels: true,
+                ..default
+            },
+        }
+    }
+}
+
+impl From<GuessProfile> for TimestampCastOptions {
+    fn from(profile: GuessProfile) -> Self {
+        Self::from_profile(profile)
+    }
+}
+
+static GLOBAL_DEFAULTS: RwLock<Option<Arc<TimestampCastOptions>>> = RwLock::new(None);
+
+/// Set the process-wide timestamp options [CastOptions::with_global_defaults] starts from,
+/// `None` for the defaults of the crate.
+///
+/// Options already built keep the defaults they started from.
+pub fn set_global_defaults(options: Option<TimestampCastOptions>) {
+    *GLOBAL_DEFAULTS.write().unwrap_or_else(|e| e.into_inner()) = options.map(Arc::new);
+}
+
+#[derive(Debug, Clone)]
+pub struct CastOptions<'a> {
+    pub safe: bool,
+    pub timestamp_options: TimestampCastOptions,
+    /// Formatting options when casting from temporal types to string.
+    pub format_options: arrow_cast::display::FormatOptions<'a>,
+    /// Timezone timestamps are displayed in when casting them to string.
+    ///
+    /// See [FormatSettings::with_timezone].
+    pub format_timezone: Option<&'a str>,
+    /// If true, casting delegates to arrow as is, overriding all timestamp options.
+    ///
+    /// For validating against arrow, the `passthrough` feature turns it on by default.
+    pub passthrough: bool,
+    /// If true, target struct fields missing from the source struct are filled with nulls.
+    ///
+    /// Struct fields are then matched by name instead of position.
+    pub allow_missing_struct_fields: bool,
+    /// If true, source struct fields missing from the target struct are dropped.
+    ///
+    /// Struct fields are then matched by name instead of position.
+    pub allow_extra_struct_fields: bool,
+    /// The profile the timestamp options started from, set by [with_profile](Self::with_profile),
+    /// for the provenance of values in [resolve](Self::resolve).
+    pub profile: Option<GuessProfile>,
+    /// The global defaults the timestamp options started from, set by
+    /// [with_global_defaults](Self::with_global_defaults), for the provenance of values in
+    /// [resolve](Self::resolve).
+    pub global_defaults: Option<Arc<TimestampCastOptions>>,
+}
+
+impl Default for CastOptions<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> CastOptions<'a> {
+    pub fn new() -> Self {
+        Self {
+            safe: true,
+            timestamp_options: TimestampCastOptions::default(),
+            format_options: arrow_cast::display::FormatOptions::default(),
+            format_timezone: None,
+            passthrough: cfg!(feature = "passthrough"),
+            allow_missing_struct_fields: false,
+            allow_extra_struct_fields: false,
+            profile: None,
+            global_defaults: None,
+        }
+    }
+
+    /// Delegate casting to arrow as is, see [passthrough](Self::passthrough).
+    pub fn with_passthrough(mut self, passthrough: bool) -> Self {
+        self.passthrough = passthrough;
+        self
+    }
+
+    /// Fill target struct fields missing from the source with nulls.
+    pub fn with_allow_missing_struct_fields(mut self, allow: bool) -> Self {
+        self.allow_missing_struct_fields = allow;
+        self
+    }
+
+    /// Drop source struct fields missing from the target.
+    pub fn with_allow_extra_struct_fields(mut self, allow: bool) -> Self {
+        self.allow_extra_struct_fields = allow;
+        self
+    }
+
+    /// Set the format string for nulls.
+    pub fn with_null(mut self, null: &'a str) -> Self {
+        self.format_options = self.format_options.with_null(null);
+        self
+    }
+
+    /// Set the date format when casting dates to string.
+    pub fn with_date_format(mut self, format: &'a str) -> Self {
+        self.format_options = self.format_options.with_date_format(Some(format));
+        self
+    }
+
+    /// Set the format when casting Date64 to string.
+    pub fn with_datetime_format(mut self, format: &'a str) -> Self {
+        self.format_options = self.format_options.with_datetime_format(Some(format));
+        self
+    }
+
+    /// Set the timestamp format when casting timestamps without timezone to string.
+    pub fn with_timestamp_format(mut self, format: &'a str) -> Self {
+        self.format_options = self.format_options.with_timestamp_format(Some(format));
+        self
+    }
+
+    /// Set the timestamp format when casting timestamps with timezone to string.
+    pub fn with_timestamp_tz_format(mut self, format: &'a str) -> Self {
+        self.format_options = self.format_options.with_timestamp_tz_format(Some(format));
+        self
+    }
+
+    /// Set the time format when casting times to string.
+    pub fn with_time_format(mut self, format: &'a str) -> Self {
+        self.format_options = self.format_options.with_time_format(Some(format));
+        self
+    }
+
+    /// Display timestamps in the timezone when casting them to string.
+    ///
+    /// See [FormatSettings::with_timezone].
+    pub fn with_timezone(mut self, timezone: &'a str) -> Self {
+        self.format_timezone = Some(timezone);
+        self
+    }
+
+    /// Set the format options and the timezone from the format settings.
+    pub fn with_format_settings(mut self, settings: FormatSettings<'a>) -> Self {
+        self.format_options = settings.to_format_options();
+        self.format_timezone = settings.timezone();
+        self
+    }
+
+    /// The effective non-finite policy, following `safe` if not set.
+    pub(crate) fn non_finite_policy(&self) -> NonFinitePolicy {
+        match self.timestamp_options.non_finite_policy {
+            Some(policy) => policy,
+            None if self.safe => NonFinitePolicy::Null,
+            None => NonFinitePolicy::Error,
+        }
+    }
+
+    /// The effective leap second policy, following `safe` if not set.
+    pub(crate) fn leap_second_policy(&self) -> LeapSecondPolicy {
+        match self.timestamp_options.leap_second_policy {
+            Some(policy) => policy,
+            None if self.safe => LeapSecondPolicy::Null,
+            None => LeapSecondPolicy::Error,
+        }
+    }
+
+    /// Start timestamp options from the preset profile.
+    pub fn with_profile(mut self, profile: GuessProfile) -> Self {
+        self.timestamp_options = TimestampCastOptions::from_profile(profile);
+        self.profile = Some(profile);
+        self.global_defaults = None;
+        self
+    }
+
+    /// Start timestamp options from the global defaults, see [set_global_defaults].
+    pub fn with_global_defaults(mut self) -> Self {
+        let defaults = GLOBAL_DEFAULTS
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+            .unwrap_or_default();
+        self.timestamp_options = defaults.as_ref().clone();
+        self.global_defaults = Some(defaults);
+        self.profile = None;
+        self
+    }
+
+    /// Set the timestamp options.
+    pub fn with_timestamp_options(mut self, options: TimestampCastOptions) -> Self {
+        self.timestamp_options = options;
+        self
+    }
+
+    /// The effective overflow behavior, following `safe` if not set.
+    pub(crate) fn overflow_behavior(&self) -> OverflowBehavior {
+        match self.timestamp_options.overflow_behavior {
+            Some(behavior) => behavior,
+            None if self.safe => OverflowBehavior::Null,
+            None => OverflowBehavior::Error,
+        }
+    }
+
+    /// Use raw arrow format options, for settings not covered by [FormatSettings].
+    pub fn with_format_options(mut self, options: arrow_cast::display::FormatOptions<'a>) -> Self {
+        self.format_options = options;
+        self
+    }
+}
+
+impl<'a> CastOptions<'a> {
+    /// Build from arrow cast options with the given timestamp options.
+    pub fn from_arrow(
+        options: arrow_cast::CastOptions<'a>,
+        timestamp_options: TimestampCastOptions,
+    ) -> Self {
+        Self {
+            safe: options.safe,
+            timestamp_options,
+            format_options: options.format_options,
+            format_timezone: None,
+            passthrough: cfg!(feature = "passthrough"),
+            allow_missing_struct_fields: false,
+            allow_extra_struct_fields: false,
+            profile: None,
+            global_defaults: None,
+        }
+    }
+}
+
+impl<'a> From<arrow_cast::CastOptions<'a>> for CastOptions<'a> {
+    fn from(options: arrow_cast::CastOptions<'a>) -> Self {
+        Self::from_arrow(options, TimestampCastOptions::default())
+    }
+}
+
+impl<'a> From<&arrow_cast::CastOptions<'a>> for CastOptions<'a> {
+    fn from(options: &arrow_cast::CastOptions<'a>) -> Self {
+        Self::from_arrow(options.clone(), TimestampCastOptions::default())
+    }
+}
+
+impl<'r, 'a> From<&'r CastOptions<'a>> for arrow_cast::CastOptions<'r> {
+    fn from(options: &'r CastOptions) -> arrow_cast::CastOptions<'r> {
+        crate::shim::cast_options(options.safe, options.format_options.clone())
+    }
+}
@@ -0,0 +1,260 @@
+//! Comparing casts with those of arrow, for integration tests of downstream crates.
+//!
+//! Casts of this crate differ from arrow's only in intentional ways, each classified by
+//! [ParityResult]. Checking a corpus of arrays against it validates upgrades of this crate
+//! and of arrow underneath it.
+//!
+//! ```rust
+//! use arrow::{array::Int64Array, datatypes::{DataType, TimeUnit}};
+//! use arrow_cast_guess_precision::{parity::{compare_with_arrow, ParityResult}, CastOptions};
+//!
+//! let array = Int64Array::from(vec![1701325744]);
+//! let to_type = DataType::Timestamp(TimeUnit::Millisecond, None);
+//! let options = CastOptions::new().with_passthrough(false);
+//! let result = compare_with_arrow(&array, &to_type, &options);
+//! assert_eq!(result, ParityResult::GuessRescaled { factor: 1000.0 });
+//! ```
+
+use arrow_array::{cast::AsArray, types::Int64Type, Array, ArrayRef};
+use arrow_cast::display::{ArrayFormatter, FormatOptions};
+use arrow_schema::{ArrowError, DataType, TimeUnit};
+
+use crate::{cast_with_report, kernel::time_unit_multiple, CastOptions, CastReport};
+
+/// Most rows listed by a [Divergence].
+pub const MAX_ROW_DIFFS: usize = 16;
+
+/// How a cast of this crate compares with the cast of arrow.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParityResult {
+    /// Both outputs are equal, or both casts fail.
+    Identical,
+    /// Rows differ only by rescaling from the guessed or assumed unit, the output being arrow's
+    /// multiplied by `factor`, with values overflowing the unit null.
+    GuessRescaled { factor: f64 },
+    /// Rows differ only by `count` strings null in arrow's output and parsed by the
+    /// fallbacks of this crate.
+    ExtraRowsParsed { count: usize },
+    /// Differences not explained by the others.
+    Divergent { details: Divergence },
+}
+
+/// Unexplained differences of a cast from arrow's.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    pub reason: String,
+    /// Number of rows differing.
+    pub differing_rows: usize,
+    /// The first differing rows, at most [MAX_ROW_DIFFS].
+    pub rows: Vec<RowDiff>,
+}
+
+/// A row differing from arrow's output, with formatted values, `None` for nulls.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowDiff {
+    pub row: usize,
+    pub ours: Option<String>,
+    pub arrow: Option<String>,
+}
+
+/// Cast by the options and by arrow, and classify how the outputs differ.
+///
+/// Arrow casts with `safe` of the options. Failures to format the outputs are divergent.
+pub fn compare_with_arrow(
+    array: &dyn Array,
+    to_type: &DataType,
+    cast_options: &CastOptions,
+) -> ParityResult {
+    let ours = cast_with_report(array, to_type, cast_options);
+    let arrow = arrow_cast::cast_with_options(array, to_type, &cast_options.into());
+    let (ours, report, arrow) = match (ours, arrow) {
+        (Err(_), Err(_)) => return ParityResult::Identical,
+        (Ok(_), Err(e)) => return divergent(format!("Only arrow failed: {e}"), 0, vec![]),
+        (Err(e), Ok(_)) => return divergent(format!("Only this crate failed: {e}"), 0, vec![]),
+        (Ok((ours, report)), Ok(arrow)) => (ours, report, arrow),
+    };
+    if ours.data_type() != arrow.data_type() {
+        return divergent(
+            format!(
+                "Output types differ: {:?} and {:?}",
+                ours.data_type(),
+                arrow.data_type()
+            ),
+            0,
+            vec![],
+        );
+    }
+    compare_rows(array, to_type, &ours, &arrow, &report, cast_options)
+        .unwrap_or_else(|e| divergent(format!("Cannot compare outputs: {e}"), 0, vec![]))
+}
+
+fn compare_rows(
+    array: &dyn Array,
+    to_type: &DataType,
+    ours: &ArrayRef,
+    arrow: &ArrayRef,
+    report: &CastReport,
+    cast_options: &CastOptions,
+) -> Result<ParityResult, ArrowError> {
+    let options = FormatOptions::default();
+    let (ours_formatter, arrow_formatter) = (
+        ArrayFormatter::try_new(ours.as_ref(), &options)?,
+        ArrayFormatter::try_new(arrow.as_ref(), &options)?,
+    );
+    let format = |formatter: &ArrayFormatter, array: &ArrayRef, row| {
+        array
+            .is_valid(row)
+            .then(|| formatter.value(row).to_string())
+    };
+    let diffs: Vec<RowDiff> = (0..ours.len())
+        .map(|row| RowDiff {
+            row,
+            ours: format(&ours_formatter, ours, row),
+            arrow: format(&arrow_formatter, arrow, row),
+        })
+        .filter(|diff| diff.ours != diff.arrow)
+        .collect();
+    if diffs.is_empty() {
+        return Ok(ParityResult::Identical);
+    }
+    let extra = |diff: &RowDiff| diff.arrow.is_none() && diff.ours.is_some();
+    if is_string(array.data_type()) && diffs.iter().all(extra) {
+        return Ok(ParityResult::ExtraRowsParsed { count: diffs.len() });
+    }
+    let from = report
+        .guessed_unit
+        .or(cast_options.timestamp_options.assume_unit);
+    if let (Some(from), DataType::Timestamp(unit, _)) = (from, to_type) {
+        if from != *unit && rescaled(ours, arrow, &diffs, from, *unit)? {
+            let factor = time_unit_multiple(*unit) as f64 / time_unit_multiple(from) as f64;
+            return Ok(ParityResult::GuessRescaled { factor });
+        }
+    }
+    let differing_rows = diffs.len();
+    let rows = diffs.into_iter().take(MAX_ROW_DIFFS).collect();
+    Ok(divergent(
+        "Rows differ beyond guessing".to_string(),
+        differing_rows,
+        rows,
+    ))
+}
+
+/// Whether the differing rows are arrow's rescaled from `from` to `to`, or null overflowing.
+fn rescaled(
+    ours: &ArrayRef,
+    arrow: &ArrayRef,
+    diffs: &[RowDiff],
+    from: TimeUnit,
+    to: TimeUnit,
+) -> Result<bool, ArrowError> {
+    let ours = arrow_cast::cast(ours, &DataType::Int64)?;
+    let arrow = arrow_cast::cast(arrow, &DataType::Int64)?;
+    let (ours, arrow) = (
+        ours.as_primitive::<Int64Type>(),
+        arrow.as_primitive::<Int64Type>(),
+    );
+    let (from, to) = (time_unit_multiple(from), time_unit_multiple(to));
+    Ok(diffs.iter().all(|diff| {
+        let row = diff.row;
+        if arrow.is_null(row) {
+            return false;
+        }
+        let value = arrow.value(row);
+        let expected = match to >= from {
+            true => value.checked_mul(to / from),
+            // Truncated, rounded or floored.
+            false => Some(value / (from / to)),
+        };
+        match (ours.is_valid(row).then(|| ours.value(row)), expected) {
+            (None, None) => true,
+            (Some(v), Some(expected)) => v.abs_diff(expected) <= 1,
+            _ => false,
+        }
+    }))
+}
+
+fn is_string(data_type: &DataType) -> bool {
+    match data_type {
+        DataType::Utf8 | DataType::LargeUtf8 | DataType::Binary | DataType::LargeBinary => true,
+        DataType::Dictionary(_, value_type) => is_string(value_type),
+        _ => false,
+    }
+}
+
+fn divergent(reason: String, differing_rows: usize, rows: Vec<RowDiff>) -> ParityResult {
+    ParityResult::Divergent {
+        details: Divergence {
+            reason,
+            differing_rows,
+            rows,
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::{Int32Array, Int64Array, StringArray};
+
+    use super::*;
+    use crate::OverflowBehavior;
+
+    #[test]
+    fn test_compare_with_arrow() {
+        let millis = DataType::Timestamp(TimeUnit::Millisecond, None);
+        let nanos = DataType::Timestamp(TimeUnit::Nanosecond, Some("+08:00".into()));
+        let options = CastOptions::new().with_passthrough(false);
+        let compare = |array: &dyn Array, to_type| compare_with_arrow(array, to_type, &options);
+
+        let array = Int64Array::from(vec![Some(1701325744956), None]);
+        assert_eq!(compare(&array, &millis), ParityResult::Identical);
+        assert_eq!(
+            compare(&array, &nanos),
+            ParityResult::GuessRescaled { factor: 1000000.0 }
+        );
+        // Downscaling from nanoseconds, and seconds overflowing nanoseconds.
+        let array = Int64Array::from(vec![1701325744956789012, -1701325744956789012]);
+        assert_eq!(
+            compare(&array, &millis),
+            ParityResult::GuessRescaled { factor: 0.000001 }
+        );
+        let array = Int32Array::from(vec![1701325744]);
+        assert_eq!(
+            compare(&array, &nanos),
+            ParityResult::GuessRescaled { factor: 1e9 }
+        );
+        assert_eq!(compare(&array, &DataType::Int64), ParityResult::Identical);
+
+        // Strings of epochs and of dates arrow can not parse.
+        let array = StringArray::from(vec![Some("1701325744956"), None]);
+        assert_eq!(
+            compare(&array, &millis),
+            ParityResult::ExtraRowsParsed { count: 1 }
+        );
+        let array = StringArray::from(vec!["2023-11-30T06:29:04.956Z", "2023-11-30 06:29", "bad"]);
+        assert_eq!(
+            compare(&array, &millis),
+            ParityResult::ExtraRowsParsed { count: 1 }
+        );
+        // Both failing.
+        let mut strict = CastOptions::new().with_passthrough(false);
+        strict.safe = false;
+        assert_eq!(
+            compare_with_arrow(&array, &millis, &strict),
+            ParityResult::Identical
+        );
+
+        // Saturating is not explained, the rows are listed up to the cap.
+        let array = Int64Array::from(vec![i64::MAX / 10; 40]);
+        let mut saturating = CastOptions::new().with_passthrough(false);
+        saturating.timestamp_options.assume_unit = Some(TimeUnit::Second);
+        saturating.timestamp_options.overflow_behavior = Some(OverflowBehavior::Saturate);
+        let ParityResult::Divergent { details } = compare_with_arrow(&array, &millis, &saturating)
+        else {
+            panic!("saturating is divergent");
+        };
+        assert_eq!(details.differing_rows, 40);
+        assert_eq!(details.rows.len(), MAX_ROW_DIFFS);
+        assert_eq!(details.rows[0].row, 0);
+        assert!(details.rows[0].ours.is_some());
+    }
+}
@@ -0,0 +1,190 @@
+//! Splitting cast timestamps into days and times of day, without chrono.
+
+use arrow_array::{
+    builder::BooleanBufferBuilder,
+    cast::AsArray,
+    types::{
+        TimestampMicrosecondType, TimestampMillisecondType, TimestampNanosecondType,
+        TimestampSecondType,
+    },
+    Array, ArrowPrimitiveType, Date32Array, PrimitiveArray, Time64NanosecondArray,
+};
+use arrow_schema::{ArrowError, DataType, TimeUnit};
+
+use crate::kernel::time_unit_multiple;
+
+/// Split a timestamp in the unit into days since the epoch and nanoseconds of the day,
+/// flooring toward the past, `None` if the days overflow `i32`.
+#[inline]
+fn split(v: i64, unit: TimeUnit) -> Option<(i32, u64)> {
+    let multiple = time_unit_multiple(unit);
+    let per_day = 86400 * multiple;
+    let days = i32::try_from(v.div_euclid(per_day)).ok()?;
+    let nanos = v.rem_euclid(per_day) as u64 * (1_000_000_000 / multiple) as u64;
+    Some((days, nanos))
+}
+
+/// The timestamp at the row as days since the epoch and nanoseconds of the day, by integer
+/// math on the stored value.
+///
+/// Instants before the epoch floor to the day they fall in, e.g. one nanosecond before
+/// the epoch is the day `-1` at `86_399_999_999_999` nanoseconds. Nanoseconds of a day
+/// exceed `u32`, so they are `u64`. The timezone of the type is ignored, the parts are of
+/// UTC. `None` for null rows, arrays not of timestamps, and days overflowing `i32`.
+///
+/// ```rust
+/// use arrow::array::TimestampMillisecondArray;
+/// use arrow_cast_guess_precision::timestamp_value_as_parts;
+///
+/// let array = TimestampMillisecondArray::from(vec![86400000, -1]);
+/// assert_eq!(timestamp_value_as_parts(&array, 0), Some((1, 0)));
+/// assert_eq!(timestamp_value_as_parts(&array, 1), Some((-1, 86_399_999_000_000)));
+/// ```
+pub fn timestamp_value_as_parts(array: &dyn Array, idx: usize) -> Option<(i32, u64)> {
+    let DataType::Timestamp(unit, _) = array.data_type() else {
+        return None;
+    };
+    if array.is_null(idx) {
+        return None;
+    }
+    let v = match unit {
+        TimeUnit::Second => array.as_primitive::<TimestampSecondType>().value(idx),
+        TimeUnit::Millisecond => array.as_primitive::<TimestampMillisecondType>().value(idx),
+        TimeUnit::Microsecond => array.as_primitive::<TimestampMicrosecondType>().value(idx),
+        TimeUnit::Nanosecond => array.as_primitive::<TimestampNanosecondType>().value(idx),
+    };
+    split(v, *unit)
+}
+
+/// The timestamps as days since the epoch and nanoseconds of the day, in one pass, as
+/// [timestamp_value_as_parts] for each row.
+///
+/// Rows null in the source share its null buffer, rows whose days overflow `i32` are also
+/// null in both outputs. Arrays not of timestamps fail.
+pub fn timestamp_parts(
+    array: &dyn Array,
+) -> Result<(Date32Array, Time64NanosecondArray), ArrowError> {
+    fn split_all<T: ArrowPrimitiveType<Native = i64>>(
+        array: &PrimitiveArray<T>,
+        unit: TimeUnit,
+    ) -> (Date32Array, Time64NanosecondArray) {
+        let mut validity: Option<BooleanBufferBuilder> = None;
+        let mut days = Vec::with_capacity(array.len());
+        let mut nanos = Vec::with_capacity(array.len());
+        for (row, v) in array.values().iter().enumerate() {
+            let parts = match array.is_valid(row) {
+                true => split(*v, unit),
+                false => None,
+            };
+            if parts.is_none() && array.is_valid(row) && validity.is_none() {
+                let mut builder = BooleanBufferBuilder::new(array.len());
+                (0..row).for_each(|row| builder.append(array.is_valid(row)));
+                validity = Some(builder);
+            }
+            if let Some(validity) = &mut validity {
+                validity.append(parts.is_some());
+            }
+            let (day, nano) = parts.unwrap_or_default();
+            days.push(day);
+            nanos.push(nano as i64);
+        }
+        let nulls = match validity {
+            Some(mut validity) => Some(validity.finish().into()),
+            None => array.nulls().cloned(),
+        };
+        (
+            Date32Array::new(days.into(), nulls.clone()),
+            Time64NanosecondArray::new(nanos.into(), nulls),
+        )
+    }
+
+    Ok(match array.data_type() {
+        DataType::Timestamp(unit @ TimeUnit::Second, _) => {
+            split_all(array.as_primitive::<TimestampSecondType>(), *unit)
+        }
+        DataType::Timestamp(unit @ TimeUnit::Millisecond, _) => {
+            split_all(array.as_primitive::<TimestampMillisecondType>(), *unit)
+        }
+        DataType::Timestamp(unit @ TimeUnit::Microsecond, _) => {
+            split_all(array.as_primitive::<TimestampMicrosecondType>(), *unit)
+        }
+        DataType::Timestamp(unit @ TimeUnit::Nanosecond, _) => {
+            split_all(array.as_primitive::<TimestampNanosecondType>(), *unit)
+        }
+        data_type => {
+            return Err(ArrowError::CastError(format!(
+                "Cannot split {data_type:?} into days and times, expected timestamps"
+            )))
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::{
+        Int64Array, TimestampMicrosecondArray, TimestampNanosecondArray, TimestampSecondArray,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_timestamp_parts() {
+        const DAY: i64 = 86_400_000_000_000;
+        // Midnights and one nanosecond before them, around and away from the epoch.
+        let cases = [
+            (0, (0, 0)),
+            (-1, (-1, DAY as u64 - 1)),
+            (DAY, (1, 0)),
+            (DAY - 1, (0, DAY as u64 - 1)),
+            (-DAY, (-1, 0)),
+            (-DAY - 1, (-2, DAY as u64 - 1)),
+            (-DAY + 1, (-1, 1)),
+            (19691 * DAY, (19691, 0)),
+            (19691 * DAY - 1, (19690, DAY as u64 - 1)),
+            (-19691 * DAY, (-19691, 0)),
+            (-19691 * DAY - 1, (-19692, DAY as u64 - 1)),
+            (i64::MIN, (-106752, 763_145_224_192)),
+        ];
+        let array =
+            TimestampNanosecondArray::from(cases.map(|(v, _)| v).to_vec()).with_timezone("+08:00");
+        for (row, (_, parts)) in cases.iter().enumerate() {
+            assert_eq!(
+                timestamp_value_as_parts(&array, row),
+                Some(*parts),
+                "row {row}"
+            );
+        }
+        let (days, nanos) = timestamp_parts(&array).unwrap();
+        for (row, (_, (day, nano))) in cases.iter().enumerate() {
+            assert_eq!((days.value(row), nanos.value(row)), (*day, *nano as i64));
+        }
+
+        // Coarser units, nulls, and days overflowing.
+        let array = TimestampSecondArray::from(vec![Some(-1), None, Some(-86400), Some(i64::MAX)]);
+        assert_eq!(
+            timestamp_value_as_parts(&array, 0),
+            Some((-1, 86_399_000_000_000))
+        );
+        assert_eq!(timestamp_value_as_parts(&array, 1), None);
+        assert_eq!(timestamp_value_as_parts(&array, 2), Some((-1, 0)));
+        assert_eq!(timestamp_value_as_parts(&array, 3), None);
+        let (days, nanos) = timestamp_parts(&array).unwrap();
+        assert_eq!(
+            days.iter().collect::<Vec<_>>(),
+            vec![Some(-1), None, Some(-1), None]
+        );
+        assert_eq!(nanos.value(0), 86_399_000_000_000);
+        let array = TimestampMicrosecondArray::from(vec![Some(-1), None]);
+        let (days, nanos) = timestamp_parts(&array).unwrap();
+        assert_eq!(
+            days.nulls().unwrap().buffer().as_ptr(),
+            array.nulls().unwrap().buffer().as_ptr()
+        );
+        assert_eq!(nanos.value(0), DAY - 1000);
+
+        // Arrays not of timestamps.
+        let array = Int64Array::from(vec![0]);
+        assert_eq!(timestamp_value_as_parts(&array, 0), None);
+        assert!(timestamp_parts(&array).is_err());
+    }
+}
@@ -0,0 +1,625 @@
+//! Planning casts of arrays and record batches ahead of the data.
+
+use std::sync::Arc;
+
+use arrow_array::{cast::AsArray, timezone::Tz, Array, ArrayRef, RecordBatch};
+use arrow_schema::{
+    ArrowError, DataType, FieldRef, Fields, IntervalUnit, Schema, SchemaRef, TimeUnit,
+};
+
+use crate::{
+    batch::{cast_batch, source_index},
+    cast_with_options,
+    extension::epoch_unit,
+    kernel,
+    shim::Timezone,
+    BinaryEpochKind, CastOptions, CastReport, EpochKind, SchemaCastOptions,
+};
+
+/// Cast of arrays of one type to another, resolved once and applied to many arrays.
+///
+/// Casting by the plan is the same as [cast_with_options], without matching the types
+/// and checking the options on each call.
+///
+/// ```rust
+/// use arrow::{array::Int64Array, datatypes::{DataType, TimeUnit}};
+/// use arrow_cast_guess_precision::{CastOptions, CastPlan};
+///
+/// let plan = CastPlan::try_new(
+///     &DataType::Int64,
+///     &DataType::Timestamp(TimeUnit::Millisecond, None),
+///     &CastOptions::new(),
+/// )
+/// .unwrap();
+/// assert!(plan.guesses());
+/// let array = plan.cast(&Int64Array::from(vec![1701325744])).unwrap();
+/// assert_eq!(array.data_type(), plan.to_type());
+/// ```
+#[derive(Debug, Clone)]
+pub struct CastPlan<'a> {
+    from: DataType,
+    to: DataType,
+    options: CastOptions<'a>,
+    action: FieldCastAction,
+    kind: CastPlanKind,
+}
+
+/// How a planned cast is done per array.
+#[derive(Debug, Clone)]
+enum CastPlanKind {
+    /// Integers of a known unit rescaled to the timestamp unit.
+    Rescale {
+        from: TimeUnit,
+        to: TimeUnit,
+        tz: Timezone,
+        /// If the rescaled timestamps are not of the target type yet.
+        convert: bool,
+    },
+    /// Anything else, by [cast_with_options].
+    General,
+}
+
+impl<'a> CastPlan<'a> {
+    /// Plan casting arrays of `from` to `to`.
+    ///
+    /// Fails if the types can not be cast, or the options are invalid for them.
+    pub fn try_new(
+        from: &DataType,
+        to: &DataType,
+        options: &CastOptions<'a>,
+    ) -> Result<Self, ArrowError> {
+        let options = &options.resolve().cast_options(options);
+        let to = &*kernel::normalize_utc(to, &options.timestamp_options);
+        let action = plan_action(from, to, options);
+        if let FieldCastAction::Unsupported { reason } = action {
+            return Err(ArrowError::CastError(reason));
+        }
+        let timestamp_options = &options.timestamp_options;
+        if let Some(tz) = timestamp_options.output_as_wall_clock_in.as_deref() {
+            if let DataType::Timestamp(_, Some(_)) = to {
+                return Err(ArrowError::CastError(format!(
+                    "Cannot output wall clock timestamps as {to:?} with a timezone"
+                )));
+            }
+            tz.parse::<Tz>()?;
+        }
+        let kind = match (from, to) {
+            (DataType::Int64, DataType::Timestamp(unit, tz))
+                if only_rescales(tz, options)
+                    && (timestamp_options.assume_unit.is_some()
+                        || !timestamp_options.guess_timestamp_precision) =>
+            {
+                let tz = if timestamp_options.use_timezone_as_is {
+                    tz.clone()
+                } else {
+                    None
+                };
+                CastPlanKind::Rescale {
+                    from: timestamp_options.assume_unit.unwrap_or(*unit),
+                    to: *unit,
+                    convert: &DataType::Timestamp(*unit, tz.clone()) != to,
+                    tz,
+                }
+            }
+            _ => CastPlanKind::General,
+        };
+        Ok(Self {
+            from: from.clone(),
+            to: to.clone(),
+            options: options.clone(),
+            action,
+            kind,
+        })
+    }
+
+    /// The source type.
+    pub fn from_type(&self) -> &DataType {
+        &self.from
+    }
+
+    /// The target type.
+    pub fn to_type(&self) -> &DataType {
+        &self.to
+    }
+
+    /// If true, the source unit is guessed from the values of each array.
+    pub fn guesses(&self) -> bool {
+        matches!(self.action, FieldCastAction::GuessingCast { .. })
+    }
+
+    /// Cast an array of the source type by the plan.
+    pub fn cast(&self, array: &dyn Array) -> Result<ArrayRef, ArrowError> {
+        if array.data_type() != &self.from {
+            return Err(ArrowError::CastError(format!(
+                "Cannot cast {:?} by the plan of casting {:?}",
+                array.data_type(),
+                self.from
+            )));
+        }
+        match &self.kind {
+            CastPlanKind::Rescale {
+                from,
+                to,
+                tz,
+                convert,
+            } => {
+                let mut report = CastReport::default();
+                let array =
+                    kernel::rescale(array.as_primitive(), *from, *to, &self.options, &mut report)?;
+                let array = kernel::make_timestamp_array(&array, *to, tz.clone());
+                if !convert {
+                    return Ok(array);
+                }
+                arrow_cast::cast_with_options(&array, &self.to, &(&self.options).into())
+            }
+            CastPlanKind::General => cast_with_options(array, &self.to, &self.options),
+        }
+    }
+}
+
+/// If casting integers to timestamps of the timezone only rescales them from their unit,
+/// without any option applied before or after rescaling by [cast_with_options].
+pub(crate) fn only_rescales(tz: &Timezone, options: &CastOptions) -> bool {
+    let timestamp_options = &options.timestamp_options;
+    let spelled_utc = matches!(tz, Some(tz) if kernel::is_utc(tz) && tz.as_ref() != "+00:00");
+    !options.passthrough
+        && timestamp_options.epoch_kind == EpochKind::Unix
+        && timestamp_options.interpretations.is_empty()
+        && !timestamp_options.verify_roundtrip
+        && !timestamp_options.keep_downscale_remainder
+        && timestamp_options.output_as_wall_clock_in.is_none()
+        && !spelled_utc
+}
+
+/// What casting a field does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldCastAction {
+    /// The source column is already of the target type.
+    NoOp,
+    /// The source column is cast without looking at its values first.
+    DirectCast,
+    /// The source unit is guessed, from the values of the column if `needs_data`.
+    GuessingCast { needs_data: bool },
+    /// The field can not be cast.
+    Unsupported { reason: String },
+}
+
+/// Plan of casting one target field.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldCastPlan {
+    /// The target field.
+    pub field: FieldRef,
+    /// Index of the matched source column, `None` if no column matches.
+    pub source: Option<usize>,
+    pub action: FieldCastAction,
+}
+
+/// Plan of casting record batches of one schema to another, see [plan_schema_cast].
+#[derive(Debug, Clone)]
+pub struct SchemaCastPlan<'a> {
+    from: SchemaRef,
+    to: SchemaRef,
+    fields: Vec<FieldCastPlan>,
+    options: SchemaCastOptions<'a>,
+}
+
+impl SchemaCastPlan<'_> {
+    /// The source schema.
+    pub fn source_schema(&self) -> SchemaRef {
+        self.from.clone()
+    }
+
+    /// The target schema.
+    pub fn schema(&self) -> SchemaRef {
+        self.to.clone()
+    }
+
+    /// Plans of the target fields, in order.
+    pub fn fields(&self) -> &[FieldCastPlan] {
+        &self.fields
+    }
+
+    /// Plans of the fields which can not be cast.
+    pub fn unsupported(&self) -> impl Iterator<Item = &FieldCastPlan> {
+        self.fields
+            .iter()
+            .filter(|plan| matches!(plan.action, FieldCastAction::Unsupported { .. }))
+    }
+
+    /// If true, every field can be cast.
+    pub fn is_supported(&self) -> bool {
+        self.unsupported().next().is_none()
+    }
+
+    /// Cast a batch of the source schema by the plan.
+    ///
+    /// Fails if any field is unsupported, or the batch does not match the source schema.
+    pub fn execute(&self, batch: &RecordBatch) -> Result<RecordBatch, ArrowError> {
+        let reasons: Vec<&str> = self
+            .fields
+            .iter()
+            .filter_map(|plan| match &plan.action {
+                FieldCastAction::Unsupported { reason } => Some(reason.as_str()),
+                _ => None,
+            })
+            .collect();
+        if !reasons.is_empty() {
+            return Err(ArrowError::CastError(format!(
+                "Cannot execute the schema cast plan: {}",
+                reasons.join("; ")
+            )));
+        }
+        if batch.num_columns() != self.from.fields().len() {
+            return Err(ArrowError::SchemaError(format!(
+                "Batch has {} columns, the plan expects {}",
+                batch.num_columns(),
+                self.from.fields().len()
+            )));
+        }
+        let mut sources = Vec::with_capacity(self.fields.len());
+        let mut columns = Vec::with_capacity(self.fields.len());
+        for plan in &self.fields {
+            let index = plan.source.expect("supported fields have a source column");
+            let column = batch.column(index);
+            let expected = self.from.field(index).data_type();
+            if column.data_type() != expected {
+                return Err(ArrowError::SchemaError(format!(
+                    "Column {index} of the batch is {:?}, the plan expects {expected:?}",
+                    column.data_type()
+                )));
+            }
+            sources.push(self.from.fields()[index].clone());
+            columns.push(column);
+        }
+        let mut pins = vec![None; self.fields.len()];
+        cast_batch(
+            &columns,
+            &sources,
+            self.to.clone(),
+            &self.options,
+            &mut pins,
+        )
+    }
+}
+
+/// Plan casting record batches of the `from` schema to the `to` schema.
+///
+/// Target fields are matched to source columns by name, or by position with
+/// [position_fallback](SchemaCastOptions::position_fallback). Fields which can not be
+/// cast are collected in the plan instead of failing.
+pub fn plan_schema_cast<'a>(
+    from: &Schema,
+    to: &Schema,
+    options: &SchemaCastOptions<'a>,
+) -> Result<SchemaCastPlan<'a>, ArrowError> {
+    let fields = to
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let source = source_index(from, index, field.name(), options);
+            let action = match source {
+                Some(source) => {
+                    let mut cast_options = options.cast_options.clone();
+                    if !options.guesses_column(field.name()) {
+                        cast_options.timestamp_options.guess_timestamp_precision = false;
+                    }
+                    if options.epoch_extensions {
+                        if let Ok(Some(unit)) = epoch_unit(from.field(source), &cast_options) {
+                            cast_options.timestamp_options.assume_unit = Some(unit);
+                        }
+                    }
+                    plan_action(
+                        from.field(source).data_type(),
+                        field.data_type(),
+                        &cast_options,
+                    )
+                }
+                None => FieldCastAction::Unsupported {
+                    reason: format!("No source column for field {}", field.name()),
+                },
+            };
+            FieldCastPlan {
+                field: field.clone(),
+                source,
+                action,
+            }
+        })
+        .collect();
+    Ok(SchemaCastPlan {
+        from: Arc::new(from.clone()),
+        to: Arc::new(to.clone()),
+        fields,
+        options: options.clone(),
+    })
+}
+
+/// The action of casting `from` to `to`, following the arms of the cast.
+fn plan_action(from: &DataType, to: &DataType, cast_options: &CastOptions) -> FieldCastAction {
+    use DataType::*;
+    let options = &cast_options.timestamp_options;
+    let guessing = options.guess_timestamp_precision
+        && options.assume_unit.is_none()
+        && options.epoch_kind == EpochKind::Unix;
+    if from == to {
+        return FieldCastAction::NoOp;
+    }
+    match (from, to) {
+        (Struct(from_fields), Struct(to_fields)) => {
+            plan_struct(from_fields, to_fields, cast_options)
+        }
+        (FixedSizeBinary(12), Timestamp(_, _))
+            if options.binary_epoch_kind == BinaryEpochKind::Int96 =>
+        {
+            FieldCastAction::DirectCast
+        }
+        (Binary | LargeBinary | FixedSizeBinary(8), Timestamp(_, _))
+            if matches!(options.binary_epoch_kind, BinaryEpochKind::RawI64(_)) && guessing =>
+        {
+            FieldCastAction::GuessingCast { needs_data: true }
+        }
+        (Float16 | Float32 | Float64, Timestamp(_, _))
+            if options.timestamps_as_fractional_seconds =>
+        {
+            FieldCastAction::DirectCast
+        }
+        // Guessed from the values, strings may be integers.
+        (
+            Int8
+            | Int16
+            | Int32
+            | Int64
+            | UInt8
+            | UInt16
+            | UInt32
+            | UInt64
+            | Float16
+            | Float32
+            | Float64
+            | Decimal128(_, _)
+            | Decimal256(_, _)
+            | Utf8
+            | LargeUtf8,
+            Timestamp(_, _),
+        ) if guessing => FieldCastAction::GuessingCast { needs_data: true },
+        (Int32 | Int64, Interval(IntervalUnit::DayTime | IntervalUnit::MonthDayNano))
+            if options.guess_timestamp_precision && options.elapsed_unit.is_none() =>
+        {
+            FieldCastAction::GuessingCast { needs_data: true }
+        }
+        (Duration(_), Timestamp(_, _)) if options.anchor.is_some() => FieldCastAction::DirectCast,
+        (Utf8 | LargeUtf8, Interval(_) | Time32(_) | Time64(_))
+        | (Timestamp(_, _), Decimal128(_, _)) => FieldCastAction::DirectCast,
+        _ if arrow_cast::can_cast_types(from, to) => FieldCastAction::DirectCast,
+        _ => FieldCastAction::Unsupported {
+            reason: format!("Cannot cast {from:?} to {to:?}"),
+        },
+    }
+}
+
+/// The action of casting struct fields, matched by position or name like the cast.
+fn plan_struct(from: &Fields, to: &Fields, cast_options: &CastOptions) -> FieldCastAction {
+    let by_name =
+        cast_options.allow_missing_struct_fields || cast_options.allow_extra_struct_fields;
+    let unsupported = |reason: String| FieldCastAction::Unsupported { reason };
+    let pairs: Vec<(Option<&DataType>, &FieldRef)> = if by_name {
+        if !cast_options.allow_extra_struct_fields {
+            if let Some(extra) = from.iter().find(|f| to.find(f.name()).is_none()) {
+                return unsupported(format!(
+                    "Cannot cast struct field {} missing from the target",
+                    extra.name()
+                ));
+            }
+        }
+        to.iter()
+            .map(|field| {
+                let source = from.find(field.name()).map(|(_, f)| f.data_type());
+                (source, field)
+            })
+            .collect()
+    } else if from.len() != to.len() {
+        return unsupported(format!(
+            "Cannot cast struct of {} fields to struct of {} fields",
+            from.len(),
+            to.len()
+        ));
+    } else {
+        from.iter()
+            .zip(to.iter())
+            .map(|(f, t)| (Some(f.data_type()), t))
+            .collect()
+    };
+    let mut action = FieldCastAction::DirectCast;
+    for (source, field) in pairs {
+        let child = match source {
+            Some(source) => plan_action(source, field.data_type(), cast_options),
+            None if cast_options.allow_missing_struct_fields => FieldCastAction::DirectCast,
+            None => unsupported(format!(
+                "Cannot cast to struct field {} missing from the source",
+                field.name()
+            )),
+        };
+        match child {
+            FieldCastAction::Unsupported { .. } => return child,
+            FieldCastAction::GuessingCast { .. } => action = child,
+            _ => {}
+        }
+    }
+    action
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::{types::TimestampMillisecondType, Int32Array, Int64Array, StringArray};
+    use arrow_schema::Field;
+
+    use super::*;
+    use crate::{DownscalePolicy, Interpretation};
+
+    #[test]
+    fn test_plan_schema_cast() {
+        let ts = DataType::Timestamp(TimeUnit::Millisecond, None);
+        let from = Schema::new(vec![
+            Field::new("id", DataType::Int32, true),
+            Field::new("ts", DataType::Int64, true),
+            Field::new("legacy", DataType::Utf8, true),
+            Field::new("list", DataType::new_list(DataType::Int32, true), true),
+        ]);
+        let to = Schema::new(vec![
+            Field::new("id", DataType::Int32, true),
+            Field::new("ts", ts.clone(), true),
+            Field::new("list", DataType::Int32, true),
+            Field::new("missing", ts.clone(), true),
+        ]);
+        let plan = plan_schema_cast(
+            &from,
+            &to,
+            &SchemaCastOptions::new(CastOptions::new().with_passthrough(false)),
+        )
+        .unwrap();
+        let actions: Vec<_> = plan.fields().iter().map(|plan| &plan.action).collect();
+        assert_eq!(actions[0], &FieldCastAction::NoOp);
+        assert_eq!(
+            actions[1],
+            &FieldCastAction::GuessingCast { needs_data: true }
+        );
+        assert!(matches!(actions[2], FieldCastAction::Unsupported { .. }));
+        assert!(matches!(actions[3], FieldCastAction::Unsupported { .. }));
+        assert_eq!(plan.unsupported().count(), 2);
+
+        let batch = RecordBatch::try_new(
+            Arc::new(from.clone()),
+            vec![
+                Arc::new(Int32Array::from(vec![1])) as ArrayRef,
+                Arc::new(Int64Array::from(vec![1701325744])),
+                Arc::new(StringArray::from(vec!["2023-11-30T06:29:04.956"])),
+                arrow_array::new_null_array(from.field(3).data_type(), 1),
+            ],
+        )
+        .unwrap();
+        let err = plan.execute(&batch).unwrap_err().to_string();
+        assert!(err.contains("No source column for field missing"));
+
+        // Fall back to the position.
+        let to = Schema::new(vec![
+            Field::new("id", DataType::Int32, true),
+            Field::new("ts", ts.clone(), true),
+            Field::new("renamed", ts, true),
+        ]);
+        let options = SchemaCastOptions::new(CastOptions::new().with_passthrough(false))
+            .with_position_fallback(true);
+        let plan = plan_schema_cast(&from, &to, &options).unwrap();
+        assert_eq!(plan.fields()[2].source, Some(2));
+        assert!(plan.is_supported());
+        let casted = plan.execute(&batch).unwrap();
+        assert_eq!(casted.schema().as_ref(), &to);
+        let ts = casted.column(1).as_primitive::<TimestampMillisecondType>();
+        assert_eq!(ts.value(0), 1701325744000);
+        let renamed = casted.column(2).as_primitive::<TimestampMillisecondType>();
+        assert_eq!(renamed.value(0), 1701325744956);
+    }
+
+    #[test]
+    fn test_cast_plan() {
+        fn is_send_sync<T: Send + Sync>() {}
+        is_send_sync::<CastPlan>();
+
+        let ms = DataType::Timestamp(TimeUnit::Millisecond, None);
+        let ns_utc = DataType::Timestamp(TimeUnit::Nanosecond, Some("+00:00".into()));
+        let integers: ArrayRef = Arc::new(Int64Array::from(vec![Some(1701325744), None]));
+        let strings: ArrayRef = Arc::new(StringArray::from(vec!["2023-11-30T06:29:04", "bad"]));
+        let mut assumed = CastOptions::new().with_passthrough(false);
+        assumed.timestamp_options.assume_unit = Some(TimeUnit::Second);
+        let mut as_is = assumed.clone();
+        as_is.timestamp_options.use_timezone_as_is = true;
+        let cases = [
+            (
+                &integers,
+                &ms,
+                CastOptions::new().with_passthrough(false),
+                true,
+            ),
+            (&integers, &ms, assumed.clone(), false),
+            (&integers, &ns_utc, assumed, false),
+            (&integers, &ns_utc, as_is, false),
+            (
+                &strings,
+                &ms,
+                CastOptions::new().with_passthrough(false),
+                true,
+            ),
+        ];
+        for (array, to_type, options, guesses) in cases {
+            let plan = CastPlan::try_new(array.data_type(), to_type, &options).unwrap();
+            assert_eq!(plan.guesses(), guesses);
+            assert_eq!(
+                &plan.cast(array).unwrap(),
+                &cast_with_options(array, to_type, &options).unwrap()
+            );
+        }
+
+        let plan = CastPlan::try_new(
+            &DataType::Int64,
+            &ms,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        assert!(plan.cast(&strings).is_err());
+        let list = DataType::new_list(DataType::Int32, true);
+        assert!(
+            CastPlan::try_new(&list, &ms, &CastOptions::new().with_passthrough(false)).is_err()
+        );
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.output_as_wall_clock_in = Some("Nowhere/Invalid".into());
+        assert!(CastPlan::try_new(&DataType::Int64, &ms, &options).is_err());
+    }
+
+    #[test]
+    fn test_cast_plan_options() {
+        // Options applied around rescaling integers of a known unit cast the same by plans.
+        let secs = DataType::Timestamp(TimeUnit::Second, None);
+        let integers: ArrayRef = Arc::new(Int64Array::from(vec![
+            Some(20231130062904),
+            Some(20231130062905),
+            None,
+        ]));
+        let mut assumed = CastOptions::new().with_passthrough(false);
+        assumed.timestamp_options.assume_unit = Some(TimeUnit::Millisecond);
+        let mut interpreted = assumed.clone();
+        interpreted.timestamp_options.interpretations = vec![Interpretation::PackedDateTime];
+        let mut verified = assumed.clone();
+        verified.timestamp_options.verify_roundtrip = true;
+        verified.timestamp_options.roundtrip_error = true;
+        let mut remainders = assumed.clone();
+        remainders.timestamp_options.keep_downscale_remainder = true;
+        remainders.timestamp_options.downscale_policy = DownscalePolicy::Error;
+        let mut not_as_is = assumed.clone();
+        not_as_is.timestamp_options.use_timezone_as_is = false;
+        let cases = [
+            (interpreted, secs.clone()),
+            (verified, secs.clone()),
+            (remainders, secs),
+            (
+                assumed.clone(),
+                DataType::Timestamp(TimeUnit::Second, Some("UTC".into())),
+            ),
+            (
+                not_as_is,
+                DataType::Timestamp(TimeUnit::Millisecond, Some("Etc/UTC".into())),
+            ),
+        ];
+        for (options, to_type) in cases {
+            let plan = CastPlan::try_new(&DataType::Int64, &to_type, &options).unwrap();
+            match (
+                plan.cast(&integers),
+                cast_with_options(&integers, &to_type, &options),
+            ) {
+                (Ok(planned), Ok(casted)) => assert_eq!(&planned, &casted, "{to_type:?}"),
+                (Err(planned), Err(casted)) => {
+                    assert_eq!(planned.to_string(), casted.to_string())
+                }
+                (planned, casted) => panic!("{planned:?} != {casted:?} for {to_type:?}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,170 @@
+//! Casting to timestamps of the unit chosen by a precision floor.
+
+use arrow_array::{Array, ArrayRef};
+use arrow_schema::{ArrowError, DataType, TimeUnit};
+
+use crate::{
+    cast_with_options, kernel::time_unit_multiple, shim::Timezone, CastOptions, PreparedColumn,
+};
+
+/// Cast to timestamps in the guessed unit of the array, or in `min_unit` if it is finer.
+///
+/// The output keeps the precision the values have and is never coarser than `min_unit`.
+/// Timestamps keep their own unit as the guessed one, and arrays without evidence, like
+/// strings of dates, are cast in `min_unit`. Values overflowing the chosen unit follow
+/// `safe` and the [overflow_behavior](crate::TimestampCastOptions::overflow_behavior).
+///
+/// Returns the array with the data type chosen.
+///
+/// ```rust
+/// use arrow::{array::Int64Array, datatypes::{DataType, TimeUnit}};
+/// use arrow_cast_guess_precision::{cast_with_min_precision, CastOptions};
+///
+/// let array = Int64Array::from(vec![1701325744956]);
+/// let (_, data_type) =
+///     cast_with_min_precision(&array, TimeUnit::Second, None, &CastOptions::new()).unwrap();
+/// assert_eq!(data_type, DataType::Timestamp(TimeUnit::Millisecond, None));
+/// ```
+pub fn cast_with_min_precision(
+    array: &dyn Array,
+    min_unit: TimeUnit,
+    tz: Timezone,
+    cast_options: &CastOptions,
+) -> Result<(ArrayRef, DataType), ArrowError> {
+    let unit = finer(source_unit(array, cast_options)?, min_unit);
+    let casted = cast_with_options(array, &DataType::Timestamp(unit, tz), cast_options)?;
+    let data_type = casted.data_type().clone();
+    Ok((casted, data_type))
+}
+
+/// The unit of the values of the array, `None` if there is no evidence.
+fn source_unit(
+    array: &dyn Array,
+    cast_options: &CastOptions,
+) -> Result<Option<TimeUnit>, ArrowError> {
+    let options = &cast_options.timestamp_options;
+    if let DataType::Timestamp(unit, _) = array.data_type() {
+        return Ok(Some(*unit));
+    }
+    if options.assume_unit.is_some() || !options.guess_timestamp_precision {
+        return Ok(options.assume_unit);
+    }
+    match PreparedColumn::try_new(array, cast_options) {
+        Ok(prepared) => prepared.guessed_unit(),
+        Err(_) => Ok(None),
+    }
+}
+
+/// The finer of the units, `floor` if `unit` is `None`.
+fn finer(unit: Option<TimeUnit>, floor: TimeUnit) -> TimeUnit {
+    match unit {
+        Some(unit) if time_unit_multiple(unit) > time_unit_multiple(floor) => unit,
+        _ => floor,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use arrow_array::{
+        cast::AsArray,
+        types::{Int64Type, TimestampNanosecondType},
+        Int32Array, Int64Array, StringArray, TimestampSecondArray,
+    };
+
+    use super::*;
+    use crate::OverflowBehavior;
+
+    #[test]
+    fn test_cast_with_min_precision() {
+        use TimeUnit::*;
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.guessing_bound_years = 1000;
+        let ints = |v: i64| Arc::new(Int64Array::from(vec![v])) as ArrayRef;
+        // (array, floor, chosen unit, first value)
+        let cases = [
+            (ints(1701325744), Millisecond, Millisecond, 1701325744000),
+            (ints(1701325744956), Second, Millisecond, 1701325744956),
+            (ints(1701325744956), Millisecond, Millisecond, 1701325744956),
+            (
+                ints(1701325744956789),
+                Millisecond,
+                Microsecond,
+                1701325744956789,
+            ),
+            (
+                ints(1701325744956789012),
+                Microsecond,
+                Nanosecond,
+                1701325744956789012,
+            ),
+            (
+                ints(1701325744956),
+                Nanosecond,
+                Nanosecond,
+                1701325744956000000,
+            ),
+            (
+                Arc::new(Int32Array::from(vec![1701325744])),
+                Second,
+                Second,
+                1701325744,
+            ),
+            (
+                Arc::new(StringArray::from(vec!["1701325744956"])),
+                Second,
+                Millisecond,
+                1701325744956,
+            ),
+            (
+                Arc::new(StringArray::from(vec!["2023-11-30T06:29:04.956Z"])),
+                Millisecond,
+                Millisecond,
+                1701325744956,
+            ),
+            (
+                Arc::new(TimestampSecondArray::from(vec![1701325744])),
+                Millisecond,
+                Millisecond,
+                1701325744000,
+            ),
+            (
+                Arc::new(TimestampSecondArray::from(vec![1701325744])),
+                Second,
+                Second,
+                1701325744,
+            ),
+        ];
+        for (array, floor, unit, value) in cases {
+            let (casted, data_type) =
+                cast_with_min_precision(&array, floor, Some("+00:00".into()), &options).unwrap();
+            assert_eq!(data_type, DataType::Timestamp(unit, Some("+00:00".into())));
+            let casted = arrow_cast::cast(&casted, &DataType::Int64).unwrap();
+            assert_eq!(
+                casted.as_primitive::<Int64Type>().value(0),
+                value,
+                "{array:?} at least in {floor:?}"
+            );
+        }
+
+        // Without evidence, the floor is chosen.
+        let nulls = Int64Array::from(vec![None]);
+        let (_, data_type) = cast_with_min_precision(&nulls, Microsecond, None, &options).unwrap();
+        assert_eq!(data_type, DataType::Timestamp(Microsecond, None));
+
+        // Seconds far from the epoch overflow nanoseconds.
+        let far = Int64Array::from(vec![30_000_000_000]);
+        let (casted, _) = cast_with_min_precision(&far, Nanosecond, None, &options).unwrap();
+        assert!(casted.is_null(0));
+        options.timestamp_options.overflow_behavior = Some(OverflowBehavior::Saturate);
+        let (casted, _) = cast_with_min_precision(&far, Nanosecond, None, &options).unwrap();
+        assert_eq!(
+            casted.as_primitive::<TimestampNanosecondType>().value(0),
+            i64::MAX
+        );
+        options.timestamp_options.overflow_behavior = None;
+        options.safe = false;
+        assert!(cast_with_min_precision(&far, Nanosecond, None, &options).is_err());
+    }
+}
@@ -0,0 +1,321 @@
+//! Columns widened to integers once, for repeated guessing and casting.
+
+use std::sync::Arc;
+
+use arrow_array::{
+    cast::AsArray,
+    types::{Float64Type, Int32Type, Int64Type},
+    Array, ArrayRef, BooleanArray, Float64Array, Int64Array,
+};
+use arrow_schema::{ArrowError, DataType, TimeUnit};
+
+use crate::{
+    cast_with_report, guess, string, CastOptions, CastReport, GuessStrategy, StringParseKind,
+};
+
+/// A column of numbers or integer strings widened to `Int64` once.
+///
+/// Guessing, casting and validating work on the cached integers without reading the
+/// source again, so the source array can be dropped once prepared. Floats keep their
+/// fractional parts for
+/// [timestamps_as_fractional_seconds](crate::TimestampCastOptions::timestamps_as_fractional_seconds).
+///
+/// Strings are taken as integers only, strings of dates fail to convert like strings out of
+/// the range of `Int64`. Values failing to convert are null, or fail casting without `safe`.
+///
+/// Cloning is cheap, the prepared values are shared.
+///
+/// ```rust
+/// use arrow::array::StringArray;
+/// use arrow::datatypes::{DataType, TimeUnit};
+/// use arrow_cast_guess_precision::{CastOptions, PreparedColumn};
+///
+/// let array = StringArray::from(vec!["1701325744956", "1701325745956"]);
+/// let prepared = PreparedColumn::try_new(&array, &CastOptions::new()).unwrap();
+/// drop(array);
+/// assert_eq!(prepared.analyze().unwrap().guessed_unit, Some(TimeUnit::Millisecond));
+/// prepared.validate().unwrap();
+/// let to_type = DataType::Timestamp(TimeUnit::Second, None);
+/// assert_eq!(prepared.cast(&to_type).unwrap().len(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PreparedColumn<'a> {
+    prepared: Arc<Prepared>,
+    options: CastOptions<'a>,
+}
+
+#[derive(Debug)]
+struct Prepared {
+    source_type: DataType,
+    integers: Int64Array,
+    /// True for values valid in the source and failing to convert.
+    failures: BooleanArray,
+    failure_count: usize,
+    /// Fractional parts of floats.
+    fractions: Option<Float64Array>,
+}
+
+impl<'a> PreparedColumn<'a> {
+    /// Prepare integers, floats, decimals or strings by the options.
+    pub fn try_new(array: &dyn Array, options: &CastOptions<'a>) -> Result<Self, ArrowError> {
+        let (integers, fractions) = widen(array, options)?;
+        let failures: BooleanArray = (0..array.len())
+            .map(|i| Some(array.is_valid(i) && integers.is_null(i)))
+            .collect();
+        let failure_count = failures.true_count();
+        Ok(Self {
+            prepared: Arc::new(Prepared {
+                source_type: array.data_type().clone(),
+                integers,
+                failures,
+                failure_count,
+                fractions,
+            }),
+            options: options.clone(),
+        })
+    }
+
+    /// The data type of the source array.
+    pub fn source_type(&self) -> &DataType {
+        &self.prepared.source_type
+    }
+
+    /// The `Int64` view of the source, null for values failing to convert.
+    pub fn integers(&self) -> &Int64Array {
+        &self.prepared.integers
+    }
+
+    /// Whether each value of the source failed to convert to `Int64`.
+    pub fn failures(&self) -> &BooleanArray {
+        &self.prepared.failures
+    }
+
+    /// Guess the unit of the values as casting to timestamps would.
+    ///
+    /// The report has the guessed unit and strategy, the values scanned and near the
+    /// boundary, and the values failing to convert as parse failures.
+    pub fn analyze(&self) -> Result<CastReport, ArrowError> {
+        let options = &self.options.timestamp_options;
+        let mut report = CastReport::default();
+        report.null_causes.parse_failure = self.prepared.failure_count;
+        if options.guess_timestamp_precision && options.assume_unit.is_none() {
+            report.guess_strategy = Some(options.guess_strategy);
+            report.guessed_unit = guess::guess_unit(self.integers(), options, &mut report)?;
+        }
+        Ok(report)
+    }
+
+    /// The unit guessed by [analyze](Self::analyze), `None` if there is no evidence.
+    pub fn guessed_unit(&self) -> Result<Option<TimeUnit>, ArrowError> {
+        self.analyze().map(|report| report.guessed_unit)
+    }
+
+    /// Cast the prepared values, see [cast_with_options](crate::cast_with_options).
+    pub fn cast(&self, to_type: &DataType) -> Result<ArrayRef, ArrowError> {
+        self.cast_with_report(to_type).map(|(array, _)| array)
+    }
+
+    /// Cast the prepared values, see [cast_with_report](crate::cast_with_report).
+    pub fn cast_with_report(
+        &self,
+        to_type: &DataType,
+    ) -> Result<(ArrayRef, CastReport), ArrowError> {
+        self.check_failures()?;
+        let fractional = self
+            .options
+            .timestamp_options
+            .timestamps_as_fractional_seconds
+            && matches!(to_type, DataType::Timestamp(_, _));
+        let (casted, mut report) = match &self.prepared.fractions {
+            Some(fractions) if fractional => {
+                let floats: Float64Array = self
+                    .integers()
+                    .iter()
+                    .zip(fractions.values())
+                    .map(|(v, fraction)| v.map(|v| v as f64 + fraction))
+                    .collect();
+                cast_with_report(&floats, to_type, &self.options)?
+            }
+            _ => cast_with_report(self.integers(), to_type, &self.options)?,
+        };
+        report.null_causes.parse_failure += self.prepared.failure_count;
+        report.nulls_introduced += self.prepared.failure_count;
+        let parsed = self.integers().len() - self.integers().null_count();
+        if let (DataType::Utf8 | DataType::LargeUtf8, DataType::Timestamp(_, _), 1..) =
+            (self.source_type(), to_type, parsed)
+        {
+            report.string_parse_counts.numeric_epoch += parsed;
+            if self.prepared.failure_count == 0 {
+                // As the cast samples the strings as integers.
+                report.string_parse_counts.numeric_sampled += parsed;
+            }
+            report.string_parse_kind = Some(StringParseKind::NumericEpoch {
+                guessed_unit: report.guessed_unit,
+            });
+        }
+        Ok((casted, report))
+    }
+
+    /// Check that all values convert and are guessed in one unit, as
+    /// [strict](crate::TimestampCastOptions::strict) casting with
+    /// [GuessStrategy::Majority] does on all values, without casting.
+    pub fn validate(&self) -> Result<(), ArrowError> {
+        if self.prepared.failure_count > 0 {
+            return Err(self.failure_error());
+        }
+        let mut options = self.options.timestamp_options.clone();
+        options.strict = true;
+        options.early_stop = false;
+        options.guess_sample_size = None;
+        if matches!(
+            options.guess_strategy,
+            GuessStrategy::FirstNonNull | GuessStrategy::PerValue
+        ) {
+            options.guess_strategy = GuessStrategy::Majority;
+        }
+        guess::guess_unit(self.integers(), &options, &mut CastReport::default())?;
+        Ok(())
+    }
+
+    /// Fail without `safe` if any value failed to convert.
+    fn check_failures(&self) -> Result<(), ArrowError> {
+        if self.options.safe || self.prepared.failure_count == 0 {
+            return Ok(());
+        }
+        Err(self.failure_error())
+    }
+
+    fn failure_error(&self) -> ArrowError {
+        let row = self.failures().values().set_indices().next().unwrap_or(0);
+        ArrowError::CastError(format!(
+            "Cannot convert {} values of {:?} to Int64, the first at row {row}",
+            self.prepared.failure_count,
+            self.source_type()
+        ))
+    }
+}
+
+/// Widen integers, floats and decimals, or parse strings, to `Int64`, with the fractional
+/// parts of floats.
+fn widen(
+    array: &dyn Array,
+    options: &CastOptions,
+) -> Result<(Int64Array, Option<Float64Array>), ArrowError> {
+    use DataType::*;
+    let mut fractions = None;
+    let integers = match array.data_type() {
+        Int32 if options.timestamp_options.fix_y2038_wrap => {
+            array
+                .as_primitive::<Int32Type>()
+                .unary::<_, Int64Type>(|v| v as u32 as i64)
+        }
+        Int8
+        | Int16
+        | Int32
+        | Int64
+        | UInt8
+        | UInt16
+        | UInt32
+        | UInt64
+        | Decimal128(_, _)
+        | Decimal256(_, _) => {
+            let integers = arrow_cast::cast(array, &Int64)?;
+            integers.as_primitive().clone()
+        }
+        Float16 | Float32 | Float64 => {
+            let floats = arrow_cast::cast(array, &Float64)?;
+            let floats = floats.as_primitive::<Float64Type>();
+            fractions = Some(floats.unary::<_, Float64Type>(|v| v - v.trunc()));
+            arrow_cast::cast(floats, &Int64)?.as_primitive().clone()
+        }
+        Utf8 | LargeUtf8 => {
+            let safe = CastOptions {
+                safe: true,
+                ..options.clone()
+            };
+            string::parse_integers(array, &safe, &mut CastReport::default())?
+                .unwrap_or_else(|| Int64Array::new_null(array.len()))
+        }
+        from_type => {
+            return Err(ArrowError::CastError(format!(
+                "Cannot prepare {from_type:?}, expected numbers or strings"
+            )))
+        }
+    };
+    Ok((integers, fractions))
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::{Float64Array, StringArray};
+
+    use super::*;
+
+    #[test]
+    fn test_prepared_column() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<PreparedColumn>();
+
+        let to_type = DataType::Timestamp(TimeUnit::Millisecond, None);
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.guess_strategy = GuessStrategy::Majority;
+        let sources: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from(vec![
+                Some(1701325744),
+                None,
+                Some(1701325745956),
+                Some(1701325746),
+            ])),
+            Arc::new(Float64Array::from(vec![Some(1701325744.5), None])),
+            Arc::new(StringArray::from(vec![
+                Some("1701325744956"),
+                None,
+                Some("1701325745956"),
+            ])),
+        ];
+        for source in sources {
+            let prepared = PreparedColumn::try_new(&source, &options).unwrap();
+            let (casted, report) = crate::cast_with_report(&source, &to_type, &options).unwrap();
+            assert_eq!(
+                prepared.analyze().unwrap().guessed_unit,
+                report.guessed_unit
+            );
+            assert_eq!(
+                prepared.cast_with_report(&to_type).unwrap(),
+                (casted, report)
+            );
+            let mut strict = options.clone();
+            strict.timestamp_options.strict = true;
+            assert_eq!(
+                prepared.validate().is_ok(),
+                crate::cast_with_options(&source, &to_type, &strict).is_ok()
+            );
+        }
+
+        // Fractional seconds are kept, and the source can be dropped once prepared.
+        let source = Float64Array::from(vec![1701325744.5]);
+        options.timestamp_options.timestamps_as_fractional_seconds = true;
+        let expected = crate::cast_with_options(&source, &to_type, &options).unwrap();
+        let prepared = PreparedColumn::try_new(&source, &options).unwrap();
+        drop(source);
+        let cloned = prepared.clone();
+        drop(prepared);
+        assert_eq!(cloned.cast(&to_type).unwrap().as_ref(), expected.as_ref());
+        assert_eq!(cloned.guessed_unit().unwrap(), Some(TimeUnit::Second));
+
+        // Strings of dates fail to convert.
+        let source = StringArray::from(vec![Some("1701325744"), Some("2023-11-30"), None]);
+        let prepared = PreparedColumn::try_new(&source, &options).unwrap();
+        assert_eq!(
+            prepared.failures().iter().collect::<Vec<_>>(),
+            vec![Some(false), Some(true), Some(false)]
+        );
+        let (casted, report) = prepared.cast_with_report(&to_type).unwrap();
+        assert_eq!(casted.null_count(), 2);
+        assert_eq!(report.null_causes.parse_failure, 1);
+        assert!(prepared.validate().is_err());
+        options.safe = false;
+        let prepared = PreparedColumn::try_new(&source, &options).unwrap();
+        assert!(prepared.cast(&to_type).is_err());
+    }
+}
@@ -0,0 +1,276 @@
+//! Report of what happened during a cast.
+
+use std::collections::HashMap;
+
+use arrow_array::{Array, ArrayRef, UInt8Array};
+use arrow_schema::{Field, TimeUnit};
+
+use crate::{
+    time_unit::{unit_code, unit_str},
+    ElapsedUnit, GuessStrategy, Interpretation, ResolvedConfig,
+};
+
+/// Field metadata key of the guessed source unit, one of `s`, `ms`, `us` and `ns`.
+pub const SOURCE_UNIT_METADATA_KEY: &str = "arrow_cast_guess_precision.source_unit";
+/// Field metadata key of how the source unit was decided.
+///
+/// The name of the [GuessStrategy] if guessed from the values of the column,
+/// `consistency_group` if decided by the consistency group of the column, or
+/// `epoch_extension` if read from the epoch metadata of the source field.
+pub const STRATEGY_METADATA_KEY: &str = "arrow_cast_guess_precision.strategy";
+
+/// What the caster decided and did while casting an array.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CastReport {
+    /// The source precision guessed from the values, if guessing happened.
+    pub guessed_unit: Option<TimeUnit>,
+    /// Number of values clamped by [OverflowBehavior::Saturate](crate::OverflowBehavior::Saturate).
+    pub saturated: usize,
+    /// The unit of elapsed time guessed when casting integers to intervals.
+    pub guessed_elapsed_unit: Option<ElapsedUnit>,
+    /// The strategy used for guessing, if guessing happened.
+    pub guess_strategy: Option<GuessStrategy>,
+    /// Number of nulls in the output which are not null in the input.
+    pub nulls_introduced: usize,
+    /// Nulls introduced by known causes.
+    ///
+    /// Causes add up to `nulls_introduced`, except for nulls introduced inside arrow kernels
+    /// for other reasons, e.g. casting `Int64` to `Int32`.
+    pub null_causes: NullCauses,
+    /// Unit codes of each value guessed per value, see [unit_code].
+    ///
+    /// Only recorded with [record_row_units](crate::TimestampCastOptions::record_row_units).
+    /// Rows are aligned with the output, rows nulled by the cast keep the unit attempted,
+    /// and rows null in the input are null.
+    pub row_units: Option<UInt8Array>,
+    /// Values near the boundary of units, see
+    /// [boundary_margin](crate::TimestampCastOptions::boundary_margin).
+    pub near_boundary: NearBoundary,
+    /// Rows whose output does not convert back to the input, up to
+    /// [roundtrip_max_mismatches](crate::TimestampCastOptions::roundtrip_max_mismatches).
+    ///
+    /// Only verified with [verify_roundtrip](crate::TimestampCastOptions::verify_roundtrip).
+    pub roundtrip_mismatches: Vec<RoundtripMismatch>,
+    /// Number of all rows whose output does not convert back to the input.
+    pub roundtrip_mismatch_count: usize,
+    /// If true, the guessed unit was refused by
+    /// [max_rescale_factor](crate::TimestampCastOptions::max_rescale_factor) and values were
+    /// taken in the target unit.
+    pub rescale_refused: bool,
+    /// Number of values read to guess the unit of the array.
+    pub values_scanned: usize,
+    /// Values ignored by guessing as outliers, by the unit each guesses in the order of
+    /// [unit_code], see
+    /// [trim_outlier_fraction](crate::TimestampCastOptions::trim_outlier_fraction).
+    pub trimmed_outliers: [usize; 4],
+    /// Number of runs of consecutive equal values whose rows repeat the decision of the
+    /// first, with [GuessStrategy::PerValue].
+    ///
+    /// Runs are counted per array cast, a run spanning arrays cast separately, e.g. the
+    /// chunks of [cast_chunks](crate::cast_chunks), counting once in each.
+    pub runs_detected: usize,
+    /// Row of the value the unit was guessed from with [GuessStrategy::FirstNonNull] and
+    /// [GuessStrategy::PerValue], see
+    /// [guess_skip_rows](crate::TimestampCastOptions::guess_skip_rows).
+    pub representative_row: Option<usize>,
+    /// The interpretation applied to the array, see
+    /// [interpretations](crate::TimestampCastOptions::interpretations).
+    pub interpretation: Option<Interpretation>,
+    /// The interpretation applied to each row with [GuessStrategy::PerValue], `None` for null
+    /// rows and rows matching none.
+    pub row_interpretations: Option<Vec<Option<Interpretation>>>,
+    /// Interpretations matching besides the applied ones, with
+    /// [MultipleMatchPolicy::Report](crate::MultipleMatchPolicy::Report).
+    pub other_interpretations: Vec<Interpretation>,
+    /// The epoch unit metadata of the field if malformed, the unit was guessed instead, see
+    /// [resolve_epoch_extension_with_report](crate::resolve_epoch_extension_with_report).
+    pub malformed_epoch_unit: Option<String>,
+    /// The invalid timezone of the source type repaired by
+    /// [repair_invalid_tz](crate::TimestampCastOptions::repair_invalid_tz).
+    pub invalid_tz: Option<String>,
+    /// How strings casting to timestamps were parsed, `None` if no string was parsed.
+    pub string_parse_kind: Option<StringParseKind>,
+    /// Number of strings parsed by each route.
+    pub string_parse_counts: StringParseCounts,
+    /// Signed remainders of downscaling each row in the source unit, as `Int32`, null for null
+    /// rows.
+    ///
+    /// Only kept with
+    /// [keep_downscale_remainder](crate::TimestampCastOptions::keep_downscale_remainder), zero
+    /// for rows not downscaled. Dictionaries keep a dictionary of remainders of their values.
+    pub remainders: Option<ArrayRef>,
+    /// The configuration in effect for the cast, see [CastOptions::resolve](crate::CastOptions::resolve).
+    pub resolved_config: Option<ResolvedConfig>,
+}
+
+/// A row whose output does not convert back to the input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundtripMismatch {
+    /// Index of the row.
+    pub row: usize,
+    /// The input value.
+    pub input: String,
+    /// The output timestamp in the target unit.
+    pub output: i64,
+    /// The output converted back to the source unit, empty on overflow.
+    pub recomputed: String,
+}
+
+/// How strings casting to timestamps were parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StringParseKind {
+    /// As ISO 8601 and RFC 3339 timestamps, including the dates and datetimes expanded to them.
+    Iso8601,
+    /// As integers of epochs, since no string parsed as a timestamp.
+    NumericEpoch {
+        /// The unit guessed from the integers, `None` if not guessed.
+        guessed_unit: Option<TimeUnit>,
+    },
+}
+
+/// Number of strings parsed by each route, see [StringParseKind].
+///
+/// Strings failing to parse are counted in [NullCauses::parse_failure].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StringParseCounts {
+    /// Parsed as timestamps.
+    pub iso8601: usize,
+    /// Parsed as integers of epochs.
+    pub numeric_epoch: usize,
+    /// Of `numeric_epoch`, parsed as integers without trying timestamps first, since the
+    /// strings sampled from their array all are integers.
+    pub numeric_sampled: usize,
+}
+
+/// Number of values within the margin of the lower bound of each unit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NearBoundary {
+    /// Near the bound between seconds and milliseconds.
+    pub millisecond: usize,
+    /// Near the bound between milliseconds and microseconds.
+    pub microsecond: usize,
+    /// Near the bound between microseconds and nanoseconds.
+    pub nanosecond: usize,
+}
+
+impl NearBoundary {
+    /// Count a value near the lower bound of the unit.
+    pub(crate) fn add(&mut self, unit: TimeUnit) {
+        match unit {
+            TimeUnit::Millisecond => self.millisecond += 1,
+            TimeUnit::Microsecond => self.microsecond += 1,
+            TimeUnit::Nanosecond => self.nanosecond += 1,
+            TimeUnit::Second => {}
+        }
+    }
+}
+
+/// Number of nulls introduced by a cast, by cause.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NullCauses {
+    /// Values failed to parse, e.g. unparsable strings or binaries of a wrong width.
+    pub parse_failure: usize,
+    /// Values overflowing the target type.
+    pub overflow: usize,
+    /// Values parsed but rejected by a policy, e.g. times out of the day.
+    pub policy: usize,
+}
+
+impl CastReport {
+    /// Annotate the field with the guessed source unit, keeping existing metadata.
+    ///
+    /// The field is returned as is if nothing was guessed.
+    pub fn annotate_field(&self, field: Field) -> Field {
+        match self.guessed_unit {
+            Some(unit) => {
+                let strategy = self.guess_strategy.unwrap_or_default();
+                annotate_field(field, unit, strategy.as_str())
+            }
+            None => field,
+        }
+    }
+
+    /// Unit codes of each row of the cast `input`, see [unit_code].
+    ///
+    /// The recorded [row_units](Self::row_units) if any, or the guessed unit for every
+    /// non-null row. `None` if nothing was guessed.
+    pub fn unit_array(&self, input: &dyn Array) -> Option<UInt8Array> {
+        if let Some(row_units) = &self.row_units {
+            return Some(row_units.clone());
+        }
+        let code = unit_code(self.guessed_unit?);
+        Some(
+            (0..input.len())
+                .map(|i| input.is_valid(i).then_some(code))
+                .collect(),
+        )
+    }
+}
+
+/// Merge the source unit and strategy into the field metadata.
+pub(crate) fn annotate_field(field: Field, unit: TimeUnit, strategy: &str) -> Field {
+    let mut metadata: HashMap<String, String> = field.metadata().clone();
+    metadata.insert(
+        SOURCE_UNIT_METADATA_KEY.to_string(),
+        unit_str(unit).to_string(),
+    );
+    metadata.insert(STRATEGY_METADATA_KEY.to_string(), strategy.to_string());
+    field.with_metadata(metadata)
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::Int64Array;
+    use arrow_schema::DataType;
+
+    use super::*;
+    use crate::{unit_from_code, CastOptions, GuessStrategy};
+
+    #[test]
+    fn test_row_units() {
+        let array = Int64Array::from(vec![
+            Some(1701325744),
+            None,
+            Some(1701325744956),
+            Some(i64::MAX),
+        ]);
+        let to_type = DataType::Timestamp(TimeUnit::Nanosecond, None);
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.guess_strategy = GuessStrategy::PerValue;
+        options.timestamp_options.record_row_units = true;
+        let (casted, report) = crate::cast_with_report(&array, &to_type, &options).unwrap();
+        let units = report.row_units.clone().unwrap();
+        assert_eq!(units.len(), casted.len());
+        let units: Vec<_> = units
+            .iter()
+            .map(|code| code.and_then(unit_from_code))
+            .collect();
+        assert_eq!(
+            units,
+            vec![
+                Some(TimeUnit::Second),
+                None,
+                Some(TimeUnit::Millisecond),
+                Some(TimeUnit::Nanosecond),
+            ]
+        );
+        assert_eq!(report.unit_array(&array), report.row_units);
+
+        // Constant for whole-array guessing, with nulls of the input.
+        let (casted, report) = crate::cast_with_report(
+            &array,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        assert!(report.row_units.is_none());
+        // Nulled by overflow, still recording the unit attempted.
+        assert!(casted.is_null(3));
+        let units = report.unit_array(&array).unwrap();
+        assert_eq!(
+            units.iter().collect::<Vec<_>>(),
+            vec![Some(0), None, Some(0), Some(0)]
+        );
+    }
+}
@@ -0,0 +1,624 @@
+//! The configuration in effect for a cast, with where each value comes from.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow_schema::TimeUnit;
+
+use crate::{
+    lower_bounds, BinaryEpochKind, CastOptions, DateOrder, DictionaryHandling, DownscalePolicy,
+    ElapsedUnit, EpochKind, GuessProfile, GuessStrategy, Interpretation, LeapSecondPolicy,
+    MultipleMatchPolicy, NaiveStringPolicy, NonFinitePolicy, OverflowBehavior, StringParseRoute,
+    TimestampCastOptions, TzRepair, UtcSpelling, GUESSING_BOUND_YEARS_FROM_ENV,
+};
+
+/// Where the value in effect of an option comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ConfigSource {
+    /// The default of the crate.
+    Default,
+    /// The `ARROW_CAST_GUESSING_BOUND_YEARS` environment variable at build time.
+    BuildEnv,
+    /// The process-wide defaults the options started from, see
+    /// [CastOptions::with_global_defaults].
+    Global,
+    /// The preset of the profile the options started from, see
+    /// [CastOptions::with_profile].
+    Profile(GuessProfile),
+    /// Set by the caller, differing from the defaults, the global defaults and the profile.
+    Explicit,
+    /// The default by a feature of the crate.
+    Feature,
+}
+
+/// A value in effect with its source.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Resolved<T> {
+    pub value: T,
+    pub source: ConfigSource,
+}
+
+/// Lower bounds of absolute values guessed in each unit, values up to the bound of
+/// milliseconds are seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GuessingBounds {
+    pub millisecond: i64,
+    pub microsecond: i64,
+    pub nanosecond: i64,
+}
+
+/// Snapshot of the configuration in effect for a cast, see [CastOptions::resolve].
+///
+/// Timestamp options are those of [TimestampCastOptions] of the same names. Casts take
+/// their options back from the snapshot by [cast_options](Self::cast_options), so the
+/// snapshot is what the kernels use.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ResolvedConfig {
+    pub safe: Resolved<bool>,
+    pub passthrough: Resolved<bool>,
+    pub allow_missing_struct_fields: Resolved<bool>,
+    pub allow_extra_struct_fields: Resolved<bool>,
+    pub format_timezone: Resolved<Option<String>>,
+    /// The arrow format options as debugged, arrow has no getters of them.
+    pub format_options: Resolved<String>,
+    /// The profile the options started from, if any.
+    pub profile: Option<GuessProfile>,
+    /// If the options started from the global defaults.
+    pub global_defaults: bool,
+    pub guess_timestamp_precision: Resolved<bool>,
+    pub use_timezone_as_is: Resolved<bool>,
+    /// Sourced from the build environment if `ARROW_CAST_GUESSING_BOUND_YEARS` was set.
+    pub guessing_bound_years: Resolved<i64>,
+    /// Bounds by [guessing_bound_years](Self::guessing_bound_years).
+    pub guessing_bounds: GuessingBounds,
+    /// `None` follows `safe`, in effect as [effective_overflow_behavior](Self::effective_overflow_behavior).
+    pub overflow_behavior: Resolved<Option<OverflowBehavior>>,
+    pub clamp_range: Resolved<Option<(i64, i64)>>,
+    pub tz_abbreviations: Resolved<Option<HashMap<String, Arc<str>>>>,
+    pub tz_abbreviations_ignore_case: Resolved<bool>,
+    pub elapsed_bound_days: Resolved<i64>,
+    pub elapsed_unit: Resolved<Option<ElapsedUnit>>,
+    pub downscale_policy: Resolved<DownscalePolicy>,
+    pub parse_compact_time: Resolved<bool>,
+    pub assume_unit: Resolved<Option<TimeUnit>>,
+    pub strict: Resolved<bool>,
+    pub binary_epoch_kind: Resolved<BinaryEpochKind>,
+    pub guess_strategy: Resolved<GuessStrategy>,
+    pub skip_sentinels: Resolved<bool>,
+    /// `None` follows `safe`, in effect as [effective_non_finite_policy](Self::effective_non_finite_policy).
+    pub non_finite_policy: Resolved<Option<NonFinitePolicy>>,
+    pub iso8601_durations: Resolved<bool>,
+    /// `None` follows `safe`, in effect as [effective_leap_second_policy](Self::effective_leap_second_policy).
+    pub leap_second_policy: Resolved<Option<LeapSecondPolicy>>,
+    pub record_row_units: Resolved<bool>,
+    pub fix_y2038_wrap: Resolved<bool>,
+    pub output_as_wall_clock_in: Resolved<Option<Arc<str>>>,
+    pub null_offset_as_utc: Resolved<bool>,
+    pub boundary_margin: Resolved<Option<f64>>,
+    pub parse_compact_date: Resolved<bool>,
+    pub named_month_order: Resolved<Option<DateOrder>>,
+    pub epoch_kind: Resolved<EpochKind>,
+    pub timestamps_as_fractional_seconds: Resolved<bool>,
+    pub normalize_strings: Resolved<bool>,
+    pub normalize_date_slashes: Resolved<bool>,
+    pub ntp_era_rollover: Resolved<bool>,
+    pub anchor: Resolved<Option<(i64, TimeUnit)>>,
+    pub verify_roundtrip: Resolved<bool>,
+    pub roundtrip_max_mismatches: Resolved<usize>,
+    pub roundtrip_epsilon: Resolved<f64>,
+    pub roundtrip_error: Resolved<bool>,
+    pub max_rescale_factor: Resolved<Option<i64>>,
+    pub naive_string_policy: Resolved<NaiveStringPolicy>,
+    pub unit_column_codes: Resolved<Option<HashMap<i64, TimeUnit>>>,
+    pub early_stop: Resolved<bool>,
+    pub guess_sample_size: Resolved<Option<usize>>,
+    pub interpretations: Resolved<Vec<Interpretation>>,
+    pub on_multiple_matches: Resolved<MultipleMatchPolicy>,
+    pub normalize_utc_tz: Resolved<Option<UtcSpelling>>,
+    pub epoch_unit_metadata_key: Resolved<String>,
+    pub repair_invalid_tz: Resolved<Option<TzRepair>>,
+    pub parse_iso_ordinal_week: Resolved<bool>,
+    pub dictionary_output: Resolved<DictionaryHandling>,
+    pub guess_skip_rows: Resolved<usize>,
+    pub guess_from_end: Resolved<bool>,
+    pub keep_downscale_remainder: Resolved<bool>,
+    pub normalize_unicode_digits: Resolved<bool>,
+    pub mixed_string_epochs: Resolved<bool>,
+    pub trim_outlier_fraction: Resolved<Option<f64>>,
+    pub string_parse_route: Resolved<StringParseRoute>,
+    /// The overflow behavior in effect, following `safe` if not set.
+    pub effective_overflow_behavior: OverflowBehavior,
+    /// The non-finite policy in effect, following `safe` if not set.
+    pub effective_non_finite_policy: NonFinitePolicy,
+    /// The leap second policy in effect, following `safe` if not set.
+    pub effective_leap_second_policy: LeapSecondPolicy,
+}
+
+impl CastOptions<'_> {
+    /// The configuration in effect for casting with the options, as recorded in
+    /// [CastReport::resolved_config](crate::CastReport::resolved_config).
+    ///
+    /// A value equal to its default is taken as the default, a value equal to the global
+    /// defaults as global, and a value equal to the preset of the profile as the profile.
+    pub fn resolve(&self) -> ResolvedConfig {
+        let options = &self.timestamp_options;
+        let layers = Layers {
+            options,
+            default: TimestampCastOptions::default(),
+            global: self.global_defaults.as_deref(),
+            preset: self
+                .profile
+                .map(|profile| (profile, TimestampCastOptions::from_profile(profile))),
+        };
+        let mut guessing_bound_years = layers.resolve(|o| &o.guessing_bound_years);
+        if guessing_bound_years.source == ConfigSource::Default && GUESSING_BOUND_YEARS_FROM_ENV {
+            guessing_bound_years.source = ConfigSource::BuildEnv;
+        }
+        let [millisecond, microsecond, nanosecond] = lower_bounds(options.guessing_bound_years);
+        let passthrough = match (cfg!(feature = "passthrough"), self.passthrough) {
+            (feature, passthrough) if feature != passthrough => ConfigSource::Explicit,
+            (true, _) => ConfigSource::Feature,
+            (false, _) => ConfigSource::Default,
+        };
+        let explicit = |is_default: bool| match is_default {
+            true => ConfigSource::Default,
+            false => ConfigSource::Explicit,
+        };
+        let format_options = format!("{:?}", self.format_options);
+        let default_format_options = format!("{:?}", CastOptions::new().format_options);
+        ResolvedConfig {
+            safe: Resolved {
+                value: self.safe,
+                source: explicit(self.safe),
+            },
+            passthrough: Resolved {
+                value: self.passthrough,
+                source: passthrough,
+            },
+            allow_missing_struct_fields: Resolved {
+                value: self.allow_missing_struct_fields,
+                source: explicit(!self.allow_missing_struct_fields),
+            },
+            allow_extra_struct_fields: Resolved {
+                value: self.allow_extra_struct_fields,
+                source: explicit(!self.allow_extra_struct_fields),
+            },
+            format_timezone: Resolved {
+                value: self.format_timezone.map(str::to_string),
+                source: explicit(self.format_timezone.is_none()),
+            },
+            format_options: Resolved {
+                source: explicit(format_options == default_format_options),
+                value: format_options,
+            },
+            profile: self.profile,
+            global_defaults: self.global_defaults.is_some(),
+            guess_timestamp_precision: layers.resolve(|o| &o.guess_timestamp_precision),
+            use_timezone_as_is: layers.resolve(|o| &o.use_timezone_as_is),
+            guessing_bound_years,
+            guessing_bounds: GuessingBounds {
+                millisecond,
+                microsecond,
+                nanosecond,
+            },
+            overflow_behavior: layers.resolve(|o| &o.overflow_behavior),
+            clamp_range: layers.resolve(|o| &o.clamp_range),
+            tz_abbreviations: layers.resolve(|o| &o.tz_abbreviations),
+            tz_abbreviations_ignore_case: layers.resolve(|o| &o.tz_abbreviations_ignore_case),
+            elapsed_bound_days: layers.resolve(|o| &o.elapsed_bound_days),
+            elapsed_unit: layers.resolve(|o| &o.elapsed_unit),
+            downscale_policy: layers.resolve(|o| &o.downscale_policy),
+            parse_compact_time: layers.resolve(|o| &o.parse_compact_time),
+            assume_unit: layers.resolve(|o| &o.assume_unit),
+            strict: layers.resolve(|o| &o.strict),
+            binary_epoch_kind: layers.resolve(|o| &o.binary_epoch_kind),
+            guess_strategy: layers.resolve(|o| &o.guess_strategy),
+            skip_sentinels: layers.resolve(|o| &o.skip_sentinels),
+            non_finite_policy: layers.resolve(|o| &o.non_finite_policy),
+            iso8601_durations: layers.resolve(|o| &o.iso8601_durations),
+            leap_second_policy: layers.resolve(|o| &o.leap_second_policy),
+            record_row_units: layers.resolve(|o| &o.record_row_units),
+            fix_y2038_wrap: layers.resolve(|o| &o.fix_y2038_wrap),
+            output_as_wall_clock_in: layers.resolve(|o| &o.output_as_wall_clock_in),
+            null_offset_as_utc: layers.resolve(|o| &o.null_offset_as_utc),
+            boundary_margin: layers.resolve(|o| &o.boundary_margin),
+            parse_compact_date: layers.resolve(|o| &o.parse_compact_date),
+            named_month_order: layers.resolve(|o| &o.named_month_order),
+            epoch_kind: layers.resolve(|o| &o.epoch_kind),
+            timestamps_as_fractional_seconds: layers
+                .resolve(|o| &o.timestamps_as_fractional_seconds),
+            normalize_strings: layers.resolve(|o| &o.normalize_strings),
+            normalize_date_slashes: layers.resolve(|o| &o.normalize_date_slashes),
+            ntp_era_rollover: layers.resolve(|o| &o.ntp_era_rollover),
+            anchor: layers.resolve(|o| &o.anchor),
+            verify_roundtrip: layers.resolve(|o| &o.verify_roundtrip),
+            roundtrip_max_mismatches: layers.resolve(|o| &o.roundtrip_max_mismatches),
+            roundtrip_epsilon: layers.resolve(|o| &o.roundtrip_epsilon),
+            roundtrip_error: layers.resolve(|o| &o.roundtrip_error),
+            max_rescale_factor: layers.resolve(|o| &o.max_rescale_factor),
+            naive_string_policy: layers.resolve(|o| &o.naive_string_policy),
+            unit_column_codes: layers.resolve(|o| &o.unit_column_codes),
+            early_stop: layers.resolve(|o| &o.early_stop),
+            guess_sample_size: layers.resolve(|o| &o.guess_sample_size),
+            interpretations: layers.resolve(|o| &o.interpretations),
+            on_multiple_matches: layers.resolve(|o| &o.on_multiple_matches),
+            normalize_utc_tz: layers.resolve(|o| &o.normalize_utc_tz),
+            epoch_unit_metadata_key: layers.resolve(|o| &o.epoch_unit_metadata_key),
+            repair_invalid_tz: layers.resolve(|o| &o.repair_invalid_tz),
+            parse_iso_ordinal_week: layers.resolve(|o| &o.parse_iso_ordinal_week),
+            dictionary_output: layers.resolve(|o| &o.dictionary_output),
+            guess_skip_rows: layers.resolve(|o| &o.guess_skip_rows),
+            guess_from_end: layers.resolve(|o| &o.guess_from_end),
+            keep_downscale_remainder: layers.resolve(|o| &o.keep_downscale_remainder),
+            normalize_unicode_digits: layers.resolve(|o| &o.normalize_unicode_digits),
+            mixed_string_epochs: layers.resolve(|o| &o.mixed_string_epochs),
+            trim_outlier_fraction: layers.resolve(|o| &o.trim_outlier_fraction),
+            string_parse_route: layers.resolve(|o| &o.string_parse_route),
+            effective_overflow_behavior: self.overflow_behavior(),
+            effective_non_finite_policy: self.non_finite_policy(),
+            effective_leap_second_policy: self.leap_second_policy(),
+        }
+    }
+}
+
+impl ResolvedConfig {
+    /// The timestamp options of the snapshot.
+    pub fn timestamp_options(&self) -> TimestampCastOptions {
+        TimestampCastOptions {
+            guess_timestamp_precision: self.guess_timestamp_precision.value,
+            use_timezone_as_is: self.use_timezone_as_is.value,
+            guessing_bound_years: self.guessing_bound_years.value,
+            overflow_behavior: self.overflow_behavior.value,
+            clamp_range: self.clamp_range.value,
+            tz_abbreviations: self.tz_abbreviations.value.clone(),
+            tz_abbreviations_ignore_case: self.tz_abbreviations_ignore_case.value,
+            elapsed_bound_days: self.elapsed_bound_days.value,
+            elapsed_unit: self.elapsed_unit.value,
+            downscale_policy: self.downscale_policy.value,
+            parse_compact_time: self.parse_compact_time.value,
+            assume_unit: self.assume_unit.value,
+            strict: self.strict.value,
+            binary_epoch_kind: self.binary_epoch_kind.value,
+            guess_strategy: self.guess_strategy.value,
+            skip_sentinels: self.skip_sentinels.value,
+            non_finite_policy: self.non_finite_policy.value,
+            iso8601_durations: self.iso8601_durations.value,
+            leap_second_policy: self.leap_second_policy.value,
+            record_row_units: self.record_row_units.value,
+            fix_y2038_wrap: self.fix_y2038_wrap.value,
+            output_as_wall_clock_in: self.output_as_wall_clock_in.value.clone(),
+            null_offset_as_utc: self.null_offset_as_utc.value,
+            boundary_margin: self.boundary_margin.value,
+            parse_compact_date: self.parse_compact_date.value,
+            named_month_order: self.named_month_order.value,
+            epoch_kind: self.epoch_kind.value,
+            timestamps_as_fractional_seconds: self.timestamps_as_fractional_seconds.value,
+            normalize_strings: self.normalize_strings.value,
+            normalize_date_slashes: self.normalize_date_slashes.value,
+            ntp_era_rollover: self.ntp_era_rollover.value,
+            anchor: self.anchor.value,
+            verify_roundtrip: self.verify_roundtrip.value,
+            roundtrip_max_mismatches: self.roundtrip_max_mismatches.value,
+            roundtrip_epsilon: self.roundtrip_epsilon.value,
+            roundtrip_error: self.roundtrip_error.value,
+            max_rescale_factor: self.max_rescale_factor.value,
+            naive_string_policy: self.naive_string_policy.value.clone(),
+            unit_column_codes: self.unit_column_codes.value.clone(),
+            early_stop: self.early_stop.value,
+            guess_sample_size: self.guess_sample_size.value,
+            interpretations: self.interpretations.value.clone(),
+            on_multiple_matches: self.on_multiple_matches.value,
+            normalize_utc_tz: self.normalize_utc_tz.value,
+            epoch_unit_metadata_key: self.epoch_unit_metadata_key.value.clone(),
+            repair_invalid_tz: self.repair_invalid_tz.value.clone(),
+            parse_iso_ordinal_week: self.parse_iso_ordinal_week.value,
+            dictionary_output: self.dictionary_output.value,
+            guess_skip_rows: self.guess_skip_rows.value,
+            guess_from_end: self.guess_from_end.value,
+            keep_downscale_remainder: self.keep_downscale_remainder.value,
+            normalize_unicode_digits: self.normalize_unicode_digits.value,
+            mixed_string_epochs: self.mixed_string_epochs.value,
+            trim_outlier_fraction: self.trim_outlier_fraction.value,
+            string_parse_route: self.string_parse_route.value,
+        }
+    }
+
+    /// The options casts use, of the snapshot resolved from `options`.
+    ///
+    /// Format options are taken from `options`, they are only debugged in the snapshot.
+    pub fn cast_options<'a>(&self, options: &CastOptions<'a>) -> CastOptions<'a> {
+        CastOptions {
+            safe: self.safe.value,
+            timestamp_options: self.timestamp_options(),
+            format_options: options.format_options.clone(),
+            format_timezone: options.format_timezone,
+            passthrough: self.passthrough.value,
+            allow_missing_struct_fields: self.allow_missing_struct_fields.value,
+            allow_extra_struct_fields: self.allow_extra_struct_fields.value,
+            profile: self.profile,
+            global_defaults: options.global_defaults.clone(),
+        }
+    }
+}
+
+/// The options with the layers they are resolved against.
+struct Layers<'o> {
+    options: &'o TimestampCastOptions,
+    default: TimestampCastOptions,
+    global: Option<&'o TimestampCastOptions>,
+    preset: Option<(GuessProfile, TimestampCastOptions)>,
+}
+
+impl Layers<'_> {
+    fn resolve<T: PartialEq + Clone>(&self, field: fn(&TimestampCastOptions) -> &T) -> Resolved<T> {
+        Resolved {
+            value: field(self.options).clone(),
+            source: self.source(field),
+        }
+    }
+
+    fn source<T: PartialEq>(&self, field: fn(&TimestampCastOptions) -> &T) -> ConfigSource {
+        let (value, default) = (field(self.options), field(&self.default));
+        match (&self.preset, self.global) {
+            (Some((profile, preset)), _) if field(preset) != default && value == field(preset) => {
+                ConfigSource::Profile(*profile)
+            }
+            (_, Some(global)) if field(global) != default && value == field(global) => {
+                ConfigSource::Global
+            }
+            _ if value != default => ConfigSource::Explicit,
+            _ => ConfigSource::Default,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::Int64Array;
+    use arrow_schema::DataType;
+
+    use super::*;
+    use crate::{set_global_defaults, GUESSING_BOUND_YEARS};
+
+    #[test]
+    fn test_resolve() {
+        let resolved = CastOptions::new().with_passthrough(false).resolve();
+        let build = match GUESSING_BOUND_YEARS_FROM_ENV {
+            true => ConfigSource::BuildEnv,
+            false => ConfigSource::Default,
+        };
+        assert_eq!(
+            resolved.guessing_bound_years,
+            Resolved {
+                value: GUESSING_BOUND_YEARS,
+                source: build
+            }
+        );
+        assert_eq!(
+            resolved.guess_strategy,
+            Resolved {
+                value: GuessStrategy::FirstNonNull,
+                source: ConfigSource::Default
+            }
+        );
+        assert_eq!(resolved.effective_overflow_behavior, OverflowBehavior::Null);
+        assert_eq!(resolved.profile, None);
+
+        // A profile, with values set over it.
+        let mut options = CastOptions::new()
+            .with_passthrough(false)
+            .with_profile(GuessProfile::Conservative);
+        options.timestamp_options.guessing_bound_years = 500;
+        options.timestamp_options.skip_sentinels = true;
+        options.safe = false;
+        let resolved = options.resolve();
+        let profile = ConfigSource::Profile(GuessProfile::Conservative);
+        assert_eq!(resolved.profile, Some(GuessProfile::Conservative));
+        assert_eq!(resolved.guess_strategy.source, profile);
+        assert_eq!(resolved.strict.source, profile);
+        assert_eq!(
+            resolved.overflow_behavior,
+            Resolved {
+                value: Some(OverflowBehavior::Error),
+                source: profile
+            }
+        );
+        assert_eq!(resolved.guessing_bound_years.source, ConfigSource::Explicit);
+        assert_eq!(resolved.skip_sentinels.source, ConfigSource::Explicit);
+        assert_eq!(resolved.safe.source, ConfigSource::Explicit);
+        // Following `safe` when not set.
+        assert_eq!(
+            resolved.effective_leap_second_policy,
+            LeapSecondPolicy::Error
+        );
+        assert_eq!(resolved.leap_second_policy.source, ConfigSource::Default);
+        assert_eq!(resolved.downscale_policy.source, ConfigSource::Default);
+
+        // The report has the snapshot, and casting follows it.
+        let bound = resolved.guessing_bounds.millisecond;
+        assert_eq!(bound, 86400 * 365 * 500);
+        let to_type = DataType::Timestamp(TimeUnit::Millisecond, None);
+        for (value, unit) in [
+            (bound, TimeUnit::Second),
+            (bound + 1, TimeUnit::Millisecond),
+        ] {
+            let array = Int64Array::from(vec![value]);
+            let (_, report) = crate::cast_with_report(&array, &to_type, &options).unwrap();
+            assert_eq!(report.guessed_unit, Some(unit));
+            assert_eq!(report.resolved_config.as_ref(), Some(&resolved));
+        }
+        // Passthrough differing from the default of the features is explicit.
+        let passthrough = !cfg!(feature = "passthrough");
+        let options = options.with_passthrough(passthrough);
+        let array = Int64Array::from(vec![bound]);
+        let (_, report) = crate::cast_with_report(&array, &to_type, &options).unwrap();
+        let resolved = report.resolved_config.unwrap();
+        assert_eq!(
+            resolved.passthrough,
+            Resolved {
+                value: passthrough,
+                source: ConfigSource::Explicit
+            }
+        );
+    }
+
+    #[test]
+    fn test_resolve_layers() {
+        // Each layer wins over the ones below it.
+        let mut global = TimestampCastOptions {
+            guessing_bound_years: 300,
+            skip_sentinels: true,
+            ..Default::default()
+        };
+        set_global_defaults(Some(global.clone()));
+        let mut options = CastOptions::new()
+            .with_passthrough(false)
+            .with_global_defaults();
+        // Options keep the global defaults they started from.
+        global.skip_sentinels = false;
+        set_global_defaults(Some(global));
+        options.timestamp_options.early_stop = true;
+        options.allow_extra_struct_fields = true;
+        let resolved = options.resolve();
+        set_global_defaults(None);
+        assert!(resolved.global_defaults);
+        assert_eq!(
+            resolved.guessing_bound_years,
+            Resolved {
+                value: 300,
+                source: ConfigSource::Global
+            }
+        );
+        assert_eq!(resolved.skip_sentinels.source, ConfigSource::Global);
+        assert_eq!(resolved.early_stop.source, ConfigSource::Explicit);
+        assert_eq!(resolved.strict.source, ConfigSource::Default);
+        assert_eq!(
+            resolved.allow_extra_struct_fields.source,
+            ConfigSource::Explicit
+        );
+        assert_eq!(resolved.format_options.source, ConfigSource::Default);
+        assert_eq!(resolved.guessing_bounds.millisecond, 86400 * 365 * 300);
+
+        // Profiles replace the global defaults.
+        let mut options = options.with_profile(GuessProfile::Lenient);
+        options.timestamp_options.guessing_bound_years = 300;
+        let resolved = options.with_timezone("+08:00").resolve();
+        assert!(!resolved.global_defaults);
+        assert_eq!(resolved.guessing_bound_years.source, ConfigSource::Explicit);
+        assert_eq!(
+            resolved.skip_sentinels.source,
+            ConfigSource::Profile(GuessProfile::Lenient)
+        );
+        assert_eq!(
+            resolved.format_timezone,
+            Resolved {
+                value: Some("+08:00".to_string()),
+                source: ConfigSource::Explicit
+            }
+        );
+        let resolved = CastOptions::new().with_null("NULL").resolve();
+        assert_eq!(resolved.format_options.source, ConfigSource::Explicit);
+        assert!(resolved.format_options.value.contains("NULL"));
+    }
+
+    #[test]
+    fn test_resolved_cast_options() {
+        // Every option differing from its default is in the snapshot the cast takes back.
+        let mut options = CastOptions::new()
+            .with_passthrough(false)
+            .with_profile(GuessProfile::Conservative)
+            .with_allow_missing_struct_fields(true);
+        options.safe = false;
+        options.timestamp_options = TimestampCastOptions {
+            guess_timestamp_precision: false,
+            use_timezone_as_is: false,
+            guessing_bound_years: 200,
+            overflow_behavior: Some(OverflowBehavior::Saturate),
+            clamp_range: Some((0, 1)),
+            tz_abbreviations: Some(HashMap::from([("CST".to_string(), "+08:00".into())])),
+            tz_abbreviations_ignore_case: true,
+            elapsed_bound_days: 30,
+            elapsed_unit: Some(ElapsedUnit::Minute),
+            downscale_policy: DownscalePolicy::Round,
+            parse_compact_time: true,
+            assume_unit: Some(TimeUnit::Microsecond),
+            strict: true,
+            binary_epoch_kind: BinaryEpochKind::Int96,
+            guess_strategy: GuessStrategy::MaxAbs,
+            skip_sentinels: true,
+            non_finite_policy: Some(NonFinitePolicy::Saturate),
+            iso8601_durations: true,
+            leap_second_policy: Some(LeapSecondPolicy::ClampTo59),
+            record_row_units: true,
+            fix_y2038_wrap: true,
+            output_as_wall_clock_in: Some("Asia/Shanghai".into()),
+            null_offset_as_utc: true,
+            boundary_margin: Some(0.1),
+            parse_compact_date: true,
+            named_month_order: Some(DateOrder::DayFirst),
+            epoch_kind: EpochKind::WindowsFiletime,
+            timestamps_as_fractional_seconds: true,
+            normalize_strings: true,
+            normalize_date_slashes: true,
+            ntp_era_rollover: true,
+            anchor: Some((1701325744, TimeUnit::Second)),
+            verify_roundtrip: true,
+            roundtrip_max_mismatches: 1,
+            roundtrip_epsilon: 0.5,
+            roundtrip_error: true,
+            max_rescale_factor: Some(1000),
+            naive_string_policy: NaiveStringPolicy::AssumeZone("+08:00".into()),
+            unit_column_codes: Some(HashMap::from([(9, TimeUnit::Nanosecond)])),
+            early_stop: true,
+            guess_sample_size: Some(10),
+            interpretations: vec![Interpretation::PackedDateTime],
+            on_multiple_matches: MultipleMatchPolicy::Report,
+            normalize_utc_tz: Some(UtcSpelling::Utc),
+            epoch_unit_metadata_key: "unit".to_string(),
+            repair_invalid_tz: Some(TzRepair::Strip),
+            parse_iso_ordinal_week: true,
+            dictionary_output: DictionaryHandling::Preserve,
+            guess_skip_rows: 2,
+            guess_from_end: true,
+            keep_downscale_remainder: true,
+            normalize_unicode_digits: true,
+            mixed_string_epochs: true,
+            trim_outlier_fraction: Some(0.1),
+            string_parse_route: StringParseRoute::Integers,
+        };
+        let resolved = options.resolve();
+        let resolved_options = resolved.cast_options(&options);
+        assert_eq!(
+            resolved_options.timestamp_options,
+            options.timestamp_options
+        );
+        assert!(!resolved_options.safe && resolved_options.allow_missing_struct_fields);
+        assert_eq!(resolved_options.resolve(), resolved);
+        assert_eq!(
+            resolved.strict.source,
+            ConfigSource::Profile(GuessProfile::Conservative)
+        );
+        assert_eq!(resolved.epoch_kind.source, ConfigSource::Explicit);
+        assert_eq!(
+            resolved.effective_overflow_behavior,
+            OverflowBehavior::Saturate
+        );
+        assert_eq!(
+            resolved.effective_non_finite_policy,
+            NonFinitePolicy::Saturate
+        );
+        assert_eq!(
+            resolved.effective_leap_second_policy,
+            LeapSecondPolicy::ClampTo59
+        );
+        let defaults = CastOptions::new().with_passthrough(false);
+        assert_eq!(
+            defaults.resolve().timestamp_options(),
+            TimestampCastOptions::default()
+        );
+    }
+}
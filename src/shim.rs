@@ -0,0 +1,48 @@
+//! Shims over arrow APIs for building against multiple arrow majors.
+//!
+//! The arrow crates are aliased at the crate root by the `arrow-*` version features,
+//! and construction of arrow types whose shape may differ across majors goes through here.
+
+use std::sync::Arc;
+
+use arrow_cast::display::FormatOptions;
+
+use crate::FormatSettings;
+
+/// The major version of arrow this crate is built against.
+#[cfg(feature = "arrow-50")]
+pub const ARROW_MAJOR_VERSION: u32 = 50;
+/// The major version of arrow this crate is built against.
+#[cfg(feature = "arrow-51")]
+pub const ARROW_MAJOR_VERSION: u32 = 51;
+/// The major version of arrow this crate is built against.
+#[cfg(feature = "arrow-52")]
+pub const ARROW_MAJOR_VERSION: u32 = 52;
+/// The major version of arrow this crate is built against.
+#[cfg(feature = "arrow-54")]
+pub const ARROW_MAJOR_VERSION: u32 = 54;
+
+/// Timezone of timestamp data types.
+pub(crate) type Timezone = Option<Arc<str>>;
+
+/// Build arrow format options from format settings.
+pub(crate) fn format_options<'a>(settings: &FormatSettings<'a>) -> FormatOptions<'a> {
+    FormatOptions::new()
+        .with_null(settings.null())
+        .with_date_format(settings.date_format())
+        .with_datetime_format(settings.datetime_format())
+        .with_timestamp_format(settings.timestamp_format())
+        .with_timestamp_tz_format(settings.timestamp_tz_format())
+        .with_time_format(settings.time_format())
+}
+
+/// Build arrow cast options.
+pub(crate) fn cast_options(
+    safe: bool,
+    format_options: FormatOptions<'_>,
+) -> arrow_cast::CastOptions<'_> {
+    arrow_cast::CastOptions {
+        safe,
+        format_options,
+    }
+}
@@ -0,0 +1,221 @@
+//! Sorting integers of guessed units by time, without casting them.
+
+use arrow_array::{cast::AsArray, types::Int64Type, Array, Int64Array};
+use arrow_schema::{ArrowError, DataType, TimeUnit};
+
+use crate::{
+    guess, guess_precision_with_bound_years, kernel::time_unit_multiple, CastOptions, CastReport,
+    GuessStrategy,
+};
+
+/// Integers ordered as the timestamps they cast to, see [sortable_view].
+///
+/// The values sort and partition in the order of time with the kernels of arrow, but they
+/// are not the values of a cast: they stay in the unit of the source, or in the finest unit
+/// of mixed sources, whatever the target type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SortableEpochs {
+    values: Int64Array,
+    unit: Option<TimeUnit>,
+    normalized: bool,
+}
+
+impl SortableEpochs {
+    /// The integers to sort by, nulls as in the source.
+    pub fn values(&self) -> &Int64Array {
+        &self.values
+    }
+
+    pub fn into_values(self) -> Int64Array {
+        self.values
+    }
+
+    /// The unit of the values, `None` if there is no evidence.
+    pub fn unit(&self) -> Option<TimeUnit> {
+        self.unit
+    }
+
+    /// Whether the source mixes units and the values are rescaled to the finest of them.
+    pub fn is_normalized(&self) -> bool {
+        self.normalized
+    }
+}
+
+/// A view of integers of epochs ordered as their timestamps, without casting them.
+///
+/// Integers guessed in one unit, or of the
+/// [assume_unit](crate::TimestampCastOptions::assume_unit), are ordered by time as they
+/// are and shared with the source. Integers mixing units are rescaled each from its own
+/// guessed unit to the finest unit present, or fail with
+/// [strict](crate::TimestampCastOptions::strict). Rescaling a value beyond the bounds of
+/// `Int64` fails too, as keys saturating at the bounds would tie. Sentinels skipped by
+/// guessing take no part in detecting units.
+///
+/// ```rust
+/// use arrow::{array::Int64Array, datatypes::TimeUnit};
+/// use arrow_cast_guess_precision::{sortable_view, CastOptions};
+///
+/// let array = Int64Array::from(vec![1701325745, 1701325744956, -1701325744]);
+/// let view = sortable_view(&array, &CastOptions::new()).unwrap();
+/// assert!(view.is_normalized());
+/// assert_eq!(view.unit(), Some(TimeUnit::Millisecond));
+/// assert_eq!(view.values().values(), &[1701325745000, 1701325744956, -1701325744000]);
+/// ```
+pub fn sortable_view(
+    array: &dyn Array,
+    cast_options: &CastOptions,
+) -> Result<SortableEpochs, ArrowError> {
+    use DataType::*;
+    let values = match array.data_type() {
+        Int64 => array.as_primitive::<Int64Type>().clone(),
+        Int8 | Int16 | Int32 | UInt8 | UInt16 | UInt32 | UInt64 => {
+            arrow_cast::cast(array, &Int64)?.as_primitive().clone()
+        }
+        from_type => {
+            return Err(ArrowError::CastError(format!(
+                "Cannot sort {from_type:?} as epochs, expected integers"
+            )))
+        }
+    };
+    let options = &cast_options.timestamp_options;
+    if options.assume_unit.is_some() || !options.guess_timestamp_precision {
+        return Ok(SortableEpochs {
+            values,
+            unit: options.assume_unit,
+            normalized: false,
+        });
+    }
+    let guess = |v: i64| guess_precision_with_bound_years(v, options.guessing_bound_years);
+    let mut present = [false; 4];
+    for (_, v) in guess::candidates(&values, options) {
+        present[crate::unit_code(guess(v)) as usize] = true;
+    }
+    let finest = (0..4).rev().find(|code| present[*code]);
+    let unit = finest.and_then(|code| crate::unit_from_code(code as u8));
+    if present.iter().filter(|present| **present).count() <= 1 {
+        return Ok(SortableEpochs {
+            values,
+            unit,
+            normalized: false,
+        });
+    }
+    if options.strict {
+        // Fail as strict guessing does.
+        let mut strict = options.clone();
+        strict.guess_strategy = GuessStrategy::Majority;
+        strict.early_stop = false;
+        strict.guess_sample_size = None;
+        guess::guess_unit(&values, &strict, &mut CastReport::default())?;
+    }
+    let finest = unit.unwrap();
+    let multiple = time_unit_multiple(finest);
+    let values = values.try_unary(|v| {
+        v.checked_mul(multiple / time_unit_multiple(guess(v)))
+            .ok_or_else(|| {
+                ArrowError::CastError(format!(
+                    "Value {v} guessed as {:?} overflows normalizing to {finest:?}",
+                    guess(v)
+                ))
+            })
+    })?;
+    Ok(SortableEpochs {
+        values,
+        unit,
+        normalized: true,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use arrow::compute::sort_to_indices;
+    use arrow_array::{Int32Array, StringArray};
+
+    use super::*;
+
+    #[test]
+    fn test_sortable_view() {
+        // One unit, negative values included, is shared as is.
+        let array = Int64Array::from(vec![
+            Some(1701325744956),
+            None,
+            Some(-1701325744956),
+            Some(1701325744955),
+            Some(-1701325744957),
+        ]);
+        let view = sortable_view(&array, &CastOptions::new().with_passthrough(false)).unwrap();
+        assert!(!view.is_normalized());
+        assert_eq!(view.unit(), Some(TimeUnit::Millisecond));
+        assert_eq!(
+            view.values().values().inner().as_ptr(),
+            array.values().inner().as_ptr()
+        );
+        let order = sort_to_indices(view.values(), None, None).unwrap();
+        assert_eq!(order.values(), &[1, 4, 2, 3, 0]);
+
+        // Mixed units sort as their casts do, per value.
+        let array = Int64Array::from(vec![
+            Some(1701325745),
+            Some(-1701325744956789),
+            Some(1701325744956),
+            None,
+            Some(-1701325745),
+            Some(1701325744956789012),
+            Some(0),
+        ]);
+        let view = sortable_view(&array, &CastOptions::new().with_passthrough(false)).unwrap();
+        assert!(view.is_normalized());
+        assert_eq!(view.unit(), Some(TimeUnit::Nanosecond));
+        assert_eq!(view.values().value(0), 1701325745000000000);
+        assert_eq!(view.values().value(1), -1701325744956789000);
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.guess_strategy = GuessStrategy::PerValue;
+        let casted = crate::cast_with_options(
+            &array,
+            &DataType::Timestamp(TimeUnit::Nanosecond, None),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(
+            sort_to_indices(view.values(), None, None).unwrap(),
+            sort_to_indices(&casted, None, None).unwrap()
+        );
+        assert_eq!(
+            sort_to_indices(view.values(), None, None).unwrap().values(),
+            &[3, 4, 1, 6, 2, 5, 0]
+        );
+        options.timestamp_options.strict = true;
+        assert!(sortable_view(&array, &options).is_err());
+
+        // Values overflowing the finest unit fail, not collapsing to the same key.
+        let array = Int64Array::from(vec![
+            100_000_000_000,
+            200_000_000_000,
+            9_000_000_000_000_000_000,
+        ]);
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.guessing_bound_years = 100_000;
+        assert!(sortable_view(&array, &options).is_err());
+
+        // Assumed units, smaller integers, and no evidence.
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.assume_unit = Some(TimeUnit::Second);
+        let view = sortable_view(&array, &options).unwrap();
+        assert_eq!(view.clone().into_values(), array);
+        assert_eq!(view.unit(), Some(TimeUnit::Second));
+        let array = Int32Array::from(vec![1701325745, -1701325744]);
+        let view = sortable_view(&array, &CastOptions::new().with_passthrough(false)).unwrap();
+        assert_eq!(view.unit(), Some(TimeUnit::Second));
+        let array = Int64Array::from(vec![None]);
+        assert_eq!(
+            sortable_view(&array, &CastOptions::new().with_passthrough(false))
+                .unwrap()
+                .unit(),
+            None
+        );
+        assert!(sortable_view(
+            &StringArray::from(vec!["1"]),
+            &CastOptions::new().with_passthrough(false)
+        )
+        .is_err());
+    }
+}
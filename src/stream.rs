@@ -0,0 +1,161 @@
+//! Async adapter casting streams of record batches.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use arrow_array::RecordBatch;
+use arrow_schema::{ArrowError, SchemaRef};
+use futures_core::Stream;
+
+use crate::{BatchCaster, SchemaCastOptions};
+
+/// A stream casting each batch of the inner stream to the schema.
+///
+/// Units guessed for columns are pinned across the stream's lifetime like [BatchCaster].
+/// Errors of the inner stream or of casting are yielded and the stream goes on,
+/// unless [GuessCastStream::with_stop_on_error] is set.
+///
+/// The inner stream must be [Unpin], pin it in a box if it is not.
+pub struct GuessCastStream<S> {
+    inner: S,
+    caster: BatchCaster<'static>,
+    stop_on_error: bool,
+    done: bool,
+}
+
+impl<S> GuessCastStream<S>
+where
+    S: Stream<Item = Result<RecordBatch, ArrowError>> + Unpin,
+{
+    pub fn new(inner: S, schema: SchemaRef, options: SchemaCastOptions<'static>) -> Self {
+        Self {
+            inner,
+            caster: BatchCaster::new(schema, options),
+            stop_on_error: false,
+            done: false,
+        }
+    }
+
+    /// End the stream after yielding the first error.
+    pub fn with_stop_on_error(mut self, stop_on_error: bool) -> Self {
+        self.stop_on_error = stop_on_error;
+        self
+    }
+
+    /// The target schema.
+    pub fn schema(&self) -> SchemaRef {
+        self.caster.schema()
+    }
+
+    /// The caster with units pinned so far.
+    pub fn caster(&self) -> &BatchCaster<'static> {
+        &self.caster
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S> Stream for GuessCastStream<S>
+where
+    S: Stream<Item = Result<RecordBatch, ArrowError>> + Unpin,
+{
+    type Item = Result<RecordBatch, ArrowError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+        let item = match Pin::new(&mut this.inner).poll_next(cx) {
+            Poll::Ready(Some(batch)) => batch.and_then(|batch| this.caster.cast(&batch)),
+            Poll::Ready(None) => {
+                this.done = true;
+                return Poll::Ready(None);
+            }
+            Poll::Pending => return Poll::Pending,
+        };
+        if item.is_err() && this.stop_on_error {
+            this.done = true;
+        }
+        Poll::Ready(Some(item))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.done {
+            return (0, Some(0));
+        }
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use arrow_array::{cast::AsArray, types::TimestampMillisecondType, ArrayRef, Int64Array};
+    use arrow_schema::{DataType, Field, Schema, TimeUnit};
+    use futures::StreamExt;
+
+    use super::*;
+    use crate::CastOptions;
+
+    fn batch(values: Vec<Option<i64>>) -> Result<RecordBatch, ArrowError> {
+        RecordBatch::try_from_iter([("ts", Arc::new(Int64Array::from(values)) as ArrayRef)])
+    }
+
+    fn assert_send<T: Send>(_: &T) {}
+
+    #[tokio::test]
+    async fn test_guess_cast_stream() {
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "ts",
+            DataType::Timestamp(TimeUnit::Millisecond, None),
+            true,
+        )]));
+        let inner = futures::stream::iter(vec![
+            batch(vec![None, None]),
+            batch(vec![Some(1701325744956)]),
+            Err(ArrowError::ComputeError("inner".to_string())),
+            // Would be guessed as seconds on its own.
+            batch(vec![Some(1701325744)]),
+        ]);
+        let stream = GuessCastStream::new(
+            inner,
+            schema.clone(),
+            SchemaCastOptions::new(CastOptions::new().with_passthrough(false)),
+        );
+        assert_send(&stream);
+        assert_eq!(stream.schema(), schema);
+
+        let batches: Vec<_> = stream.collect().await;
+        assert_eq!(batches.len(), 4);
+        // The all-null batch pins nothing.
+        assert_eq!(batches[0].as_ref().unwrap().column(0).null_count(), 2);
+        let values = |i: usize| {
+            batches[i]
+                .as_ref()
+                .unwrap()
+                .column(0)
+                .as_primitive::<TimestampMillisecondType>()
+                .value(0)
+        };
+        assert_eq!(values(1), 1701325744956);
+        assert!(batches[2].is_err());
+        assert_eq!(values(3), 1701325744);
+
+        let inner = futures::stream::iter(vec![
+            Err(ArrowError::ComputeError("inner".to_string())),
+            batch(vec![Some(1701325744956)]),
+        ]);
+        let stream = GuessCastStream::new(
+            inner,
+            schema,
+            SchemaCastOptions::new(CastOptions::new().with_passthrough(false)),
+        )
+        .with_stop_on_error(true);
+        let batches: Vec<_> = stream.collect().await;
+        assert_eq!(batches.len(), 1);
+    }
+}
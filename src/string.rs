@@ -0,0 +1,1035 @@
+//! Preprocessing strings before parsing them as timestamps.
+
+use std::borrow::Cow;
+use std::ops::Range;
+use std::sync::Arc;
+
+use arrow_array::{
+    cast::AsArray,
+    temporal_conversions::{date32_to_datetime, date64_to_datetime},
+    timezone::Tz,
+    types::{Date32Type, Date64Type, Int64Type},
+    Array, ArrayRef, GenericStringArray, Int64Array, OffsetSizeTrait, StringArray,
+};
+use arrow_cast::parse::{string_to_datetime, Parser};
+use arrow_schema::{ArrowError, DataType, TimeUnit};
+
+use crate::{
+    kernel::{make_timestamp_array, time_unit_multiple},
+    shim::Timezone,
+    CastOptions, CastReport, DateOrder, LeapSecondPolicy, NaiveStringPolicy, StringParseRoute,
+    TimestampCastOptions,
+};
+
+/// UTC as a timezone of arrow.
+fn utc() -> Tz {
+    "+00:00".parse().unwrap()
+}
+
+/// Rewrite strings with a trailing timezone abbreviation to RFC3339 strings in UTC.
+///
+/// Returns `None` if no string is rewritten, fails without the `chrono` feature if
+/// abbreviations are set.
+#[cfg(feature = "chrono")]
+pub(crate) fn resolve_tz_abbreviations(
+    array: &dyn Array,
+    options: &TimestampCastOptions,
+) -> Result<Option<ArrayRef>, ArrowError> {
+    use chrono::{SecondsFormat, TimeZone, Utc};
+
+    let Some(abbreviations) = options.tz_abbreviations.as_ref() else {
+        return Ok(None);
+    };
+    let lookup = |token: &str| -> Option<&str> {
+        if let Some(tz) = abbreviations.get(token) {
+            return Some(tz);
+        }
+        if !options.tz_abbreviations_ignore_case {
+            return None;
+        }
+        abbreviations
+            .iter()
+            .find(|(abbr, _)| abbr.eq_ignore_ascii_case(token))
+            .map(|(_, tz)| tz.as_ref())
+    };
+    let resolve = |s: &str| -> Option<String> {
+        let s = s.trim_end();
+        let (head, token) = s.rsplit_once(char::is_whitespace)?;
+        let tz: Tz = lookup(token)?.parse().ok()?;
+        let naive = string_to_datetime(&Utc, head.trim_end()).ok()?.naive_utc();
+        let datetime = tz.from_local_datetime(&naive).earliest()?;
+        Some(
+            datetime
+                .with_timezone(&Utc)
+                .to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        )
+    };
+    Ok(match array.data_type() {
+        DataType::Utf8 => rewrite::<i32>(array, resolve),
+        DataType::LargeUtf8 => rewrite::<i64>(array, resolve),
+        _ => None,
+    })
+}
+
+#[cfg(not(feature = "chrono"))]
+pub(crate) fn resolve_tz_abbreviations(
+    _array: &dyn Array,
+    options: &TimestampCastOptions,
+) -> Result<Option<ArrayRef>, ArrowError> {
+    match options.tz_abbreviations {
+        Some(_) => Err(ArrowError::CastError(
+            "Resolving timezone abbreviations requires the chrono feature".to_string(),
+        )),
+        None => Ok(None),
+    }
+}
+
+/// Expand dates and datetimes without seconds to strings parsed as timestamps.
+///
+/// `YYYY-MM-DD` and, with [parse_compact_date](TimestampCastOptions::parse_compact_date),
+/// `YYYYMMDD` are midnight, so are the ordinal dates `YYYY-DDD` and week dates `YYYY-Www-D`
+/// with [parse_iso_ordinal_week](TimestampCastOptions::parse_iso_ordinal_week).
+/// `YYYY-MM-DD HH:MM` is at second zero. Strings with month names
+/// are parsed with [named_month_order](TimestampCastOptions::named_month_order). They are naive
+/// like other strings without an offset, so a column mixing them is interpreted consistently.
+///
+/// Returns `None` if no string is expanded.
+pub(crate) fn expand_dates(array: &dyn Array, options: &TimestampCastOptions) -> Option<ArrayRef> {
+    let formats = options.named_month_order.map(named_month_formats);
+    let midnight = |days: i32| Some(format!("{}T00:00:00", date32_to_datetime(days)?.date()));
+    let expand = |s: &str| -> Option<String> {
+        let s = s.trim();
+        let b = s.as_bytes();
+        match b.len() {
+            8 if options.parse_iso_ordinal_week && b[4] == b'-' => {
+                midnight(Date32Type::parse_formatted(s, "%Y-%j")?)
+            }
+            10 if options.parse_iso_ordinal_week && b[5] == b'W' => {
+                midnight(Date32Type::parse_formatted(s, "%G-W%V-%u")?)
+            }
+            8 if options.parse_compact_date && b.iter().all(u8::is_ascii_digit) => {
+                midnight(Date32Type::parse_formatted(s, "%Y%m%d")?)
+            }
+            10 => midnight(Date32Type::parse_formatted(s, "%Y-%m-%d")?),
+            16 if matches!(b[10], b'T' | b' ') => {
+                Date64Type::parse_formatted(&s.replacen(' ', "T", 1), "%Y-%m-%dT%H:%M")?;
+                Some(format!("{s}:00"))
+            }
+            _ => {
+                let formats = formats.as_ref()?;
+                if !s.bytes().any(|b| b.is_ascii_alphabetic()) {
+                    return None;
+                }
+                formats.iter().find_map(|format| {
+                    let datetime = if format.contains(':') {
+                        date64_to_datetime(Date64Type::parse_formatted(s, format)?)?
+                    } else {
+                        date32_to_datetime(Date32Type::parse_formatted(s, format)?)?
+                    };
+                    Some(datetime.format("%Y-%m-%dT%H:%M:%S").to_string())
+                })
+            }
+        }
+    };
+    match array.data_type() {
+        DataType::Utf8 => rewrite::<i32>(array, expand),
+        DataType::LargeUtf8 => rewrite::<i64>(array, expand),
+        _ => None,
+    }
+}
+
+/// Normalize nonstandard offsets and separators of strings which fail to parse, see
+/// [normalize_strings](TimestampCastOptions::normalize_strings).
+///
+/// Returns `None` if no string is normalized.
+pub(crate) fn normalize(array: &dyn Array, options: &TimestampCastOptions) -> Option<ArrayRef> {
+    if !options.normalize_strings {
+        return None;
+    }
+    let utc = utc();
+    let normalize = |s: &str| -> Option<String> {
+        let s = s.trim();
+        if is_integer_like(s) || string_to_datetime(&utc, s).is_ok() {
+            return None;
+        }
+        let mut normalized = match s.strip_suffix(" UTC").or_else(|| s.strip_suffix(" Z")) {
+            Some(head) => format!("{}Z", head.trim_end()),
+            None => normalize_offset(s).unwrap_or_else(|| s.to_string()),
+        };
+        if options.normalize_date_slashes {
+            let b = normalized.as_bytes();
+            if b.len() >= 10 && b[4] == b'/' && b[7] == b'/' {
+                let date = normalized[..10].replace('/', "-");
+                if Date32Type::parse_formatted(&date, "%Y-%m-%d").is_some() {
+                    normalized.replace_range(..10, &date);
+                }
+            }
+        }
+        (normalized != s).then_some(normalized)
+    };
+    match array.data_type() {
+        DataType::Utf8 => rewrite::<i32>(array, normalize),
+        DataType::LargeUtf8 => rewrite::<i64>(array, normalize),
+        _ => None,
+    }
+}
+
+/// Rewrite a trailing `±HHMM`, `±HH` or `±HH:MM` offset after the date to `±HH:MM`,
+/// removing whitespace before it.
+fn normalize_offset(s: &str) -> Option<String> {
+    let sign = s.rfind(['+', '-']).filter(|&i| i > 10)?;
+    let offset = &s[sign + 1..];
+    let (hours, minutes) = match offset.len() {
+        2 => (offset, "00"),
+        4 => offset.split_at(2),
+        5 if offset.as_bytes()[2] == b':' => (&offset[..2], &offset[3..]),
+        _ => return None,
+    };
+    if !(hours.bytes().chain(minutes.bytes())).all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let head = s[..sign].trim_end();
+    Some(format!("{head}{}{hours}:{minutes}", &s[sign..sign + 1]))
+}
+
+/// Apply the [naive_string_policy](TimestampCastOptions::naive_string_policy) of strings
+/// casting to timestamps without a timezone.
+///
+/// Returns `None` if no string is changed.
+pub(crate) fn apply_naive_policy(
+    array: &dyn Array,
+    cast_options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<Option<ArrayRef>, ArrowError> {
+    match &cast_options.timestamp_options.naive_string_policy {
+        NaiveStringPolicy::AssumeUtc => Ok(None),
+        NaiveStringPolicy::AssumeZone(tz) => {
+            let tz: Tz = tz.parse()?;
+            let resolve = |s: &str| -> Option<String> {
+                if has_offset(s) {
+                    return None;
+                }
+                let datetime = string_to_datetime(&tz, s.trim()).ok()?;
+                Some(
+                    datetime
+                        .naive_utc()
+                        .format("%Y-%m-%dT%H:%M:%S%.fZ")
+                        .to_string(),
+                )
+            };
+            Ok(match array.data_type() {
+                DataType::Utf8 => rewrite::<i32>(array, resolve),
+                DataType::LargeUtf8 => rewrite::<i64>(array, resolve),
+                _ => None,
+            })
+        }
+        NaiveStringPolicy::ErrorOnOffset => match array.data_type() {
+            DataType::Utf8 => reject_offsets::<i32>(array, cast_options, report),
+            DataType::LargeUtf8 => reject_offsets::<i64>(array, cast_options, report),
+            _ => Ok(None),
+        },
+    }
+}
+
+/// Whether the string is a datetime with an explicit offset, parsed the same in any timezone.
+fn has_offset(s: &str) -> bool {
+    let s = s.trim();
+    let east: Tz = "+01:00".parse().unwrap();
+    match (string_to_datetime(&utc(), s), string_to_datetime(&east, s)) {
+        (Ok(utc), Ok(east)) => utc == east,
+        _ => false,
+    }
+}
+
+/// Null strings with an explicit offset when `safe`, or fail on the first of them.
+fn reject_offsets<O: OffsetSizeTrait>(
+    array: &dyn Array,
+    cast_options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<Option<ArrayRef>, ArrowError> {
+    let array = array.as_string::<O>();
+    let mut rejected = 0;
+    let mut strings = Vec::with_capacity(array.len());
+    for s in array.iter() {
+        match s {
+            Some(s) if has_offset(s) => {
+                if !cast_options.safe {
+                    return Err(ArrowError::CastError(format!(
+                        "Cannot cast {s:?} with an explicit offset to a timestamp without timezone"
+                    )));
+                }
+                rejected += 1;
+                strings.push(None);
+            }
+            s => strings.push(s),
+        }
+    }
+    if rejected == 0 {
+        return Ok(None);
+    }
+    report.null_causes.policy += rejected;
+    Ok(Some(Arc::new(
+        strings.into_iter().collect::<GenericStringArray<O>>(),
+    )))
+}
+
+/// Formats of datetimes with month names in the order, with optional times.
+fn named_month_formats(order: DateOrder) -> Vec<String> {
+    let dates: &[&str] = match order {
+        DateOrder::MonthFirst => &["{month} %d, %Y", "{month} %d %Y"],
+        DateOrder::DayFirst => &["%d-{month}-%Y", "%d {month} %Y", "%d {month}, %Y"],
+    };
+    let times = ["", " %H:%M:%S", " %H:%M", " %I:%M:%S %p", " %I:%M %p"];
+    let mut formats = Vec::new();
+    // Abbreviated and full month names.
+    for month in ["%b", "%B"] {
+        for date in dates {
+            for time in times {
+                formats.push(format!("{}{time}", date.replace("{month}", month)));
+            }
+        }
+    }
+    formats
+}
+
+/// Byte range of a seconds field of `60` in the string, with the fraction following it.
+fn find_leap_second(s: &str) -> Option<Range<usize>> {
+    let b = s.as_bytes();
+    let digits = |range: Range<usize>| b[range].iter().all(u8::is_ascii_digit);
+    (5..b.len().saturating_sub(2)).find_map(|p| {
+        let is_leap = b[p] == b':'
+            && b[p - 3] == b':'
+            && &b[p + 1..p + 3] == b"60"
+            && digits(p - 5..p - 3)
+            && digits(p - 2..p)
+            && b.get(p + 3).is_none_or(|b| !b.is_ascii_digit());
+        if !is_leap {
+            return None;
+        }
+        let mut end = p + 3;
+        if matches!(b.get(end), Some(b'.' | b',')) {
+            end += 1;
+            while b.get(end).is_some_and(u8::is_ascii_digit) {
+                end += 1;
+            }
+        }
+        Some(p + 1..end)
+    })
+}
+
+/// Whether any string has a leap second.
+pub(crate) fn has_leap_seconds(array: &dyn Array) -> bool {
+    match array.data_type() {
+        DataType::Utf8 => array
+            .as_string::<i32>()
+            .iter()
+            .flatten()
+            .any(|s| find_leap_second(s).is_some()),
+        DataType::LargeUtf8 => array
+            .as_string::<i64>()
+            .iter()
+            .flatten()
+            .any(|s| find_leap_second(s).is_some()),
+        _ => false,
+    }
+}
+
+/// Replace leap seconds by second `59` of the same minute, dropping the fraction,
+/// returning the rows replaced for [shift_leap_seconds].
+///
+/// With [LeapSecondPolicy::Null] and [LeapSecondPolicy::Error], leap seconds are null or
+/// fail the cast instead.
+pub(crate) fn replace_leap_seconds(
+    array: &dyn Array,
+    cast_options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<(ArrayRef, Vec<usize>), ArrowError> {
+    let policy = cast_options.leap_second_policy();
+    let strings = arrow_cast::cast(array, &DataType::Utf8)?;
+    let strings = strings.as_string::<i32>();
+    let mut leaps = Vec::new();
+    let mut values = Vec::with_capacity(strings.len());
+    for (i, s) in strings.iter().enumerate() {
+        let Some((s, range)) = s.and_then(|s| Some((s, find_leap_second(s)?))) else {
+            values.push(s.map(Cow::Borrowed));
+            continue;
+        };
+        match policy {
+            LeapSecondPolicy::Null => {
+                report.null_causes.policy += 1;
+                values.push(None);
+            }
+            LeapSecondPolicy::Error => {
+                return Err(ArrowError::CastError(format!(
+                    "Cannot cast string '{s}' at row {i} to timestamp: leap second"
+                )))
+            }
+            LeapSecondPolicy::ClampTo59 | LeapSecondPolicy::FoldToNextSecond => {
+                leaps.push(i);
+                values.push(Some(Cow::Owned(format!(
+                    "{}59{}",
+                    &s[..range.start],
+                    &s[range.end..]
+                ))));
+            }
+        }
+    }
+    let array: StringArray = values.into_iter().collect();
+    Ok((Arc::new(array), leaps))
+}
+
+/// Shift timestamps of the rows replaced by [replace_leap_seconds] by the policy.
+pub(crate) fn shift_leap_seconds(
+    array: &ArrayRef,
+    leaps: &[usize],
+    unit: TimeUnit,
+    tz: &Timezone,
+    cast_options: &CastOptions,
+) -> Result<ArrayRef, ArrowError> {
+    let offset = match cast_options.leap_second_policy() {
+        LeapSecondPolicy::ClampTo59 => time_unit_multiple(unit) - 1,
+        LeapSecondPolicy::FoldToNextSecond => time_unit_multiple(unit),
+        LeapSecondPolicy::Null | LeapSecondPolicy::Error => return Ok(array.clone()),
+    };
+    if leaps.is_empty() {
+        return Ok(array.clone());
+    }
+    let integers = arrow_cast::cast(array, &DataType::Int64)?;
+    let mut values: Vec<Option<i64>> = integers.as_primitive::<Int64Type>().iter().collect();
+    for &i in leaps {
+        values[i] = values[i].and_then(|v| v.checked_add(offset));
+    }
+    Ok(make_timestamp_array(
+        &Int64Array::from(values),
+        unit,
+        tz.clone(),
+    ))
+}
+
+/// Strings of the rows null in `parsed`, null for the others.
+pub(crate) fn unparsed(array: &dyn Array, parsed: &dyn Array) -> Result<StringArray, ArrowError> {
+    let strings = arrow_cast::cast(array, &DataType::Utf8)?;
+    Ok(strings
+        .as_string::<i32>()
+        .iter()
+        .enumerate()
+        .map(|(i, s)| s.filter(|_| parsed.is_null(i)))
+        .collect())
+}
+
+/// Timestamps of `parsed`, filled by those of `filling` where null.
+pub(crate) fn fill_timestamps(
+    parsed: &dyn Array,
+    filling: &dyn Array,
+    unit: TimeUnit,
+    tz: &Timezone,
+) -> Result<ArrayRef, ArrowError> {
+    let parsed = arrow_cast::cast(parsed, &DataType::Int64)?;
+    let filling = arrow_cast::cast(filling, &DataType::Int64)?;
+    let values: Int64Array = parsed
+        .as_primitive::<Int64Type>()
+        .iter()
+        .zip(filling.as_primitive::<Int64Type>())
+        .map(|(parsed, filling)| parsed.or(filling))
+        .collect();
+    Ok(make_timestamp_array(&values, unit, tz.clone()))
+}
+
+/// Parse strings to integers, returns `None` if no string is an integer.
+///
+/// Strings not fitting in `i64`, including integers beyond its range, are null
+/// when `safe`, or fail the cast with the row index otherwise.
+pub(crate) fn parse_integers(
+    array: &dyn Array,
+    cast_options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<Option<Int64Array>, ArrowError> {
+    let (integers, failures) = integers_of(array, &cast_options.timestamp_options)?;
+    accept_integers(array, integers, failures, cast_options, report)
+}
+
+/// Integers of the strings, null where not an integer, with the number of strings which
+/// are not.
+pub(crate) fn integers_of(
+    array: &dyn Array,
+    options: &TimestampCastOptions,
+) -> Result<(Int64Array, usize), ArrowError> {
+    let strings = arrow_cast::cast(array, &DataType::Utf8)?;
+    let mut failures = 0;
+    let integers = strings
+        .as_string::<i32>()
+        .iter()
+        .map(|s| {
+            let v = parse_integer(s?, options);
+            failures += usize::from(v.is_none());
+            v
+        })
+        .collect();
+    Ok((integers, failures))
+}
+
+/// Take the integers of [integers_of] as [parse_integers] does, counting them in the report.
+pub(crate) fn accept_integers(
+    array: &dyn Array,
+    integers: Int64Array,
+    failures: usize,
+    cast_options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<Option<Int64Array>, ArrowError> {
+    if integers.null_count() == integers.len() {
+        return Ok(None);
+    }
+    if failures > 0 && !cast_options.safe {
+        let strings = arrow_cast::cast(array, &DataType::Utf8)?;
+        let strings = strings.as_string::<i32>();
+        let i = (0..strings.len())
+            .find(|&i| strings.is_valid(i) && integers.is_null(i))
+            .unwrap_or_default();
+        let s = strings.value(i);
+        let reason = if is_integer_like(s) {
+            "out of range of Int64"
+        } else {
+            "not an integer"
+        };
+        return Err(ArrowError::CastError(format!(
+            "Cannot cast string '{s}' at row {i} to timestamp integer: {reason}"
+        )));
+    }
+    report.null_causes.parse_failure += failures;
+    report.string_parse_counts.numeric_epoch += integers.len() - integers.null_count();
+    Ok(Some(integers))
+}
+
+/// Number of strings sampled by [sample_route].
+const INTEGER_SAMPLES: usize = 8;
+
+/// The route of the strings by their first non-null strings, up to [INTEGER_SAMPLES].
+///
+/// Arrays sampling all as integers take [StringParseRoute::Integers], integers never parse
+/// as timestamps. Others, and ambiguous samples like compact dates with
+/// [parse_compact_date](TimestampCastOptions::parse_compact_date), take
+/// [StringParseRoute::Timestamps].
+pub(crate) fn sample_route(array: &dyn Array, options: &TimestampCastOptions) -> StringParseRoute {
+    fn sample<O: OffsetSizeTrait>(
+        array: &GenericStringArray<O>,
+        options: &TimestampCastOptions,
+    ) -> bool {
+        let is_integer = |s: &str| {
+            let compact_date = options.parse_compact_date && s.trim().len() == 8;
+            !compact_date && parse_integer(s, options).is_some()
+        };
+        let mut samples = array.iter().flatten().take(INTEGER_SAMPLES).peekable();
+        samples.peek().is_some() && samples.all(is_integer)
+    }
+    let integers = match array.data_type() {
+        DataType::Utf8 => sample(array.as_string::<i32>(), options),
+        DataType::LargeUtf8 => sample(array.as_string::<i64>(), options),
+        _ => false,
+    };
+    match integers {
+        true => StringParseRoute::Integers,
+        false => StringParseRoute::Timestamps,
+    }
+}
+
+/// Code points of the digit zero of Unicode decimal digit blocks, followed by one to nine.
+const UNICODE_ZEROS: [u32; 22] = [
+    0x0660, 0x06F0, 0x07C0, 0x0966, 0x09E6, 0x0A66, 0x0AE6, 0x0B66, 0x0BE6, 0x0C66, 0x0CE6, 0x0D66,
+    0x0DE6, 0x0E50, 0x0ED0, 0x0F20, 0x1040, 0x1090, 0x17E0, 0x1810, 0x1946, 0xFF10,
+];
+
+/// Parse an integer without a leading byte order mark, reading Unicode decimal digits with
+/// [normalize_unicode_digits](TimestampCastOptions::normalize_unicode_digits).
+fn parse_integer(s: &str, options: &TimestampCastOptions) -> Option<i64> {
+    let s = s.strip_prefix('\u{feff}').unwrap_or(s);
+    if s.is_ascii() || !options.normalize_unicode_digits {
+        return s.parse().ok();
+    }
+    let ascii: String = s.chars().map(ascii_digit).collect::<Option<_>>()?;
+    ascii.parse().ok()
+}
+
+/// The ASCII form of a Unicode decimal digit or full-width sign, ASCII kept as is.
+fn ascii_digit(c: char) -> Option<char> {
+    match c {
+        '\u{ff0b}' => Some('+'),
+        '\u{ff0d}' => Some('-'),
+        c if c.is_ascii() => Some(c),
+        c => UNICODE_ZEROS
+            .iter()
+            .find(|zero| (**zero..**zero + 10).contains(&(c as u32)))
+            .and_then(|zero| char::from_digit(c as u32 - zero, 10)),
+    }
+}
+
+/// Whether the string is an optionally signed sequence of digits.
+fn is_integer_like(s: &str) -> bool {
+    let digits = s.strip_prefix(['-', '+']).unwrap_or(s);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Rewrite strings by `f`, returns `None` if nothing changed.
+fn rewrite<O: OffsetSizeTrait>(
+    array: &dyn Array,
+    f: impl Fn(&str) -> Option<String>,
+) -> Option<ArrayRef> {
+    let array = array
+        .as_any()
+        .downcast_ref::<GenericStringArray<O>>()
+        .unwrap();
+    let mut changed = false;
+    let strings: Vec<Option<Cow<str>>> = array
+        .iter()
+        .map(|s| {
+            s.map(|s| match f(s) {
+                Some(rewritten) => {
+                    changed = true;
+                    Cow::Owned(rewritten)
+                }
+                None => Cow::Borrowed(s),
+            })
+        })
+        .collect();
+    if !changed {
+        return None;
+    }
+    Some(Arc::new(
+        strings.into_iter().collect::<GenericStringArray<O>>(),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::types::{TimestampMillisecondType, TimestampSecondType};
+
+    use super::*;
+    use crate::{StringParseCounts, StringParseKind};
+
+    #[test]
+    fn test_huge_integer_strings() {
+        let array = StringArray::from(vec![
+            Some("18446744073709551615"),
+            Some("123456789012345678901234"),
+            Some("-9223372036854775809"),
+            None,
+            Some("1701325744956"),
+            Some("1701325745956"),
+        ]);
+        let to_type = DataType::Timestamp(TimeUnit::Millisecond, None);
+        let (casted, report) = crate::cast_with_report(
+            &array,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        // Huge rows are excluded from guessing, which picks milliseconds.
+        assert_eq!(report.guessed_unit, Some(TimeUnit::Millisecond));
+        assert_eq!(report.null_causes.parse_failure, 3);
+        let casted = casted.as_primitive::<TimestampMillisecondType>();
+        assert_eq!(casted.null_count(), 4);
+        assert_eq!(casted.value(4), 1701325744956);
+        assert_eq!(casted.value(5), 1701325745956);
+
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.safe = false;
+        let err = crate::cast_with_options(&array, &to_type, &options).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("'18446744073709551615' at row 0 to timestamp integer: out of range"));
+
+        // Valid integers, and then valid timestamps, succeed without `safe`.
+        let array = StringArray::from(vec![Some("1701325744956"), None]);
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        assert_eq!(casted.null_count(), 1);
+        let array = StringArray::from(vec!["2023-11-30T06:29:04.956Z"]);
+        assert!(crate::cast_with_options(&array, &to_type, &options).is_ok());
+        let array = StringArray::from(vec!["2023-11-30T06:29:04.956Z", "bad"]);
+        assert!(crate::cast_with_options(&array, &to_type, &options).is_err());
+    }
+
+    #[test]
+    fn test_unicode_digits() {
+        let to_type = DataType::Timestamp(TimeUnit::Millisecond, None);
+        // A byte order mark on the first row of a file.
+        let array = StringArray::from(vec!["\u{feff}1701325744956", "1701325745956"]);
+        let (casted, report) = crate::cast_with_report(
+            &array,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        assert_eq!(report.null_causes.parse_failure, 0);
+        let casted = casted.as_primitive::<TimestampMillisecondType>();
+        assert_eq!(casted.values(), &[1701325744956, 1701325745956]);
+
+        // Full-width digits, and Arabic-Indic digits, only with the flag.
+        let array = StringArray::from(vec![
+            "１７０１３２５７４４",
+            "１７０１３２５７４４９５６",
+            "－１７０１３２５７４４９５６",
+            "١٧٠١٣٢٥٧٤٤٩٥٦",
+            "1701325744956",
+            "１７½",
+        ]);
+        let casted = crate::cast_with_options(
+            &array,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        assert_eq!(casted.null_count(), 5);
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.normalize_unicode_digits = true;
+        options.timestamp_options.guess_strategy = crate::GuessStrategy::PerValue;
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        let casted = casted.as_primitive::<TimestampMillisecondType>();
+        assert_eq!(
+            casted.iter().collect::<Vec<_>>(),
+            vec![
+                Some(1701325744000),
+                Some(1701325744956),
+                Some(-1701325744956),
+                Some(1701325744956),
+                Some(1701325744956),
+                None
+            ]
+        );
+
+        // Strings parsing as timestamps are not affected.
+        let array = StringArray::from(vec!["2023-11-30T06:29:04.956Z", "１７０１３２５７４４"]);
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        assert!(casted.is_null(1));
+    }
+
+    #[test]
+    fn test_string_parse_kind() {
+        let to_type = DataType::Timestamp(TimeUnit::Millisecond, None);
+        let report = |strings: Vec<Option<&str>>| {
+            let array = StringArray::from(strings);
+            crate::cast_with_report(
+                &array,
+                &to_type,
+                &CastOptions::new().with_passthrough(false),
+            )
+            .unwrap()
+            .1
+        };
+
+        let iso = report(vec![
+            Some("2023-11-30T06:29:04Z"),
+            // Expanded to midnight.
+            Some("2023-11-30"),
+            Some("bad"),
+            None,
+        ]);
+        assert_eq!(iso.string_parse_kind, Some(StringParseKind::Iso8601));
+        assert_eq!(
+            iso.string_parse_counts,
+            StringParseCounts {
+                iso8601: 2,
+                numeric_epoch: 0,
+                numeric_sampled: 0
+            }
+        );
+        assert_eq!(iso.null_causes.parse_failure, 1);
+
+        let numeric = report(vec![Some("1701325744"), Some("bad"), None]);
+        assert_eq!(
+            numeric.string_parse_kind,
+            Some(StringParseKind::NumericEpoch {
+                guessed_unit: Some(TimeUnit::Second)
+            })
+        );
+        assert_eq!(
+            numeric.string_parse_counts,
+            StringParseCounts {
+                iso8601: 0,
+                numeric_epoch: 1,
+                numeric_sampled: 0
+            }
+        );
+        assert_eq!(numeric.null_causes.parse_failure, 1);
+
+        let failed = report(vec![Some("bad"), None]);
+        assert_eq!(failed.string_parse_kind, None);
+        assert_eq!(failed.string_parse_counts, StringParseCounts::default());
+        let array = StringArray::from(vec!["1"]);
+        let (_, other) = crate::cast_with_report(
+            &array,
+            &DataType::Int64,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        assert_eq!(other.string_parse_kind, None);
+    }
+
+    #[test]
+    fn test_leap_seconds() {
+        let array = StringArray::from(vec![
+            Some("2016-12-31T23:59:60Z"),
+            Some("2017-01-01T08:59:60.5+09:00"),
+            Some("2016-12-31T23:59:59Z"),
+            None,
+        ]);
+        let end_of_2016: i64 = 1483228799;
+        let cast = |policy: Option<LeapSecondPolicy>, safe: bool, unit: TimeUnit| {
+            let mut options = CastOptions::new().with_passthrough(false);
+            options.safe = safe;
+            options.timestamp_options.leap_second_policy = policy;
+            crate::cast_with_report(&array, &DataType::Timestamp(unit, None), &options)
+        };
+
+        let (casted, _) = cast(
+            Some(LeapSecondPolicy::ClampTo59),
+            true,
+            TimeUnit::Millisecond,
+        )
+        .unwrap();
+        let casted = casted.as_primitive::<TimestampMillisecondType>();
+        assert_eq!(casted.value(0), end_of_2016 * 1000 + 999);
+        assert_eq!(casted.value(1), end_of_2016 * 1000 + 999);
+        assert_eq!(casted.value(2), end_of_2016 * 1000);
+        assert!(casted.is_null(3));
+        let (casted, _) = cast(Some(LeapSecondPolicy::ClampTo59), true, TimeUnit::Second).unwrap();
+        let casted = casted.as_primitive::<TimestampSecondType>();
+        assert_eq!(casted.value(0), end_of_2016);
+        assert_eq!(casted.value(1), end_of_2016);
+
+        let (casted, _) = cast(
+            Some(LeapSecondPolicy::FoldToNextSecond),
+            true,
+            TimeUnit::Second,
+        )
+        .unwrap();
+        let casted = casted.as_primitive::<TimestampSecondType>();
+        assert_eq!(casted.value(0), end_of_2016 + 1);
+        assert_eq!(casted.value(1), end_of_2016 + 1);
+        assert_eq!(casted.value(2), end_of_2016);
+
+        let (casted, report) = cast(Some(LeapSecondPolicy::Null), false, TimeUnit::Second).unwrap();
+        assert_eq!(casted.null_count(), 3);
+        assert_eq!(report.null_causes.policy, 2);
+        // Not set follows `safe`.
+        let (casted, _) = cast(None, true, TimeUnit::Second).unwrap();
+        assert_eq!(casted.null_count(), 3);
+
+        let err = cast(Some(LeapSecondPolicy::Error), true, TimeUnit::Second).unwrap_err();
+        assert!(err.to_string().contains("'2016-12-31T23:59:60Z' at row 0"));
+        assert!(cast(None, false, TimeUnit::Second).is_err());
+    }
+
+    #[test]
+    fn test_date_only_strings() {
+        let array = StringArray::from(vec![
+            Some("2023-11-30T06:29:04"),
+            Some("2023-11-30"),
+            Some("2023-11-30 06:29"),
+            Some("20231130"),
+            Some("2023-13-30"),
+            None,
+        ]);
+        let to_type = DataType::Timestamp(TimeUnit::Millisecond, Some("+08:00".into()));
+        let mut options = CastOptions::new().with_passthrough(false);
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        let casted = casted.as_primitive::<TimestampMillisecondType>();
+        let full = casted.value(0);
+        assert_eq!(casted.value(1), full - (6 * 3600 + 29 * 60 + 4) * 1000);
+        assert_eq!(casted.value(2), full - 4 * 1000);
+        assert_eq!(
+            casted
+                .value_as_datetime_with_tz(1, "+08:00".parse().unwrap())
+                .unwrap()
+                .to_rfc3339(),
+            "2023-11-30T00:00:00+08:00"
+        );
+        assert!(casted.is_null(3));
+        assert!(casted.is_null(4));
+        assert!(casted.is_null(5));
+
+        options.timestamp_options.parse_compact_date = true;
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        let casted = casted.as_primitive::<TimestampMillisecondType>();
+        assert_eq!(casted.value(3), casted.value(1));
+    }
+
+    #[test]
+    fn test_iso_ordinal_week_dates() {
+        let array = StringArray::from(vec![
+            "2023-334",
+            "2023-W48-4",
+            // Monday of the first week of 2020 is in 2019.
+            "2020-W01-1",
+            "2020-366",
+            "2023-366",
+            "2023-W54-1",
+            "2023-11-30",
+        ]);
+        let to_type = DataType::Timestamp(TimeUnit::Second, Some("+08:00".into()));
+        let mut options = CastOptions::new().with_passthrough(false);
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        assert_eq!(casted.null_count(), 6);
+
+        options.timestamp_options.parse_iso_ordinal_week = true;
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        let casted = casted.as_primitive::<TimestampSecondType>();
+        let dates: Vec<Option<String>> = (0..casted.len())
+            .map(|i| {
+                casted
+                    .value_as_datetime_with_tz(i, "+08:00".parse().unwrap())
+                    .filter(|_| casted.is_valid(i))
+                    .map(|dt| dt.to_rfc3339())
+            })
+            .collect();
+        assert_eq!(
+            dates,
+            vec![
+                Some("2023-11-30T00:00:00+08:00".to_string()),
+                Some("2023-11-30T00:00:00+08:00".to_string()),
+                Some("2019-12-30T00:00:00+08:00".to_string()),
+                Some("2020-12-31T00:00:00+08:00".to_string()),
+                None,
+                None,
+                Some("2023-11-30T00:00:00+08:00".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_named_month_strings() {
+        let array = StringArray::from(vec![
+            "Nov 30, 2023 12:00 PM",
+            "November 30 2023 06:29:04",
+            "nov 30, 2023 12:29:04 am",
+            "Nov 30, 2023",
+            "30-Nov-2023 06:29:04",
+            "03-04-2023",
+        ]);
+        let to_type = DataType::Timestamp(TimeUnit::Second, None);
+        let mut options = CastOptions::new().with_passthrough(false);
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        assert_eq!(casted.null_count(), 6);
+
+        let datetimes = |options: &CastOptions| -> Vec<Option<String>> {
+            let casted = crate::cast_with_options(&array, &to_type, options).unwrap();
+            let casted = casted.as_primitive::<TimestampSecondType>();
+            (0..casted.len())
+                .map(|i| {
+                    casted
+                        .value_as_datetime(i)
+                        .filter(|_| casted.is_valid(i))
+                        .map(|dt| dt.to_string())
+                })
+                .collect()
+        };
+        options.timestamp_options.named_month_order = Some(DateOrder::MonthFirst);
+        assert_eq!(
+            datetimes(&options),
+            vec![
+                Some("2023-11-30 12:00:00".to_string()),
+                Some("2023-11-30 06:29:04".to_string()),
+                Some("2023-11-30 00:29:04".to_string()),
+                Some("2023-11-30 00:00:00".to_string()),
+                None,
+                None,
+            ]
+        );
+        options.timestamp_options.named_month_order = Some(DateOrder::DayFirst);
+        let datetimes = datetimes(&options);
+        assert_eq!(datetimes[4].as_deref(), Some("2023-11-30 06:29:04"));
+        assert_eq!(datetimes.iter().flatten().count(), 1);
+
+        options.safe = false;
+        assert!(crate::cast_with_options(&array, &to_type, &options).is_err());
+    }
+
+    #[test]
+    fn test_normalize_strings() {
+        let pairs = [
+            ("2023-11-30 06:29:04.000 +0800", "2023-11-30T06:29:04+08:00"),
+            ("2023/11/30T06:29:04+08", "2023-11-30T06:29:04+08:00"),
+            ("2023-11-30 06:29:04 UTC", "2023-11-30T06:29:04Z"),
+            ("2023-11-30T06:29:04 Z", "2023-11-30T06:29:04Z"),
+            ("2023/11/30 06:29:04 -0530", "2023-11-30T06:29:04-05:30"),
+        ];
+        let (nonstandard, normalized): (Vec<_>, Vec<_>) = pairs.into_iter().unzip();
+        let nonstandard = StringArray::from(nonstandard);
+        let normalized = StringArray::from(normalized);
+        let to_type = DataType::Timestamp(TimeUnit::Second, None);
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.normalize_strings = true;
+        options.timestamp_options.normalize_date_slashes = true;
+        let expected = crate::cast_with_options(&normalized, &to_type, &options).unwrap();
+        assert_eq!(expected.null_count(), 0);
+        let casted = crate::cast_with_options(&nonstandard, &to_type, &options).unwrap();
+        assert_eq!(&casted, &expected);
+
+        // Not normalized without opting in.
+        let casted = crate::cast_with_options(
+            &nonstandard,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        assert!(casted.null_count() > 0);
+        // Strings which parse are kept.
+        assert!(normalize(&normalized, &options.timestamp_options).is_none());
+    }
+
+    #[test]
+    fn test_naive_string_policy() {
+        let array = StringArray::from(vec![
+            Some("2023-11-30T06:29:04+08:00"),
+            Some("2023-11-30T06:29:04-05:00"),
+            Some("2023-11-30T06:29:04"),
+            Some("2023-11-30 06:29:04Z"),
+            None,
+        ]);
+        let to_type = DataType::Timestamp(TimeUnit::Second, None);
+        let values = |options: &CastOptions| {
+            let casted = crate::cast_with_options(&array, &to_type, options).unwrap();
+            casted
+                .as_primitive::<TimestampSecondType>()
+                .iter()
+                .collect::<Vec<_>>()
+        };
+        let naive = 1701325744;
+        let offsets = [naive - 8 * 3600, naive + 5 * 3600];
+
+        let mut options = CastOptions::new().with_passthrough(false);
+        assert_eq!(
+            values(&options),
+            [
+                Some(offsets[0]),
+                Some(offsets[1]),
+                Some(naive),
+                Some(naive),
+                None
+            ]
+        );
+
+        options.timestamp_options.naive_string_policy =
+            NaiveStringPolicy::AssumeZone("+08:00".into());
+        assert_eq!(
+            values(&options),
+            [
+                Some(offsets[0]),
+                Some(offsets[1]),
+                Some(naive - 8 * 3600),
+                Some(naive),
+                None
+            ]
+        );
+
+        options.timestamp_options.naive_string_policy = NaiveStringPolicy::ErrorOnOffset;
+        assert_eq!(values(&options), [None, None, Some(naive), None, None]);
+        let (_, report) = crate::cast_with_report(&array, &to_type, &options).unwrap();
+        assert_eq!(report.null_causes.policy, 3);
+        options.safe = false;
+        assert!(crate::cast_with_options(&array, &to_type, &options).is_err());
+
+        // Timezones in the target are not affected.
+        let to_type = DataType::Timestamp(TimeUnit::Second, Some("+00:00".into()));
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        assert_eq!(casted.null_count(), 1);
+    }
+}
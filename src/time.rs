@@ -0,0 +1,464 @@
+//! Casting time-of-day strings to `Time32` and `Time64`.
+
+use std::sync::Arc;
+
+use arrow_array::{
+    cast::AsArray,
+    types::{
+        Date32Type, Date64Type, Int64Type, Time32MillisecondType, Time32SecondType,
+        Time64MicrosecondType, Time64NanosecondType,
+    },
+    Array, ArrayRef, PrimitiveArray,
+};
+use arrow_schema::{ArrowError, DataType, TimeUnit};
+
+use crate::{
+    kernel::{downscale, make_timestamp_array, overflow, time_unit_multiple},
+    CastOptions, CastReport,
+};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Guess the unit of a time-of-day integer, `None` if it is not within one day in nanoseconds.
+///
+/// ```text
+/// value < 86_400             => Second
+/// value < 86_400_000         => Millisecond
+/// value < 86_400_000_000     => Microsecond
+/// value < 86_400_000_000_000 => Nanosecond
+/// ```
+#[inline]
+pub const fn guess_time_of_day_unit(value: i64) -> Option<TimeUnit> {
+    if value < 0 {
+        return None;
+    }
+    if value < SECONDS_PER_DAY {
+        return Some(TimeUnit::Second);
+    }
+    if value < SECONDS_PER_DAY * 1_000 {
+        return Some(TimeUnit::Millisecond);
+    }
+    if value < SECONDS_PER_DAY * 1_000_000 {
+        return Some(TimeUnit::Microsecond);
+    }
+    if value < SECONDS_PER_DAY * 1_000_000_000 {
+        return Some(TimeUnit::Nanosecond);
+    }
+    None
+}
+
+/// Parse `HH:MM[:SS[.fraction]]`, precision is chosen by the number of fraction digits.
+fn parse_clock(s: &str) -> Option<(i64, TimeUnit)> {
+    let (clock, fraction) = match s.split_once('.') {
+        Some((clock, fraction)) => (clock, Some(fraction)),
+        None => (s, None),
+    };
+    let parts: Vec<&str> = clock.split(':').collect();
+    let component = |part: &str, max: i64| -> Option<i64> {
+        if part.is_empty() || part.len() > 2 || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        part.parse().ok().filter(|v| *v < max)
+    };
+    let (hour, minute, second) = match parts.as_slice() {
+        [hour, minute] if fraction.is_none() => (component(hour, 24)?, component(minute, 60)?, 0),
+        [hour, minute, second] => (
+            component(hour, 24)?,
+            component(minute, 60)?,
+            component(second, 60)?,
+        ),
+        _ => return None,
+    };
+    let seconds = hour * 3_600 + minute * 60 + second;
+    let Some(fraction) = fraction else {
+        return Some((seconds, TimeUnit::Second));
+    };
+    if fraction.is_empty() || fraction.len() > 9 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let (unit, digits) = match fraction.len() {
+        1..=3 => (TimeUnit::Millisecond, 3),
+        4..=6 => (TimeUnit::Microsecond, 6),
+        _ => (TimeUnit::Nanosecond, 9),
+    };
+    let value: i64 = fraction.parse().ok()?;
+    let value = value * 10_i64.pow((digits - fraction.len()) as u32);
+    Some((seconds * time_unit_multiple(unit) + value, unit))
+}
+
+/// Parse the compact `HHMMSS` form.
+fn parse_compact(s: &str) -> Option<i64> {
+    let digit = |i: usize| -> i64 { (s.as_bytes()[i] - b'0') as i64 };
+    let (hour, minute, second) = (
+        digit(0) * 10 + digit(1),
+        digit(2) * 10 + digit(3),
+        digit(4) * 10 + digit(5),
+    );
+    (hour < 24 && minute < 60 && second < 60).then_some(hour * 3_600 + minute * 60 + second)
+}
+
+/// Cast `Utf8`/`LargeUtf8` strings to `Time32` or `Time64`.
+///
+/// Bare digit strings are time-of-day integers, in a guessed unit when guessing is enabled.
+/// Fraction digits beyond the target unit are handled by the downscale policy.
+pub(crate) fn cast_string_to_time(
+    array: &dyn Array,
+    to_type: &DataType,
+    cast_options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<ArrayRef, ArrowError> {
+    let to = match to_type {
+        DataType::Time32(unit) | DataType::Time64(unit) => *unit,
+        _ => unreachable!("cast_string_to_time is only called for time types"),
+    };
+    let options = &cast_options.timestamp_options;
+    let strings = arrow_cast::cast(array, &DataType::Utf8)?;
+    let strings = strings.as_string::<i32>();
+
+    let mut values = Vec::with_capacity(strings.len());
+    for s in strings.iter() {
+        let Some(s) = s else {
+            values.push(None);
+            continue;
+        };
+        let trimmed = s.trim();
+        let parsed = if !trimmed.is_empty() && trimmed.bytes().all(|b| b.is_ascii_digit()) {
+            if options.parse_compact_time && trimmed.len() == 6 {
+                parse_compact(trimmed).map(|v| (v, TimeUnit::Second))
+            } else if options.guess_timestamp_precision {
+                trimmed.parse().ok().and_then(|v: i64| {
+                    let unit = guess_time_of_day_unit(v)?;
+                    report.guessed_unit.get_or_insert(unit);
+                    Some((v, unit))
+                })
+            } else {
+                trimmed.parse().ok().map(|v| (v, to))
+            }
+        } else {
+            parse_clock(trimmed).or_else(|| {
+                arrow_cast::parse::string_to_time_nanoseconds(trimmed)
+                    .ok()
+                    .map(|v| (v, TimeUnit::Nanosecond))
+            })
+        };
+        let value = match parsed {
+            Some((v, from)) => {
+                let (from_size, to_size) = (time_unit_multiple(from), time_unit_multiple(to));
+                if from_size > to_size {
+                    match downscale(v, from_size / to_size, options.downscale_policy) {
+                        Some(v) => Some(v),
+                        None => {
+                            return Err(ArrowError::CastError(format!(
+                                "Cannot cast string '{s}' to {to_type:?} without losing precision"
+                            )))
+                        }
+                    }
+                } else {
+                    v.checked_mul(to_size / from_size)
+                }
+            }
+            None => None,
+        };
+        match value.filter(|v| (0..SECONDS_PER_DAY * time_unit_multiple(to)).contains(v)) {
+            Some(v) => values.push(Some(v)),
+            None if cast_options.safe => {
+                let causes = &mut report.null_causes;
+                match (parsed, value) {
+                    (None, _) => causes.parse_failure += 1,
+                    (_, None) => causes.overflow += 1,
+                    _ => causes.policy += 1,
+                }
+                values.push(None);
+            }
+            None => {
+                return Err(ArrowError::CastError(format!(
+                    "Cannot cast string '{s}' to value of {to_type:?} type"
+                )))
+            }
+        }
+    }
+
+    Ok(match to_type {
+        DataType::Time32(TimeUnit::Second) => Arc::new(
+            values
+                .into_iter()
+                .map(|v| v.map(|v| v as i32))
+                .collect::<PrimitiveArray<Time32SecondType>>(),
+        ),
+        DataType::Time32(TimeUnit::Millisecond) => Arc::new(
+            values
+                .into_iter()
+                .map(|v| v.map(|v| v as i32))
+                .collect::<PrimitiveArray<Time32MillisecondType>>(),
+        ),
+        DataType::Time64(TimeUnit::Microsecond) => Arc::new(
+            values
+                .into_iter()
+                .collect::<PrimitiveArray<Time64MicrosecondType>>(),
+        ),
+        DataType::Time64(TimeUnit::Nanosecond) => Arc::new(
+            values
+                .into_iter()
+                .collect::<PrimitiveArray<Time64NanosecondType>>(),
+        ),
+        _ => {
+            return Err(ArrowError::CastError(format!(
+                "Unsupported time type {to_type:?}"
+            )))
+        }
+    })
+}
+
+/// Combine dates and times of day to timestamps, see [crate::combine_date_time].
+pub(crate) fn combine_date_time(
+    date: &dyn Array,
+    time: &dyn Array,
+    to_type: &DataType,
+    cast_options: &CastOptions,
+) -> Result<ArrayRef, ArrowError> {
+    if date.len() != time.len() {
+        return Err(ArrowError::InvalidArgumentError(format!(
+            "Length of times {} does not match length of dates {}",
+            time.len(),
+            date.len()
+        )));
+    }
+    let DataType::Timestamp(to, tz) = to_type else {
+        return Err(ArrowError::CastError(format!(
+            "Cannot combine dates and times to {to_type:?}, expected a timestamp"
+        )));
+    };
+    let options = &cast_options.timestamp_options;
+    let to_size = time_unit_multiple(*to);
+    let mut report = CastReport::default();
+
+    // Dates in milliseconds.
+    let dates: Vec<Option<i64>> = match date.data_type() {
+        DataType::Date32 => date
+            .as_primitive::<Date32Type>()
+            .iter()
+            .map(|d| d.map(|d| d as i64 * SECONDS_PER_DAY * 1_000))
+            .collect(),
+        DataType::Date64 => date.as_primitive::<Date64Type>().iter().collect(),
+        other => {
+            return Err(ArrowError::CastError(format!(
+                "Cannot combine dates of {other:?}, expected Date32 or Date64"
+            )))
+        }
+    };
+    // Times of day with their units, `Err` for integers not within one day.
+    let times: Vec<Option<Result<(i64, TimeUnit), i64>>> = match time.data_type() {
+        DataType::Time32(unit) | DataType::Time64(unit) => {
+            let unit = *unit;
+            let integer = match time.data_type() {
+                DataType::Time32(_) => DataType::Int32,
+                _ => DataType::Int64,
+            };
+            let time = arrow_cast::cast(&arrow_cast::cast(time, &integer)?, &DataType::Int64)?;
+            time.as_primitive::<Int64Type>()
+                .iter()
+                .map(|t| t.map(|t| Ok((t, unit))))
+                .collect()
+        }
+        DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32
+        | DataType::UInt64 => {
+            let time = arrow_cast::cast(time, &DataType::Int64)?;
+            time.as_primitive::<Int64Type>()
+                .iter()
+                .map(|t| {
+                    t.map(|t| {
+                        let unit = if options.guess_timestamp_precision {
+                            guess_time_of_day_unit(t)
+                        } else {
+                            Some(*to).filter(|to| {
+                                (0..SECONDS_PER_DAY * time_unit_multiple(*to)).contains(&t)
+                            })
+                        };
+                        unit.map(|unit| (t, unit)).ok_or(t)
+                    })
+                })
+                .collect()
+        }
+        other => {
+            return Err(ArrowError::CastError(format!(
+                "Cannot combine times of {other:?}, expected Time32, Time64 or integers"
+            )))
+        }
+    };
+
+    let mut values = Vec::with_capacity(dates.len());
+    for (i, (date, time)) in dates.into_iter().zip(times).enumerate() {
+        let (Some(date), Some(time)) = (date, time) else {
+            values.push(None);
+            continue;
+        };
+        let (time, unit) = match time {
+            Ok(time) => time,
+            Err(_) if cast_options.safe => {
+                report.null_causes.policy += 1;
+                values.push(None);
+                continue;
+            }
+            Err(time) => {
+                return Err(ArrowError::CastError(format!(
+                    "Cannot combine {time} at row {i} as a time of day"
+                )))
+            }
+        };
+        let from_size = time_unit_multiple(unit);
+        let time = if from_size > to_size {
+            match downscale(time, from_size / to_size, options.downscale_policy) {
+                Some(time) => time,
+                None => {
+                    return Err(ArrowError::CastError(format!(
+                        "Cannot combine time {time} in {unit:?} to {to:?} without losing precision"
+                    )))
+                }
+            }
+        } else {
+            time * (to_size / from_size)
+        };
+        let date = if to_size >= 1_000 {
+            date.checked_mul(to_size / 1_000)
+        } else {
+            Some(date.div_euclid(1_000))
+        };
+        match date.and_then(|date| date.checked_add(time)) {
+            Some(v) => values.push(Some(v)),
+            None => {
+                let negative = date.is_none_or(|date| date < 0);
+                values.push(overflow(negative, cast_options, &mut report, || {
+                    format!("Cannot combine date and time at row {i} to {to:?}: overflow")
+                })?)
+            }
+        }
+    }
+    let tz = if options.use_timezone_as_is {
+        tz.clone()
+    } else {
+        None
+    };
+    let array = make_timestamp_array(&PrimitiveArray::from(values), *to, tz);
+    arrow_cast::cast_with_options(&array, to_type, &cast_options.into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::DownscalePolicy;
+
+    #[test]
+    fn test_string_to_time() {
+        assert_eq!(guess_time_of_day_unit(86_399), Some(TimeUnit::Second));
+        assert_eq!(guess_time_of_day_unit(86_400), Some(TimeUnit::Millisecond));
+        assert_eq!(guess_time_of_day_unit(86_400_000_000_000), None);
+
+        let array = arrow_array::StringArray::from(vec![
+            Some("06:29:04"),
+            Some("06:29:04.956"),
+            Some("06:29:04.9561234"),
+            Some("062904"),
+            Some("23364000"),
+            Some("24:00:00"),
+            None,
+        ]);
+        let to_type = DataType::Time64(TimeUnit::Microsecond);
+        let (casted, report) = crate::cast_with_report(
+            &array,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        assert_eq!(report.guessed_unit, Some(TimeUnit::Second));
+        let casted = casted.as_primitive::<Time64MicrosecondType>();
+        let base = (6 * 3_600 + 29 * 60 + 4) * 1_000_000;
+        assert_eq!(casted.value(0), base);
+        assert_eq!(casted.value(1), base + 956_000);
+        assert_eq!(casted.value(2), base + 956_123);
+        // Guessed as seconds and milliseconds of day.
+        assert_eq!(casted.value(3), 62_904_000_000);
+        assert_eq!(casted.value(4), 23_364_000_000);
+        assert!(casted.is_null(5));
+        assert!(casted.is_null(6));
+
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.parse_compact_time = true;
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        assert_eq!(
+            casted.as_primitive::<Time64MicrosecondType>().value(3),
+            base
+        );
+
+        // Rounded or rejected by the downscale policy.
+        options.timestamp_options.downscale_policy = DownscalePolicy::Round;
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        assert_eq!(
+            casted.as_primitive::<Time64MicrosecondType>().value(2),
+            base + 956_123
+        );
+        let to_type = DataType::Time32(TimeUnit::Second);
+        let casted = crate::cast_with_options(&array, &to_type, &options).unwrap();
+        assert_eq!(
+            casted.as_primitive::<Time32SecondType>().value(1),
+            (base / 1_000_000 + 1) as i32
+        );
+        options.timestamp_options.downscale_policy = DownscalePolicy::Error;
+        assert!(crate::cast_with_options(&array, &to_type, &options).is_err());
+
+        options.timestamp_options.downscale_policy = DownscalePolicy::Truncate;
+        options.safe = false;
+        assert!(crate::cast_with_options(&array, &to_type, &options).is_err());
+    }
+
+    #[test]
+    fn test_combine_date_time() {
+        let dates = arrow_array::Date32Array::from(vec![Some(19691), Some(-1), None, Some(0)]);
+        let times = arrow_array::Time64MicrosecondArray::from(vec![
+            Some(23_344_956_000),
+            Some(86_399_500_000),
+            Some(0),
+            None,
+        ]);
+        let to_type = DataType::Timestamp(TimeUnit::Millisecond, None);
+        let casted = crate::combine_date_time(
+            &dates,
+            &times,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        let casted = casted.as_primitive::<arrow_array::types::TimestampMillisecondType>();
+        assert_eq!(casted.value(0), 1701325744956);
+        // 1969-12-31T23:59:59.500
+        assert_eq!(casted.value(1), -500);
+        assert!(casted.is_null(2));
+        assert!(casted.is_null(3));
+
+        // Integers of times guessed as seconds and milliseconds of day.
+        let dates = arrow_array::Date64Array::from(vec![1701302400000, 1701302400000, 0]);
+        let times = arrow_array::Int64Array::from(vec![23344, 23344956, -1]);
+        let to_type = DataType::Timestamp(TimeUnit::Second, None);
+        let casted = crate::combine_date_time(
+            &dates,
+            &times,
+            &to_type,
+            &CastOptions::new().with_passthrough(false),
+        )
+        .unwrap();
+        let casted = casted.as_primitive::<arrow_array::types::TimestampSecondType>();
+        assert_eq!(casted.value(0), 1701325744);
+        assert_eq!(casted.value(1), 1701325744);
+        assert!(casted.is_null(2));
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.safe = false;
+        assert!(crate::combine_date_time(&dates, &times, &to_type, &options).is_err());
+        let times = arrow_array::Int64Array::from(vec![0]);
+        assert!(crate::combine_date_time(&dates, &times, &to_type, &options).is_err());
+    }
+}
@@ -0,0 +1,129 @@
+//! The canonical codes and spellings of time units.
+//!
+//! Reports, provenance arrays, unit columns and field metadata all read and write units
+//! through this mapping. The codes and the spellings are a compatibility guarantee:
+//!
+//! | Unit          | Code | Spelling |
+//! |---------------|------|----------|
+//! | `Second`      | 0    | `s`      |
+//! | `Millisecond` | 1    | `ms`     |
+//! | `Microsecond` | 2    | `us`     |
+//! | `Nanosecond`  | 3    | `ns`     |
+//!
+//! ```rust
+//! use arrow::datatypes::TimeUnit;
+//! use arrow_cast_guess_precision::time_unit::{unit_code, unit_from_str, unit_str};
+//!
+//! assert_eq!(unit_code(TimeUnit::Millisecond), 1);
+//! assert_eq!(unit_str(TimeUnit::Microsecond), "us");
+//! assert_eq!(unit_from_str("Milliseconds"), Some(TimeUnit::Millisecond));
+//! ```
+
+use arrow_schema::TimeUnit;
+
+/// Code of the unit, as in [CastReport::row_units](crate::CastReport::row_units).
+pub const fn unit_code(unit: TimeUnit) -> u8 {
+    match unit {
+        TimeUnit::Second => 0,
+        TimeUnit::Millisecond => 1,
+        TimeUnit::Microsecond => 2,
+        TimeUnit::Nanosecond => 3,
+    }
+}
+
+/// The unit of a code, `None` if unknown.
+pub const fn unit_from_code(code: u8) -> Option<TimeUnit> {
+    match code {
+        0 => Some(TimeUnit::Second),
+        1 => Some(TimeUnit::Millisecond),
+        2 => Some(TimeUnit::Microsecond),
+        3 => Some(TimeUnit::Nanosecond),
+        _ => None,
+    }
+}
+
+/// Spelling of the unit, as written in field metadata.
+pub const fn unit_str(unit: TimeUnit) -> &'static str {
+    match unit {
+        TimeUnit::Second => "s",
+        TimeUnit::Millisecond => "ms",
+        TimeUnit::Microsecond => "us",
+        TimeUnit::Nanosecond => "ns",
+    }
+}
+
+/// The unit of a spelling, `None` if unknown.
+///
+/// Spellings are matched case-insensitively, trimmed, and optionally plural: the canonical
+/// ones of [unit_str], `µs` and `μs`, `sec`, `msec`, `usec` and `nsec`, and the names of
+/// the units in full or short like `milli`. Single letters other than `s` are not units,
+/// `m` being minutes as well.
+pub fn unit_from_str(s: &str) -> Option<TimeUnit> {
+    let s = s.trim().to_lowercase();
+    let singular = s.strip_suffix('s').filter(|s| !s.is_empty()).unwrap_or(&s);
+    Some(match singular {
+        "s" if s == "s" => TimeUnit::Second,
+        "sec" | "second" => TimeUnit::Second,
+        "m" if s == "ms" => TimeUnit::Millisecond,
+        "msec" | "milli" | "millisecond" => TimeUnit::Millisecond,
+        "u" | "µ" | "μ" if s.ends_with('s') => TimeUnit::Microsecond,
+        "usec" | "micro" | "microsecond" => TimeUnit::Microsecond,
+        "n" if s == "ns" => TimeUnit::Nanosecond,
+        "nsec" | "nano" | "nanosecond" => TimeUnit::Nanosecond,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_unit_mapping() {
+        // The mapping is a compatibility guarantee.
+        let units = [
+            (TimeUnit::Second, 0, "s"),
+            (TimeUnit::Millisecond, 1, "ms"),
+            (TimeUnit::Microsecond, 2, "us"),
+            (TimeUnit::Nanosecond, 3, "ns"),
+        ];
+        for (unit, code, spelling) in units {
+            assert_eq!(unit_code(unit), code);
+            assert_eq!(unit_from_code(code), Some(unit));
+            assert_eq!(unit_str(unit), spelling);
+            assert_eq!(unit_from_str(spelling), Some(unit));
+            assert_eq!(crate::EPOCH_UNITS[code as usize], spelling);
+        }
+        assert_eq!(unit_from_code(4), None);
+
+        for (s, unit) in [
+            (" S ", TimeUnit::Second),
+            ("secs", TimeUnit::Second),
+            ("Seconds", TimeUnit::Second),
+            ("MS", TimeUnit::Millisecond),
+            ("millis", TimeUnit::Millisecond),
+            ("milliseconds", TimeUnit::Millisecond),
+            ("msec", TimeUnit::Millisecond),
+            ("µs", TimeUnit::Microsecond),
+            ("μs", TimeUnit::Microsecond),
+            ("micros", TimeUnit::Microsecond),
+            ("Microsecond", TimeUnit::Microsecond),
+            ("nanos", TimeUnit::Nanosecond),
+            ("NANOSECONDS", TimeUnit::Nanosecond),
+        ] {
+            assert_eq!(unit_from_str(s), Some(unit), "{s}");
+        }
+        for s in ["", "m", "u", "n", "µ", "min", "minutes", "ss", "fortnights"] {
+            assert_eq!(unit_from_str(s), None, "{s}");
+        }
+
+        // Metadata and provenance spell units the same.
+        let field = arrow_schema::Field::new("ts", arrow_schema::DataType::Int64, true);
+        for (unit, _, spelling) in units {
+            let field = crate::annotate_epoch_extension(field.clone(), unit);
+            assert_eq!(field.metadata()[crate::EPOCH_UNIT_METADATA_KEY], spelling);
+            let field = crate::report::annotate_field(field, unit, "majority");
+            assert_eq!(field.metadata()[crate::SOURCE_UNIT_METADATA_KEY], spelling);
+        }
+    }
+}
@@ -0,0 +1,528 @@
+//! Describing casts to timestamps in field metadata, to invert them.
+
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use arrow_array::{cast::AsArray, types::Int64Type, Array, ArrayRef};
+use arrow_schema::{ArrowError, DataType, Field, TimeUnit};
+
+use crate::{
+    epoch::{FILETIME_EPOCH_OFFSET_SECONDS, NTP_EPOCH_OFFSET_SECONDS},
+    kernel::time_unit_multiple,
+    time_unit::{unit_from_str, unit_str},
+    CastOptions, CastReport, EpochKind, GuessStrategy,
+};
+
+/// Field metadata key of the [TransformDescription] of a cast column, as JSON.
+pub const TRANSFORM_METADATA_KEY: &str = "arrow_cast_guess_precision.transform";
+
+/// Version of the JSON of [TransformDescription] written by this crate.
+pub const TRANSFORM_VERSION: i64 = 1;
+
+/// How a column was cast to timestamps, written as a flat JSON object of version
+/// [TRANSFORM_VERSION] under [TRANSFORM_METADATA_KEY]:
+///
+/// ```json
+/// {"version":1,"source_type":"Int64","source_unit":"ms","target_unit":"ns",
+///  "multiplier":1000000,"divisor":1,"epoch_kind":"unix","epoch_offset_seconds":0,
+///  "nulled_overflow":0,"nulled_parse_failure":0,"nulled_policy":0,"saturated":0,
+///  "losses":""}
+/// ```
+///
+/// Values were multiplied by `multiplier` or divided by `divisor` from `source_unit` to
+/// `target_unit`, after moving them from the epoch of `epoch_kind` by its offset. The
+/// `source_unit` is `null` for units guessed per value. `losses` lists why the original
+/// values can not be reconstructed, separated by `; `, empty for lossless casts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransformDescription {
+    pub version: i64,
+    /// The source data type, as displayed by arrow.
+    pub source_type: String,
+    pub source_unit: Option<TimeUnit>,
+    pub target_unit: TimeUnit,
+    pub multiplier: i64,
+    pub divisor: i64,
+    pub epoch_kind: EpochKind,
+    /// Seconds from the epoch of the epoch kind to the unix epoch.
+    pub epoch_offset_seconds: i64,
+    pub nulled_overflow: usize,
+    pub nulled_parse_failure: usize,
+    pub nulled_policy: usize,
+    pub saturated: usize,
+    pub losses: Vec<String>,
+}
+
+impl TransformDescription {
+    /// Describe the cast of `source_type` to `to_type` by its report, `None` if the target
+    /// is not a timestamp.
+    pub fn new(
+        source_type: &DataType,
+        to_type: &DataType,
+        report: &CastReport,
+        cast_options: &CastOptions,
+    ) -> Option<Self> {
+        let DataType::Timestamp(target_unit, _) = to_type else {
+            return None;
+        };
+        let options = &cast_options.timestamp_options;
+        let source_unit = match report.guess_strategy {
+            Some(GuessStrategy::PerValue) => None,
+            _ if report.rescale_refused => Some(*target_unit),
+            _ => Some(
+                report
+                    .guessed_unit
+                    .or(options.assume_unit)
+                    .unwrap_or(*target_unit),
+            ),
+        };
+        let (from, to) = (
+            source_unit.map_or(1, time_unit_multiple),
+            time_unit_multiple(*target_unit),
+        );
+        let (multiplier, divisor) = (to.max(from) / from, from.max(to) / to);
+        let epoch_offset_seconds = match options.epoch_kind {
+            EpochKind::Unix => 0,
+            EpochKind::WindowsFiletime => FILETIME_EPOCH_OFFSET_SECONDS,
+            EpochKind::NtpFixedPoint => NTP_EPOCH_OFFSET_SECONDS,
+        };
+
+        let mut losses = Vec::new();
+        if !source_type.is_integer() {
+            losses.push(format!("source type {source_type} is not integers"));
+        }
+        if source_unit.is_none() {
+            losses.push("units guessed per value".to_string());
+        }
+        if divisor > 1 {
+            losses.push(format!("downscaled by {divisor}"));
+        }
+        if options.epoch_kind != EpochKind::Unix || !options.interpretations.is_empty() {
+            losses.push("integers interpreted as other epochs".to_string());
+        }
+        if options.fix_y2038_wrap && source_type == &DataType::Int32 {
+            losses.push("Int32 wrapped".to_string());
+        }
+        if options.output_as_wall_clock_in.is_some() {
+            losses.push("shifted to the wall clock".to_string());
+        }
+        if report.nulls_introduced > 0 {
+            losses.push(format!("{} rows nulled", report.nulls_introduced));
+        }
+        if report.saturated > 0 {
+            losses.push(format!("{} rows saturated", report.saturated));
+        }
+        Some(Self {
+            version: TRANSFORM_VERSION,
+            source_type: source_type.to_string(),
+            source_unit,
+            target_unit: *target_unit,
+            multiplier,
+            divisor,
+            epoch_kind: options.epoch_kind,
+            epoch_offset_seconds,
+            nulled_overflow: report.null_causes.overflow,
+            nulled_parse_failure: report.null_causes.parse_failure,
+            nulled_policy: report.null_causes.policy,
+            saturated: report.saturated,
+            losses,
+        })
+    }
+
+    /// Whether the original values can be reconstructed exactly.
+    pub fn is_lossless(&self) -> bool {
+        self.losses.is_empty()
+    }
+
+    /// The description as JSON.
+    pub fn to_json(&self) -> String {
+        let unit = |unit: Option<TimeUnit>| match unit {
+            Some(unit) => format!("\"{}\"", unit_str(unit)),
+            None => "null".to_string(),
+        };
+        let epoch_kind = match self.epoch_kind {
+            EpochKind::Unix => "unix",
+            EpochKind::WindowsFiletime => "windows_filetime",
+            EpochKind::NtpFixedPoint => "ntp_fixed_point",
+        };
+        format!(
+            "{{\"version\":{},\"source_type\":{},\"source_unit\":{},\"target_unit\":{},\
+             \"multiplier\":{},\"divisor\":{},\"epoch_kind\":\"{epoch_kind}\",\
+             \"epoch_offset_seconds\":{},\"nulled_overflow\":{},\"nulled_parse_failure\":{},\
+             \"nulled_policy\":{},\"saturated\":{},\"losses\":{}}}",
+            self.version,
+            quote(&self.source_type),
+            unit(self.source_unit),
+            unit(Some(self.target_unit)),
+            self.multiplier,
+            self.divisor,
+            self.epoch_offset_seconds,
+            self.nulled_overflow,
+            self.nulled_parse_failure,
+            self.nulled_policy,
+            self.saturated,
+            quote(&self.losses.join("; ")),
+        )
+    }
+
+    /// Read a description from JSON, failing on versions newer than [TRANSFORM_VERSION].
+    pub fn from_json(json: &str) -> Result<Self, ArrowError> {
+        let error = |reason: &str| {
+            ArrowError::ParseError(format!("Invalid transform description {json}: {reason}"))
+        };
+        let mut values = parse_object(json).ok_or_else(|| error("malformed JSON"))?;
+        let mut take = |key: &str| values.remove(key).ok_or_else(|| error(key));
+        let number = |value: Value| match value {
+            Value::Number(v) => Ok(v),
+            _ => Err(error("expected a number")),
+        };
+        let string = |value: Value| match value {
+            Value::String(s) => Ok(s),
+            _ => Err(error("expected a string")),
+        };
+        let unit = |value: Value| match value {
+            Value::Null => Ok(None),
+            Value::String(s) => unit_from_str(&s)
+                .map(Some)
+                .ok_or_else(|| error("unknown unit")),
+            _ => Err(error("expected a unit")),
+        };
+        let count = |value: Value| number(value).map(|v| v.max(0) as usize);
+        let version = number(take("version")?)?;
+        if !(1..=TRANSFORM_VERSION).contains(&version) {
+            return Err(error("unsupported version"));
+        }
+        let epoch_kind = match string(take("epoch_kind")?)?.as_str() {
+            "unix" => EpochKind::Unix,
+            "windows_filetime" => EpochKind::WindowsFiletime,
+            "ntp_fixed_point" => EpochKind::NtpFixedPoint,
+            _ => return Err(error("unknown epoch kind")),
+        };
+        let losses = string(take("losses")?)?;
+        Ok(Self {
+            version,
+            source_type: string(take("source_type")?)?,
+            source_unit: unit(take("source_unit")?)?,
+            target_unit: unit(take("target_unit")?)?.ok_or_else(|| error("target_unit"))?,
+            multiplier: number(take("multiplier")?)?,
+            divisor: number(take("divisor")?)?,
+            epoch_kind,
+            epoch_offset_seconds: number(take("epoch_offset_seconds")?)?,
+            nulled_overflow: count(take("nulled_overflow")?)?,
+            nulled_parse_failure: count(take("nulled_parse_failure")?)?,
+            nulled_policy: count(take("nulled_policy")?)?,
+            saturated: count(take("saturated")?)?,
+            losses: losses
+                .split("; ")
+                .filter(|loss| !loss.is_empty())
+                .map(String::from)
+                .collect(),
+        })
+    }
+
+    /// The description in the metadata of the field, `None` if absent.
+    pub fn from_field(field: &Field) -> Option<Result<Self, ArrowError>> {
+        field
+            .metadata()
+            .get(TRANSFORM_METADATA_KEY)
+            .map(|json| Self::from_json(json))
+    }
+
+    /// Merge the description into the field metadata.
+    pub fn annotate_field(&self, field: Field) -> Field {
+        let mut metadata: HashMap<String, String> = field.metadata().clone();
+        metadata.insert(TRANSFORM_METADATA_KEY.to_string(), self.to_json());
+        field.with_metadata(metadata)
+    }
+}
+
+/// Reconstruct the source integers of timestamps cast as described in the field metadata,
+/// see [embed_transform](crate::SchemaCastOptions::embed_transform).
+///
+/// Only lossless casts of integers are inverted, exactly: casts downscaling, nulling or
+/// saturating values, guessing units per value, or from other epochs, strings and floats
+/// fail with their losses.
+pub fn invert_cast(array: &dyn Array, field: &Field) -> Result<ArrayRef, ArrowError> {
+    let description = TransformDescription::from_field(field).ok_or_else(|| {
+        ArrowError::CastError(format!(
+            "Cannot invert the cast of field {}: no transform description",
+            field.name()
+        ))
+    })??;
+    if !description.is_lossless() {
+        return Err(ArrowError::CastError(format!(
+            "Cannot invert the lossy cast of field {}: {}",
+            field.name(),
+            description.losses.join(", ")
+        )));
+    }
+    let source_type = integer_type(&description.source_type).ok_or_else(|| {
+        ArrowError::CastError(format!(
+            "Cannot invert the cast of field {} to {}",
+            field.name(),
+            description.source_type
+        ))
+    })?;
+    let target_unit = description.target_unit;
+    if !matches!(array.data_type(), DataType::Timestamp(unit, _) if *unit == target_unit) {
+        return Err(ArrowError::CastError(format!(
+            "Cannot invert the cast of field {} from {:?}, expected timestamps in {target_unit:?}",
+            field.name(),
+            array.data_type()
+        )));
+    }
+    let multiplier = description.multiplier;
+    let integers = arrow_cast::cast(array, &DataType::Int64)?;
+    let integers = integers
+        .as_primitive::<Int64Type>()
+        .try_unary::<_, Int64Type, _>(|v| match v % multiplier {
+            0 => Ok(v / multiplier),
+            _ => Err(ArrowError::CastError(format!(
+                "Cannot invert timestamp {v}: not a multiple of {multiplier}"
+            ))),
+        })?;
+    let options = arrow_cast::CastOptions {
+        safe: false,
+        ..Default::default()
+    };
+    arrow_cast::cast_with_options(&integers, &source_type, &options)
+}
+
+/// The integer type displayed as `name`.
+fn integer_type(name: &str) -> Option<DataType> {
+    use DataType::*;
+    [Int8, Int16, Int32, Int64, UInt8, UInt16, UInt32, UInt64]
+        .into_iter()
+        .find(|data_type| data_type.to_string() == name)
+}
+
+/// A JSON string of `s`, escaping quotes, backslashes and control characters.
+fn quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '\u{8}' => quoted.push_str("\\b"),
+            '\u{c}' => quoted.push_str("\\f"),
+            c if c < ' ' => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// A value of a flat JSON object.
+enum Value {
+    String(String),
+    Number(i64),
+    Null,
+}
+
+/// Parse a flat JSON object of strings, integers and nulls.
+fn parse_object(json: &str) -> Option<HashMap<String, Value>> {
+    let mut chars = json.chars().peekable();
+    let mut values = HashMap::new();
+    expect(&mut chars, '{')?;
+    if skip_whitespace(&mut chars) == Some('}') {
+        chars.next();
+    } else {
+        loop {
+            skip_whitespace(&mut chars);
+            let key = parse_string(&mut chars)?;
+            expect(&mut chars, ':')?;
+            let value = match skip_whitespace(&mut chars)? {
+                '"' => Value::String(parse_string(&mut chars)?),
+                'n' => {
+                    chars.by_ref().take(4).eq("null".chars()).then_some(())?;
+                    Value::Null
+                }
+                _ => {
+                    let mut number = String::new();
+                    while let Some(c) = chars.next_if(|c| *c == '-' || c.is_ascii_digit()) {
+                        number.push(c);
+                    }
+                    Value::Number(number.parse().ok()?)
+                }
+            };
+            values.insert(key, value);
+            match skip_whitespace(&mut chars)? {
+                ',' => chars.next(),
+                '}' => {
+                    chars.next();
+                    break;
+                }
+                _ => return None,
+            };
+        }
+    }
+    skip_whitespace(&mut chars).is_none().then_some(values)
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) -> Option<char> {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    chars.peek().copied()
+}
+
+fn expect(chars: &mut Peekable<Chars>, c: char) -> Option<()> {
+    skip_whitespace(chars);
+    chars.next_if_eq(&c).map(|_| ())
+}
+
+/// Parse a JSON string with the escapes of RFC 8259.
+fn parse_string(chars: &mut Peekable<Chars>) -> Option<String> {
+    expect(chars, '"')?;
+    let mut s = String::new();
+    loop {
+        let c = match chars.next()? {
+            '"' => return Some(s),
+            '\\' => match chars.next()? {
+                c @ ('"' | '\\' | '/') => c,
+                'b' => '\u{8}',
+                'f' => '\u{c}',
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                'u' => match parse_hex4(chars)? {
+                    high @ 0xD800..=0xDBFF => {
+                        (chars.next()? == '\\' && chars.next()? == 'u').then_some(())?;
+                        let low = parse_hex4(chars)?;
+                        (0xDC00..=0xDFFF).contains(&low).then_some(())?;
+                        char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))?
+                    }
+                    code => char::from_u32(code)?,
+                },
+                _ => return None,
+            },
+            c if c < ' ' => return None,
+            c => c,
+        };
+        s.push(c);
+    }
+}
+
+fn parse_hex4(chars: &mut Peekable<Chars>) -> Option<u32> {
+    let hex: String = chars.by_ref().take(4).collect();
+    (hex.len() == 4 && hex.chars().all(|c| c.is_ascii_hexdigit())).then_some(())?;
+    u32::from_str_radix(&hex, 16).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use arrow_array::{Int64Array, RecordBatch, StringArray};
+    use arrow_schema::Schema;
+
+    use super::*;
+    use crate::{cast_record_batch, SchemaCastOptions};
+
+    #[test]
+    fn test_invert_cast() {
+        let millis = Int64Array::from(vec![Some(1701325744956), None, Some(-1701325744957)]);
+        let strings = StringArray::from(vec![Some("2023-11-30T06:29:04.956Z"), None, None]);
+        let batch = RecordBatch::try_from_iter([
+            ("millis", Arc::new(millis.clone()) as ArrayRef),
+            ("strings", Arc::new(strings) as ArrayRef),
+        ])
+        .unwrap();
+        let nanos = DataType::Timestamp(TimeUnit::Nanosecond, Some("+08:00".into()));
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("millis", nanos.clone(), true),
+            Field::new("strings", nanos, true),
+        ]));
+        let options = SchemaCastOptions::new(CastOptions::new().with_passthrough(false))
+            .with_embed_transform(true);
+        let casted = cast_record_batch(&batch, schema, &options).unwrap();
+
+        // Through IPC.
+        let mut buf = Vec::new();
+        let mut writer =
+            arrow::ipc::writer::StreamWriter::try_new(&mut buf, &casted.schema()).unwrap();
+        writer.write(&casted).unwrap();
+        writer.finish().unwrap();
+        drop(writer);
+        let mut reader = arrow::ipc::reader::StreamReader::try_new(buf.as_slice(), None).unwrap();
+        let read = reader.next().unwrap().unwrap();
+        let schema = read.schema();
+        let description = TransformDescription::from_field(schema.field(0))
+            .unwrap()
+            .unwrap();
+        assert_eq!(description.source_unit, Some(TimeUnit::Millisecond));
+        assert_eq!(description.multiplier, 1_000_000);
+        assert!(description.is_lossless());
+        assert_eq!(
+            TransformDescription::from_json(&description.to_json()).unwrap(),
+            description
+        );
+        let inverted = invert_cast(read.column(0), schema.field(0)).unwrap();
+        assert_eq!(inverted.as_primitive::<Int64Type>(), &millis);
+        assert_eq!(
+            inverted.as_primitive::<Int64Type>().values(),
+            millis.values()
+        );
+
+        // Strings are not reconstructed.
+        let err = invert_cast(read.column(1), schema.field(1)).unwrap_err();
+        assert!(err.to_string().contains("source type Utf8"), "{err}");
+        assert!(invert_cast(read.column(0), &Field::new("millis", DataType::Int64, true)).is_err());
+
+        // Downscaling and nulling lose values.
+        let nanos = Int64Array::from(vec![1701325744956789012, 1701325744956]);
+        let batch = RecordBatch::try_from_iter([("nanos", Arc::new(nanos) as ArrayRef)]).unwrap();
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "nanos",
+            DataType::Timestamp(TimeUnit::Millisecond, None),
+            true,
+        )]));
+        let casted = cast_record_batch(&batch, schema, &options).unwrap();
+        let field = casted.schema().field(0).clone();
+        let description = TransformDescription::from_field(&field).unwrap().unwrap();
+        assert_eq!(description.divisor, 1_000_000);
+        let err = invert_cast(casted.column(0), &field).unwrap_err();
+        assert!(err.to_string().contains("downscaled by 1000000"), "{err}");
+
+        let seconds = Int64Array::from(vec![1701325744, i64::MAX / 100]);
+        let batch =
+            RecordBatch::try_from_iter([("seconds", Arc::new(seconds) as ArrayRef)]).unwrap();
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "seconds",
+            DataType::Timestamp(TimeUnit::Millisecond, None),
+            true,
+        )]));
+        let casted = cast_record_batch(&batch, schema, &options).unwrap();
+        let field = casted.schema().field(0).clone();
+        let description = TransformDescription::from_field(&field).unwrap().unwrap();
+        assert_eq!(description.nulled_overflow, 1);
+        let err = invert_cast(casted.column(0), &field).unwrap_err();
+        assert!(err.to_string().contains("1 rows nulled"), "{err}");
+
+        // Strings round trip with control characters, quotes and non-ASCII characters.
+        let mut description = description;
+        description.source_type = "Timestamp(Second, Some(\"Europe/Zürich\"))".to_string();
+        description.losses = vec!["a\nb\t\\c\u{1}\u{8}\u{c}\r/".to_string(), "😀".to_string()];
+        let json = description.to_json();
+        assert!(json.contains(r#""a\nb\t\\c\u0001\b\f\r/; 😀""#), "{json}");
+        assert!(!json.chars().any(|c| c < ' '));
+        assert_eq!(TransformDescription::from_json(&json).unwrap(), description);
+        // As written by other JSON writers.
+        let json = json.replace("😀", r"\ud83d\ude00").replace("ü", r"\u00FC");
+        let json = json.replace("r/", r"r\/");
+        assert_eq!(TransformDescription::from_json(&json).unwrap(), description);
+        for invalid in [r"\x", r"\u12", r"\u+123", r"\ud83d", r"\ud83dx", "\n"] {
+            let json = description.to_json().replace("😀", invalid);
+            assert!(TransformDescription::from_json(&json).is_err(), "{invalid}");
+        }
+
+        // Newer versions are refused.
+        let newer = description
+            .to_json()
+            .replace("\"version\":1", "\"version\":2");
+        assert!(TransformDescription::from_json(&newer).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+//! Casting arrays behind an `Arc`, dispatched once on the data type.
+
+use arrow_array::{cast::AsArray, downcast_primitive_array, Array, ArrayRef};
+use arrow_schema::{ArrowError, DataType};
+
+use crate::{cast_with_report_concrete, CastOptions, CastReport};
+
+/// Cast by the concrete array, not through the `Arc` it may be behind.
+pub(crate) fn cast_typed(
+    array: &dyn Array,
+    to_type: &DataType,
+    cast_options: &CastOptions,
+) -> Result<(ArrayRef, CastReport), ArrowError> {
+    cast_with_report_concrete(strip_arc(array), to_type, cast_options)
+}
+
+/// The concrete array of primitive and string arrays.
+///
+/// Arrays behind `Arc<dyn Array>` forward each call through the `Arc`, so they are downcast
+/// once here and called through the vtable of their own type from then on. Kernels still
+/// read them as `&dyn Array`.
+fn strip_arc(array: &dyn Array) -> &dyn Array {
+    downcast_primitive_array!(
+        array => array as &dyn Array,
+        DataType::Utf8 => array.as_string::<i32>(),
+        DataType::LargeUtf8 => array.as_string::<i64>(),
+        _ => array
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use arrow_array::{Float64Array, Int32Array, Int64Array, LargeStringArray, StringArray};
+    use arrow_schema::TimeUnit;
+
+    use super::*;
+    use crate::GuessStrategy;
+
+    #[test]
+    fn test_cast_typed() {
+        let arrays: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from(vec![Some(1701325744956), None, Some(-1)])),
+            Arc::new(Int32Array::from(vec![Some(1701325744), None])),
+            Arc::new(Float64Array::from(vec![Some(1701325744.5), Some(f64::NAN)])),
+            Arc::new(StringArray::from(vec![Some("1701325744956789"), None])),
+            Arc::new(LargeStringArray::from(vec![
+                Some("2023-11-30T06:29:04.956Z"),
+                Some("not a timestamp"),
+            ])),
+        ];
+        let to_type = DataType::Timestamp(TimeUnit::Millisecond, Some("+08:00".into()));
+        let mut options = CastOptions::new().with_passthrough(false);
+        for array in &arrays {
+            let arc: &dyn Array = array;
+            assert!(!std::ptr::addr_eq(arc, array.as_ref()));
+            assert!(std::ptr::addr_eq(strip_arc(arc), array.as_ref()));
+        }
+        for strategy in [GuessStrategy::FirstNonNull, GuessStrategy::PerValue] {
+            options.timestamp_options.guess_strategy = strategy;
+            for array in &arrays {
+                // Behind an `Arc`, and of the concrete type, as cast without dispatching.
+                let expected = cast_with_report_concrete(array, &to_type, &options).unwrap();
+                assert_eq!(cast_typed(array, &to_type, &options).unwrap(), expected);
+                assert_eq!(
+                    cast_typed(array.as_ref(), &to_type, &options).unwrap(),
+                    expected
+                );
+            }
+        }
+        // Errors are the same too.
+        options.safe = false;
+        let err = |array: &dyn Array| cast_typed(array, &to_type, &options).unwrap_err();
+        assert_eq!(
+            err(&arrays[4]).to_string(),
+            err(arrays[4].as_ref()).to_string()
+        );
+    }
+}
@@ -0,0 +1,195 @@
+//! Casting integers with the unit of each row given by another column.
+
+use arrow_array::{cast::AsArray, types::Int64Type, Array, ArrayRef, PrimitiveArray};
+use arrow_schema::{ArrowError, DataType, TimeUnit};
+
+use crate::{
+    guess_precision_with_bound_years,
+    kernel::{make_timestamp_array, normalize_utc, rescale_value},
+    time_unit::{unit_from_code, unit_from_str},
+    CastOptions, CastReport,
+};
+
+/// Cast integers to timestamps, rescaled from the unit of the same row in `units`.
+///
+/// Units are strings read by [unit_from_str](crate::unit_from_str), like `s`, `ms`, `µs` or
+/// the names of the units, or integer codes of [unit_column_codes](crate::TimestampCastOptions::unit_column_codes),
+/// dictionary encoded or not. Rows of null or unknown units are guessed by themselves when
+/// guessing, or else null when `safe` or error otherwise.
+pub fn cast_with_unit_column(
+    values: &dyn Array,
+    units: &dyn Array,
+    to_type: &DataType,
+    cast_options: &CastOptions,
+) -> Result<ArrayRef, ArrowError> {
+    if values.len() != units.len() {
+        return Err(ArrowError::InvalidArgumentError(format!(
+            "Length of units {} does not match length of values {}",
+            units.len(),
+            values.len()
+        )));
+    }
+    let to_type = &*normalize_utc(to_type, &cast_options.timestamp_options);
+    let DataType::Timestamp(unit, tz) = to_type else {
+        return Err(ArrowError::CastError(format!(
+            "Cannot cast with a unit column to {to_type:?}, expected a timestamp"
+        )));
+    };
+    let timestamp_options = &cast_options.timestamp_options;
+    let row_units = decode_units(units, cast_options)?;
+    let integers = arrow_cast::cast(values, &DataType::Int64)?;
+    let mut report = CastReport::default();
+    let mut casted = Vec::with_capacity(values.len());
+    for (row, v) in integers.as_primitive::<Int64Type>().iter().enumerate() {
+        let Some(v) = v else {
+            casted.push(None);
+            continue;
+        };
+        let from = match row_units[row] {
+            Some(from) => from,
+            None if timestamp_options.guess_timestamp_precision => {
+                guess_precision_with_bound_years(v, timestamp_options.guessing_bound_years)
+            }
+            None if cast_options.safe => {
+                casted.push(None);
+                continue;
+            }
+            None => {
+                return Err(ArrowError::CastError(format!(
+                    "Cannot cast {v} without a known unit in row {row}"
+                )))
+            }
+        };
+        casted.push(rescale_value(v, from, *unit, cast_options, &mut report)?);
+    }
+    let tz = if timestamp_options.use_timezone_as_is {
+        tz.clone()
+    } else {
+        None
+    };
+    let array = make_timestamp_array(&PrimitiveArray::from(casted), *unit, tz);
+    arrow_cast::cast_with_options(&array, to_type, &cast_options.into())
+}
+
+/// Units of each row, decoding the values of dictionaries once.
+fn decode_units(
+    units: &dyn Array,
+    cast_options: &CastOptions,
+) -> Result<Vec<Option<TimeUnit>>, ArrowError> {
+    if let Some(dictionary) = units.as_any_dictionary_opt() {
+        let values = decode_units(dictionary.values().as_ref(), cast_options)?;
+        let keys = dictionary.normalized_keys();
+        return Ok((0..units.len())
+            .map(|i| {
+                dictionary
+                    .keys()
+                    .is_valid(i)
+                    .then(|| values[keys[i]])
+                    .flatten()
+            })
+            .collect());
+    }
+    match units.data_type() {
+        DataType::Utf8 | DataType::LargeUtf8 => {
+            let strings = arrow_cast::cast(units, &DataType::Utf8)?;
+            Ok(strings
+                .as_string::<i32>()
+                .iter()
+                .map(|s| s.and_then(unit_from_str))
+                .collect())
+        }
+        data_type if data_type.is_integer() => {
+            let codes = &cast_options.timestamp_options.unit_column_codes;
+            let integers = arrow_cast::cast(units, &DataType::Int64)?;
+            Ok(integers
+                .as_primitive::<Int64Type>()
+                .iter()
+                .map(|code| match codes {
+                    Some(codes) => codes.get(&code?).copied(),
+                    None => unit_from_code(u8::try_from(code?).ok()?),
+                })
+                .collect())
+        }
+        other => Err(ArrowError::CastError(format!(
+            "Cannot read units from {other:?}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use arrow_array::{
+        types::{Int8Type, TimestampMicrosecondType},
+        DictionaryArray, Int64Array, Int8Array, StringArray,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_cast_with_unit_column() {
+        let values = Int64Array::from(vec![
+            Some(1701325744),
+            Some(1701325744956),
+            Some(1701325744956789),
+            Some(1701325744956789012),
+            Some(1701325744),
+            None,
+        ]);
+        let to_type = DataType::Timestamp(TimeUnit::Microsecond, None);
+        let expected = |casted: &ArrayRef| {
+            casted
+                .as_primitive::<TimestampMicrosecondType>()
+                .iter()
+                .collect::<Vec<_>>()
+        };
+        let units = StringArray::from(vec![
+            Some("s"),
+            Some("MS"),
+            Some("µs"),
+            Some("nanoseconds"),
+            Some("fortnights"),
+            Some("ms"),
+        ]);
+        let mut options = CastOptions::new().with_passthrough(false);
+        let casted = cast_with_unit_column(&values, &units, &to_type, &options).unwrap();
+        assert_eq!(
+            expected(&casted),
+            [
+                Some(1701325744000000),
+                Some(1701325744956000),
+                Some(1701325744956789),
+                Some(1701325744956789),
+                // Guessed by itself.
+                Some(1701325744000000),
+                None
+            ]
+        );
+
+        // Dictionary of integer codes, mapped.
+        let codes = DictionaryArray::<Int8Type>::new(
+            Int8Array::from(vec![0, 1, 2, 3, 4, 1]),
+            std::sync::Arc::new(Int64Array::from(vec![10, 11, 12, 13, 19])),
+        );
+        options.timestamp_options.unit_column_codes = Some(HashMap::from([
+            (10, TimeUnit::Second),
+            (11, TimeUnit::Millisecond),
+            (12, TimeUnit::Microsecond),
+            (13, TimeUnit::Nanosecond),
+        ]));
+        let by_codes = cast_with_unit_column(&values, &codes, &to_type, &options).unwrap();
+        assert_eq!(&by_codes, &casted);
+        options.timestamp_options.unit_column_codes = None;
+        let codes = Int8Array::from(vec![0, 1, 2, 3, 9, 1]);
+        let by_codes = cast_with_unit_column(&values, &codes, &to_type, &options).unwrap();
+        assert_eq!(&by_codes, &casted);
+
+        options.timestamp_options.guess_timestamp_precision = false;
+        let casted = cast_with_unit_column(&values, &units, &to_type, &options).unwrap();
+        assert!(casted.is_null(4));
+        options.safe = false;
+        assert!(cast_with_unit_column(&values, &units, &to_type, &options).is_err());
+        assert!(cast_with_unit_column(&values, &units.slice(0, 2), &to_type, &options).is_err());
+    }
+}
@@ -0,0 +1,185 @@
+//! Verifying casts to timestamps by converting the output back to the source unit.
+
+use arrow_array::{
+    cast::AsArray,
+    types::{Float64Type, Int32Type, Int64Type},
+    Array, ArrayRef, Int64Array,
+};
+use arrow_schema::{ArrowError, DataType, TimeUnit};
+
+use crate::{
+    kernel::{convert_unit, time_unit_multiple},
+    time_unit::unit_from_code,
+    CastOptions, CastReport, EpochKind, GuessStrategy, RoundtripMismatch,
+};
+
+/// Source values compared with the round trip of the output.
+enum Source {
+    Integers(Int64Array),
+    Floats(Vec<Option<f64>>),
+}
+
+/// Convert the output back to the source unit of each row and collect the mismatches.
+pub(crate) fn verify_roundtrip(
+    input: &dyn Array,
+    output: &ArrayRef,
+    cast_options: &CastOptions,
+    report: &mut CastReport,
+) -> Result<(), ArrowError> {
+    use DataType::*;
+    let options = &cast_options.timestamp_options;
+    let Timestamp(unit, _) = output.data_type() else {
+        return Ok(());
+    };
+    if options.epoch_kind != EpochKind::Unix {
+        return Ok(());
+    }
+    // Fractional floats are seconds.
+    let (source, seconds) = match input.data_type() {
+        Int32 if options.fix_y2038_wrap => {
+            let integers = input.as_primitive::<Int32Type>().unary(|v| v as u32 as i64);
+            (Source::Integers(integers), false)
+        }
+        Float16 | Float32 | Float64 => {
+            let floats = arrow_cast::cast(input, &Float64)?;
+            let floats = floats.as_primitive::<Float64Type>();
+            let seconds = options.timestamps_as_fractional_seconds;
+            (Source::Floats(floats.iter().collect()), seconds)
+        }
+        Int8 | Int16 | Int32 | Int64 | UInt8 | UInt16 | UInt32 | UInt64 => {
+            let integers = arrow_cast::cast(input, &Int64)?;
+            (Source::Integers(integers.as_primitive().clone()), false)
+        }
+        Utf8 | LargeUtf8 => {
+            let strings = arrow_cast::cast(input, &Utf8)?;
+            let integers = strings
+                .as_string::<i32>()
+                .iter()
+                .map(|s| s.and_then(|s| s.trim().parse().ok()))
+                .collect();
+            (Source::Integers(integers), false)
+        }
+        _ => return Ok(()),
+    };
+    let per_value = options.guess_timestamp_precision
+        && options.assume_unit.is_none()
+        && options.guess_strategy == GuessStrategy::PerValue;
+    let unit_of_row = |row: usize| -> Option<TimeUnit> {
+        if seconds {
+            return Some(TimeUnit::Second);
+        }
+        if let Some(units) = &report.row_units {
+            return units
+                .is_valid(row)
+                .then(|| unit_from_code(units.value(row)))?;
+        }
+        if per_value {
+            return None;
+        }
+        Some(options.assume_unit.or(report.guessed_unit).unwrap_or(*unit))
+    };
+    let outputs = arrow_cast::cast(output, &Int64)?;
+    let outputs = outputs.as_primitive::<Int64Type>();
+    let mut mismatches = Vec::new();
+    for (row, output) in outputs.iter().enumerate() {
+        let (Some(output), Some(from)) = (output, unit_of_row(row)) else {
+            continue;
+        };
+        let mismatch = match &source {
+            Source::Integers(integers) => {
+                if integers.is_null(row) {
+                    continue;
+                }
+                let input = integers.value(row);
+                let recomputed = convert_unit(output, *unit, from);
+                (recomputed != Some(input)).then(|| {
+                    let recomputed = recomputed.map(|v| v.to_string()).unwrap_or_default();
+                    (input.to_string(), recomputed)
+                })
+            }
+            Source::Floats(floats) => {
+                let Some(input) = floats[row] else {
+                    continue;
+                };
+                let scale = time_unit_multiple(from) as f64 / time_unit_multiple(*unit) as f64;
+                let recomputed = output as f64 * scale;
+                ((input - recomputed).abs() > options.roundtrip_epsilon)
+                    .then(|| (input.to_string(), recomputed.to_string()))
+            }
+        };
+        if let Some((input, recomputed)) = mismatch {
+            mismatches.push(RoundtripMismatch {
+                row,
+                input,
+                output,
+                recomputed,
+            });
+        }
+    }
+    report.roundtrip_mismatch_count += mismatches.len();
+    if options.roundtrip_error {
+        if let Some(mismatch) = mismatches.first() {
+            return Err(ArrowError::CastError(format!(
+                "Round trip of row {} diverges: input {}, output {}, recomputed {}",
+                mismatch.row, mismatch.input, mismatch.output, mismatch.recomputed
+            )));
+        }
+    }
+    mismatches.truncate(options.roundtrip_max_mismatches);
+    report.roundtrip_mismatches = mismatches;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use arrow_array::Float64Array;
+
+    use super::*;
+
+    #[test]
+    fn test_verify_roundtrip() {
+        let array = Int64Array::from(vec![
+            Some(1701325744956789012),
+            None,
+            Some(1701325744000000000),
+            Some(1701325745000000001),
+        ]);
+        let to_type = DataType::Timestamp(TimeUnit::Second, None);
+        let mut options = CastOptions::new().with_passthrough(false);
+        let (_, report) = crate::cast_with_report(&array, &to_type, &options).unwrap();
+        assert!(report.roundtrip_mismatches.is_empty());
+
+        options.timestamp_options.verify_roundtrip = true;
+        let (_, report) = crate::cast_with_report(&array, &to_type, &options).unwrap();
+        assert_eq!(report.roundtrip_mismatch_count, 2);
+        assert_eq!(
+            report.roundtrip_mismatches[0],
+            RoundtripMismatch {
+                row: 0,
+                input: "1701325744956789012".to_string(),
+                output: 1701325744,
+                recomputed: "1701325744000000000".to_string(),
+            }
+        );
+        assert_eq!(report.roundtrip_mismatches[1].row, 3);
+
+        options.timestamp_options.roundtrip_max_mismatches = 1;
+        let (_, report) = crate::cast_with_report(&array, &to_type, &options).unwrap();
+        assert_eq!(report.roundtrip_mismatches.len(), 1);
+        assert_eq!(report.roundtrip_mismatch_count, 2);
+
+        options.timestamp_options.roundtrip_error = true;
+        let err = crate::cast_with_report(&array, &to_type, &options).unwrap_err();
+        assert!(err.to_string().contains("row 0 diverges"));
+
+        // Floats within the epsilon.
+        let array = Float64Array::from(vec![1701325744.5]);
+        let mut options = CastOptions::new().with_passthrough(false);
+        options.timestamp_options.verify_roundtrip = true;
+        let (_, report) = crate::cast_with_report(&array, &to_type, &options).unwrap();
+        assert_eq!(report.roundtrip_mismatch_count, 1);
+        options.timestamp_options.roundtrip_epsilon = 0.5;
+        let (_, report) = crate::cast_with_report(&array, &to_type, &options).unwrap();
+        assert_eq!(report.roundtrip_mismatch_count, 0);
+    }
+}